@@ -5,6 +5,9 @@ fn main() {
     cxx_build::bridge("src/lib.rs")
         .compile("rust_practice_lab");
 
+    println!("cargo:rerun-if-changed=src/lib.rs");
+    println!("cargo:rerun-if-changed=include/counting.h");
+
     // Print the target directory for debugging
     println!("cargo:warning=Target directory: {}", env::var("OUT_DIR").unwrap());
 }
\ No newline at end of file
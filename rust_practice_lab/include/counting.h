@@ -0,0 +1,12 @@
+#pragma once
+#include <cstdint>
+#include <functional>
+
+// wraps any C++ callable so Rust can call it once for every number it counts
+struct CountingCallback {
+    std::function<void(int32_t)> callback;
+
+    void on_number(int32_t number) const {
+        callback(number);
+    }
+};
@@ -0,0 +1,69 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio_stream::Stream;
+
+pub type SleepFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+// yields the fibonacci numbers one by one, waiting `delay` between two values
+// the waiting is done with an async sleep, so the executor thread is free to run other tasks meanwhile
+// dropping the stream cancels it, there is no background task that keeps running
+pub struct FibonacciStream {
+    remaining: u64,
+    current_value: u64,
+    next_value: u64,
+    delay: Duration,
+    make_sleep: fn(Duration) -> SleepFuture,
+    sleep: Option<SleepFuture>
+}
+
+// same sequence as the loop in the async_await example: 1, 2, 3, 5, 8, ...
+// the stream ends after `count` values, or earlier when the next value would not fit in a u64
+// sleeps with tokio::time::sleep, so it has to be polled inside a tokio runtime
+pub fn fibonacci_stream(count: u64, delay: Duration) -> FibonacciStream {
+    fibonacci_stream_with_sleep(count, delay, |delay| Box::pin(tokio::time::sleep(delay)))
+}
+
+// the same stream with another timer, e.g. crate::futures::sleep to run it without tokio
+pub fn fibonacci_stream_with_sleep(count: u64, delay: Duration, make_sleep: fn(Duration) -> SleepFuture) -> FibonacciStream {
+    FibonacciStream {
+        remaining: count,
+        current_value: 0,
+        next_value: 1,
+        delay,
+        make_sleep,
+        sleep: None
+    }
+}
+
+impl Stream for FibonacciStream {
+    type Item = u64;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<u64>> {
+        if self.remaining == 0 {
+            return Poll::Ready(None);
+        }
+        // wait for the pause after the previous value, registers the waker with the timer
+        if let Some(sleep) = self.sleep.as_mut() {
+            if sleep.as_mut().poll(cx).is_pending() {
+                return Poll::Pending;
+            }
+        }
+
+        let Some(value) = self.current_value.checked_add(self.next_value) else {
+            self.remaining = 0;
+            return Poll::Ready(None);
+        };
+        self.current_value = self.next_value;
+        self.next_value = value;
+        self.remaining -= 1;
+
+        self.sleep = Some((self.make_sleep)(self.delay));
+        Poll::Ready(Some(value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.remaining as usize))
+    }
+}
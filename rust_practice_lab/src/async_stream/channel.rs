@@ -0,0 +1,43 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio_stream::Stream;
+use crate::channels::{Receiver, SendError, Sender};
+use super::combinators::next;
+
+// adapters between streams and the channels module
+// both sides wait with wakers (poll_recv, send_async), so a thread and an async task can talk through one channel
+
+// yields the messages of a receiver, ends when the channel is empty and every sender is gone
+pub struct ReceiverStream<T> {
+    receiver: Receiver<T>
+}
+
+pub fn from_receiver<T>(receiver: Receiver<T>) -> ReceiverStream<T> {
+    ReceiverStream { receiver }
+}
+
+impl<T> ReceiverStream<T> {
+    pub fn into_inner(self) -> Receiver<T> {
+        self.receiver
+    }
+}
+
+impl<T> Stream for ReceiverStream<T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        self.receiver.poll_recv(cx).map(Result::ok)
+    }
+}
+
+// sends every item of the stream, waiting for room in a bounded channel
+// stops with the first item nobody can receive anymore
+pub async fn forward<S>(mut stream: S, sender: &Sender<S::Item>) -> Result<(), SendError<S::Item>>
+where
+    S: Stream + Unpin
+{
+    while let Some(item) = next(&mut stream).await {
+        sender.send_async(item).await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,301 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio_stream::Stream;
+use crate::futures::{sleep, MockTimer, Sleep};
+
+// combinators for any Stream
+//
+// they need the stream they wrap to be Unpin, so they can poll it without unsafe pin projection.
+// generators, ReceiverStream and the combinators themselves are Unpin, anything else can be wrapped in Box::pin first.
+// the closures are never pinned, so they don't have to be Unpin
+//
+// tokio_stream::StreamExt has methods with the same names, import only one of the two traits in a module
+
+pub trait StreamCombinators: Stream + Sized {
+    fn map<B, F: FnMut(Self::Item) -> B>(self, f: F) -> Map<Self, F> {
+        Map { stream: self, f }
+    }
+
+    fn filter<F: FnMut(&Self::Item) -> bool>(self, predicate: F) -> Filter<Self, F> {
+        Filter { stream: self, predicate }
+    }
+
+    // for a stream of futures: runs up to `limit` of them at the same time and yields their outputs as they finish
+    // panics when limit is 0
+    fn buffer_unordered(self, limit: usize) -> BufferUnordered<Self>
+    where
+        Self::Item: Future
+    {
+        assert!(limit > 0, "buffer_unordered needs room for at least one future");
+        BufferUnordered { stream: self, in_flight: Vec::new(), limit, exhausted: false }
+    }
+
+    // yields at most one item per period, items that come faster wait
+    fn throttle(self, period: Duration) -> Throttle<Self> {
+        Throttle { stream: self, period, pause: None, timer: None }
+    }
+
+    // collects items into chunks of `max_size`, a chunk is yielded early when `timeout` passed since its first item
+    // panics when max_size is 0
+    fn chunks_timeout(self, max_size: usize, timeout: Duration) -> ChunksTimeout<Self> {
+        assert!(max_size > 0, "chunks need room for at least one item");
+        ChunksTimeout { stream: self, max_size, timeout, chunk: Vec::new(), deadline: None, exhausted: false, timer: None }
+    }
+
+    // interleaves the items of both streams as they come, ends when both have ended
+    fn merge<S: Stream<Item = Self::Item>>(self, other: S) -> Merge<Self, S> {
+        Merge { first: self, second: other, first_done: false, second_done: false, first_turn: true }
+    }
+}
+
+impl<S: Stream> StreamCombinators for S {}
+
+// the next item of a stream, for async code that doesn't want to import tokio_stream::StreamExt
+pub async fn next<S: Stream + Unpin>(stream: &mut S) -> Option<S::Item> {
+    std::future::poll_fn(|cx| Pin::new(&mut *stream).poll_next(cx)).await
+}
+
+//=================================================================
+
+pub struct Map<S, F> {
+    stream: S,
+    f: F
+}
+
+impl<S: Unpin, F> Unpin for Map<S, F> {}
+
+impl<S: Stream + Unpin, B, F: FnMut(S::Item) -> B> Stream for Map<S, F> {
+    type Item = B;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<B>> {
+        let this = self.get_mut();
+        Pin::new(&mut this.stream).poll_next(cx).map(|item| item.map(&mut this.f))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.stream.size_hint()
+    }
+}
+
+pub struct Filter<S, F> {
+    stream: S,
+    predicate: F
+}
+
+impl<S: Unpin, F> Unpin for Filter<S, F> {}
+
+impl<S: Stream + Unpin, F: FnMut(&S::Item) -> bool> Stream for Filter<S, F> {
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<S::Item>> {
+        let this = self.get_mut();
+        loop {
+            match Pin::new(&mut this.stream).poll_next(cx) {
+                Poll::Ready(Some(item)) if !(this.predicate)(&item) => continue,
+                other => return other
+            }
+        }
+    }
+}
+
+//=================================================================
+
+pub struct BufferUnordered<S: Stream> {
+    stream: S,
+    in_flight: Vec<Pin<Box<S::Item>>>,
+    limit: usize,
+    exhausted: bool
+}
+
+impl<S: Stream + Unpin> Stream for BufferUnordered<S>
+where
+    S::Item: Future
+{
+    type Item = <S::Item as Future>::Output;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        while !this.exhausted && this.in_flight.len() < this.limit {
+            match Pin::new(&mut this.stream).poll_next(cx) {
+                Poll::Ready(Some(future)) => this.in_flight.push(Box::pin(future)),
+                Poll::Ready(None) => this.exhausted = true,
+                Poll::Pending => break
+            }
+        }
+        // every future gets polled with our waker, whichever one wakes us up gets checked on the next poll
+        for index in 0..this.in_flight.len() {
+            if let Poll::Ready(output) = this.in_flight[index].as_mut().poll(cx) {
+                drop(this.in_flight.swap_remove(index));
+                return Poll::Ready(Some(output));
+            }
+        }
+        if this.exhausted && this.in_flight.is_empty() {
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+//=================================================================
+
+// a sleep on the mocked clock when there is one, on the real one otherwise
+fn sleep_on(timer: &Option<MockTimer>, duration: Duration) -> Sleep {
+    match timer {
+        Some(timer) => timer.sleep(duration),
+        None => sleep(duration)
+    }
+}
+
+pub struct Throttle<S> {
+    stream: S,
+    period: Duration,
+    // running after every item until the next one may go out
+    pause: Option<Sleep>,
+    timer: Option<MockTimer>
+}
+
+impl<S> Throttle<S> {
+    // waits on the clock of the MockTimer instead of the real one
+    pub fn timer(mut self, timer: &MockTimer) -> Self {
+        self.timer = Some(timer.clone());
+        self
+    }
+}
+
+impl<S: Stream + Unpin> Stream for Throttle<S> {
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<S::Item>> {
+        let this = self.get_mut();
+        if let Some(pause) = this.pause.as_mut() {
+            if Pin::new(pause).poll(cx).is_pending() {
+                return Poll::Pending;
+            }
+            this.pause = None;
+        }
+        let item = Pin::new(&mut this.stream).poll_next(cx);
+        if let Poll::Ready(Some(_)) = item {
+            this.pause = Some(sleep_on(&this.timer, this.period));
+        }
+        item
+    }
+}
+
+//=================================================================
+
+pub struct ChunksTimeout<S: Stream> {
+    stream: S,
+    max_size: usize,
+    timeout: Duration,
+    chunk: Vec<S::Item>,
+    // started by the first item of a chunk
+    deadline: Option<Sleep>,
+    exhausted: bool,
+    timer: Option<MockTimer>
+}
+
+impl<S: Stream> ChunksTimeout<S> {
+    // times the chunks on the clock of the MockTimer instead of the real one
+    pub fn timer(mut self, timer: &MockTimer) -> Self {
+        self.timer = Some(timer.clone());
+        self
+    }
+}
+
+// the collected items are only moved, never pinned
+impl<S: Stream + Unpin> Unpin for ChunksTimeout<S> {}
+
+impl<S: Stream + Unpin> Stream for ChunksTimeout<S> {
+    type Item = Vec<S::Item>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Vec<S::Item>>> {
+        let this = self.get_mut();
+        if this.exhausted {
+            return Poll::Ready(None);
+        }
+        loop {
+            match Pin::new(&mut this.stream).poll_next(cx) {
+                Poll::Ready(Some(item)) => {
+                    if this.chunk.is_empty() {
+                        this.deadline = Some(sleep_on(&this.timer, this.timeout));
+                    }
+                    this.chunk.push(item);
+                    if this.chunk.len() >= this.max_size {
+                        this.deadline = None;
+                        return Poll::Ready(Some(std::mem::take(&mut this.chunk)));
+                    }
+                }
+                Poll::Ready(None) => {
+                    // hand out what is left, the next poll ends the stream
+                    this.exhausted = true;
+                    this.deadline = None;
+                    if this.chunk.is_empty() {
+                        return Poll::Ready(None);
+                    }
+                    return Poll::Ready(Some(std::mem::take(&mut this.chunk)));
+                }
+                Poll::Pending => break
+            }
+        }
+        if let Some(deadline) = this.deadline.as_mut() {
+            if Pin::new(deadline).poll(cx).is_ready() {
+                this.deadline = None;
+                return Poll::Ready(Some(std::mem::take(&mut this.chunk)));
+            }
+        }
+        Poll::Pending
+    }
+}
+
+//=================================================================
+
+pub struct Merge<A, B> {
+    first: A,
+    second: B,
+    first_done: bool,
+    second_done: bool,
+    // alternates which stream is asked first, so a busy stream can't starve the other one
+    first_turn: bool
+}
+
+impl<A, B> Stream for Merge<A, B>
+where
+    A: Stream + Unpin,
+    B: Stream<Item = A::Item> + Unpin
+{
+    type Item = A::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<A::Item>> {
+        let this = self.get_mut();
+        this.first_turn = !this.first_turn;
+        for poll_first in [this.first_turn, !this.first_turn] {
+            let polled = match poll_first {
+                true if !this.first_done => poll_side(&mut this.first, &mut this.first_done, cx),
+                false if !this.second_done => poll_side(&mut this.second, &mut this.second_done, cx),
+                _ => None
+            };
+            if let Some(item) = polled {
+                return Poll::Ready(Some(item));
+            }
+        }
+        if this.first_done && this.second_done {
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+fn poll_side<S: Stream + Unpin>(stream: &mut S, done: &mut bool, cx: &mut Context<'_>) -> Option<S::Item> {
+    match Pin::new(stream).poll_next(cx) {
+        Poll::Ready(Some(item)) => Some(item),
+        Poll::Ready(None) => {
+            *done = true;
+            None
+        }
+        Poll::Pending => None
+    }
+}
@@ -0,0 +1,79 @@
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::thread;
+use std::time::Duration;
+use tokio_stream::Stream;
+use crate::channels::unbounded;
+use crate::futures::sleep;
+use crate::knapsack::{get_knapsack_items_with_progress, Item, KnapsackProgress};
+use super::channel::{from_receiver, ReceiverStream};
+use super::generator::stream;
+
+// the fibonacci numbers 1, 2, 3, 5, ... with a pause between two values
+// the same sequence as async_fibonacci::fibonacci_stream, but written as a generator instead of a hand-made poll_next
+// ends after `count` values, or earlier when the next value would not fit in a u64
+pub fn fibonacci(count: u64, delay: Duration) -> impl Stream<Item = u64> + Unpin + Send {
+    stream(move |emitter| async move {
+        let (mut current_value, mut next_value) = (0u64, 1u64);
+        for i in 0..count {
+            let Some(value) = current_value.checked_add(next_value) else {
+                break;
+            };
+            if i > 0 {
+                sleep(delay).await;
+            }
+            current_value = next_value;
+            next_value = value;
+            emitter.emit(value).await;
+        }
+    })
+}
+
+//=================================================================
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum KnapsackEvent {
+    // sent after every subset size that has been fully checked
+    Progress(KnapsackProgress),
+    // the last event, the best items that fit
+    Finished(Vec<Item>)
+}
+
+// progress of a knapsack search running on its own thread
+// dropping the feed cancels the search
+pub struct KnapsackFeed {
+    events: ReceiverStream<KnapsackEvent>,
+    cancelled: Arc<AtomicBool>
+}
+
+pub fn knapsack_progress(items: Vec<Item>, weight_limit: i32) -> KnapsackFeed {
+    let (sender, receiver) = unbounded();
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let search_cancelled = Arc::clone(&cancelled);
+    // a blocking search, so it gets a thread of its own instead of an executor thread
+    thread::spawn(move || {
+        let best_items = get_knapsack_items_with_progress(&items, weight_limit, &search_cancelled, |progress| {
+            let _ = sender.send(KnapsackEvent::Progress(progress));
+        });
+        if let Some(best_items) = best_items {
+            let _ = sender.send(KnapsackEvent::Finished(best_items));
+        }
+    });
+    KnapsackFeed { events: from_receiver(receiver), cancelled }
+}
+
+impl Stream for KnapsackFeed {
+    type Item = KnapsackEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<KnapsackEvent>> {
+        Pin::new(&mut self.events).poll_next(cx)
+    }
+}
+
+impl Drop for KnapsackFeed {
+    fn drop(&mut self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,88 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{Context, Poll};
+use tokio_stream::Stream;
+
+// generator-style streams: the async closure gets an Emitter and awaits emit(value) for every item
+//
+// emit puts the value in a slot shared with the stream and returns Pending once. that makes the poll
+// of the closure's future return, the stream finds the value in the slot and hands it out. the next
+// poll_next resumes the closure right after the emit.
+// the closure only runs while the stream is polled, so a slow consumer automatically slows it down
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    // only moves a value in or out, can't be left half done
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+pub struct Emitter<T> {
+    slot: Arc<Mutex<Option<T>>>
+}
+
+impl<T> Emitter<T> {
+    // hands the value to whoever polls the stream, resolves when they ask for the next one
+    pub fn emit(&self, value: T) -> Emit<'_, T> {
+        Emit { emitter: self, value: Some(value) }
+    }
+}
+
+pub struct Emit<'a, T> {
+    emitter: &'a Emitter<T>,
+    value: Option<T>
+}
+
+// the value is only moved in and out, never pinned
+impl<T> Unpin for Emit<'_, T> {}
+
+impl<T> Future for Emit<'_, T> {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<()> {
+        match self.value.take() {
+            Some(value) => {
+                let mut slot = lock(&self.emitter.slot);
+                assert!(slot.is_none(), "emit has to be awaited before emitting the next value");
+                *slot = Some(value);
+                // no wake needed, the stream returns the value right away and gets polled again for the next one
+                Poll::Pending
+            }
+            None => Poll::Ready(())
+        }
+    }
+}
+
+pub struct Generator<T, F> {
+    slot: Arc<Mutex<Option<T>>>,
+    // boxed so the generator itself is Unpin and works with all combinators
+    future: Option<Pin<Box<F>>>
+}
+
+pub fn stream<T, G, F>(generator: G) -> Generator<T, F>
+where
+    G: FnOnce(Emitter<T>) -> F,
+    F: Future<Output = ()>
+{
+    let slot = Arc::new(Mutex::new(None));
+    let future = generator(Emitter { slot: Arc::clone(&slot) });
+    Generator { slot, future: Some(Box::pin(future)) }
+}
+
+impl<T, F: Future<Output = ()>> Stream for Generator<T, F> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let Some(future) = self.future.as_mut() else {
+            return Poll::Ready(None);
+        };
+        let finished = future.as_mut().poll(cx).is_ready();
+        if finished {
+            self.future = None;
+        }
+        match lock(&self.slot).take() {
+            Some(value) => Poll::Ready(Some(value)),
+            None if finished => Poll::Ready(None),
+            None => Poll::Pending
+        }
+    }
+}
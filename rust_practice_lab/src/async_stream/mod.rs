@@ -0,0 +1,32 @@
+// async streams: the async version of an iterator
+//
+// the Stream trait is the one from futures-core (re-exported by tokio-stream), so these streams work
+// with tokio's StreamExt as well as with the combinators here
+//     generator.rs: build a stream from an async closure that emits values
+//     combinators.rs: map, filter, buffer_unordered, throttle, chunks_timeout and merge
+//     channel.rs: turn a channels::Receiver into a stream, and pour a stream into a channels::Sender
+//     feeds.rs: a streaming fibonacci and a knapsack progress feed built on the pieces above
+//
+//     let numbers = stream(|emitter| async move {
+//         for i in 0..3 {
+//             emitter.emit(i).await;
+//         }
+//     });
+//     let mut doubled = numbers.map(|i| i * 2);
+//     block_on(async {
+//         while let Some(value) = next(&mut doubled).await {
+//             println!("{}", value);
+//         }
+//     });
+//
+// the timing combinators sleep on the timer wheel of crate::futures, so they run on any executor
+pub mod generator;
+pub mod combinators;
+pub mod channel;
+pub mod feeds;
+
+pub use tokio_stream::Stream;
+pub use generator::{stream, Emitter, Generator};
+pub use combinators::{next, StreamCombinators};
+pub use channel::{forward, from_receiver, ReceiverStream};
+pub use feeds::{fibonacci, knapsack_progress, KnapsackEvent, KnapsackFeed};
@@ -0,0 +1,301 @@
+use std::mem::MaybeUninit;
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, AtomicU64, AtomicUsize, Ordering};
+use crate::model::checkable::AtomicI64;
+use crate::crossbeam_utilities::epoch;
+
+// lock-free data structures on top of std::sync::atomic
+//
+// memory reclamation: a node that was taken out of a stack or queue can still be read by a thread
+// that loaded the pointer just before. freeing it right away would be a use-after-free, and reusing
+// its address could fool a compare_exchange (the ABA problem). so every operation pins the thread with
+// crossbeam_utilities::epoch and removed nodes are handed to defer_destroy, which frees them once no
+// pinned thread can still hold them. a node can't be reused while we look at it, which rules out ABA as well.
+
+//=================================================================
+// Treiber stack: a singly linked list where push and pop swing the head pointer with compare_exchange
+//
+// orderings:
+//     push: Release on the successful compare_exchange, publishes the node's value and next pointer
+//     pop: Acquire when loading the head, so the node's fields written before the push are visible
+//     failed compare_exchange: Relaxed/Acquire, we only retry with the fresh value
+
+struct StackNode<T> {
+    value: MaybeUninit<T>,
+    next: *mut StackNode<T>
+}
+
+pub struct TreiberStack<T> {
+    head: AtomicPtr<StackNode<T>>
+}
+
+unsafe impl<T: Send> Send for TreiberStack<T> {}
+unsafe impl<T: Send> Sync for TreiberStack<T> {}
+
+impl<T> TreiberStack<T> {
+    pub fn new() -> Self {
+        Self { head: AtomicPtr::new(ptr::null_mut()) }
+    }
+
+    pub fn push(&self, value: T) {
+        let node = Box::into_raw(Box::new(StackNode {
+            value: MaybeUninit::new(value),
+            next: ptr::null_mut()
+        }));
+        let mut head = self.head.load(Ordering::Relaxed);
+        loop {
+            // SAFETY: the node isn't shared until the compare_exchange succeeds
+            unsafe { (*node).next = head };
+            match self.head.compare_exchange_weak(head, node, Ordering::Release, Ordering::Relaxed) {
+                Ok(_) => return,
+                Err(current) => head = current
+            }
+        }
+    }
+
+    pub fn pop(&self) -> Option<T> {
+        let guard = epoch::pin();
+        let mut head = self.head.load(Ordering::Acquire);
+        loop {
+            if head.is_null() {
+                return None;
+            }
+            // SAFETY: we are pinned, a node popped by another thread isn't freed before we unpin
+            let next = unsafe { (*head).next };
+            match self.head.compare_exchange_weak(head, next, Ordering::Acquire, Ordering::Acquire) {
+                Ok(_) => {
+                    // SAFETY: winning the compare_exchange makes us the only owner of the value
+                    let value = unsafe { (*head).value.assume_init_read() };
+                    // SAFETY: unlinked, and only the winner of the compare_exchange gets here
+                    unsafe { guard.defer_destroy(head) };
+                    return Some(value);
+                }
+                Err(current) => head = current
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.load(Ordering::Acquire).is_null()
+    }
+}
+
+impl<T> Default for TreiberStack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for TreiberStack<T> {
+    fn drop(&mut self) {
+        let mut node = *self.head.get_mut();
+        while !node.is_null() {
+            // SAFETY: exclusive access, nodes still on the stack own their value
+            let mut boxed = unsafe { Box::from_raw(node) };
+            unsafe { boxed.value.assume_init_drop() };
+            node = boxed.next;
+        }
+    }
+}
+
+//=================================================================
+// Michael-Scott queue: a linked list with a dummy node at the front
+// enqueue links a node after the tail and then swings the tail, dequeue swings the head
+// a thread that finds the tail lagging behind moves it forward first ("helping"), so no thread waits on another
+//
+// orderings:
+//     linking a node (tail.next compare_exchange): Release, publishes the node's value
+//     reading next pointers and head/tail: Acquire, pairs with the Release above
+//     swinging head/tail: Release on success, the new node's contents stay visible to whoever loads it
+
+struct QueueNode<T> {
+    value: MaybeUninit<T>,
+    next: AtomicPtr<QueueNode<T>>
+}
+
+impl<T> QueueNode<T> {
+    fn new(value: MaybeUninit<T>) -> *mut Self {
+        Box::into_raw(Box::new(Self {
+            value,
+            next: AtomicPtr::new(ptr::null_mut())
+        }))
+    }
+}
+
+pub struct MsQueue<T> {
+    head: AtomicPtr<QueueNode<T>>,
+    tail: AtomicPtr<QueueNode<T>>
+}
+
+unsafe impl<T: Send> Send for MsQueue<T> {}
+unsafe impl<T: Send> Sync for MsQueue<T> {}
+
+impl<T> MsQueue<T> {
+    pub fn new() -> Self {
+        let dummy = QueueNode::new(MaybeUninit::uninit());
+        Self { head: AtomicPtr::new(dummy), tail: AtomicPtr::new(dummy) }
+    }
+
+    pub fn enqueue(&self, value: T) {
+        let node = QueueNode::new(MaybeUninit::new(value));
+        let _guard = epoch::pin();
+        loop {
+            let tail = self.tail.load(Ordering::Acquire);
+            // SAFETY: we are pinned, so the tail we loaded isn't freed under us
+            let next = unsafe { (*tail).next.load(Ordering::Acquire) };
+            if next.is_null() {
+                let linked = unsafe { (*tail).next.compare_exchange(ptr::null_mut(), node, Ordering::Release, Ordering::Relaxed) };
+                if linked.is_ok() {
+                    // may fail when another thread already helped, that is fine
+                    let _ = self.tail.compare_exchange(tail, node, Ordering::Release, Ordering::Relaxed);
+                    return;
+                }
+            } else {
+                // the tail is lagging behind, help move it before trying again
+                let _ = self.tail.compare_exchange(tail, next, Ordering::Release, Ordering::Relaxed);
+            }
+        }
+    }
+
+    pub fn dequeue(&self) -> Option<T> {
+        let guard = epoch::pin();
+        loop {
+            let head = self.head.load(Ordering::Acquire);
+            let tail = self.tail.load(Ordering::Acquire);
+            // SAFETY: we are pinned, so head and next aren't freed under us
+            let next = unsafe { (*head).next.load(Ordering::Acquire) };
+            if next.is_null() {
+                return None;
+            }
+            if head == tail {
+                // there is a node after the tail, the enqueuer just hasn't moved the tail yet
+                let _ = self.tail.compare_exchange(tail, next, Ordering::Release, Ordering::Relaxed);
+                continue;
+            }
+            if self.head.compare_exchange(head, next, Ordering::AcqRel, Ordering::Relaxed).is_ok() {
+                // next is the new dummy, winning the compare_exchange makes its value ours
+                let value = unsafe { (*next).value.assume_init_read() };
+                // SAFETY: the old dummy is unlinked, and only the winner of the compare_exchange gets here
+                unsafe { guard.defer_destroy(head) };
+                return Some(value);
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        let _guard = epoch::pin();
+        let head = self.head.load(Ordering::Acquire);
+        unsafe { (*head).next.load(Ordering::Acquire).is_null() }
+    }
+}
+
+impl<T> Default for MsQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for MsQueue<T> {
+    fn drop(&mut self) {
+        // the dummy's value is either uninitialised or already moved out, every node after it owns its value
+        let dummy = *self.head.get_mut();
+        let mut node = unsafe { Box::from_raw(dummy) }.next.into_inner();
+        while !node.is_null() {
+            let mut boxed = unsafe { Box::from_raw(node) };
+            unsafe { boxed.value.assume_init_drop() };
+            node = boxed.next.into_inner();
+        }
+        // the nodes dequeued earlier belong to the epoch collector now, their values were moved out already
+    }
+}
+
+//=================================================================
+// atomic max register: remembers the highest value it was ever given
+// this is what the knapsack search needs for its "highest combined value", without a lock
+//
+// orderings: Relaxed would be enough for the number alone, but update uses AcqRel/Acquire so a thread
+// that sees a new maximum also sees what the updating thread wrote before it (e.g. the matching items)
+// with the model feature the atomic is the model::sync one, so the knapsack shared best that uses this register can be model checked
+
+#[derive(Debug)]
+pub struct AtomicMax {
+    value: AtomicI64
+}
+
+impl AtomicMax {
+    pub fn new(initial: i64) -> Self {
+        Self { value: AtomicI64::new(initial) }
+    }
+
+    pub fn get(&self) -> i64 {
+        self.value.load(Ordering::Acquire)
+    }
+
+    // returns true when the candidate became the new maximum
+    // the same as AtomicI64::fetch_max, written out to show the compare_exchange loop
+    pub fn update(&self, candidate: i64) -> bool {
+        let mut current = self.value.load(Ordering::Acquire);
+        while candidate > current {
+            match self.value.compare_exchange_weak(current, candidate, Ordering::AcqRel, Ordering::Acquire) {
+                Ok(_) => return true,
+                Err(actual) => current = actual
+            }
+        }
+        false
+    }
+}
+
+impl Default for AtomicMax {
+    fn default() -> Self {
+        Self::new(i64::MIN)
+    }
+}
+
+//=================================================================
+// sharded counter: every thread adds to its own shard, so adding threads don't fight over one cache line
+// reading sums all the shards
+//
+// orderings: Relaxed everywhere, the counter doesn't publish any other data
+// sum() is not linearisable while adds are running, it reads the shards one after the other. it always
+// lies between the adds finished before sum() started and the adds started before sum() returned,
+// and it is exact once all adds are done
+
+// each shard on its own cache line, otherwise neighbouring shards would still share one
+#[repr(align(64))]
+#[derive(Default)]
+struct PaddedCounter(AtomicU64);
+
+static NEXT_SHARD: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    static SHARD_INDEX: usize = NEXT_SHARD.fetch_add(1, Ordering::Relaxed);
+}
+
+pub struct ShardedCounter {
+    shards: Vec<PaddedCounter>
+}
+
+impl ShardedCounter {
+    pub fn new(shards: usize) -> Self {
+        Self { shards: (0..shards.max(1)).map(|_| PaddedCounter::default()).collect() }
+    }
+
+    pub fn add(&self, amount: u64) {
+        let index = SHARD_INDEX.with(|index| *index) % self.shards.len();
+        self.shards[index].0.fetch_add(amount, Ordering::Relaxed);
+    }
+
+    pub fn increment(&self) {
+        self.add(1);
+    }
+
+    pub fn sum(&self) -> u64 {
+        self.shards.iter().map(|shard| shard.0.load(Ordering::Relaxed)).sum()
+    }
+}
+
+impl Default for ShardedCounter {
+    fn default() -> Self {
+        Self::new(std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4))
+    }
+}
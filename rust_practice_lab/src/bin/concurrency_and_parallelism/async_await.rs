@@ -1,20 +1,24 @@
 use std::future::Future;
+use std::time::Duration;
+use tokio_stream::StreamExt;
+use rust_practice_lab::async_fibonacci::fibonacci_stream;
 
 async fn fib(n: u64) -> Option<u64> {
     if n > 60 {
         println!("Please use something smaller than 60, this is a small example");
         return None;
     }
-    let mut next_val = 1;
-    let mut curr_val = 0;
-    for i in 0..n {
-        let temp= curr_val + next_val;
-        curr_val = next_val;
-        next_val = temp;
-        println!("iteration {}, value: {}", i, next_val);
-        std::thread::sleep(std::time::Duration::from_millis(100)); // Add a small delay
+    // the stream sleeps with tokio::time::sleep between values (instead of std::thread::sleep)
+    // so while this future waits, the other futures in the join! below get to run
+    let mut values = fibonacci_stream(n, Duration::from_millis(100));
+    let mut last_value = 1;
+    let mut i = 0;
+    while let Some(value) = values.next().await {
+        println!("iteration {}, value: {}", i, value);
+        last_value = value;
+        i += 1;
     }
-    return Some(next_val);
+    Some(last_value)
 }
 
 fn using_fib(n: u64) -> impl Future<Output = Option<u64>> {
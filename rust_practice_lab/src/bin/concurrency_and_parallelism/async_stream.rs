@@ -0,0 +1,125 @@
+use std::ops::Deref;
+use std::thread;
+use std::time::{Duration, Instant};
+use colored::*;
+use rust_practice_lab::async_stream::*;
+use rust_practice_lab::channels::{bounded, unbounded};
+use rust_practice_lab::futures::{sleep, Executor};
+use rust_practice_lab::knapsack::*;
+
+pub fn stream_examples() {
+    println!("Async streams, run on the executor from the futures module\n");
+
+    let executor = Executor::new();
+    executor.block_on(async {
+        println!("Generator based fibonacci stream:");
+        let mut values = fibonacci(10, Duration::from_millis(20));
+        while let Some(value) = next(&mut values).await {
+            print!(" {}", value);
+        }
+        println!();
+
+        println!("\nEven fibonacci numbers, squared modulo 1000, in chunks of 4 or whatever came within 15ms:");
+        let mut chunks = fibonacci(30, Duration::from_millis(2))
+            .filter(|value| value % 2 == 0)
+            .map(|value| (value % 1000) * (value % 1000) % 1000)
+            .chunks_timeout(4, Duration::from_millis(15));
+        while let Some(chunk) = next(&mut chunks).await {
+            println!("    {:?}", chunk);
+        }
+
+        println!("\nMerging a fast and a slow stream:");
+        let fast = stream(|emitter| async move {
+            for i in 0..6 {
+                sleep(Duration::from_millis(10)).await;
+                emitter.emit(format!("fast {}", i)).await;
+            }
+        });
+        let slow = stream(|emitter| async move {
+            for i in 0..2 {
+                sleep(Duration::from_millis(25)).await;
+                emitter.emit(format!("slow {}", i)).await;
+            }
+        });
+        let mut merged = fast.merge(slow);
+        while let Some(message) = next(&mut merged).await {
+            print!(" [{}]", message);
+        }
+        println!();
+
+        println!("\nbuffer_unordered: three downloads at a time, they finish in their own order:");
+        let start = Instant::now();
+        let downloads = stream(|emitter| async move {
+            for (file, millis) in [("a", 60), ("b", 20), ("c", 40), ("d", 10), ("e", 30)] {
+                emitter.emit(async move {
+                    sleep(Duration::from_millis(millis)).await;
+                    file
+                }).await;
+            }
+        });
+        let mut finished = downloads.buffer_unordered(3);
+        while let Some(file) = next(&mut finished).await {
+            println!("    {} done after {} ms", file, start.elapsed().as_millis());
+        }
+
+        println!("\nThrottle, one value per 50ms:");
+        let start = Instant::now();
+        let mut throttled = fibonacci(5, Duration::ZERO).throttle(Duration::from_millis(50));
+        while let Some(value) = next(&mut throttled).await {
+            println!("    {} at {} ms", value, start.elapsed().as_millis());
+        }
+    });
+
+    println!("\n{}: a thread sends into a channel, the stream side receives without blocking", "from_receiver".green());
+    let (sender, receiver) = unbounded();
+    let producer = thread::spawn(move || {
+        for i in 1..=3 {
+            thread::sleep(Duration::from_millis(10));
+            sender.send(i * 100).unwrap();
+        }
+    });
+    let received: Vec<i32> = executor.block_on(async {
+        let mut messages = from_receiver(receiver);
+        let mut received = Vec::new();
+        while let Some(message) = next(&mut messages).await {
+            received.push(message);
+        }
+        received
+    });
+    producer.join().unwrap();
+    println!("    received {:?}", received);
+
+    println!("\n{}: a stream fills a bounded channel that a slow thread empties", "forward".green());
+    let (sender, receiver) = bounded(2);
+    let consumer = thread::spawn(move || {
+        receiver.iter().inspect(|_: &u64| thread::sleep(Duration::from_millis(5))).count()
+    });
+    executor.block_on(async move {
+        forward(fibonacci(20, Duration::ZERO), &sender).await.unwrap();
+    });
+    println!("    the consumer got {} values", consumer.join().unwrap());
+
+    println!("\nKnapsack progress feed\n");
+    let mut knapsack: Vec<Item> = Vec::new();
+    for _ in 0..20 {
+        let mut item = Item::new(0, 0);
+        item.randomize();
+        knapsack.push(item.deref().to_owned());
+    }
+    executor.block_on(async {
+        let mut feed = knapsack_progress(knapsack, 300);
+        while let Some(event) = next(&mut feed).await {
+            match event {
+                KnapsackEvent::Progress(progress) => println!(
+                    "    checked every combination of {} out of {} items, best value so far: {}",
+                    progress.subset_size, progress.max_subset_size, progress.best_value
+                ),
+                KnapsackEvent::Finished(best_items) => println!(
+                    "    {} items with a total value of {}",
+                    best_items.len(),
+                    best_items.iter().map(|item| item.value).sum::<i32>()
+                )
+            }
+        }
+    });
+}
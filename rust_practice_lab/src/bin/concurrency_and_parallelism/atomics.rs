@@ -0,0 +1,67 @@
+use std::ops::Deref;
+use std::thread;
+use std::time::Instant;
+use colored::*;
+use rust_practice_lab::atomics::*;
+use rust_practice_lab::knapsack::*;
+
+const THREADS: usize = 4;
+const OPERATIONS_PER_THREAD: usize = 10000;
+
+pub fn lock_free_examples() {
+    println!("Lock-free structures from std::sync::atomic, {} threads\n", THREADS);
+
+    let stack = TreiberStack::new();
+    thread::scope(|scope| {
+        for t in 0..THREADS {
+            let stack = &stack;
+            scope.spawn(move || (0..OPERATIONS_PER_THREAD).for_each(|i| stack.push(t * OPERATIONS_PER_THREAD + i)));
+        }
+    });
+    let mut popped = 0;
+    while stack.pop().is_some() {
+        popped += 1;
+    }
+    println!("Treiber stack: pushed and popped {} values", popped);
+
+    let queue = MsQueue::new();
+    let dequeued: usize = thread::scope(|scope| {
+        let producers: Vec<_> = (0..THREADS / 2)
+            .map(|_| scope.spawn(|| (0..OPERATIONS_PER_THREAD).for_each(|i| queue.enqueue(i))))
+            .collect();
+        let consumers: Vec<_> = (0..THREADS / 2)
+            .map(|_| scope.spawn(|| (0..OPERATIONS_PER_THREAD).filter(|_| queue.dequeue().is_some()).count()))
+            .collect();
+        producers.into_iter().for_each(|producer| producer.join().unwrap());
+        consumers.into_iter().map(|consumer| consumer.join().unwrap()).sum()
+    });
+    let left_over = std::iter::from_fn(|| queue.dequeue()).count();
+    println!("Michael-Scott queue: {} dequeued while producing, {} left afterwards", dequeued, left_over);
+
+    let counter = ShardedCounter::default();
+    thread::scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|| (0..OPERATIONS_PER_THREAD).for_each(|_| counter.increment()));
+        }
+    });
+    println!("Sharded counter: {}", counter.sum());
+
+    println!("\nKnapsack with the highest value in an atomic max register\n");
+
+    let mut knapsack: Vec<Item> = Vec::new();
+    for _ in 0..18 {
+        let mut item = Item::new(0, 0);
+        item.randomize();
+        knapsack.push(item.deref().to_owned());
+    }
+
+    let start = Instant::now();
+    let best_items = get_knapsack_items_par_iter(&knapsack, 300);
+    println!("Execution time in microseconds, {}: {}", "mutex for every combination".red(), start.elapsed().as_micros());
+    println!("Total value: {}", best_items.iter().fold(0, |acc, item| acc + item.value));
+
+    let start = Instant::now();
+    let best_items = get_knapsack_items_par_iter_atomic(&knapsack, 300);
+    println!("Execution time in microseconds, {}: {}", "atomic max register".green(), start.elapsed().as_micros());
+    println!("Total value: {}", best_items.iter().fold(0, |acc, item| acc + item.value));
+}
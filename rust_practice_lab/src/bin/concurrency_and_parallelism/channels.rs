@@ -0,0 +1,168 @@
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+use colored::*;
+use rust_practice_lab::channels::*;
+
+const MESSAGE_COUNT: u64 = 200000;
+const PRODUCERS: u64 = 4;
+
+// sends MESSAGE_COUNT numbers from PRODUCERS threads through our channel, returns the sum and how long it took
+fn our_channel_throughput(capacity: Option<usize>) -> (u64, Duration) {
+    let (sender, receiver) = match capacity {
+        Some(capacity) => bounded(capacity),
+        None => unbounded()
+    };
+    let start = Instant::now();
+    let mut handles = vec![];
+    for producer in 0..PRODUCERS {
+        let sender = sender.clone();
+        handles.push(thread::spawn(move || {
+            for i in (producer..MESSAGE_COUNT).step_by(PRODUCERS as usize) {
+                sender.send(i).unwrap();
+            }
+        }));
+    }
+    drop(sender);
+    let sum: u64 = receiver.iter().sum();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    (sum, start.elapsed())
+}
+
+// the same workload on the channels from the standard library
+fn std_channel_throughput(capacity: Option<usize>) -> (u64, Duration) {
+    let start = Instant::now();
+    let mut handles = vec![];
+    let sum: u64 = match capacity {
+        Some(capacity) => {
+            let (sender, receiver) = mpsc::sync_channel(capacity);
+            for producer in 0..PRODUCERS {
+                let sender = sender.clone();
+                handles.push(thread::spawn(move || {
+                    for i in (producer..MESSAGE_COUNT).step_by(PRODUCERS as usize) {
+                        sender.send(i).unwrap();
+                    }
+                }));
+            }
+            drop(sender);
+            receiver.iter().sum()
+        }
+        None => {
+            let (sender, receiver) = mpsc::channel();
+            for producer in 0..PRODUCERS {
+                let sender = sender.clone();
+                handles.push(thread::spawn(move || {
+                    for i in (producer..MESSAGE_COUNT).step_by(PRODUCERS as usize) {
+                        sender.send(i).unwrap();
+                    }
+                }));
+            }
+            drop(sender);
+            receiver.iter().sum()
+        }
+    };
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    (sum, start.elapsed())
+}
+
+fn main() {
+    println!("Bounded channel: the producer has to wait for the slow consumer\n");
+
+    let (sender, receiver) = bounded(2);
+    let producer = thread::spawn(move || {
+        for i in 1..=5 {
+            let start = Instant::now();
+            sender.send(i).unwrap();
+            println!("    sent {} after waiting {:?}", i, start.elapsed());
+        }
+    });
+    for message in &receiver {
+        thread::sleep(Duration::from_millis(50));
+        println!("    received {}", message);
+    }
+    producer.join().unwrap();
+    println!("    all senders are gone, the receiver loop ended by itself");
+
+    println!("\n====================================================================================================\n");
+
+    println!("Multiple producers and multiple consumers\n");
+
+    let (sender, receiver) = unbounded::<u32>();
+    let mut consumers = vec![];
+    for consumer in 0..3 {
+        let receiver = receiver.clone();
+        consumers.push(thread::spawn(move || {
+            let received: Vec<u32> = receiver.iter().collect();
+            println!("    consumer {} received {} messages", consumer, received.len());
+            received.len()
+        }));
+    }
+    drop(receiver);
+    let mut producers = vec![];
+    for producer in 0..3 {
+        let sender = sender.clone();
+        producers.push(thread::spawn(move || {
+            for i in 0..100 {
+                sender.send(producer * 100 + i).unwrap();
+            }
+        }));
+    }
+    drop(sender);
+    for producer in producers {
+        producer.join().unwrap();
+    }
+    let total: usize = consumers.into_iter().map(|consumer| consumer.join().unwrap()).sum();
+    println!("    {} messages received in total, every message exactly once", total);
+
+    println!("\n====================================================================================================\n");
+
+    println!("Non blocking and timed operations\n");
+
+    let (sender, receiver) = bounded(1);
+    println!("    try_send on an empty channel: {:?}", sender.try_send("first"));
+    println!("    try_send on a full channel: {:?}", sender.try_send("second"));
+    println!("    recv_timeout with a message: {:?}", receiver.recv_timeout(Duration::from_millis(10)));
+    println!("    recv_timeout without a message: {:?}", receiver.recv_timeout(Duration::from_millis(10)));
+    drop(sender);
+    println!("    try_recv after the sender was dropped: {:?}", receiver.try_recv());
+
+    println!("\n====================================================================================================\n");
+
+    println!("Select over several receivers\n");
+
+    let (fast_sender, fast_receiver) = unbounded();
+    let (slow_sender, slow_receiver) = unbounded();
+    thread::spawn(move || {
+        for i in 0..3 {
+            thread::sleep(Duration::from_millis(10));
+            fast_sender.send(format!("fast {}", i)).unwrap();
+        }
+    });
+    thread::spawn(move || {
+        thread::sleep(Duration::from_millis(25));
+        slow_sender.send("slow".to_string()).unwrap();
+    });
+    while let Ok((index, message)) = select(&[&fast_receiver, &slow_receiver]) {
+        println!("    receiver {} got: {}", index, message);
+    }
+    println!("    both channels are disconnected");
+
+    println!("\n====================================================================================================\n");
+
+    println!("Throughput, {} messages from {} producers\n", MESSAGE_COUNT, PRODUCERS);
+
+    for capacity in [Some(1), Some(64), None] {
+        let (our_sum, our_time) = our_channel_throughput(capacity);
+        let (std_sum, std_time) = std_channel_throughput(capacity);
+        assert_eq!(our_sum, std_sum);
+        let label = match capacity {
+            Some(capacity) => format!("bounded({})", capacity),
+            None => "unbounded".to_string()
+        };
+        println!("    {:<14} {}: {:>12?}   {}: {:>12?}", label, "ours".green(), our_time, "std::sync::mpsc".blue(), std_time);
+    }
+}
@@ -0,0 +1,67 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Instant;
+use colored::*;
+use rust_practice_lab::atomics::MsQueue;
+use rust_practice_lab::crossbeam_utilities::epoch::Collector;
+use rust_practice_lab::crossbeam_utilities::HazardQueue;
+
+const THREADS: usize = 4;
+const OPERATIONS_PER_THREAD: usize = 50000;
+
+pub fn reclamation_examples() {
+    println!("Memory reclamation with epochs and hazard pointers\n");
+
+    // a pinned thread holds back everything deferred after it pinned
+    let collector = Collector::new();
+    let freed = Arc::new(AtomicUsize::new(0));
+    let reader = collector.register();
+    let writer = collector.register();
+    let reader_guard = reader.pin();
+    for _ in 0..100 {
+        let freed = Arc::clone(&freed);
+        writer.pin().defer(move || { freed.fetch_add(1, Ordering::Relaxed); });
+    }
+    writer.pin().flush();
+    println!("Deferred 100 frees while another thread is pinned, freed so far: {}", freed.load(Ordering::Relaxed));
+    drop(reader_guard);
+    // the epoch has to move twice before the garbage is old enough
+    for _ in 0..3 {
+        writer.pin().flush();
+    }
+    println!("After the reader unpinned, freed: {} (epoch {})", freed.load(Ordering::Relaxed), collector.epoch());
+
+    println!("\nThe same queue algorithm with both schemes, {} threads\n", THREADS);
+
+    let epoch_queue = MsQueue::new();
+    let start = Instant::now();
+    thread::scope(|scope| {
+        for t in 0..THREADS {
+            let queue = &epoch_queue;
+            scope.spawn(move || {
+                for i in 0..OPERATIONS_PER_THREAD {
+                    queue.enqueue(t * OPERATIONS_PER_THREAD + i);
+                    queue.dequeue();
+                }
+            });
+        }
+    });
+    println!("Execution time in microseconds, {}: {}", "epochs".green(), start.elapsed().as_micros());
+
+    let hazard_queue = HazardQueue::new();
+    let start = Instant::now();
+    thread::scope(|scope| {
+        for t in 0..THREADS {
+            let queue = &hazard_queue;
+            scope.spawn(move || {
+                for i in 0..OPERATIONS_PER_THREAD {
+                    queue.enqueue(t * OPERATIONS_PER_THREAD + i);
+                    queue.dequeue();
+                }
+            });
+        }
+    });
+    println!("Execution time in microseconds, {}: {}", "hazard pointers".blue(), start.elapsed().as_micros());
+    println!("Nodes still waiting to be freed by the hazard queue: {}", hazard_queue.retired_count());
+}
@@ -0,0 +1,53 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use colored::*;
+use rust_practice_lab::*;
+use rust_practice_lab::rayon_parallel::{thread_counts, time_on_threads};
+
+// below this n the parallel version just runs the naive recursion
+const SEQUENTIAL_CUTOFF: i64 = 20;
+
+fn time<F: FnOnce() -> i64>(f: F) -> (i64, Duration) {
+    let start = Instant::now();
+    let result = f();
+    (result, start.elapsed())
+}
+
+fn speedup(baseline: Duration, other: Duration) -> f64 {
+    baseline.as_secs_f64() / other.as_secs_f64().max(f64::EPSILON)
+}
+
+fn main() {
+    let thread_counts = thread_counts();
+
+    println!("Comparing fibonacci implementations, sequential cutoff for the parallel version: {}", SEQUENTIAL_CUTOFF);
+    println!("Speedups are relative to the {} recursive version\n", "naive".red());
+
+    for n in [20, 25, 30, 35] {
+        println!("====================================================================================================\n");
+        println!("n = {}\n", n);
+
+        let (naive_result, naive_time) = time(|| fibonacci_recursive(n));
+        println!("    {:<24} result: {:<12} time: {:>12?}", "naive".red(), naive_result, naive_time);
+
+        // a fresh cache every time, otherwise the second n would already be cached
+        let cache = Mutex::new(HashMap::new());
+        let (memoized_result, memoized_time) = time(|| fibonacci_memoized(n, &cache));
+        println!("    {:<24} result: {:<12} time: {:>12?}  speedup: {:.2}x", "memoized".green(), memoized_result, memoized_time, speedup(naive_time, memoized_time));
+
+        let (iterative_result, iterative_time) = time(|| fibonacci_iterative(n));
+        println!("    {:<24} result: {:<12} time: {:>12?}  speedup: {:.2}x", "iterative".green(), iterative_result, iterative_time, speedup(naive_time, iterative_time));
+
+        for &threads in &thread_counts {
+            let (parallel_result, parallel_time) = time_on_threads(threads, || fibonacci_parallel(n, SEQUENTIAL_CUTOFF));
+            let label = format!("parallel ({} threads)", threads);
+            println!("    {:<24} result: {:<12} time: {:>12?}  speedup: {:.2}x", label.green(), parallel_result, parallel_time, speedup(naive_time, parallel_time));
+            assert_eq!(parallel_result, naive_result);
+        }
+
+        assert_eq!(memoized_result, naive_result);
+        assert_eq!(iterative_result, naive_result);
+        println!();
+    }
+}
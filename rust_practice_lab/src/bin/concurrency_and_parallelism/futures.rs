@@ -0,0 +1,67 @@
+use std::time::{Duration, Instant};
+use colored::*;
+use tokio_stream::StreamExt;
+use rust_practice_lab::async_fibonacci::fibonacci_stream_with_sleep;
+use rust_practice_lab::futures::*;
+
+// the fib function of the async_await example, only the timer is ours instead of tokio's
+async fn fib(n: u64) -> Option<u64> {
+    if n > 60 {
+        println!("Please use something smaller than 60, this is a small example");
+        return None;
+    }
+    let mut values = fibonacci_stream_with_sleep(n, Duration::from_millis(10), |delay| Box::pin(sleep(delay)));
+    let mut last_value = 1;
+    let mut i = 0;
+    while let Some(value) = values.next().await {
+        if i % 10 == 0 {
+            println!("    fib({}) iteration {}, value: {}", n, i, value);
+        }
+        last_value = value;
+        i += 1;
+    }
+    Some(last_value)
+}
+
+pub fn executor_examples() {
+    println!("Async fibonacci on our own executor, no tokio runtime involved\n");
+
+    let executor = Executor::new();
+    let handles = [10, 40, 59].map(|n| executor.spawn(fib(n)));
+    let start = Instant::now();
+    let results = executor.block_on(async {
+        let mut results = Vec::new();
+        for handle in handles {
+            results.push(handle.await);
+        }
+        results
+    });
+    println!("\nfib results: {:?}", results);
+    // the three streams sleep at the same time, so this is about the time of the longest one
+    println!("Execution time in milliseconds, {}: {}", "single-threaded executor".green(), start.elapsed().as_millis());
+
+    println!("\nA thousand sleeping tasks on a multi-threaded executor\n");
+
+    let executor = ThreadPoolExecutor::new(4);
+    let start = Instant::now();
+    let handles: Vec<_> = (0..1000u64)
+        .map(|i| executor.spawn(async move {
+            sleep(Duration::from_millis(50 + i % 50)).await;
+            i
+        }))
+        .collect();
+    let sum: u64 = executor.block_on(async {
+        let mut sum = 0;
+        for handle in handles {
+            sum += handle.await;
+        }
+        sum
+    });
+    println!("sum of the task results: {}", sum);
+    println!("Execution time in milliseconds, {} with {} threads: {}", "thread pool executor".blue(), executor.threads(), start.elapsed().as_millis());
+
+    println!("\nblock_on without an executor: {}", block_on(async {
+        sleep(Duration::from_millis(5)).await;
+        "woken up by the timer wheel"
+    }));
+}
@@ -0,0 +1,21 @@
+use std::error::Error;
+use colored::*;
+use tokio::net::TcpListener;
+use rust_practice_lab::knapsack_service::serve;
+
+const DEFAULT_PORT: u16 = 7878;
+
+// usage: knapsack_server [port]
+// only listens on localhost, the protocol is described in src/knapsack_service/protocol.rs
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    let port = match std::env::args().nth(1) {
+        Some(port) => port.parse::<u16>()?,
+        None => DEFAULT_PORT
+    };
+
+    let listener = TcpListener::bind(("127.0.0.1", port)).await?;
+    println!("Knapsack service listening on {}", listener.local_addr()?.to_string().green());
+    serve(listener).await?;
+    Ok(())
+}
@@ -1,7 +1,9 @@
 mod threads;
 mod async_await;
+#[allow(dead_code)] // also the root of the mutexes binary, its main isn't used here
 mod mutexes;
 mod atomics;
+#[allow(dead_code)] // also the root of the channels binary, its main isn't used here
 mod channels;
 mod futures;
 mod async_stream;
@@ -15,7 +17,6 @@ use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use std::thread;
 use itertools::Itertools;
-use rayon::prelude::*;
 use colored::*;
 use rand::Rng;
 use rust_practice_lab::knapsack::*;
@@ -33,23 +34,7 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     println!("\n====================================================================================================\n");
 
-    println!("Example using rayon crate for parallelism and par_iter()\n");
-
-    let numbers: Vec<u128> = (1..=1000000).collect(); // Create a large vector of numbers
-
-    let time = Instant::now();
-    let sum: u128 = numbers.iter().sum();
-    let elapsed = time.elapsed().as_micros();
-    println!("Execution time in microseconds, {}: {}", "not parallel".red(), elapsed);
-    println!("The sum of numbers from 1 to 1,000,000 is: {}", sum);
-
-    println!(" ");
-
-    let time = Instant::now();
-    let sum: u128 = numbers.par_iter().sum(); // Calculate the sum in parallel
-    let elapsed = time.elapsed().as_micros();
-    println!("Execution time in microseconds, {}: {}", "parallel".green(), elapsed);
-    println!("The sum of numbers from 1 to 1,000,000 is: {}", sum);
+    rayon_parallel::parallel_algorithm_examples();
 
     println!("\n====================================================================================================\n");
 
@@ -223,5 +208,29 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     println!("\n====================================================================================================\n");
 
+    threads::thread_pool_examples();
+
+    println!("\n====================================================================================================\n");
+
+    atomics::lock_free_examples();
+
+    println!("\n====================================================================================================\n");
+
+    crossbeam_utilities::reclamation_examples();
+
+    println!("\n====================================================================================================\n");
+
+    futures::executor_examples();
+
+    println!("\n====================================================================================================\n");
+
+    async_stream::stream_examples();
+
+    println!("\n====================================================================================================\n");
+
+    pinning::pinning_examples();
+
+    println!("\n====================================================================================================\n");
+
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,134 @@
+use std::ops::Deref;
+use std::thread;
+use std::time::{Duration, Instant};
+use colored::*;
+use rust_practice_lab::knapsack::*;
+use rust_practice_lab::mutexes::*;
+
+const THREADS: usize = 8;
+const INCREMENTS_PER_THREAD: usize = 20000;
+const KNAPSACK_ITEM_COUNT: u8 = 18;
+const KNAPSACK_WEIGHT_LIMIT: i32 = 300;
+
+// every thread increments the same counter, about the worst contention a lock can get
+fn counter_benchmark<L: RawLock>() -> Duration {
+    let counter: Mutex<usize, L> = Mutex::new(0);
+    let start = Instant::now();
+    thread::scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|| {
+                for _ in 0..INCREMENTS_PER_THREAD {
+                    *counter.lock().unwrap() += 1;
+                }
+            });
+        }
+    });
+    let elapsed = start.elapsed();
+    assert_eq!(counter.into_inner().unwrap(), THREADS * INCREMENTS_PER_THREAD);
+    elapsed
+}
+
+fn std_counter_benchmark() -> Duration {
+    let counter = std::sync::Mutex::new(0);
+    let start = Instant::now();
+    thread::scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|| {
+                for _ in 0..INCREMENTS_PER_THREAD {
+                    *counter.lock().unwrap() += 1;
+                }
+            });
+        }
+    });
+    let elapsed = start.elapsed();
+    assert_eq!(counter.into_inner().unwrap(), THREADS * INCREMENTS_PER_THREAD);
+    elapsed
+}
+
+fn knapsack_benchmark<L: RawLock>(items: &[Item]) -> (i32, Duration) {
+    let start = Instant::now();
+    let best_items = get_knapsack_items_par_iter_with_lock::<L>(items, KNAPSACK_WEIGHT_LIMIT);
+    (best_items.iter().map(|item| item.value).sum(), start.elapsed())
+}
+
+fn main() {
+    println!("{} threads each increment a shared counter {} times\n", THREADS, INCREMENTS_PER_THREAD);
+
+    println!("    {:<18} {:>12?}", "std::sync::Mutex".blue(), std_counter_benchmark());
+    println!("    {:<18} {:>12?}", SpinLock::name().green(), counter_benchmark::<SpinLock>());
+    println!("    {:<18} {:>12?}", TicketLock::name().green(), counter_benchmark::<TicketLock>());
+    println!("    {:<18} {:>12?}", McsLock::name().green(), counter_benchmark::<McsLock>());
+    println!("    {:<18} {:>12?}", RawRwLock::name().green(), counter_benchmark::<RawRwLock>());
+    println!("    {:<18} {:>12?}", QueueLock::name().green(), counter_benchmark::<QueueLock>());
+
+    println!("\n====================================================================================================\n");
+
+    println!("Knapsack with {} items, the shared best is updated under each lock\n", KNAPSACK_ITEM_COUNT);
+
+    let mut knapsack: Vec<Item> = Vec::new();
+    for _ in 0..KNAPSACK_ITEM_COUNT {
+        let mut item = Item::new(0, 0);
+        item.randomize();
+        knapsack.push(item.deref().to_owned());
+    }
+
+    let start = Instant::now();
+    let best_items = get_knapsack_items_par_iter(&knapsack, KNAPSACK_WEIGHT_LIMIT);
+    let std_elapsed = start.elapsed();
+    let best_value: i32 = best_items.iter().map(|item| item.value).sum();
+    println!("    {:<18} value: {:<6} time: {:>12?}", "std::sync::Mutex".blue(), best_value, std_elapsed);
+
+    let results = [
+        (SpinLock::name(), knapsack_benchmark::<SpinLock>(&knapsack)),
+        (TicketLock::name(), knapsack_benchmark::<TicketLock>(&knapsack)),
+        (McsLock::name(), knapsack_benchmark::<McsLock>(&knapsack)),
+        (RawRwLock::name(), knapsack_benchmark::<RawRwLock>(&knapsack)),
+        (QueueLock::name(), knapsack_benchmark::<QueueLock>(&knapsack))
+    ];
+    for (name, (value, elapsed)) in results {
+        println!("    {:<18} value: {:<6} time: {:>12?}", name.green(), value, elapsed);
+        assert_eq!(value, best_value);
+    }
+
+    println!("\n====================================================================================================\n");
+
+    println!("A panic while holding the lock poisons the mutex\n");
+
+    let shared: Mutex<Vec<i32>, TicketLock> = Mutex::new(vec![1, 2, 3]);
+    let _ = thread::scope(|scope| {
+        scope.spawn(|| {
+            let mut numbers = shared.lock().unwrap();
+            numbers.push(4);
+            panic!("panicking with the lock held");
+        }).join()
+    });
+    println!("    poisoned: {}", shared.is_poisoned());
+    match shared.lock() {
+        Ok(numbers) => println!("    data: {:?}", *numbers),
+        Err(poisoned) => println!("    lock() returned an error, the data is still there: {:?}", *poisoned.into_inner())
+    }
+
+    println!("\n====================================================================================================\n");
+
+    println!("Readers share the RwLock, a waiting writer keeps new readers out\n");
+
+    let table = RwLock::new(0u64);
+    let start = Instant::now();
+    thread::scope(|scope| {
+        for _ in 0..THREADS - 1 {
+            scope.spawn(|| {
+                let mut sum = 0;
+                for _ in 0..INCREMENTS_PER_THREAD {
+                    sum += *table.read();
+                }
+                sum
+            });
+        }
+        scope.spawn(|| {
+            for _ in 0..100 {
+                *table.write() += 1;
+            }
+        });
+    });
+    println!("    {} reads and 100 writes in {:?}, final value {}", (THREADS - 1) * INCREMENTS_PER_THREAD, start.elapsed(), table.into_inner());
+}
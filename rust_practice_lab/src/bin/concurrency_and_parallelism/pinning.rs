@@ -0,0 +1,101 @@
+use std::pin::pin;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use colored::*;
+use rust_practice_lab::futures::{sleep, timeout, Executor};
+use rust_practice_lab::mutexes::{Mutex, QueueLock};
+use rust_practice_lab::pinning::*;
+
+pub fn pinning_examples() {
+    println!("Pinning: values that other things point into, so they must stay where they are\n");
+
+    println!("{}: the tokens point into the buffer of the struct itself", "TokenBuffer".green());
+    let mut buffer = Box::pin(TokenBuffer::<64>::new());
+    buffer.as_mut().push_str("fn main() { println!(").unwrap();
+    buffer.as_mut().push_str("\"hi\"); }").unwrap();
+    let tokens: Vec<&str> = buffer.as_ref().tokens().collect();
+    println!("    {:?}", tokens);
+    println!("    {} bytes left", buffer.remaining());
+    // moving the Box moves the pointer to the buffer, not the buffer itself
+    let moved = buffer;
+    println!("    after moving the box the tokens still read {:?}", moved.as_ref().tokens().collect::<Vec<_>>());
+    let mut small = pin!(TokenBuffer::<8>::new());
+    if let Err(error) = small.as_mut().push_str("this does not fit") {
+        println!("    a buffer on the stack: {}", error);
+    }
+
+    println!("\n{}: threads park in a queue of nodes that live on their own stacks", "WaitQueue".green());
+    let queue = Arc::new(WaitQueue::new());
+    let open = Arc::new(AtomicBool::new(false));
+    let waiters: Vec<_> = (0..3)
+        .map(|i| {
+            let queue = Arc::clone(&queue);
+            let open = Arc::clone(&open);
+            thread::spawn(move || {
+                let start = Instant::now();
+                while !open.load(Ordering::Acquire) {
+                    queue.wait_blocking_if(|| !open.load(Ordering::Acquire));
+                }
+                println!("    thread {} waited {} ms", i, start.elapsed().as_millis());
+            })
+        })
+        .collect();
+    thread::sleep(Duration::from_millis(30));
+    open.store(true, Ordering::Release);
+    println!("    opened, notify_all woke {} waiters", queue.notify_all());
+    waiters.into_iter().for_each(|waiter| waiter.join().unwrap());
+
+    println!("\nThe same queue with async tasks on the executor");
+    let executor = Executor::new();
+    let woken = Arc::new(AtomicUsize::new(0));
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let queue = Arc::clone(&queue);
+            let woken = Arc::clone(&woken);
+            executor.spawn(async move {
+                queue.wait_if(|| true).await;
+                woken.fetch_add(1, Ordering::Relaxed);
+            })
+        })
+        .collect();
+    executor.run_until_stalled();
+    println!("    4 tasks are waiting, notify_one twice");
+    queue.notify_one();
+    queue.notify_one();
+    executor.run_until_stalled();
+    println!("    {} tasks finished", woken.load(Ordering::Relaxed));
+    queue.notify_all();
+    executor.block_on(async {
+        for handle in handles {
+            handle.await;
+        }
+    });
+    println!("    after notify_all: {} tasks finished", woken.load(Ordering::Relaxed));
+
+    println!("\n{}: a parking lock built on the wait queue", "QueueLock".green());
+    let counter: Mutex<usize, QueueLock> = Mutex::new(0);
+    let start = Instant::now();
+    thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| {
+                for _ in 0..10000 {
+                    *counter.lock().unwrap() += 1;
+                }
+            });
+        }
+    });
+    println!("    4 threads counted to {} in {:?}", counter.into_inner().unwrap(), start.elapsed());
+
+    println!("\n{}: a pin projected future around another future", "timeout".green());
+    executor.block_on(async {
+        let quick = timeout(Duration::from_millis(50), async {
+            sleep(Duration::from_millis(5)).await;
+            "done"
+        });
+        println!("    5 ms of work with 50 ms to spare: {:?}", quick.await);
+        let slow = timeout(Duration::from_millis(10), sleep(Duration::from_millis(100)));
+        println!("    100 ms of work with 10 ms to spare: {:?}", slow.await);
+    });
+}
@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use colored::*;
+use rand::Rng;
+use rust_practice_lab::rayon_parallel::*;
+
+const VALUE_COUNT: usize = 2_000_000;
+const HISTOGRAM_BUCKETS: usize = 16;
+const KEYS: u64 = 1000;
+
+fn speedup(baseline: Duration, other: Duration) -> f64 {
+    baseline.as_secs_f64() / other.as_secs_f64().max(f64::EPSILON)
+}
+
+// times the sequential version once and the parallel one on every thread count, both get their own copy of
+// the input so copying it isn't part of the time. every parallel result has to match the sequential one
+fn benchmark<I, R>(name: &str, input: &I, sequential: impl Fn(I) -> R, parallel: impl Fn(I) -> R + Sync)
+where
+    I: Clone + Send,
+    R: PartialEq + Send
+{
+    println!("{}", name);
+    let copy = input.clone();
+    let start = Instant::now();
+    let expected = sequential(copy);
+    let sequential_time = start.elapsed();
+    println!("    {:<24} time: {:>12?}", "sequential".red(), sequential_time);
+
+    for threads in thread_counts() {
+        let copy = input.clone();
+        let (result, parallel_time) = time_on_threads(threads, || parallel(copy));
+        let label = format!("parallel ({} threads)", threads);
+        println!("    {:<24} time: {:>12?}  speedup: {:.2}x", label.green(), parallel_time, speedup(sequential_time, parallel_time));
+        assert!(result == expected, "{} on {} threads differs from the sequential version", name, threads);
+    }
+    println!();
+}
+
+pub fn parallel_algorithm_examples() {
+    println!("Parallel algorithms on rayon, {} values, compared with their sequential versions\n", VALUE_COUNT);
+
+    let mut rng = rand::thread_rng();
+    let values: Vec<u64> = (0..VALUE_COUNT).map(|_| rng.gen_range(0..1_000_000)).collect();
+
+    let sums = par_prefix_sum(&values);
+    println!("Prefix sums, the last one is the sum of everything: {}\n", sums[sums.len() - 1]);
+    benchmark("prefix sum", &values.as_slice(), prefix_sum, par_prefix_sum);
+    benchmark(
+        "running maximum (a scan with max)",
+        &values.as_slice(),
+        |values| scan(values, 0, u64::max),
+        |values| par_scan(values, 0, u64::max)
+    );
+
+    let sort_with = |sort: fn(&mut [u64])| move |mut values: Vec<u64>| {
+        sort(&mut values);
+        values
+    };
+    benchmark("merge sort", &values, sort_with(merge_sort), sort_with(par_merge_sort));
+    benchmark("sample sort (against the sequential merge sort)", &values, sort_with(merge_sort), sort_with(par_sample_sort));
+
+    let bucket_of = |value: &u64| (*value as usize * HISTOGRAM_BUCKETS) / 1_000_000;
+    let counts = par_histogram(&values, HISTOGRAM_BUCKETS, bucket_of);
+    println!("Histogram of the values in {} buckets: {:?}\n", HISTOGRAM_BUCKETS, counts);
+    benchmark(
+        "histogram",
+        &values.as_slice(),
+        |values| histogram(values, HISTOGRAM_BUCKETS, bucket_of),
+        |values| par_histogram(values, HISTOGRAM_BUCKETS, bucket_of)
+    );
+
+    // sales per customer: the key is the customer, the value an amount
+    let pairs: Vec<(u64, u64)> = values.iter().map(|&value| (value % KEYS, value / KEYS)).collect();
+    let totals: HashMap<u64, u64> = par_reduce_by_key(&pairs, |a, b| a + b);
+    let best = totals.iter().max_by_key(|(_, total)| **total).expect("there are values");
+    println!("Reduce by key over {} keys, the largest total is {} for key {}\n", totals.len(), best.1, best.0);
+    benchmark(
+        "reduce by key (sum per key)",
+        &pairs.as_slice(),
+        |pairs| reduce_by_key(pairs, |a, b| a + b),
+        |pairs| par_reduce_by_key(pairs, |a, b| a + b)
+    );
+}
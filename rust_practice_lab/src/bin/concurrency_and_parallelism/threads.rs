@@ -0,0 +1,60 @@
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+use colored::*;
+use rust_practice_lab::threads::ThreadPool;
+
+const TASK_COUNT: u64 = 2000;
+
+fn sum_of_squares(n: u64) -> u64 {
+    (1..=n).map(|i| i * i).sum()
+}
+
+// recursive fork-join on the pool, the small cases run on the current thread
+fn parallel_fibonacci(pool: &ThreadPool, n: u64) -> u64 {
+    if n < 20 {
+        return rust_practice_lab::fibonacci_recursive(n as i64) as u64;
+    }
+    let (first_number, second_number) = pool.join(|| parallel_fibonacci(pool, n - 1), || parallel_fibonacci(pool, n - 2));
+    first_number + second_number
+}
+
+pub fn thread_pool_examples() {
+    println!("Work-stealing thread pool versus one OS thread per task, {} small tasks\n", TASK_COUNT);
+
+    let start = Instant::now();
+    let handles: Vec<_> = (0..TASK_COUNT).map(|i| std::thread::spawn(move || sum_of_squares(i))).collect();
+    let sum: u64 = handles.into_iter().map(|handle| handle.join().unwrap()).sum();
+    println!("Execution time in microseconds, {}: {}", "thread per task".red(), start.elapsed().as_micros());
+    println!("Sum: {}", sum);
+
+    let pool = ThreadPool::new(std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4));
+    let start = Instant::now();
+    let handles: Vec<_> = (0..TASK_COUNT).map(|i| pool.spawn(move || sum_of_squares(i))).collect();
+    let sum: u64 = handles.into_iter().map(|handle| handle.join()).sum();
+    println!("Execution time in microseconds, {}: {}", "thread pool".green(), start.elapsed().as_micros());
+    println!("Sum: {}", sum);
+
+    println!("\nScoped jobs can borrow local data, no Arc needed");
+    let numbers: Vec<u64> = (1..=1000).collect();
+    let total = AtomicU64::new(0);
+    pool.scope(|scope| {
+        for chunk in numbers.chunks(100) {
+            let total = &total;
+            scope.spawn(move |_| {
+                total.fetch_add(chunk.iter().sum::<u64>(), Ordering::Relaxed);
+            });
+        }
+    });
+    println!("Sum of 1 to 1000 in chunks of 100: {}", total.load(Ordering::Relaxed));
+
+    println!("\nNested join: fibonacci(30) = {}", parallel_fibonacci(&pool, 30));
+
+    println!("\nA panic inside a job comes back out of join()");
+    let handle = pool.spawn(|| -> u64 { panic!("this job fails on purpose") });
+    let result = catch_unwind(AssertUnwindSafe(|| handle.join()));
+    println!("join() panicked: {}", result.is_err());
+
+    // waits for everything that is still queued before the workers exit
+    pool.shutdown();
+}
@@ -0,0 +1,98 @@
+// the fleet tool only uses part of the vehicle model
+#[allow(dead_code, clippy::module_inception)]
+mod traits;
+
+use std::io::{self, BufRead};
+use std::process;
+use traits::default_type_parameters::{Length, Mass, Money, Power};
+use traits::trait_objects::Fleet;
+use traits::traits::poly;
+use traits::traits::Vehicles;
+use traits::vehicle_records;
+
+// Runs fleet queries (the language is described in traits/fleet_query.rs) from the command line
+//     fleet "kind = Car and price < 65000 order by price desc"
+//     fleet --vehicles garage.json "count, sum(price) group by kind"
+//     fleet < queries.txt
+// Without a query every line of the standard input is one, empty lines and lines starting with # are skipped.
+// The vehicles come from a file written by vehicle_records::to_json, or a small demo fleet without --vehicles.
+
+const USAGE: &str = "usage: fleet [--vehicles FILE] [QUERY...]";
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let mut vehicles_file = None;
+    let mut words = Vec::new();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--vehicles" => match args.next() {
+                Some(path) => vehicles_file = Some(path),
+                None => fail("--vehicles needs a file")
+            },
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ => words.push(arg)
+        }
+    }
+    let fleet = match vehicles_file {
+        Some(path) => load(&path),
+        None => demo_fleet().into_iter().collect()
+    };
+
+    // the words of one query don't have to be quoted together, only < and > need quotes in a shell
+    if !words.is_empty() {
+        if !run(&fleet, &words.join(" ")) {
+            process::exit(1);
+        }
+        return;
+    }
+    for line in io::stdin().lock().lines() {
+        let line = line.unwrap_or_else(|error| fail(&format!("can't read the queries: {}", error)));
+        let query = line.trim();
+        if !query.is_empty() && !query.starts_with('#') {
+            run(&fleet, query);
+            println!();
+        }
+    }
+}
+
+// prints the result or the error, false for an error
+fn run(fleet: &Fleet, query: &str) -> bool {
+    match fleet.query(query) {
+        Ok(result) => {
+            println!("{}", result);
+            true
+        }
+        Err(error) => {
+            eprintln!("{}", error.render(query));
+            false
+        }
+    }
+}
+
+fn load(path: &str) -> Fleet {
+    let json = std::fs::read_to_string(path).unwrap_or_else(|error| fail(&format!("can't read {}: {}", path, error)));
+    match vehicle_records::from_json(&json) {
+        Ok(vehicles) => vehicles.into_iter().collect(),
+        Err(error) => fail(&format!("{}: {}", path, error))
+    }
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}\n{}", message, USAGE);
+    process::exit(2)
+}
+
+fn demo_fleet() -> Vec<Vehicles> {
+    vec![
+        poly::Car::new(poly::AbstractVehicle {name: "Family Car".to_string(), price: Money::new(32000.0)}, Power::from_horse_power(150.0)).into(),
+        poly::Car::new(poly::AbstractVehicle {name: "Sports Car".to_string(), price: Money::new(95000.0)}, Power::from_horse_power(450.0)).into(),
+        poly::Car::new(poly::AbstractVehicle {name: "Old Van".to_string(), price: Money::new(8000.0)}, Power::from_horse_power(90.0)).into(),
+        poly::Boat::new(poly::AbstractVehicle {name: "Fishing Boat".to_string(), price: Money::new(45000.0)}, Mass::from_kilograms(40.0)).into(),
+        poly::AmphibiousBoat::new(poly::Boat::new(poly::AbstractVehicle {name: "Duck Tour".to_string(), price: Money::new(120000.0)}, Mass::from_kilograms(20.0)), 6).into(),
+        poly::Airplane::new(poly::AbstractVehicle {name: "Crop Duster".to_string(), price: Money::new(180000.0)}, Length::from_meters(12.0)).into(),
+        poly::Airplane::new(poly::AbstractVehicle {name: "Jet".to_string(), price: Money::new(2500000.0)}, Length::from_meters(35.0)).into()
+    ]
+}
@@ -2,6 +2,37 @@ mod traits;
 // rs file
 pub use traits::traits::poly;     // file_name::mod_name
 pub use traits::traits::Vehicles;
+use traits::traits::VehicleKind;
+use rust_practice_lab::knapsack;
+use rust_practice_lab::mutexes::TicketLock;
+use poly::Vehicle;
+use traits::trait_objects::{Capability, Fleet};
+use traits::associated_constants::{self, spec_of, Category, Specification, Terrain, REGISTRY};
+use traits::route_simulation::{self, EventKind, World};
+use traits::vehicle_records;
+use traits::fleet_query;
+use traits::decimal::{Decimal, RoundingMode};
+use traits::pricing::{Basis, CurrencyCode, Depreciation, LineKind, PricingEngine, PricingError, Price, RatesTable, Rule};
+use traits::traits::vehicle_events::{render_json, render_text};
+use traits::fleet_planning::{FleetPlanner, Plan, Utility};
+use traits::default_type_parameters::{Currency, Eur, ExchangeRate, Length, Mass, Money, Power, Speed, Usd};
+use std::time::Duration;
+
+// 1 USD in other currencies, the last column is the number of digits after the point
+const RATES: &str = "\
+# rates of 2026-10-01
+base USD
+EUR 0.92
+JPY 149.5 0
+CHF 0.8811
+";
+
+// Adds the prices up as decimals, f64 money picks up rounding errors with every addition
+// A price that isn't a finite amount stops the sum, the error says which one
+fn sum_prices<C: Currency>(prices: impl Iterator<Item = Money<C>>) -> Result<Price, PricingError> {
+    let amount = prices.map(|price| Price::from_money(price).map(|price| price.amount)).sum::<Result<Decimal, _>>()?;
+    Ok(Price::new(amount, CurrencyCode::of::<C>()))
+}
 
 fn main() {
     let numbers: Vec<i32> = vec![1, 2, 3, 4, 5, 6, 7, 8, 9];
@@ -15,22 +46,22 @@ fn main() {
     println!("\n====================================================================================================\n");
 
     let boat = poly::Boat::new(
-        poly::AbstractVehicle {name: "Boat", price: 67000.0},
-        56
+        poly::AbstractVehicle {name: "Boat".to_string(), price: Money::new(67000.0)},
+        Mass::from_kilograms(56.0)
     );
     let hybrid_boat = poly::AmphibiousBoat::new(
         poly::Boat::new(
-            poly::AbstractVehicle {name: "Boat", price: 67000.0}, 67
+            poly::AbstractVehicle {name: "Boat".to_string(), price: Money::new(67000.0)}, Mass::from_kilograms(67.0)
         ),
         4
     );
     let airplane = poly::Airplane::new(
-        poly::AbstractVehicle {name: "Airplane", price: 6700000.0},
-        30
+        poly::AbstractVehicle {name: "Airplane".to_string(), price: Money::new(6700000.0)},
+        Length::from_meters(30.0)
     );
     let car = poly::Car::new(
-        poly::AbstractVehicle {name: "SuperFast", price: 64500.0},
-        300
+        poly::AbstractVehicle {name: "SuperFast".to_string(), price: Money::new(64500.0)},
+        Power::from_horse_power(300.0)
     );
 
     // A vec with objects that implement the vehicle trait
@@ -80,42 +111,28 @@ fn main() {
     // filter only cheap vehicles
     let cheap_vehicles: Vec<&traits::traits::Vehicles> = vehicle_vec
         .iter()
-        .filter(|&vehicle| match vehicle {
-            Vehicles::AmphibiousBoat(hybrid_boat) => hybrid_boat.boat.vehicle.price < 65000.0,
-            Vehicles::Airplane(airplane) => airplane.vehicle.price < 65000.0,
-            Vehicles::Boat(boat) => boat.vehicle.price < 65000.0,
-            Vehicles::Car(car) => car.vehicle.price < 65000.0,
-            _ => false
-        })
+        .filter(|&vehicle| vehicle.price() < Money::new(65000.0)) // Vehicles is a Vehicle itself, it asks the variant
         .collect();
 
     println!("Cheap collection of vehicles: ");
     for cheap_vehicle in &cheap_vehicles {
-        println!("    {:?}", cheap_vehicle);
+        println!("    {:?} {} for {}", cheap_vehicle.kind(), cheap_vehicle.name(), cheap_vehicle.price());
     }
 
     println!("\n====================================================================================================\n");
 
     // get sum of the prize of all vehicles and return that sum
-    let vehicles_prizes_sum = vehicle_vec
-        .iter()
-        .fold(0.0, |acc, vehicle| {
-            let price = match vehicle {
-            Vehicles::AmphibiousBoat(hybrid_boat) => hybrid_boat.boat.vehicle.price,
-            Vehicles::Airplane(airplane) => airplane.vehicle.price,
-            Vehicles::Boat(boat) => boat.vehicle.price,
-            Vehicles::Car(car) => car.vehicle.price,
-            _ => 0.0
-        };
-        acc + price
-        });
-
-    println!("The sum of all the vehicle prizes is: {}", vehicles_prizes_sum);
+    let vehicles_prizes_sum = sum_prices(vehicle_vec.iter().map(|vehicle| vehicle.price()));
+
+    match vehicles_prizes_sum {
+        Ok(sum) => println!("The sum of all the vehicle prizes is: {}", sum),
+        Err(error) => println!("The vehicle prizes can't be added up: {}", error)
+    }
 
     println!("\n====================================================================================================\n");
 
     // combine filter on car and then sum all the prices together in one operation
-    let car_sum_prices = vehicle_vec
+    let car_sum_prices = sum_prices(vehicle_vec
         .iter()
         .filter_map(|vehicle| {
             if let Vehicles::Car(car) = vehicle {
@@ -125,9 +142,319 @@ fn main() {
                 None
             }
         })
-        .fold(0.0, |acc, car| acc + car.vehicle.price); // here, only cars are left
+        .map(|car| car.vehicle.price)); // here, only cars are left
+
+    match car_sum_prices {
+        Ok(sum) => println!("The sum of all the car prizes is: {}", sum),
+        Err(error) => println!("The car prizes can't be added up: {}", error)
+    }
+
+    println!("\n====================================================================================================\n");
+
+    // the same vehicles as trait objects, filtered by what they can do instead of by enum variant
+    let mut fleet = Fleet::new();
+    fleet.extend(vehicle_vec.into_iter().map(Box::<dyn poly::Vehicle>::from));
+    fleet.add(poly::Car::new(poly::AbstractVehicle {name: "Slowpoke".to_string(), price: Money::new(3500.0)}, Power::from_horse_power(60.0)));
+    fleet.add(poly::Boat::new(poly::AbstractVehicle {name: "Dinghy".to_string(), price: Money::new(900.0)}, Mass::from_kilograms(5.0)));
+    println!("Fleet of {} vehicles:", fleet.len());
+    for vehicle in fleet.iter() {
+        println!("    {:?}: {}", vehicle.capabilities(), vehicle.describe());
+    }
+    println!();
+
+    println!("Vehicles that can go on water: {}", fleet.with(Capability::Sea).count());
+    println!("Vehicles on land:");
+    for land_vehicle in fleet.land() {
+        println!("{}", land_vehicle.drift());
+    }
+    println!("Vehicles at sea:");
+    for sea_vehicle in fleet.sea() {
+        println!("{}", sea_vehicle.drop_sail());
+    }
+    println!("Vehicles in the air:");
+    for air_vehicle in fleet.air() {
+        println!("{}", air_vehicle.fly());
+    }
+    println!();
+
+    // back to the concrete type, a Car has horse power, a dyn Vehicle doesn't
+    let horse_power: Power = fleet.of_type::<poly::Car>().map(|car| car.horse_power).sum();
+    println!("Horse power of all cars in the fleet: {} ({:.1} kW)", horse_power, horse_power.kilowatts());
+    let airplanes = fleet.iter().filter(|vehicle| vehicle.is::<poly::Airplane>()).count();
+    println!("Airplanes in the fleet: {}", airplanes);
+
+    let boats = fleet.take::<poly::Boat>();
+    println!("Sold {} boats, {} vehicles left, empty: {}", boats.len(), fleet.len(), fleet.is_empty());
+
+    println!("\n====================================================================================================\n");
+
+    // quantities divide and multiply into other quantities, and convert between unit systems
+    let flight = Length::from_kilometers(1200.0);
+    let cruise_speed = flight / Duration::from_secs(90 * 60);
+    println!("The airplane flies {:.0} miles in 1.5 hours, that is {} or {:.0} knots", flight.miles(), cruise_speed, cruise_speed.knots());
+    let trip = Length::from_miles(100.0);
+    let trip_time = trip / Speed::from_kilometers_per_hour(120.0);
+    println!("The car drives {:.1} km at 120 km/h in {:.1} minutes, back again it has driven {} m", trip.kilometers(), trip_time.as_secs_f64() / 60.0, (trip * 2.0).meters());
+
+    let usd_to_eur = ExchangeRate::<Usd, Eur>::new(0.92);
+    let boat_prices: Money = boats.iter().map(|boat| boat.vehicle.price).sum();
+    println!("The sold boats brought in {}, which is {} or back again {}", boat_prices, boat_prices * usd_to_eur, boat_prices * usd_to_eur * usd_to_eur.inverse());
+    // boat_prices + boat_prices * usd_to_eur;     // Money<Usd> + Money<Eur> doesn't compile
+    let anchors: Mass = boats.iter().map(|boat| boat.anchor_weight).sum();
+    println!("Their anchors weigh {} ({:.1} lb)", anchors, anchors.pounds());
+
+    println!("\n====================================================================================================\n");
+
+    // every vehicle type has its specification as constants, known before the program runs
+    println!("Vehicle types:");
+    for spec in &REGISTRY {
+        println!("    {:<15} {:?}, up to {}, {} passengers, {} wheels, drives on {:?}", spec.model, spec.category, spec.max_speed, spec.max_passengers, spec.wheels, spec.terrain);
+    }
+    println!("Most passengers fit in a {}", associated_constants::MOST_PASSENGERS.model);
+    for terrain in [Terrain::Road, Terrain::Water, Terrain::Air] {
+        if let Some(spec) = associated_constants::fastest_on(terrain) {
+            println!("Fastest on {:?}: {}", terrain, spec.model);
+        }
+    }
+    let for_a_team: Vec<&str> = associated_constants::carrying(6).map(|spec| spec.model).collect();
+    println!("Fit a team of 6: {:?}", for_a_team);
+    println!("A group of 23 needs {} car trips or {} boat trips", associated_constants::trips_needed::<poly::Car>(23), associated_constants::trips_needed::<poly::Boat>(23));
+    println!("Is a {} amphibious? {}", poly::AmphibiousBoat::MODEL, poly::AmphibiousBoat::AMPHIBIOUS);
+    println!();
+
+    for car in fleet.of_type::<poly::Car>() {
+        print!("{}", render_text(&associated_constants::test_drive_land_vehicle_v2(car)));
+    }
+    for amphibious_boat in fleet.of_type::<poly::AmphibiousBoat>() {
+        print!("{}", render_text(&associated_constants::test_drive_land_vehicle_v2(amphibious_boat)));
+    }
+    for boat in &boats {
+        print!("{}", render_text(&associated_constants::test_drive_sea_vehicle_v2(boat)));
+    }
+    for airplane in fleet.of_type::<poly::Airplane>() {
+        print!("{}", render_text(&associated_constants::test_drive_air_vehicle_v2(airplane)));
+    }
+    // the test drives only return events, printing them as text is one way, JSON for another program is another
+    if let Some(airplane) = fleet.of_type::<poly::Airplane>().next() {
+        println!("The same air test drive as JSON:\n{}", render_json(&associated_constants::test_drive_air_vehicle_v2(airplane)));
+    }
+
+    println!("\n====================================================================================================\n");
+
+    // buying vehicles is a knapsack problem, the price is the weight and the usefulness the value
+    let on_sale: Vec<Vehicles> = vec![
+        poly::Car::new(poly::AbstractVehicle {name: "Family Car".to_string(), price: Money::new(32000.0)}, Power::from_horse_power(150.0)).into(),
+        poly::Car::new(poly::AbstractVehicle {name: "Sports Car".to_string(), price: Money::new(95000.0)}, Power::from_horse_power(450.0)).into(),
+        poly::Car::new(poly::AbstractVehicle {name: "Old Van".to_string(), price: Money::new(8000.0)}, Power::from_horse_power(90.0)).into(),
+        poly::Boat::new(poly::AbstractVehicle {name: "Fishing Boat".to_string(), price: Money::new(45000.0)}, Mass::from_kilograms(40.0)).into(),
+        poly::AmphibiousBoat::new(poly::Boat::new(poly::AbstractVehicle {name: "Duck Tour".to_string(), price: Money::new(120000.0)}, Mass::from_kilograms(20.0)), 6).into(),
+        poly::Airplane::new(poly::AbstractVehicle {name: "Crop Duster".to_string(), price: Money::new(180000.0)}, Length::from_meters(12.0)).into(),
+        poly::Airplane::new(poly::AbstractVehicle {name: "Jet".to_string(), price: Money::new(2500000.0)}, Length::from_meters(35.0)).into()
+    ];
+    let print_plan = |title: &str, plan: &Plan| {
+        println!("{}: {:.0} points for {}", title, plan.total_score, plan.total_price);
+        for vehicle in &plan.chosen {
+            println!("    {:?} {} for {}", vehicle.kind(), vehicle.name(), vehicle.price());
+        }
+    };
+
+    let budget = Money::new(250000.0);
+    let horse_power_only = Utility::new().per_horse_power(1.0);
+    match FleetPlanner::new().utility(horse_power_only).plan(&on_sale, budget) {
+        Ok(plan) => print_plan("Most horse power for 250000", &plan),
+        Err(error) => println!("No fleet: {}", error)
+    }
+    match FleetPlanner::new().utility(horse_power_only).require(Capability::Air).plan(&on_sale, budget) {
+        Ok(plan) => print_plan("Most horse power for 250000 with something that can fly", &plan),
+        Err(error) => println!("No fleet: {}", error)
+    }
+    match FleetPlanner::new().plan(&on_sale, budget) {
+        Ok(plan) => print_plan("Best fleet for 250000 by the default utility", &plan),
+        Err(error) => println!("No fleet: {}", error)
+    }
+    let fast_and_versatile = Utility::new().per_terrain(300.0).per_kilometer_per_hour(1.0);
+    let planner = FleetPlanner::new()
+        .utility(fast_and_versatile)
+        .require(Capability::Air)
+        .require_kind(VehicleKind::AmphibiousBoat)
+        .price_unit(100.0)
+        .solver(knapsack::get_knapsack_items_par_iter_with_lock::<TicketLock>);
+    match planner.plan(&on_sale, Money::new(150000.0)) {
+        Ok(plan) => print_plan("Fleet for 150000 with an amphibious boat and in the air", &plan),
+        Err(error) => println!("No fleet for 150000 with an amphibious boat and in the air: {}", error)
+    }
+    match planner.plan(&on_sale, Money::new(400000.0)) {
+        Ok(plan) => print_plan("Fast fleet for 400000 with an amphibious boat and in the air", &plan),
+        Err(error) => println!("No fleet: {}", error)
+    }
+
+    println!("\n====================================================================================================\n");
+
+    // the vehicles on sale travel from the city to the island, each over the terrain it can go on
+    let mut world = World::new();
+    let city = world.add_place("City");
+    let harbour = world.add_place("Harbour");
+    let airport = world.add_place("Airport");
+    let island = world.add_place("Island");
+    let connections = [
+        (city, harbour, Terrain::Road, Length::from_kilometers(30.0)),
+        (city, airport, Terrain::Road, Length::from_kilometers(15.0)),
+        (harbour, island, Terrain::Water, Length::from_nautical_miles(20.0)),
+        (airport, island, Terrain::Air, Length::from_kilometers(60.0))
+    ];
+    for (a, b, terrain, length) in connections {
+        world.connect(a, b, terrain, length).expect("the lengths are positive");
+    }
+    let tick = Duration::from_secs(15 * 60);
+    for vehicle in on_sale.iter().filter(|vehicle| vehicle.kind() != VehicleKind::Car || vehicle.name() == "Old Van") {
+        let start = world.place(if vehicle.kind() == VehicleKind::Boat { "Harbour" } else { "City" }).expect("both places were added");
+        match world.route(spec_of(vehicle.kind()), start, island) {
+            Ok(route) => {
+                let stops: Vec<&str> = route.places().into_iter().map(|place| world.name(place)).collect();
+                println!("{} takes {:?}, {:.1} km in {:.0} minutes", vehicle.name(), stops, route.length().kilometers(), route.duration().as_secs_f64() / 60.0);
+                for event in route_simulation::simulate(&route, tick) {
+                    println!("    {}", route_simulation::describe_event(&world, &route, &event));
+                    // every terrain has its own behaviour
+                    if let EventKind::Departed { terrain, .. } = event.kind {
+                        let behaviour = match terrain {
+                            Terrain::Road => vehicle.drive(),
+                            Terrain::Water => vehicle.as_sea().expect("only sea vehicles get a route over water").drop_sail(),
+                            Terrain::Air => vehicle.as_air().expect("only air vehicles get a route through the air").fly()
+                        };
+                        println!("        {}", behaviour);
+                    }
+                }
+            }
+            Err(error) => println!("{}: {}", vehicle.name(), error)
+        }
+    }
+
+    println!("\n====================================================================================================\n");
+
+    // names are owned, so vehicles can be made up while the program runs, saved and read back
+    let mut garage = on_sale.clone();
+    for number in 1..=2 {
+        garage.push(poly::Car::new(poly::AbstractVehicle {name: format!("Rental Car {}", number), price: Money::new(21000.0)}, Power::from_horse_power(95.0)).into());
+    }
+    let json = vehicle_records::to_json(&garage);
+    let bytes = vehicle_records::to_bytes(&garage);
+    println!("{} vehicles take {} bytes as JSON and {} bytes in binary", garage.len(), json.len(), bytes.len());
+    let same_from_json = vehicle_records::from_json(&json).is_ok_and(|vehicles| vehicles == garage);
+    let same_from_bytes = vehicle_records::from_bytes(&bytes).is_ok_and(|vehicles| vehicles == garage);
+    println!("Read back the same vehicles from JSON: {}, from binary: {}", same_from_json, same_from_bytes);
+    println!("The amphibious boat as JSON:\n{}", vehicle_records::to_json(&garage[4..5]));
+    let broken = [
+        r#"{"version": 2, "vehicles": []}"#,
+        r#"{"version": 1, "vehicles": [{"type": "Bike", "vehicle": {"name": "Bmx", "price": {"amount": 300.0, "currency": "USD"}}}]}"#,
+        r#"{"version": 1, "vehicles": [{"type": "Boat", "vehicle": {"name": "Tub", "price": {"amount": 50.0, "currency": "USD"}}, "anchor_weight": 1.0, "colour": "red"}]}"#
+    ];
+    for json in broken {
+        if let Err(error) = vehicle_records::from_json(json) {
+            println!("    {}", error);
+        }
+    }
+    if let Err(error) = vehicle_records::from_bytes(&bytes[..bytes.len() - 3]) {
+        println!("    {}", error);
+    }
 
-    println!("The sum of all the car prizes is: {}", car_sum_prices);
+    println!("\n====================================================================================================\n");
+
+    // the filters and sums written by hand above, as queries over the garage
+    let garage: Fleet = garage.into_iter().collect();
+    let queries = [
+        "kind = Car and price < 65000 order by price desc",
+        "sum(price) where kind = Car",
+        "can Sea or can Air order by max_speed desc limit 3",
+        "count, sum(price), max(horse_power) group by kind order by sum(price) desc",
+        "price < cheap",
+        "kind > Car",
+        "avg(name) group by kind"
+    ];
+    for query in queries {
+        match garage.query(query) {
+            Ok(result) => println!("{}\n{}\n", query, result),
+            Err(error) => println!("{}\n", error.render(query))
+        }
+    }
+    match fleet_query::parse("not (can Sea or price > 100000)") {
+        Ok(query) => println!("Parsed once, run twice: {} of {} vehicles in the garage, any in the first fleet: {}", query.run(&garage).len(), garage.len(), !query.run(&fleet).is_empty()),
+        Err(error) => println!("{}", error)
+    }
+
+    println!("\n====================================================================================================\n");
+
+    // prices as decimals, ten parking tickets of 0.10 dollar don't add up to 1 as f64
+    let tickets = [Money::<Usd>::new(0.1); 10];
+    let float_sum: Money = tickets.iter().copied().sum();
+    match sum_prices(tickets.iter().copied()) {
+        Ok(exact_sum) => println!("Ten tickets of 0.10 USD as f64: {}, as decimals: {}", float_sum, exact_sum),
+        Err(error) => println!("The tickets can't be added up: {}", error)
+    }
+    // one ticket that isn't a finite amount makes the whole sum an error instead of leaving it out
+    if let Err(error) = sum_prices(tickets.iter().copied().chain([Money::new(f64::NAN)])) {
+        println!("With a broken ticket: {}", error);
+    }
+    let third = Decimal::ONE.div(Decimal::from(3), 2, RoundingMode::HalfEven);
+    println!("A third in cents is {}, three of them are {}", third, third * Decimal::from(3));
+    for mode in RoundingMode::ALL {
+        let rounded: Vec<String> = ["2.345", "-2.345", "2.355", "2.341"]
+            .iter()
+            .map(|number| match number.parse::<Decimal>() {
+                Ok(number) => number.round(2, mode).to_string(),
+                Err(error) => format!("{:?}: {}", number, error)
+            })
+            .collect();
+        println!("    {:<10} {}", format!("{:?}", mode), rounded.join("  "));
+    }
+    println!();
+
+    let rates = match RatesTable::parse(RATES) {
+        Ok(rates) => rates,
+        Err(error) => panic!("the rates in iterators.rs are broken: {}", error)
+    };
+    let currencies: Vec<String> = rates.currencies().map(|currency| currency.to_string()).collect();
+    println!("Rates for 1 {}: {}", rates.base(), currencies.join(", "));
+    let eur = CurrencyCode::of::<Eur>();
+    let engine = PricingEngine::new(rates.clone(), eur)
+        .depreciation(Category::Watercraft, Depreciation::DecliningBalance { yearly: Decimal::new(10, 2) })
+        .rule(Rule::flat("registration", Price::new(Decimal::from(150), eur)).only(Category::Automobile).only(Category::Amphibian))
+        .rule(Rule::above("luxury tax", Price::new(Decimal::from(60000), CurrencyCode::USD), Decimal::new(10, 2)))
+        .rule(Rule::percentage("VAT", Decimal::new(21, 2), Basis::Subtotal));
+    for (category, schedule) in [Category::Automobile, Category::Watercraft, Category::Amphibian, Category::Aircraft].map(|category| (category, engine.schedule(category))) {
+        println!("    {:<11} {:<38} 100000 is worth {} after 5 years", category.name(), schedule.to_string(), schedule.value_after(Decimal::from(100000), 5, 2, RoundingMode::HalfEven));
+    }
+    let ages = [3, 1, 12, 5, 2, 8, 0];
+    match engine.quote_fleet(on_sale.iter().zip(ages)) {
+        Ok(quote) => {
+            println!("{}", quote);
+            let depreciation: Decimal = quote.quotes.iter().flat_map(|quote| &quote.lines).filter(|line| line.kind == LineKind::Depreciation).map(|line| line.amount).sum();
+            println!("Of which depreciation: {} {}", depreciation, quote.currency);
+        }
+        Err(error) => println!("{}", error)
+    }
+    // every mode gives other cents, but every quote still adds up to its lines to the cent
+    for mode in [RoundingMode::HalfUp, RoundingMode::Down, RoundingMode::Ceiling] {
+        let engine = PricingEngine::new(rates.clone(), eur).rounding(mode).rule(Rule::percentage("VAT", Decimal::new(21, 2), Basis::Value));
+        if let Ok(quote) = engine.quote_fleet(on_sale.iter().zip(ages)) {
+            let adds_up = quote.quotes.iter().all(|quote| quote.lines.iter().map(|line| line.amount).sum::<Decimal>() == quote.total);
+            println!("    {:<10} {:>14} {}, every quote adds up: {}", format!("{:?}", mode), quote.total, quote.currency, adds_up);
+        }
+    }
+    let in_yen = PricingEngine::new(rates.clone(), "JPY".parse().unwrap_or(CurrencyCode::USD)).quote(on_sale[2].as_vehicle(), 0);
+    if let Ok(quote) = in_yen {
+        println!("{}", quote);
+    }
+    let gbp: CurrencyCode = "GBP".parse().unwrap_or(CurrencyCode::USD);
+    if let Err(error) = PricingEngine::new(rates.clone(), gbp).quote(on_sale[2].as_vehicle(), 0) {
+        println!("{}", error);
+    }
+    let rates = rates.rate(gbp, Decimal::new(79, 2), 2);
+    if let Ok(rate) = rates.exchange_rate(gbp, eur, 4, RoundingMode::HalfEven) {
+        println!("With pounds in the table 1 GBP is {} EUR", rate);
+    }
+    if let Err(error) = RatesTable::parse("base USD\nEUR -0.92") {
+        println!("{}", error);
+    }
 
     println!("\n====================================================================================================\n");
 
@@ -0,0 +1,228 @@
+use super::default_type_parameters::Speed;
+use super::traits::poly::{AirVehicle, Airplane, AmphibiousBoat, Boat, Car, LandVehicle, SeaVehicle};
+use super::traits::VehicleKind;
+use super::traits::vehicle_events::{TestDrive, VehicleEvent};
+
+// Associated constants
+// A trait can require constants next to functions. Every type fills them in once, at compile time,
+// and generic code reads them through the type: T::MAX_SPEED, no object needed.
+// Like functions they can have a default which a type may override (WHEELS),
+// and a default may be computed from the other constants (SECONDARY_SPEED, AMPHIBIOUS).
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Terrain {
+    Road,
+    Water,
+    Air
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Category {
+    Automobile,
+    Watercraft,
+    Amphibian,
+    Aircraft
+}
+
+impl Category {
+    pub const fn name(self) -> &'static str {
+        match self {
+            Category::Automobile => "Automobile",
+            Category::Watercraft => "Watercraft",
+            Category::Amphibian => "Amphibian",
+            Category::Aircraft => "Aircraft"
+        }
+    }
+}
+
+// const fn, so it can be used to compute other constants
+pub const fn covers(terrain: &[Terrain], wanted: Terrain) -> bool {
+    // iterators and == on enums aren't available in a const fn, a while loop and `as u8` are
+    let mut i = 0;
+    while i < terrain.len() {
+        if terrain[i] as u8 == wanted as u8 {
+            return true;
+        }
+        i += 1;
+    }
+    false
+}
+
+pub trait Specification {
+    const KIND: VehicleKind;
+    const MODEL: &'static str;
+    const CATEGORY: Category;
+    // on the first terrain, the other ones are done at SECONDARY_SPEED
+    const MAX_SPEED: Speed;
+    const TERRAIN: &'static [Terrain];
+    const SECONDARY_SPEED: Speed = Self::MAX_SPEED;
+    const MAX_PASSENGERS: u16;
+    const WHEELS: u8 = 0;
+    const AMPHIBIOUS: bool = covers(Self::TERRAIN, Terrain::Road) && covers(Self::TERRAIN, Terrain::Water);
+}
+
+impl Specification for Car {
+    const KIND: VehicleKind = VehicleKind::Car;
+    const MODEL: &'static str = "Car";
+    const CATEGORY: Category = Category::Automobile;
+    const MAX_SPEED: Speed = Speed::from_kilometers_per_hour(200.0);
+    const TERRAIN: &'static [Terrain] = &[Terrain::Road];
+    const MAX_PASSENGERS: u16 = 5;
+    const WHEELS: u8 = 4;
+}
+
+impl Specification for Boat {
+    const KIND: VehicleKind = VehicleKind::Boat;
+    const MODEL: &'static str = "Boat";
+    const CATEGORY: Category = Category::Watercraft;
+    const MAX_SPEED: Speed = Speed::from_knots(30.0);
+    const TERRAIN: &'static [Terrain] = &[Terrain::Water];
+    const MAX_PASSENGERS: u16 = 12;
+}
+
+impl Specification for AmphibiousBoat {
+    const KIND: VehicleKind = VehicleKind::AmphibiousBoat;
+    const MODEL: &'static str = "AmphibiousBoat";
+    const CATEGORY: Category = Category::Amphibian;
+    const MAX_SPEED: Speed = Speed::from_kilometers_per_hour(110.0);
+    const TERRAIN: &'static [Terrain] = &[Terrain::Road, Terrain::Water];
+    const SECONDARY_SPEED: Speed = Speed::from_knots(6.0);
+    const MAX_PASSENGERS: u16 = 6;
+    const WHEELS: u8 = 4;
+}
+
+impl Specification for Airplane {
+    const KIND: VehicleKind = VehicleKind::Airplane;
+    const MODEL: &'static str = "Airplane";
+    const CATEGORY: Category = Category::Aircraft;
+    const MAX_SPEED: Speed = Speed::from_kilometers_per_hour(900.0);
+    const TERRAIN: &'static [Terrain] = &[Terrain::Air, Terrain::Road];
+    // taxiing
+    const SECONDARY_SPEED: Speed = Speed::from_kilometers_per_hour(50.0);
+    const MAX_PASSENGERS: u16 = 180;
+    const WHEELS: u8 = 10;
+}
+
+// Mistakes in the constants fail the build instead of a test
+const _: () = assert!(AmphibiousBoat::AMPHIBIOUS && !Car::AMPHIBIOUS && !Airplane::AMPHIBIOUS);
+const _: () = assert!(covers(Airplane::TERRAIN, Terrain::Air) && !covers(Boat::TERRAIN, Terrain::Air));
+
+//=================================================================
+// The constants of one type as a value, so different types fit in one table
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct VehicleSpec {
+    pub kind: VehicleKind,
+    pub model: &'static str,
+    pub category: Category,
+    pub max_speed: Speed,
+    pub terrain: &'static [Terrain],
+    pub secondary_speed: Speed,
+    pub max_passengers: u16,
+    pub wheels: u8
+}
+
+impl VehicleSpec {
+    pub const fn of<T: Specification>() -> Self {
+        Self {
+            kind: T::KIND,
+            model: T::MODEL,
+            category: T::CATEGORY,
+            max_speed: T::MAX_SPEED,
+            terrain: T::TERRAIN,
+            secondary_speed: T::SECONDARY_SPEED,
+            max_passengers: T::MAX_PASSENGERS,
+            wheels: T::WHEELS
+        }
+    }
+
+    pub const fn covers(&self, terrain: Terrain) -> bool {
+        covers(self.terrain, terrain)
+    }
+
+    pub const fn speed_on(&self, terrain: Terrain) -> Option<Speed> {
+        if terrain as u8 == self.terrain[0] as u8 {
+            Some(self.max_speed)
+        } else if self.covers(terrain) {
+            Some(self.secondary_speed)
+        } else {
+            None
+        }
+    }
+}
+
+// Every built-in vehicle type, built at compile time
+pub const REGISTRY: [VehicleSpec; 4] = [
+    VehicleSpec::of::<Car>(),
+    VehicleSpec::of::<Boat>(),
+    VehicleSpec::of::<AmphibiousBoat>(),
+    VehicleSpec::of::<Airplane>()
+];
+
+// Also evaluated at compile time, with a loop over the registry
+pub const MOST_PASSENGERS: VehicleSpec = most_passengers(&REGISTRY);
+
+const fn most_passengers(registry: &[VehicleSpec]) -> VehicleSpec {
+    let mut most = registry[0];
+    let mut i = 1;
+    while i < registry.len() {
+        if registry[i].max_passengers > most.max_passengers {
+            most = registry[i];
+        }
+        i += 1;
+    }
+    most
+}
+
+// The specification of a vehicle that is only known at runtime, e.g. a Vehicles value
+pub fn spec_of(kind: VehicleKind) -> &'static VehicleSpec {
+    REGISTRY.iter().find(|spec| spec.kind == kind).expect("every vehicle kind is in the registry")
+}
+
+pub fn fastest_on(terrain: Terrain) -> Option<&'static VehicleSpec> {
+    REGISTRY
+        .iter()
+        .filter_map(|spec| Some((spec, spec.speed_on(terrain)?)))
+        .max_by(|(_, a), (_, b)| a.partial_cmp(b).expect("speeds aren't NaN"))
+        .map(|(spec, _)| spec)
+}
+
+pub fn carrying(passengers: u16) -> impl Iterator<Item = &'static VehicleSpec> {
+    REGISTRY.iter().filter(move |spec| spec.max_passengers >= passengers)
+}
+
+//=================================================================
+// Generic functions read the constants of the type they are called with
+
+// like poly::test_drive_land_vehicle_v1, but it knows where it may drive and how fast
+pub fn test_drive_land_vehicle_v2<T: LandVehicle + Specification>(land_vehicle: &T) -> Vec<VehicleEvent> {
+    let mut events = vec![
+        VehicleEvent::TestDrive(TestDrive::LandVehicleV2 { category: T::CATEGORY.name(), wheels: T::WHEELS, max_speed: T::MAX_SPEED }),
+        land_vehicle.drive()
+    ];
+    // a land vehicle that also floats gets tested on the water as well
+    if T::AMPHIBIOUS {
+        events.push(VehicleEvent::ContinuedOnWater { model: T::MODEL, speed: T::SECONDARY_SPEED });
+    }
+    events.push(land_vehicle.drift());
+    events
+}
+
+pub fn test_drive_sea_vehicle_v2<T: SeaVehicle + Specification>(sea_vehicle: &T) -> Vec<VehicleEvent> {
+    vec![
+        VehicleEvent::TestDrive(TestDrive::SeaVehicleV2 { category: T::CATEGORY.name(), max_passengers: T::MAX_PASSENGERS, max_speed: T::MAX_SPEED }),
+        sea_vehicle.drive(),
+        sea_vehicle.drop_sail()
+    ]
+}
+
+pub fn test_drive_air_vehicle_v2<T: AirVehicle + Specification>(air_vehicle: &T) -> Vec<VehicleEvent> {
+    vec![
+        VehicleEvent::TestDrive(TestDrive::AirVehicleV2 { category: T::CATEGORY.name(), max_passengers: T::MAX_PASSENGERS, max_speed: T::MAX_SPEED }),
+        air_vehicle.fly()
+    ]
+}
+
+// how many trips a group needs, with T only as a type
+pub fn trips_needed<T: Specification>(passengers: u16) -> u16 {
+    passengers.div_ceil(T::MAX_PASSENGERS)
+}
@@ -0,0 +1,305 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, Mul, Neg, Sub};
+use std::str::FromStr;
+
+// Decimal numbers for money
+// An f64 can't hold 0.1, so 0.1 + 0.2 is 0.30000000000000004 and sums of prices drift.
+// A Decimal is an integer count of units and a scale, 12.34 is 1234 units at scale 2,
+// so adding, subtracting and multiplying are exact. Only division and round lose digits,
+// and both take the RoundingMode that decides which way the lost digits go.
+//
+// Arithmetic through the operators panics on overflow, like integers in a debug build,
+// the checked_* methods return None instead. 38 digits are enough for any price.
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RoundingMode {
+    // away from zero when the dropped digits are a half or more, 2.345 -> 2.35, -2.345 -> -2.35
+    HalfUp,
+    // towards zero on exactly a half, 2.345 -> 2.34
+    HalfDown,
+    // to the even neighbour on exactly a half, 2.345 -> 2.34 but 2.355 -> 2.36 (banker's rounding)
+    HalfEven,
+    // away from zero, 2.341 -> 2.35
+    Up,
+    // towards zero, truncating, 2.349 -> 2.34
+    Down,
+    // towards positive infinity, 2.341 -> 2.35 and -2.349 -> -2.34
+    Ceiling,
+    // towards negative infinity, 2.349 -> 2.34 and -2.341 -> -2.35
+    Floor
+}
+
+impl RoundingMode {
+    pub const ALL: [RoundingMode; 7] = [
+        RoundingMode::HalfUp,
+        RoundingMode::HalfDown,
+        RoundingMode::HalfEven,
+        RoundingMode::Up,
+        RoundingMode::Down,
+        RoundingMode::Ceiling,
+        RoundingMode::Floor
+    ];
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Decimal {
+    units: i128,
+    scale: u32
+}
+
+impl Decimal {
+    pub const ZERO: Decimal = Decimal::new(0, 0);
+    pub const ONE: Decimal = Decimal::new(1, 0);
+
+    // Decimal::new(1234, 2) is 12.34
+    pub const fn new(units: i128, scale: u32) -> Self {
+        Self { units, scale }
+    }
+
+    // the shortest decimal that prints as the same f64, so 0.1 becomes exactly 0.1.
+    // None for NaN, infinity and numbers too large for 38 digits
+    pub fn from_f64(value: f64) -> Option<Decimal> {
+        if !value.is_finite() {
+            return None;
+        }
+        // Display of an f64 never uses an exponent
+        value.to_string().parse().ok()
+    }
+
+    pub const fn is_zero(self) -> bool {
+        self.units == 0
+    }
+
+    pub const fn is_negative(self) -> bool {
+        self.units < 0
+    }
+
+    // the same value with `scale` digits after the point, exact when that adds digits
+    pub fn round(self, scale: u32, mode: RoundingMode) -> Decimal {
+        self.checked_round(scale, mode).expect("decimal overflow")
+    }
+
+    pub fn checked_round(self, scale: u32, mode: RoundingMode) -> Option<Decimal> {
+        if scale >= self.scale {
+            let units = self.units.checked_mul(pow10(scale - self.scale)?)?;
+            return Some(Decimal::new(units, scale));
+        }
+        let units = rounded_quotient(self.units, pow10(self.scale - scale)?, mode)?;
+        Some(Decimal::new(units, scale))
+    }
+
+    // without trailing zeros, 1.2500 -> 1.25
+    pub fn normalize(self) -> Decimal {
+        let mut decimal = self;
+        while decimal.scale > 0 && decimal.units % 10 == 0 {
+            decimal = Decimal::new(decimal.units / 10, decimal.scale - 1);
+        }
+        decimal
+    }
+
+    pub fn checked_add(self, other: Decimal) -> Option<Decimal> {
+        let (a, b, scale) = align(self, other)?;
+        Some(Decimal::new(a.checked_add(b)?, scale))
+    }
+
+    pub fn checked_sub(self, other: Decimal) -> Option<Decimal> {
+        let (a, b, scale) = align(self, other)?;
+        Some(Decimal::new(a.checked_sub(b)?, scale))
+    }
+
+    // the scales add up, trailing zeros are dropped so repeated products stay small
+    pub fn checked_mul(self, other: Decimal) -> Option<Decimal> {
+        Some(Decimal::new(self.units.checked_mul(other.units)?, self.scale.checked_add(other.scale)?).normalize())
+    }
+
+    // self / divisor with `scale` digits after the point, rounded once. None when dividing by zero
+    pub fn checked_div(self, divisor: Decimal, scale: u32, mode: RoundingMode) -> Option<Decimal> {
+        if divisor.is_zero() {
+            return None;
+        }
+        // self.units / 10^self.scale / (divisor.units / 10^divisor.scale) * 10^scale, in integers
+        let numerator = self.units.checked_mul(pow10(scale.checked_add(divisor.scale)?)?)?;
+        let denominator = divisor.units.checked_mul(pow10(self.scale)?)?;
+        Some(Decimal::new(rounded_quotient(numerator, denominator, mode)?, scale))
+    }
+
+    pub fn div(self, divisor: Decimal, scale: u32, mode: RoundingMode) -> Decimal {
+        self.checked_div(divisor, scale, mode).expect("decimal division by zero or overflow")
+    }
+}
+
+fn pow10(exponent: u32) -> Option<i128> {
+    10i128.checked_pow(exponent)
+}
+
+// both as units at the larger of the two scales
+fn align(a: Decimal, b: Decimal) -> Option<(i128, i128, u32)> {
+    let scale = a.scale.max(b.scale);
+    Some((a.units.checked_mul(pow10(scale - a.scale)?)?, b.units.checked_mul(pow10(scale - b.scale)?)?, scale))
+}
+
+// numerator / denominator as an integer, the remainder decides the last step
+fn rounded_quotient(numerator: i128, denominator: i128, mode: RoundingMode) -> Option<i128> {
+    let quotient = numerator.checked_div(denominator)?;
+    let remainder = numerator % denominator;
+    if remainder == 0 {
+        return Some(quotient);
+    }
+    let negative = (numerator < 0) != (denominator < 0);
+    let remainder = remainder.unsigned_abs();
+    // compared without doubling, 2 * remainder could overflow
+    let rest = denominator.unsigned_abs() - remainder;
+    let away_from_zero = match mode {
+        RoundingMode::HalfUp => remainder >= rest,
+        RoundingMode::HalfDown => remainder > rest,
+        RoundingMode::HalfEven => remainder > rest || (remainder == rest && quotient % 2 != 0),
+        RoundingMode::Up => true,
+        RoundingMode::Down => false,
+        RoundingMode::Ceiling => !negative,
+        RoundingMode::Floor => negative
+    };
+    match (away_from_zero, negative) {
+        (false, _) => Some(quotient),
+        (true, false) => quotient.checked_add(1),
+        (true, true) => quotient.checked_sub(1)
+    }
+}
+
+// 1.5 and 1.50 are the same number, only Display shows the difference
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Decimal) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Decimal {}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Decimal) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Decimal) -> Ordering {
+        match align(*self, *other) {
+            Some((a, b, _)) => a.cmp(&b),
+            // too many digits to line up in an i128, compare the digits instead
+            None => self.units.signum().cmp(&other.units.signum()).then_with(|| {
+                let magnitude = compare_magnitude(*self, *other);
+                if self.is_negative() { magnitude.reverse() } else { magnitude }
+            })
+        }
+    }
+}
+
+// |a| against |b|, the integer digits first, then the fractions padded to the same length
+fn compare_magnitude(a: Decimal, b: Decimal) -> Ordering {
+    let digits = |decimal: Decimal| {
+        let text = decimal.to_string();
+        let text = text.trim_start_matches('-');
+        let (integer, fraction) = text.split_once('.').unwrap_or((text, ""));
+        (integer.to_string(), fraction.to_string())
+    };
+    let ((a_integer, a_fraction), (b_integer, b_fraction)) = (digits(a), digits(b));
+    let width = a_fraction.len().max(b_fraction.len());
+    a_integer
+        .len()
+        .cmp(&b_integer.len())
+        .then_with(|| a_integer.cmp(&b_integer))
+        .then_with(|| format!("{:0<width$}", a_fraction).cmp(&format!("{:0<width$}", b_fraction)))
+}
+
+impl Add for Decimal {
+    type Output = Decimal;
+
+    fn add(self, other: Decimal) -> Decimal {
+        self.checked_add(other).expect("decimal overflow")
+    }
+}
+
+impl Sub for Decimal {
+    type Output = Decimal;
+
+    fn sub(self, other: Decimal) -> Decimal {
+        self.checked_sub(other).expect("decimal overflow")
+    }
+}
+
+impl Mul for Decimal {
+    type Output = Decimal;
+
+    fn mul(self, other: Decimal) -> Decimal {
+        self.checked_mul(other).expect("decimal overflow")
+    }
+}
+
+impl Neg for Decimal {
+    type Output = Decimal;
+
+    fn neg(self) -> Decimal {
+        Decimal::new(-self.units, self.scale)
+    }
+}
+
+impl Sum for Decimal {
+    fn sum<I: Iterator<Item = Decimal>>(iter: I) -> Decimal {
+        iter.fold(Decimal::ZERO, |acc, decimal| acc + decimal)
+    }
+}
+
+impl From<i64> for Decimal {
+    fn from(value: i64) -> Decimal {
+        Decimal::new(value as i128, 0)
+    }
+}
+
+// every digit after the point that is in the scale, 4140.00 stays 4140.00
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = self.units.unsigned_abs().to_string();
+        let scale = self.scale as usize;
+        let digits = if digits.len() <= scale { format!("{}{}", "0".repeat(scale + 1 - digits.len()), digits) } else { digits };
+        let (integer, fraction) = digits.split_at(digits.len() - scale);
+        let sign = if self.units < 0 { "-" } else { "" };
+        let number = if fraction.is_empty() { format!("{}{}", sign, integer) } else { format!("{}{}.{}", sign, integer, fraction) };
+        // so {:>12} lines up columns of amounts
+        f.pad(&number)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseDecimalError {
+    pub input: String
+}
+
+impl fmt::Display for ParseDecimalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} is not a decimal number", self.input)
+    }
+}
+
+impl std::error::Error for ParseDecimalError {}
+
+// "12", "-0.50", "1_000.25"; no exponents, at most 38 digits
+impl FromStr for Decimal {
+    type Err = ParseDecimalError;
+
+    fn from_str(input: &str) -> Result<Decimal, ParseDecimalError> {
+        let error = || ParseDecimalError { input: input.to_string() };
+        let (negative, unsigned) = match input.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, input)
+        };
+        let unsigned = unsigned.replace('_', "");
+        let (integer, fraction) = unsigned.split_once('.').unwrap_or((&unsigned, ""));
+        let digits = format!("{}{}", integer, fraction);
+        if integer.is_empty() || !digits.bytes().all(|byte| byte.is_ascii_digit()) || (unsigned.contains('.') && fraction.is_empty()) {
+            return Err(error());
+        }
+        let units: i128 = digits.parse().map_err(|_| error())?;
+        Ok(Decimal::new(if negative { -units } else { units }, fraction.len() as u32))
+    }
+}
@@ -0,0 +1,462 @@
+use std::fmt;
+use std::iter::Sum;
+use std::marker::PhantomData;
+use std::ops::{Add, Div, Mul, Sub};
+use std::time::Duration;
+use serde::ser::SerializeStruct;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+// Default type parameters
+// The operator traits are generic over the right hand side, but default it to Self:
+//     pub trait Add<Rhs = Self> { type Output; fn add(self, rhs: Rhs) -> Self::Output; }
+// so `impl Add for Mass` means Mass + Mass. Writing the parameter out gives other combinations,
+// `impl Div<Duration> for Length` is Length / Duration and can return a Speed.
+//
+// Every quantity is a newtype around an f64 in SI units (watt, kilogram, meter, meter per second).
+// A Mass is not a Length, so `price + anchor_weight` doesn't compile, the bare f64 / u16 fields did.
+// Serialized they are the bare number in SI units, and read back from one.
+
+// Add, Sub and Sum for one quantity with itself, scaling by a plain number with Mul<f64> / Div<f64>
+macro_rules! quantity_arithmetic {
+    ($quantity:ident) => {
+        impl Add for $quantity {
+            type Output = $quantity;
+
+            fn add(self, rhs: $quantity) -> $quantity {
+                $quantity(self.0 + rhs.0)
+            }
+        }
+
+        impl Sub for $quantity {
+            type Output = $quantity;
+
+            fn sub(self, rhs: $quantity) -> $quantity {
+                $quantity(self.0 - rhs.0)
+            }
+        }
+
+        impl Mul<f64> for $quantity {
+            type Output = $quantity;
+
+            fn mul(self, factor: f64) -> $quantity {
+                $quantity(self.0 * factor)
+            }
+        }
+
+        impl Div<f64> for $quantity {
+            type Output = $quantity;
+
+            fn div(self, divisor: f64) -> $quantity {
+                $quantity(self.0 / divisor)
+            }
+        }
+
+        // Dividing two of the same quantity cancels the unit
+        impl Div for $quantity {
+            type Output = f64;
+
+            fn div(self, rhs: $quantity) -> f64 {
+                self.0 / rhs.0
+            }
+        }
+
+        impl Sum for $quantity {
+            fn sum<I: Iterator<Item = $quantity>>(iter: I) -> $quantity {
+                iter.fold($quantity(0.0), |acc, quantity| acc + quantity)
+            }
+        }
+    };
+}
+
+//=================================================================
+const WATTS_PER_HORSE_POWER: f64 = 745.699872;
+const KILOGRAMS_PER_POUND: f64 = 0.45359237;
+const METERS_PER_FOOT: f64 = 0.3048;
+const METERS_PER_MILE: f64 = 1609.344;
+const METERS_PER_NAUTICAL_MILE: f64 = 1852.0;
+
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd, Default, Serialize, Deserialize)]
+pub struct Power(f64);
+
+impl Power {
+    pub const fn from_watts(watts: f64) -> Self {
+        Self(watts)
+    }
+
+    pub const fn from_kilowatts(kilowatts: f64) -> Self {
+        Self(kilowatts * 1000.0)
+    }
+
+    // mechanical horse power
+    pub const fn from_horse_power(horse_power: f64) -> Self {
+        Self(horse_power * WATTS_PER_HORSE_POWER)
+    }
+
+    pub const fn watts(self) -> f64 {
+        self.0
+    }
+
+    pub const fn kilowatts(self) -> f64 {
+        self.0 / 1000.0
+    }
+
+    pub const fn horse_power(self) -> f64 {
+        self.0 / WATTS_PER_HORSE_POWER
+    }
+}
+
+impl fmt::Display for Power {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.0} hp", self.horse_power())
+    }
+}
+
+quantity_arithmetic!(Power);
+
+//=================================================================
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd, Default, Serialize, Deserialize)]
+pub struct Mass(f64);
+
+impl Mass {
+    pub const fn from_kilograms(kilograms: f64) -> Self {
+        Self(kilograms)
+    }
+
+    pub const fn from_pounds(pounds: f64) -> Self {
+        Self(pounds * KILOGRAMS_PER_POUND)
+    }
+
+    pub const fn from_tonnes(tonnes: f64) -> Self {
+        Self(tonnes * 1000.0)
+    }
+
+    pub const fn kilograms(self) -> f64 {
+        self.0
+    }
+
+    pub const fn pounds(self) -> f64 {
+        self.0 / KILOGRAMS_PER_POUND
+    }
+
+    pub const fn tonnes(self) -> f64 {
+        self.0 / 1000.0
+    }
+}
+
+impl fmt::Display for Mass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} kg", self.0)
+    }
+}
+
+quantity_arithmetic!(Mass);
+
+//=================================================================
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd, Default, Serialize, Deserialize)]
+pub struct Length(f64);
+
+impl Length {
+    pub const fn from_meters(meters: f64) -> Self {
+        Self(meters)
+    }
+
+    pub const fn from_kilometers(kilometers: f64) -> Self {
+        Self(kilometers * 1000.0)
+    }
+
+    pub const fn from_feet(feet: f64) -> Self {
+        Self(feet * METERS_PER_FOOT)
+    }
+
+    pub const fn from_miles(miles: f64) -> Self {
+        Self(miles * METERS_PER_MILE)
+    }
+
+    pub const fn from_nautical_miles(nautical_miles: f64) -> Self {
+        Self(nautical_miles * METERS_PER_NAUTICAL_MILE)
+    }
+
+    pub const fn meters(self) -> f64 {
+        self.0
+    }
+
+    pub const fn kilometers(self) -> f64 {
+        self.0 / 1000.0
+    }
+
+    pub const fn feet(self) -> f64 {
+        self.0 / METERS_PER_FOOT
+    }
+
+    pub const fn miles(self) -> f64 {
+        self.0 / METERS_PER_MILE
+    }
+
+    pub const fn nautical_miles(self) -> f64 {
+        self.0 / METERS_PER_NAUTICAL_MILE
+    }
+}
+
+impl fmt::Display for Length {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} m", self.0)
+    }
+}
+
+quantity_arithmetic!(Length);
+
+//=================================================================
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd, Default, Serialize, Deserialize)]
+pub struct Speed(f64);
+
+impl Speed {
+    pub const fn from_meters_per_second(meters_per_second: f64) -> Self {
+        Self(meters_per_second)
+    }
+
+    pub const fn from_kilometers_per_hour(kilometers_per_hour: f64) -> Self {
+        Self(kilometers_per_hour / 3.6)
+    }
+
+    pub const fn from_miles_per_hour(miles_per_hour: f64) -> Self {
+        Self(miles_per_hour * METERS_PER_MILE / 3600.0)
+    }
+
+    pub const fn from_knots(knots: f64) -> Self {
+        Self(knots * METERS_PER_NAUTICAL_MILE / 3600.0)
+    }
+
+    pub const fn meters_per_second(self) -> f64 {
+        self.0
+    }
+
+    pub const fn kilometers_per_hour(self) -> f64 {
+        self.0 * 3.6
+    }
+
+    pub const fn miles_per_hour(self) -> f64 {
+        self.0 * 3600.0 / METERS_PER_MILE
+    }
+
+    pub const fn knots(self) -> f64 {
+        self.0 * 3600.0 / METERS_PER_NAUTICAL_MILE
+    }
+}
+
+impl fmt::Display for Speed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.1} km/h", self.kilometers_per_hour())
+    }
+}
+
+quantity_arithmetic!(Speed);
+
+//=================================================================
+// Power per mass, how well a vehicle accelerates
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd, Default, Serialize, Deserialize)]
+pub struct PowerToWeight(f64);
+
+impl PowerToWeight {
+    pub const fn watts_per_kilogram(self) -> f64 {
+        self.0
+    }
+
+    pub const fn horse_power_per_tonne(self) -> f64 {
+        self.0 / WATTS_PER_HORSE_POWER * 1000.0
+    }
+}
+
+impl fmt::Display for PowerToWeight {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.1} hp/t", self.horse_power_per_tonne())
+    }
+}
+
+quantity_arithmetic!(PowerToWeight);
+
+//=================================================================
+// Derived units, every combination that means something gets its own impl, the others don't compile
+
+// distance / time = speed
+impl Div<Duration> for Length {
+    type Output = Speed;
+
+    fn div(self, time: Duration) -> Speed {
+        Speed(self.0 / time.as_secs_f64())
+    }
+}
+
+// speed * time = distance
+impl Mul<Duration> for Speed {
+    type Output = Length;
+
+    fn mul(self, time: Duration) -> Length {
+        Length(self.0 * time.as_secs_f64())
+    }
+}
+
+// distance / speed = time
+// panics when that is no Duration: a negative or NaN length or speed, or a zero speed. checked_div doesn't
+impl Div<Speed> for Length {
+    type Output = Duration;
+
+    fn div(self, speed: Speed) -> Duration {
+        self.checked_div(speed).expect("a travel time has to be a finite number of seconds, zero or more")
+    }
+}
+
+impl Length {
+    // the time it takes at `speed`, None where the / operator panics
+    pub fn checked_div(self, speed: Speed) -> Option<Duration> {
+        Duration::try_from_secs_f64(self.0 / speed.0).ok()
+    }
+}
+
+impl Div<Mass> for Power {
+    type Output = PowerToWeight;
+
+    fn div(self, mass: Mass) -> PowerToWeight {
+        PowerToWeight(self.0 / mass.0)
+    }
+}
+
+//=================================================================
+// Money is generic over its currency, with a default: Money means Money<Usd>.
+// Money<Eur> + Money<Usd> doesn't compile, an ExchangeRate has to convert it first.
+pub trait Currency: Copy + fmt::Debug + PartialEq + PartialOrd + Default {
+    const CODE: &'static str;
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd, Default)]
+pub struct Usd;
+
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd, Default)]
+pub struct Eur;
+
+impl Currency for Usd {
+    const CODE: &'static str = "USD";
+}
+
+impl Currency for Eur {
+    const CODE: &'static str = "EUR";
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd, Default)]
+pub struct Money<C: Currency = Usd> {
+    amount: f64,
+    currency: PhantomData<C>
+}
+
+impl<C: Currency> Money<C> {
+    pub const fn new(amount: f64) -> Self {
+        Self { amount, currency: PhantomData }
+    }
+
+    pub const fn amount(self) -> f64 {
+        self.amount
+    }
+
+    pub fn currency(self) -> &'static str {
+        C::CODE
+    }
+}
+
+// {"amount": 4500.0, "currency": "USD"}, the currency only exists in the type, so it is written out by hand
+impl<C: Currency> Serialize for Money<C> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut money = serializer.serialize_struct("Money", 2)?;
+        money.serialize_field("amount", &self.amount)?;
+        money.serialize_field("currency", C::CODE)?;
+        money.end()
+    }
+}
+
+// the currency in the record has to be the one in the type, 4500 EUR is no Money<Usd>
+impl<'de, C: Currency> Deserialize<'de> for Money<C> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct Record {
+            amount: f64,
+            currency: String
+        }
+
+        let record = Record::deserialize(deserializer)?;
+        if record.currency != C::CODE {
+            return Err(D::Error::custom(format!("expected an amount in {}, got {}", C::CODE, record.currency)));
+        }
+        Ok(Money::new(record.amount))
+    }
+}
+
+impl<C: Currency> fmt::Display for Money<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.amount, C::CODE)
+    }
+}
+
+// Rhs defaults to Self, and Self is Money<C>, so only the same currency can be added
+impl<C: Currency> Add for Money<C> {
+    type Output = Money<C>;
+
+    fn add(self, rhs: Money<C>) -> Money<C> {
+        Money::new(self.amount + rhs.amount)
+    }
+}
+
+impl<C: Currency> Sub for Money<C> {
+    type Output = Money<C>;
+
+    fn sub(self, rhs: Money<C>) -> Money<C> {
+        Money::new(self.amount - rhs.amount)
+    }
+}
+
+impl<C: Currency> Mul<f64> for Money<C> {
+    type Output = Money<C>;
+
+    fn mul(self, factor: f64) -> Money<C> {
+        Money::new(self.amount * factor)
+    }
+}
+
+impl<C: Currency> Div<f64> for Money<C> {
+    type Output = Money<C>;
+
+    fn div(self, divisor: f64) -> Money<C> {
+        Money::new(self.amount / divisor)
+    }
+}
+
+impl<C: Currency> Sum for Money<C> {
+    fn sum<I: Iterator<Item = Money<C>>>(iter: I) -> Money<C> {
+        iter.fold(Money::new(0.0), |acc, money| acc + money)
+    }
+}
+
+// How many To one From is worth, 1 USD = 0.92 EUR is ExchangeRate::<Usd, Eur>::new(0.92)
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ExchangeRate<From: Currency, To: Currency = Usd> {
+    rate: f64,
+    currencies: PhantomData<(From, To)>
+}
+
+impl<From: Currency, To: Currency> ExchangeRate<From, To> {
+    pub const fn new(rate: f64) -> Self {
+        Self { rate, currencies: PhantomData }
+    }
+
+    pub fn inverse(self) -> ExchangeRate<To, From> {
+        ExchangeRate::new(1.0 / self.rate)
+    }
+}
+
+// Money<Usd> * ExchangeRate<Usd, Eur> = Money<Eur>, a rate for another currency doesn't compile
+impl<From: Currency, To: Currency> Mul<ExchangeRate<From, To>> for Money<From> {
+    type Output = Money<To>;
+
+    fn mul(self, rate: ExchangeRate<From, To>) -> Money<To> {
+        Money::new(self.amount * rate.rate)
+    }
+}
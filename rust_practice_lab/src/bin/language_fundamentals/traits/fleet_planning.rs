@@ -0,0 +1,336 @@
+use std::collections::HashSet;
+use std::fmt;
+use rust_practice_lab::knapsack::{self, Item};
+use super::associated_constants::spec_of;
+use super::default_type_parameters::Money;
+use super::trait_objects::Capability;
+use super::traits::poly::{Car, Vehicle};
+use super::traits::{VehicleKind, Vehicles};
+
+// Buying vehicles under a budget is a knapsack problem: the price is the weight, the budget the weight limit
+// and how useful a vehicle is the value. The planner turns the vehicles into knapsack Items,
+// lets one of the knapsack solvers pick, and maps the picked items back to vehicles.
+// By default that is knapsack::get_knapsack_items_dynamic, any other solver can be given instead.
+//
+// The solvers don't know about requirements ("at least one air vehicle"). For every way to pick one
+// vehicle per requirement, the planner buys those first and lets the solver fill the rest of the budget,
+// the best of those plans wins. Every plan that meets the requirements contains such a pick, so nothing
+// is missed. A vehicle that is no cheaper, no more useful and meets no more requirements than another one
+// is never needed in a pick, leaving those out keeps the number of picks small.
+
+// How much each property of a vehicle is worth, the score of a vehicle is the sum
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Utility {
+    per_horse_power: f64,
+    per_passenger: f64,
+    per_terrain: f64,
+    per_kilometer_per_hour: f64
+}
+
+impl Utility {
+    // worth nothing, add the properties that matter
+    pub fn new() -> Self {
+        Self { per_horse_power: 0.0, per_passenger: 0.0, per_terrain: 0.0, per_kilometer_per_hour: 0.0 }
+    }
+
+    pub fn per_horse_power(mut self, points: f64) -> Self {
+        self.per_horse_power = points;
+        self
+    }
+
+    pub fn per_passenger(mut self, points: f64) -> Self {
+        self.per_passenger = points;
+        self
+    }
+
+    // for every terrain the vehicle can go on
+    pub fn per_terrain(mut self, points: f64) -> Self {
+        self.per_terrain = points;
+        self
+    }
+
+    pub fn per_kilometer_per_hour(mut self, points: f64) -> Self {
+        self.per_kilometer_per_hour = points;
+        self
+    }
+
+    pub fn score(&self, vehicle: &Vehicles) -> f64 {
+        let spec = spec_of(vehicle.kind());
+        // only cars know their engine power
+        let horse_power = vehicle.as_vehicle().downcast_ref::<Car>().map_or(0.0, |car| car.horse_power.horse_power());
+        self.per_horse_power * horse_power
+            + self.per_passenger * spec.max_passengers as f64
+            + self.per_terrain * spec.terrain.len() as f64
+            + self.per_kilometer_per_hour * spec.max_speed.kilometers_per_hour()
+    }
+}
+
+impl Default for Utility {
+    fn default() -> Self {
+        Self::new().per_horse_power(1.0).per_passenger(10.0).per_terrain(100.0)
+    }
+}
+
+// Every requirement asks for at least one vehicle of its kind
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Requirement {
+    Capability(Capability),
+    Kind(VehicleKind)
+}
+
+impl Requirement {
+    pub fn is_met_by(&self, vehicle: &Vehicles) -> bool {
+        match *self {
+            Requirement::Capability(capability) => vehicle.as_vehicle().can(capability),
+            Requirement::Kind(kind) => vehicle.kind() == kind
+        }
+    }
+}
+
+impl fmt::Display for Requirement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Requirement::Capability(capability) => write!(f, "at least one {:?} vehicle", capability),
+            Requirement::Kind(kind) => write!(f, "at least one {:?}", kind)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlanError {
+    // no vehicle on offer meets the requirement
+    Unsatisfiable(Requirement),
+    // the vehicles that meet the requirements together cost more than the budget
+    OverBudget { cheapest: Money, budget: Money },
+    // the price doesn't fit in a knapsack weight (i32) with this price unit
+    PriceTooLarge(Money),
+    // zero, negative or not a number
+    InvalidPriceUnit(f64),
+    // negative or not a number
+    InvalidBudget(Money),
+    // the requirements can be met in more ways than MAX_FORCED_PICKS, the solver would run once for each
+    TooManyPicks { picks: usize },
+    // the solver's checks turned the problem down, e.g. too many vehicles or weights that overflow
+    Unsolvable(String)
+}
+
+impl fmt::Display for PlanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlanError::Unsatisfiable(requirement) => write!(f, "no vehicle on offer meets the requirement {}", requirement),
+            PlanError::OverBudget { cheapest, budget } => write!(f, "meeting the requirements costs at least {}, the budget is {}", cheapest, budget),
+            PlanError::PriceTooLarge(price) => write!(f, "{} is too large for the knapsack solvers, use a larger price unit", price),
+            PlanError::InvalidPriceUnit(price_unit) => write!(f, "the price unit has to be a positive number of dollars, got {}", price_unit),
+            PlanError::InvalidBudget(budget) => write!(f, "the budget has to be zero or more, got {}", budget),
+            PlanError::TooManyPicks { picks } => write!(f, "the requirements can be met in {} ways, at most {} are tried", picks, MAX_FORCED_PICKS),
+            PlanError::Unsolvable(reason) => write!(f, "the knapsack solver can't plan these vehicles: {}", reason)
+        }
+    }
+}
+
+impl std::error::Error for PlanError {}
+
+#[derive(Debug)]
+pub struct Plan<'a> {
+    pub chosen: Vec<&'a Vehicles>,
+    pub total_price: Money,
+    pub total_score: f64
+}
+
+//=================================================================
+pub type Solver = fn(&[Item], i32) -> Vec<Item>;
+// the checks a solver needs before it is given a problem, like knapsack::validate_knapsack_problem
+pub type Validator = fn(&[Item], i32) -> Result<(), String>;
+
+// the solver runs once per pick, the planner gives up above this many
+pub const MAX_FORCED_PICKS: usize = 256;
+
+pub struct FleetPlanner<S = Solver> {
+    utility: Utility,
+    requirements: Vec<Requirement>,
+    price_unit: f64,
+    solver: S,
+    validate: Validator
+}
+
+impl FleetPlanner {
+    pub fn new() -> Self {
+        Self {
+            utility: Utility::default(),
+            requirements: Vec::new(),
+            price_unit: 1.0,
+            solver: knapsack::get_knapsack_items_dynamic,
+            validate: knapsack::validate_dynamic_knapsack_problem
+        }
+    }
+}
+
+impl Default for FleetPlanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: Fn(&[Item], i32) -> Vec<Item>> FleetPlanner<S> {
+    pub fn utility(mut self, utility: Utility) -> Self {
+        self.utility = utility;
+        self
+    }
+
+    pub fn require(mut self, capability: Capability) -> Self {
+        self.requirements.push(Requirement::Capability(capability));
+        self
+    }
+
+    pub fn require_kind(mut self, kind: VehicleKind) -> Self {
+        self.requirements.push(Requirement::Kind(kind));
+        self
+    }
+
+    // prices are counted in whole units of this many dollars, rounded up, the budget is rounded down,
+    // so a plan never costs more than the budget. the solvers take i32 weights and the default solver
+    // takes a step per unit of the budget, large prices need a large unit. has to be positive
+    pub fn price_unit(mut self, price_unit: f64) -> Self {
+        self.price_unit = price_unit;
+        self
+    }
+
+    // any knapsack solver instead of the default one, e.g. knapsack::get_knapsack_items_par_iter_with_lock::<mutexes::TicketLock>
+    // its problems are checked with knapsack::validate_knapsack_problem, like the exhaustive solvers need
+    pub fn solver<T: Fn(&[Item], i32) -> Vec<Item>>(self, solver: T) -> FleetPlanner<T> {
+        FleetPlanner {
+            utility: self.utility,
+            requirements: self.requirements,
+            price_unit: self.price_unit,
+            solver,
+            validate: knapsack::validate_knapsack_problem
+        }
+    }
+
+    pub fn plan<'a>(&self, vehicles: &'a [Vehicles], budget: Money) -> Result<Plan<'a>, PlanError> {
+        if !(self.price_unit.is_finite() && self.price_unit > 0.0) {
+            return Err(PlanError::InvalidPriceUnit(self.price_unit));
+        }
+        if !(budget.amount().is_finite() && budget.amount() >= 0.0) {
+            return Err(PlanError::InvalidBudget(budget));
+        }
+        let items = vehicles.iter().map(|vehicle| self.item(vehicle)).collect::<Result<Vec<Item>, PlanError>>()?;
+        let weight_limit = (budget.amount() / self.price_unit).floor().min(i32::MAX as f64) as i32;
+
+        // asking twice for the same thing is asking once, so there are at most 7 different requirements
+        let mut requirements: Vec<Requirement> = Vec::new();
+        for requirement in &self.requirements {
+            if !requirements.contains(requirement) {
+                requirements.push(*requirement);
+            }
+        }
+        // bit j is set when the vehicle meets requirement j
+        let masks: Vec<u8> = vehicles
+            .iter()
+            .map(|vehicle| (0..requirements.len()).filter(|&j| requirements[j].is_met_by(vehicle)).fold(0, |mask, j| mask | 1 << j))
+            .collect();
+        let mut candidates = Vec::new();
+        for (j, requirement) in requirements.iter().enumerate() {
+            let meeting: Vec<usize> = (0..vehicles.len())
+                .filter(|&i| masks[i] & 1 << j != 0)
+                .filter(|&i| !(0..vehicles.len()).any(|other| dominates(&items, &masks, other, i)))
+                .collect();
+            if meeting.is_empty() {
+                return Err(PlanError::Unsatisfiable(*requirement));
+            }
+            candidates.push(meeting);
+        }
+        let picks = candidates.iter().fold(1usize, |picks, meeting| picks.saturating_mul(meeting.len()));
+        if picks > MAX_FORCED_PICKS {
+            return Err(PlanError::TooManyPicks { picks });
+        }
+
+        let chosen = self.plan_with(&items, &candidates, weight_limit, budget)?;
+        let chosen: Vec<&Vehicles> = chosen.into_iter().map(|i| &vehicles[i]).collect();
+        let total_price = chosen.iter().map(|vehicle| vehicle.price()).sum();
+        let total_score = chosen.iter().map(|vehicle| self.utility.score(vehicle)).sum();
+        Ok(Plan { chosen, total_price, total_score })
+    }
+
+    fn item(&self, vehicle: &Vehicles) -> Result<Item, PlanError> {
+        let weight = (vehicle.price().amount() / self.price_unit).ceil();
+        if weight > i32::MAX as f64 {
+            return Err(PlanError::PriceTooLarge(vehicle.price()));
+        }
+        // the solvers need positive weights, a free vehicle still takes one unit
+        Ok(Item { weight: (weight as i32).max(1), value: self.utility.score(vehicle).round() as i32 })
+    }
+
+    // the forced picks, each filled up by the solver
+    fn plan_with(&self, items: &[Item], candidates: &[Vec<usize>], weight_limit: i32, budget: Money) -> Result<Vec<usize>, PlanError> {
+        let mut best: Option<(i64, Vec<usize>)> = None;
+        let mut cheapest_forced = i64::MAX;
+        let mut tried = HashSet::new();
+        for mut forced in picks(candidates) {
+            // one vehicle can meet several requirements
+            forced.sort_unstable();
+            forced.dedup();
+            if !tried.insert(forced.clone()) {
+                continue;
+            }
+            let forced_weight: i64 = forced.iter().map(|&i| items[i].weight as i64).sum();
+            cheapest_forced = cheapest_forced.min(forced_weight);
+            if forced_weight > weight_limit as i64 {
+                continue;
+            }
+            let mut chosen = forced.clone();
+            chosen.extend(fill(&self.solver, self.validate, items, &forced, weight_limit - forced_weight as i32)?);
+            let value: i64 = chosen.iter().map(|&i| items[i].value as i64).sum();
+            if best.as_ref().is_none_or(|(best_value, _)| value > *best_value) {
+                best = Some((value, chosen));
+            }
+        }
+
+        let Some((_, mut chosen)) = best else {
+            return Err(PlanError::OverBudget { cheapest: Money::new(cheapest_forced as f64 * self.price_unit), budget });
+        };
+        chosen.sort_unstable();
+        Ok(chosen)
+    }
+}
+
+// a dominates b when it is no heavier, no less valuable and meets every requirement b meets,
+// equal vehicles are told apart by their position so they don't dominate each other
+fn dominates(items: &[Item], masks: &[u8], a: usize, b: usize) -> bool {
+    a != b
+        && masks[a] & masks[b] == masks[b]
+        && items[a].weight <= items[b].weight
+        && items[a].value >= items[b].value
+        && (items[a] != items[b] || masks[a] != masks[b] || a < b)
+}
+
+// the solver picks from the vehicles that weren't forced, its items are matched back to indices
+fn fill<S: Fn(&[Item], i32) -> Vec<Item>>(solver: &S, validate: Validator, items: &[Item], forced: &[usize], weight_limit: i32) -> Result<Vec<usize>, PlanError> {
+    let mut open: Vec<usize> = (0..items.len()).filter(|i| !forced.contains(i)).collect();
+    if open.is_empty() || weight_limit <= 0 {
+        return Ok(Vec::new());
+    }
+    let open_items: Vec<Item> = open.iter().map(|&i| items[i]).collect();
+    validate(&open_items, weight_limit).map_err(PlanError::Unsolvable)?;
+    let mut filled = Vec::new();
+    // equal items are interchangeable, any vehicle with the same weight and value will do
+    for item in solver(&open_items, weight_limit) {
+        let position = open.iter().position(|&i| items[i] == item).expect("the solver only returns items it was given");
+        filled.push(open.swap_remove(position));
+    }
+    Ok(filled)
+}
+
+// every way to pick one index out of each list
+fn picks(candidates: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    candidates.iter().fold(vec![Vec::new()], |picks, options| {
+        picks
+            .iter()
+            .flat_map(|pick| options.iter().map(move |&option| {
+                let mut pick = pick.clone();
+                pick.push(option);
+                pick
+            }))
+            .collect()
+    })
+}
@@ -0,0 +1,796 @@
+use std::cmp::Ordering;
+use std::fmt;
+use super::associated_constants::spec_of;
+use super::trait_objects::{Capability, Fleet};
+use super::traits::poly::{Car, Vehicle};
+use super::traits::{VehicleKind, Vehicles};
+
+// A small query language over a Fleet, instead of writing every filter and fold by hand
+//     kind = Car and price < 65000 order by price desc
+//     can Sea or (passengers >= 10 and not kind = Airplane) limit 3
+//     count, sum(price) where price < 65000 group by kind order by sum(price) desc
+//
+// query      := [aggregates [where]] [condition] [group by field] [order by (field | aggregate) [asc | desc]] [limit number]
+// aggregates := aggregate ("," aggregate)*
+// aggregate  := count | (sum | avg | min | max) "(" field ")"
+// condition  := condition or condition | condition and condition | not condition | "(" condition ")"
+//             | can (Land | Sea | Air) | field (= | != | < | <= | > | >=) value
+// fields     : kind (Car, Boat, ...), name ("text"), price (dollars), horse_power (cars only),
+//              max_speed (km/h), passengers and wheels (from the specification of the kind)
+//
+// parse checks everything it can up front: unknown fields, a price compared with a name, sum(name).
+// A parsed Query always runs, a vehicle without a value (the horse power of a boat) matches no comparison.
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Field {
+    Kind,
+    Name,
+    Price,
+    HorsePower,
+    MaxSpeed,
+    Passengers,
+    Wheels
+}
+
+impl Field {
+    pub const ALL: [Field; 7] = [Field::Kind, Field::Name, Field::Price, Field::HorsePower, Field::MaxSpeed, Field::Passengers, Field::Wheels];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Field::Kind => "kind",
+            Field::Name => "name",
+            Field::Price => "price",
+            Field::HorsePower => "horse_power",
+            Field::MaxSpeed => "max_speed",
+            Field::Passengers => "passengers",
+            Field::Wheels => "wheels"
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Field> {
+        Field::ALL.into_iter().find(|field| field.name() == name)
+    }
+
+    fn is_number(self) -> bool {
+        !matches!(self, Field::Kind | Field::Name)
+    }
+
+    // what a value for this field looks like, for error messages
+    fn expects(self) -> &'static str {
+        match self {
+            Field::Kind => "a vehicle kind like Car",
+            Field::Name => "a name in double quotes",
+            _ => "a number"
+        }
+    }
+
+    pub fn value(self, vehicle: &dyn Vehicle) -> Option<Value> {
+        let kind = VehicleKind::of(vehicle);
+        match self {
+            Field::Kind => kind.map(Value::Kind),
+            Field::Name => Some(Value::Text(vehicle.name().to_string())),
+            Field::Price => Some(Value::Number(vehicle.price().amount())),
+            Field::HorsePower => car(vehicle).map(|car| Value::Number(car.horse_power.horse_power())),
+            Field::MaxSpeed => kind.map(|kind| Value::Number(spec_of(kind).max_speed.kilometers_per_hour())),
+            Field::Passengers => kind.map(|kind| Value::Number(spec_of(kind).max_passengers as f64)),
+            Field::Wheels => kind.map(|kind| Value::Number(spec_of(kind).wheels as f64))
+        }
+    }
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+// only cars know their horse power, also when the whole enum is in the fleet
+fn car(vehicle: &dyn Vehicle) -> Option<&Car> {
+    match vehicle.downcast_ref::<Vehicles>() {
+        Some(Vehicles::Car(car)) => Some(car),
+        Some(_) => None,
+        None => vehicle.downcast_ref::<Car>()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Kind(VehicleKind),
+    Text(String),
+    Number(f64)
+}
+
+impl Value {
+    // values of one field always have the same type, kinds are ordered like the vehicles! list
+    fn compare(&self, other: &Value) -> Ordering {
+        let position = |kind: &VehicleKind| VehicleKind::ALL.iter().position(|other| other == kind);
+        match (self, other) {
+            (Value::Kind(a), Value::Kind(b)) => position(a).cmp(&position(b)),
+            (Value::Text(a), Value::Text(b)) => a.cmp(b),
+            (Value::Number(a), Value::Number(b)) => a.partial_cmp(b).unwrap_or(Ordering::Equal),
+            _ => Ordering::Equal
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Kind(kind) => write!(f, "{:?}", kind),
+            Value::Text(text) => write!(f, "{}", text),
+            Value::Number(number) => write!(f, "{}", format_number(*number))
+        }
+    }
+}
+
+// whole numbers without decimals, the rest with two
+fn format_number(number: f64) -> String {
+    if number.fract() == 0.0 {
+        format!("{:.0}", number)
+    } else {
+        format!("{:.2}", number)
+    }
+}
+
+// values come first, vehicles without a value last, whatever the direction
+fn compare_optional(a: &Option<Value>, b: &Option<Value>, descending: bool) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) if descending => a.compare(b).reverse(),
+        (Some(a), Some(b)) => a.compare(b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal
+    }
+}
+
+//=================================================================
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual
+}
+
+impl Comparison {
+    fn holds(self, ordering: Ordering) -> bool {
+        match self {
+            Comparison::Equal => ordering == Ordering::Equal,
+            Comparison::NotEqual => ordering != Ordering::Equal,
+            Comparison::Less => ordering == Ordering::Less,
+            Comparison::LessOrEqual => ordering != Ordering::Greater,
+            Comparison::Greater => ordering == Ordering::Greater,
+            Comparison::GreaterOrEqual => ordering != Ordering::Less
+        }
+    }
+
+    fn symbol(self) -> &'static str {
+        match self {
+            Comparison::Equal => "=",
+            Comparison::NotEqual => "!=",
+            Comparison::Less => "<",
+            Comparison::LessOrEqual => "<=",
+            Comparison::Greater => ">",
+            Comparison::GreaterOrEqual => ">="
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    Compare { field: Field, comparison: Comparison, value: Value },
+    Can(Capability),
+    Not(Box<Condition>),
+    And(Box<Condition>, Box<Condition>),
+    Or(Box<Condition>, Box<Condition>)
+}
+
+impl Condition {
+    pub fn matches(&self, vehicle: &dyn Vehicle) -> bool {
+        match self {
+            Condition::Compare { field, comparison, value } => {
+                field.value(vehicle).is_some_and(|actual| comparison.holds(actual.compare(value)))
+            }
+            Condition::Can(capability) => vehicle.can(*capability),
+            Condition::Not(condition) => !condition.matches(vehicle),
+            Condition::And(a, b) => a.matches(vehicle) && b.matches(vehicle),
+            Condition::Or(a, b) => a.matches(vehicle) || b.matches(vehicle)
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Aggregate {
+    Count,
+    Sum(Field),
+    Average(Field),
+    Min(Field),
+    Max(Field)
+}
+
+impl Aggregate {
+    // None when there is nothing to take the average, minimum or maximum of
+    pub fn apply(self, vehicles: &[&dyn Vehicle]) -> Option<f64> {
+        let numbers = |field: Field| {
+            vehicles.iter().filter_map(move |vehicle| match field.value(*vehicle) {
+                Some(Value::Number(number)) => Some(number),
+                _ => None
+            })
+        };
+        match self {
+            Aggregate::Count => Some(vehicles.len() as f64),
+            Aggregate::Sum(field) => Some(numbers(field).sum()),
+            Aggregate::Average(field) => {
+                let (sum, count) = numbers(field).fold((0.0, 0), |(sum, count), number| (sum + number, count + 1));
+                (count > 0).then(|| sum / count as f64)
+            }
+            Aggregate::Min(field) => numbers(field).reduce(f64::min),
+            Aggregate::Max(field) => numbers(field).reduce(f64::max)
+        }
+    }
+}
+
+impl fmt::Display for Aggregate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Aggregate::Count => write!(f, "count"),
+            Aggregate::Sum(field) => write!(f, "sum({})", field),
+            Aggregate::Average(field) => write!(f, "avg({})", field),
+            Aggregate::Min(field) => write!(f, "min({})", field),
+            Aggregate::Max(field) => write!(f, "max({})", field)
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SortKey {
+    Field(Field),
+    Aggregate(Aggregate)
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Order {
+    pub key: SortKey,
+    pub descending: bool
+}
+
+// Without aggregates the query lists vehicles, with them it returns one row per group (one row without group by)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    pub aggregates: Vec<Aggregate>,
+    pub condition: Option<Condition>,
+    pub group_by: Option<Field>,
+    pub order: Option<Order>,
+    pub limit: Option<usize>
+}
+
+//=================================================================
+// Errors point at the part of the query they are about, as byte offsets
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErrorKind {
+    UnexpectedCharacter(char),
+    UnterminatedString,
+    InvalidNumber(String),
+    // the parser needed one thing and found another, `found` is "the end of the query" when there was nothing
+    Expected { expected: &'static str, found: String },
+    UnknownField(String),
+    UnknownKind(String),
+    UnknownCapability(String),
+    // a value of the wrong type for the field, like price = "cheap"
+    TypeMismatch { field: Field, expected: &'static str },
+    // kinds can only be compared with = and !=
+    Unordered { field: Field, comparison: Comparison },
+    // sum, avg, min and max need a field with numbers
+    NotANumber { aggregate: &'static str, field: Field },
+    // a list of vehicles can't be ordered by an aggregate, groups only by their field or an aggregate
+    InvalidOrder(SortKey),
+    InvalidLimit(String)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryError {
+    pub kind: ErrorKind,
+    pub span: Span
+}
+
+impl QueryError {
+    // the query with the span underlined and the message after it, for a terminal
+    pub fn render(&self, query: &str) -> String {
+        let column = query[..self.span.start].chars().count();
+        let width = query[self.span.start..self.span.end].chars().count().max(1);
+        format!("{}\n{}{} {}", query, " ".repeat(column), "^".repeat(width), self.kind)
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorKind::UnexpectedCharacter(c) => write!(f, "unexpected character {:?}", c),
+            ErrorKind::UnterminatedString => write!(f, "the name has no closing double quote"),
+            ErrorKind::InvalidNumber(number) => write!(f, "{} is not a number", number),
+            ErrorKind::Expected { expected, found } => write!(f, "expected {}, found {}", expected, found),
+            ErrorKind::UnknownField(field) => {
+                let fields: Vec<&str> = Field::ALL.iter().map(|field| field.name()).collect();
+                write!(f, "unknown field `{}`, the fields are {}", field, fields.join(", "))
+            }
+            ErrorKind::UnknownKind(kind) => write!(f, "unknown vehicle kind `{}`, the kinds are {:?}", kind, VehicleKind::ALL),
+            ErrorKind::UnknownCapability(capability) => write!(f, "unknown capability `{}`, a vehicle can {:?}", capability, Capability::ALL),
+            ErrorKind::TypeMismatch { field, expected } => write!(f, "{} has to be compared with {}", field, expected),
+            ErrorKind::Unordered { field, comparison } => write!(f, "{} can only be compared with = or !=, not {}", field, comparison.symbol()),
+            ErrorKind::NotANumber { aggregate, field } => write!(f, "{} needs a field with numbers, {} isn't one", aggregate, field),
+            ErrorKind::InvalidOrder(SortKey::Aggregate(aggregate)) => write!(f, "only a query with aggregates can be ordered by {}", aggregate),
+            ErrorKind::InvalidOrder(SortKey::Field(field)) => write!(f, "groups can only be ordered by their group field or an aggregate, not by {}", field),
+            ErrorKind::InvalidLimit(limit) => write!(f, "the limit has to be a whole number, not {}", limit)
+        }
+    }
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at {}..{}", self.kind, self.span.start, self.span.end)
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+//=================================================================
+// Tokens
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Number(f64),
+    Text(String),
+    Compare(Comparison),
+    Open,
+    Close,
+    Comma
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Word(word) => write!(f, "`{}`", word),
+            Token::Number(number) => write!(f, "{}", format_number(*number)),
+            Token::Text(text) => write!(f, "{:?}", text),
+            Token::Compare(comparison) => write!(f, "`{}`", comparison.symbol()),
+            Token::Open => write!(f, "`(`"),
+            Token::Close => write!(f, "`)`"),
+            Token::Comma => write!(f, "`,`")
+        }
+    }
+}
+
+fn tokenize(query: &str) -> Result<Vec<(Token, Span)>, QueryError> {
+    let mut tokens = Vec::new();
+    let mut chars = query.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        if c.is_whitespace() {
+            continue;
+        }
+        let token = match c {
+            '(' => Token::Open,
+            ')' => Token::Close,
+            ',' => Token::Comma,
+            '=' => Token::Compare(Comparison::Equal),
+            '!' if chars.next_if(|&(_, c)| c == '=').is_some() => Token::Compare(Comparison::NotEqual),
+            '<' if chars.next_if(|&(_, c)| c == '=').is_some() => Token::Compare(Comparison::LessOrEqual),
+            '<' => Token::Compare(Comparison::Less),
+            '>' if chars.next_if(|&(_, c)| c == '=').is_some() => Token::Compare(Comparison::GreaterOrEqual),
+            '>' => Token::Compare(Comparison::Greater),
+            '"' => {
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((_, c)) => text.push(c),
+                        None => return Err(QueryError { kind: ErrorKind::UnterminatedString, span: Span { start, end: query.len() } })
+                    }
+                }
+                Token::Text(text)
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                let mut word = c.to_string();
+                while let Some((_, c)) = chars.next_if(|&(_, c)| c.is_ascii_alphanumeric() || c == '_') {
+                    word.push(c);
+                }
+                Token::Word(word)
+            }
+            // 65000, 65_000 and 1.5
+            c if c.is_ascii_digit() => {
+                let mut number = c.to_string();
+                while let Some((_, c)) = chars.next_if(|&(_, c)| c.is_ascii_alphanumeric() || c == '_' || c == '.') {
+                    number.push(c);
+                }
+                let end = start + number.len();
+                match number.replace('_', "").parse() {
+                    Ok(parsed) => Token::Number(parsed),
+                    Err(_) => return Err(QueryError { kind: ErrorKind::InvalidNumber(number), span: Span { start, end } })
+                }
+            }
+            c => return Err(QueryError { kind: ErrorKind::UnexpectedCharacter(c), span: Span { start, end: start + c.len_utf8() } })
+        };
+        let end = chars.peek().map_or(query.len(), |&(end, _)| end);
+        tokens.push((token, Span { start, end }));
+    }
+    Ok(tokens)
+}
+
+//=================================================================
+// Parsing
+
+pub fn parse(query: &str) -> Result<Query, QueryError> {
+    let mut parser = Parser { tokens: tokenize(query)?, position: 0, end: query.len() };
+    parser.query()
+}
+
+struct Parser {
+    tokens: Vec<(Token, Span)>,
+    position: usize,
+    end: usize
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(token, _)| token)
+    }
+
+    // the span of the next token, the end of the query when there is none
+    fn span(&self) -> Span {
+        self.tokens.get(self.position).map_or(Span { start: self.end, end: self.end }, |(_, span)| *span)
+    }
+
+    fn at_word(&self, word: &str) -> bool {
+        matches!(self.peek(), Some(Token::Word(next)) if next == word)
+    }
+
+    fn eat_word(&mut self, word: &str) -> bool {
+        let found = self.at_word(word);
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn expected(&self, expected: &'static str) -> QueryError {
+        let found = self.peek().map_or("the end of the query".to_string(), |token| token.to_string());
+        QueryError { kind: ErrorKind::Expected { expected, found }, span: self.span() }
+    }
+
+    fn expect_word(&mut self, word: &'static str, expected: &'static str) -> Result<(), QueryError> {
+        if self.eat_word(word) {
+            Ok(())
+        } else {
+            Err(self.expected(expected))
+        }
+    }
+
+    fn expect(&mut self, token: Token, expected: &'static str) -> Result<(), QueryError> {
+        if self.peek() == Some(&token) {
+            self.position += 1;
+            Ok(())
+        } else {
+            Err(self.expected(expected))
+        }
+    }
+
+    fn at_aggregate(&self) -> bool {
+        let function = ["sum", "avg", "min", "max"].iter().any(|function| self.at_word(function));
+        self.at_word("count") || (function && matches!(self.tokens.get(self.position + 1), Some((Token::Open, _))))
+    }
+
+    fn query(&mut self) -> Result<Query, QueryError> {
+        let mut aggregates = Vec::new();
+        if self.at_aggregate() {
+            aggregates.push(self.aggregate()?);
+            while self.peek() == Some(&Token::Comma) {
+                self.position += 1;
+                aggregates.push(self.aggregate()?);
+            }
+        }
+        // optional in front of a condition, the condition is optional as well
+        self.eat_word("where");
+        let condition = if self.peek().is_none() || ["group", "order", "limit"].iter().any(|word| self.at_word(word)) {
+            None
+        } else {
+            Some(self.or()?)
+        };
+
+        let mut group_by = None;
+        if self.eat_word("group") {
+            self.expect_word("by", "`by` after `group`")?;
+            group_by = Some(self.field()?.0);
+            // grouping without aggregates counts the groups
+            if aggregates.is_empty() {
+                aggregates.push(Aggregate::Count);
+            }
+        }
+
+        let mut order = None;
+        if self.eat_word("order") {
+            self.expect_word("by", "`by` after `order`")?;
+            let span = self.span();
+            let key = if self.at_aggregate() { SortKey::Aggregate(self.aggregate()?) } else { SortKey::Field(self.field()?.0) };
+            let valid = match key {
+                SortKey::Field(field) => aggregates.is_empty() || group_by == Some(field),
+                SortKey::Aggregate(_) => !aggregates.is_empty()
+            };
+            if !valid {
+                let end = self.tokens[self.position - 1].1.end;
+                return Err(QueryError { kind: ErrorKind::InvalidOrder(key), span: Span { start: span.start, end } });
+            }
+            let descending = self.eat_word("desc");
+            if !descending {
+                self.eat_word("asc");
+            }
+            order = Some(Order { key, descending });
+        }
+
+        let mut limit = None;
+        if self.eat_word("limit") {
+            let span = self.span();
+            match self.peek() {
+                Some(Token::Number(number)) if number.fract() == 0.0 && *number >= 0.0 => limit = Some(*number as usize),
+                Some(Token::Number(number)) => {
+                    return Err(QueryError { kind: ErrorKind::InvalidLimit(format_number(*number)), span });
+                }
+                _ => return Err(self.expected("a number of results"))
+            }
+            self.position += 1;
+        }
+
+        if self.peek().is_some() {
+            return Err(self.expected("`and`, `or`, `group by`, `order by`, `limit` or the end of the query"));
+        }
+        Ok(Query { aggregates, condition, group_by, order, limit })
+    }
+
+    fn aggregate(&mut self) -> Result<Aggregate, QueryError> {
+        if self.eat_word("count") {
+            return Ok(Aggregate::Count);
+        }
+        let Some(Token::Word(function)) = self.peek().cloned() else {
+            return Err(self.expected("count, sum, avg, min or max"));
+        };
+        let aggregate: fn(Field) -> Aggregate = match function.as_str() {
+            "sum" => Aggregate::Sum,
+            "avg" => Aggregate::Average,
+            "min" => Aggregate::Min,
+            "max" => Aggregate::Max,
+            _ => return Err(self.expected("count, sum, avg, min or max"))
+        };
+        self.position += 1;
+        self.expect(Token::Open, "`(`")?;
+        let (field, span) = self.field()?;
+        if !field.is_number() {
+            let name = match aggregate(field) {
+                Aggregate::Sum(_) => "sum",
+                Aggregate::Average(_) => "avg",
+                Aggregate::Min(_) => "min",
+                _ => "max"
+            };
+            return Err(QueryError { kind: ErrorKind::NotANumber { aggregate: name, field }, span });
+        }
+        self.expect(Token::Close, "`)`")?;
+        Ok(aggregate(field))
+    }
+
+    fn field(&mut self) -> Result<(Field, Span), QueryError> {
+        let span = self.span();
+        let Some(Token::Word(word)) = self.peek() else {
+            return Err(self.expected("a field"));
+        };
+        let field = Field::from_name(word).ok_or_else(|| QueryError { kind: ErrorKind::UnknownField(word.clone()), span })?;
+        self.position += 1;
+        Ok((field, span))
+    }
+
+    // `or` binds weaker than `and`, which binds weaker than `not`
+    fn or(&mut self) -> Result<Condition, QueryError> {
+        let mut condition = self.and()?;
+        while self.eat_word("or") {
+            condition = Condition::Or(Box::new(condition), Box::new(self.and()?));
+        }
+        Ok(condition)
+    }
+
+    fn and(&mut self) -> Result<Condition, QueryError> {
+        let mut condition = self.not()?;
+        while self.eat_word("and") {
+            condition = Condition::And(Box::new(condition), Box::new(self.not()?));
+        }
+        Ok(condition)
+    }
+
+    fn not(&mut self) -> Result<Condition, QueryError> {
+        if self.eat_word("not") {
+            return Ok(Condition::Not(Box::new(self.not()?)));
+        }
+        if self.peek() == Some(&Token::Open) {
+            self.position += 1;
+            let condition = self.or()?;
+            self.expect(Token::Close, "`)`")?;
+            return Ok(condition);
+        }
+        if self.eat_word("can") {
+            let span = self.span();
+            let Some(Token::Word(word)) = self.peek() else {
+                return Err(self.expected("a capability like Sea"));
+            };
+            let capability = Capability::ALL.into_iter().find(|capability| format!("{:?}", capability) == *word);
+            let capability = capability.ok_or_else(|| QueryError { kind: ErrorKind::UnknownCapability(word.clone()), span })?;
+            self.position += 1;
+            return Ok(Condition::Can(capability));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Condition, QueryError> {
+        if self.peek().is_none() {
+            return Err(self.expected("a condition"));
+        }
+        let (field, _) = self.field()?;
+        let span = self.span();
+        let Some(Token::Compare(comparison)) = self.peek().cloned() else {
+            return Err(self.expected("a comparison like = or <"));
+        };
+        if field == Field::Kind && !matches!(comparison, Comparison::Equal | Comparison::NotEqual) {
+            return Err(QueryError { kind: ErrorKind::Unordered { field, comparison }, span });
+        }
+        self.position += 1;
+
+        let span = self.span();
+        let value = match (field, self.peek()) {
+            (_, None) => return Err(self.expected(field.expects())),
+            (Field::Kind, Some(Token::Word(word))) => {
+                let kind = VehicleKind::ALL.iter().find(|kind| format!("{:?}", kind) == *word);
+                Value::Kind(*kind.ok_or_else(|| QueryError { kind: ErrorKind::UnknownKind(word.clone()), span })?)
+            }
+            (Field::Name, Some(Token::Text(text))) => Value::Text(text.clone()),
+            (_, Some(Token::Number(number))) if field.is_number() => Value::Number(*number),
+            _ => return Err(QueryError { kind: ErrorKind::TypeMismatch { field, expected: field.expects() }, span })
+        };
+        self.position += 1;
+        Ok(Condition::Compare { field, comparison, value })
+    }
+}
+
+//=================================================================
+// Running
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Row {
+    // None without group by, or for the vehicles that have no value for the group field
+    pub group: Option<Value>,
+    // one per aggregate of the query, in the same order
+    pub values: Vec<Option<f64>>
+}
+
+pub enum QueryResult<'a> {
+    Vehicles(Vec<&'a dyn Vehicle>),
+    Rows { group_by: Option<Field>, aggregates: Vec<Aggregate>, rows: Vec<Row> }
+}
+
+impl Query {
+    pub fn run<'a>(&self, fleet: &'a Fleet) -> QueryResult<'a> {
+        let matching: Vec<&dyn Vehicle> = fleet
+            .iter()
+            .filter(|vehicle| self.condition.as_ref().is_none_or(|condition| condition.matches(*vehicle)))
+            .collect();
+        let limit = self.limit.unwrap_or(usize::MAX);
+
+        if self.aggregates.is_empty() {
+            let mut vehicles = matching;
+            if let Some(Order { key: SortKey::Field(field), descending }) = self.order {
+                // stable, vehicles with the same value stay in fleet order
+                vehicles.sort_by(|a, b| compare_optional(&field.value(*a), &field.value(*b), descending));
+            }
+            vehicles.truncate(limit);
+            return QueryResult::Vehicles(vehicles);
+        }
+
+        let mut groups: Vec<(Option<Value>, Vec<&dyn Vehicle>)> = Vec::new();
+        match self.group_by {
+            Some(field) => {
+                for vehicle in matching {
+                    let key = field.value(vehicle);
+                    match groups.iter_mut().find(|(group, _)| *group == key) {
+                        Some((_, members)) => members.push(vehicle),
+                        None => groups.push((key, vec![vehicle]))
+                    }
+                }
+                groups.sort_by(|a, b| compare_optional(&a.0, &b.0, false));
+            }
+            None => groups.push((None, matching))
+        }
+
+        if let Some(Order { key, descending }) = self.order {
+            let sort_value = |(group, members): &(Option<Value>, Vec<&dyn Vehicle>)| match key {
+                SortKey::Field(_) => group.clone(),
+                SortKey::Aggregate(aggregate) => aggregate.apply(members).map(Value::Number)
+            };
+            groups.sort_by(|a, b| compare_optional(&sort_value(a), &sort_value(b), descending));
+        }
+        let rows = groups
+            .into_iter()
+            .take(limit)
+            .map(|(group, members)| Row { group, values: self.aggregates.iter().map(|aggregate| aggregate.apply(&members)).collect() })
+            .collect();
+        QueryResult::Rows { group_by: self.group_by, aggregates: self.aggregates.clone(), rows }
+    }
+}
+
+impl QueryResult<'_> {
+    // the number of vehicles or rows
+    pub fn len(&self) -> usize {
+        match self {
+            QueryResult::Vehicles(vehicles) => vehicles.len(),
+            QueryResult::Rows { rows, .. } => rows.len()
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Fleet {
+    // parse and run in one go, e.g. fleet.query("can Air order by price")
+    pub fn query(&self, query: &str) -> Result<QueryResult<'_>, QueryError> {
+        Ok(parse(query)?.run(self))
+    }
+}
+
+impl fmt::Debug for QueryResult<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QueryResult::Vehicles(vehicles) => f.debug_list().entries(vehicles.iter().map(|vehicle| vehicle.name())).finish(),
+            QueryResult::Rows { rows, .. } => f.debug_list().entries(rows).finish()
+        }
+    }
+}
+
+// a line per vehicle or per group
+impl fmt::Display for QueryResult<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QueryResult::Vehicles(vehicles) if vehicles.is_empty() => write!(f, "no vehicles"),
+            QueryResult::Vehicles(vehicles) => {
+                let lines: Vec<String> = vehicles
+                    .iter()
+                    .map(|vehicle| {
+                        let kind = VehicleKind::of(*vehicle).map_or("?".to_string(), |kind| format!("{:?}", kind));
+                        format!("{:<15} {:<25} {}", kind, vehicle.name(), vehicle.price())
+                    })
+                    .collect();
+                write!(f, "{}", lines.join("\n"))
+            }
+            QueryResult::Rows { group_by, aggregates, rows } => {
+                let lines: Vec<String> = rows
+                    .iter()
+                    .map(|row| {
+                        let values: Vec<String> = aggregates
+                            .iter()
+                            .zip(&row.values)
+                            .map(|(aggregate, value)| format!("{} = {}", aggregate, value.map_or("-".to_string(), format_number)))
+                            .collect();
+                        match (group_by, &row.group) {
+                            (Some(_), Some(group)) => format!("{}: {}", group, values.join(", ")),
+                            (Some(field), None) => format!("no {}: {}", field, values.join(", ")),
+                            (None, _) => values.join(", ")
+                        }
+                    })
+                    .collect();
+                write!(f, "{}", lines.join("\n"))
+            }
+        }
+    }
+}
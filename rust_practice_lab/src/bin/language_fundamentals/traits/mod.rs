@@ -1,4 +1,11 @@
 pub mod traits;
 pub mod trait_objects;
 pub mod associated_constants;
-pub mod default_type_parameters;
+pub mod fleet_planning;
+pub mod route_simulation;
+pub mod vehicle_records;
+pub mod fleet_query;
+pub mod decimal;
+pub mod pricing;
+// declared by traits.rs, the vehicles in there use it
+pub use self::traits::default_type_parameters;
@@ -0,0 +1,487 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+use super::associated_constants::{spec_of, Category};
+use super::decimal::{Decimal, ParseDecimalError, RoundingMode};
+use super::default_type_parameters::{Currency, Money};
+use super::traits::poly::Vehicle;
+use super::traits::{VehicleKind, Vehicles};
+
+// What a vehicle costs today, line by line
+// A quote starts at the list price converted into the quote currency, takes off depreciation for the age
+// of the vehicle (a schedule per category) and adds the fees and taxes that apply to its category:
+//     Family Car (Car, 3 years old)
+//         list price 32000 USD                                29440.00 EUR
+//         depreciation after 3 years, 15% declining          -11360.16 EUR
+//         registration                                          150.00 EUR
+//         VAT 21% of the subtotal                              3828.27 EUR
+//         total                                               22058.11 EUR
+//
+// All amounts are Decimals, every line is rounded once to the minor units of the currency with the
+// engine's RoundingMode, and the total is the exact sum of the lines, so a quote always adds up.
+// Vehicles keep their Money (an f64 in dollars), it becomes a Decimal through its shortest printed form.
+
+#[derive(Debug)]
+pub enum PricingError {
+    // not three capital letters
+    InvalidCurrency(String),
+    // a currency the rates table doesn't know
+    UnknownCurrency(CurrencyCode),
+    // a price that is NaN, infinite or too large for a Decimal
+    InvalidAmount(f64),
+    InvalidRates { line: usize, reason: String },
+    // a vehicle type that isn't in the vehicles! list, it has no category
+    UnknownVehicle(String),
+    // a conversion, a line or a total with more digits than a Decimal holds
+    Overflow
+}
+
+impl fmt::Display for PricingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PricingError::InvalidCurrency(code) => write!(f, "{:?} is not a currency code, those are three capital letters like EUR", code),
+            PricingError::UnknownCurrency(code) => write!(f, "there is no exchange rate for {}", code),
+            PricingError::InvalidAmount(amount) => write!(f, "{} can't be priced, it is not a finite amount", amount),
+            PricingError::InvalidRates { line, reason } => write!(f, "invalid rates table, line {}: {}", line, reason),
+            PricingError::UnknownVehicle(name) => write!(f, "{} is not one of the known vehicle types", name),
+            PricingError::Overflow => write!(f, "the amount has more digits than a Decimal can hold")
+        }
+    }
+}
+
+impl std::error::Error for PricingError {}
+
+//=================================================================
+// Currencies at runtime, Money<C> only knows its currency in the type
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CurrencyCode([u8; 3]);
+
+impl CurrencyCode {
+    pub const USD: CurrencyCode = CurrencyCode(*b"USD");
+
+    pub fn of<C: Currency>() -> CurrencyCode {
+        C::CODE.parse().expect("the currency types have valid codes")
+    }
+
+    pub fn as_str(&self) -> &str {
+        std::str::from_utf8(&self.0).expect("codes are checked to be ASCII")
+    }
+}
+
+impl FromStr for CurrencyCode {
+    type Err = PricingError;
+
+    fn from_str(code: &str) -> Result<CurrencyCode, PricingError> {
+        match code.as_bytes() {
+            &[a, b, c] if code.bytes().all(|byte| byte.is_ascii_uppercase()) => Ok(CurrencyCode([a, b, c])),
+            _ => Err(PricingError::InvalidCurrency(code.to_string()))
+        }
+    }
+}
+
+impl fmt::Display for CurrencyCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.as_str())
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Price {
+    pub amount: Decimal,
+    pub currency: CurrencyCode
+}
+
+impl Price {
+    pub const fn new(amount: Decimal, currency: CurrencyCode) -> Self {
+        Self { amount, currency }
+    }
+
+    pub fn from_money<C: Currency>(money: Money<C>) -> Result<Price, PricingError> {
+        let amount = Decimal::from_f64(money.amount()).ok_or(PricingError::InvalidAmount(money.amount()))?;
+        Ok(Price::new(amount, CurrencyCode::of::<C>()))
+    }
+}
+
+impl fmt::Display for Price {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.amount, self.currency)
+    }
+}
+
+//=================================================================
+// What one unit of the base currency is worth in the others, kept locally instead of fetched
+//     # 1 USD is worth
+//     base USD
+//     EUR 0.92
+//     JPY 149.5 0
+// The optional last column is the number of digits after the point (the minor units), 2 when left out.
+
+// a Decimal holds 38 digits, 18 after the point still leave 20 for the amount
+pub const MAX_MINOR_UNITS: u32 = 18;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Rate {
+    per_base: Decimal,
+    minor_units: u32
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RatesTable {
+    base: CurrencyCode,
+    rates: BTreeMap<CurrencyCode, Rate>
+}
+
+impl RatesTable {
+    // only the base currency, at 1 with 2 minor units
+    pub fn new(base: CurrencyCode) -> Self {
+        let mut rates = BTreeMap::new();
+        rates.insert(base, Rate { per_base: Decimal::ONE, minor_units: 2 });
+        Self { base, rates }
+    }
+
+    pub fn rate(mut self, currency: CurrencyCode, per_base: Decimal, minor_units: u32) -> Self {
+        assert!(per_base > Decimal::ZERO, "an exchange rate has to be positive");
+        assert!(minor_units <= MAX_MINOR_UNITS, "a currency has at most {} minor units", MAX_MINOR_UNITS);
+        self.rates.insert(currency, Rate { per_base, minor_units });
+        self
+    }
+
+    pub fn parse(text: &str) -> Result<RatesTable, PricingError> {
+        let mut table: Option<RatesTable> = None;
+        for (index, line) in text.lines().enumerate() {
+            let error = |reason: String| PricingError::InvalidRates { line: index + 1, reason };
+            let words: Vec<&str> = line.split('#').next().unwrap_or_default().split_whitespace().collect();
+            match (words.as_slice(), &mut table) {
+                ([], _) => {}
+                (["base", code], None) => table = Some(RatesTable::new(code.parse().map_err(|e: PricingError| error(e.to_string()))?)),
+                (["base", ..], Some(_)) => return Err(error("the base currency is given twice".to_string())),
+                (_, None) => return Err(error("the table has to start with `base` and a currency".to_string())),
+                ([code, rate, rest @ ..], Some(table)) if rest.len() <= 1 => {
+                    let code: CurrencyCode = code.parse().map_err(|e: PricingError| error(e.to_string()))?;
+                    let rate: Decimal = rate.parse().map_err(|e: ParseDecimalError| error(e.to_string()))?;
+                    let minor_units = match rest {
+                        [digits] => digits.parse().map_err(|_| error(format!("{:?} is not a number of digits", digits)))?,
+                        _ => 2
+                    };
+                    if minor_units > MAX_MINOR_UNITS {
+                        return Err(error(format!("{} can have at most {} digits, got {}", code, MAX_MINOR_UNITS, minor_units)));
+                    }
+                    if rate <= Decimal::ZERO {
+                        return Err(error(format!("the rate of {} has to be positive", code)));
+                    }
+                    if code == table.base && rate != Decimal::ONE {
+                        return Err(error(format!("the base currency {} is worth 1 of itself", code)));
+                    }
+                    table.rates.insert(code, Rate { per_base: rate, minor_units });
+                }
+                _ => return Err(error("expected a currency, its rate and optionally its digits".to_string()))
+            }
+        }
+        table.ok_or(PricingError::InvalidRates { line: 0, reason: "the table is empty".to_string() })
+    }
+
+    pub fn base(&self) -> CurrencyCode {
+        self.base
+    }
+
+    pub fn currencies(&self) -> impl Iterator<Item = CurrencyCode> + '_ {
+        self.rates.keys().copied()
+    }
+
+    fn lookup(&self, currency: CurrencyCode) -> Result<Rate, PricingError> {
+        self.rates.get(&currency).copied().ok_or(PricingError::UnknownCurrency(currency))
+    }
+
+    pub fn minor_units(&self, currency: CurrencyCode) -> Result<u32, PricingError> {
+        Ok(self.lookup(currency)?.minor_units)
+    }
+
+    // how many `to` one `from` is worth, with `scale` digits
+    pub fn exchange_rate(&self, from: CurrencyCode, to: CurrencyCode, scale: u32, mode: RoundingMode) -> Result<Decimal, PricingError> {
+        self.convert(Decimal::ONE, from, to, scale, mode)
+    }
+
+    // amount in `from` as `to`, rounded once to `scale` digits: amount * rate(to) / rate(from)
+    pub fn convert(&self, amount: Decimal, from: CurrencyCode, to: CurrencyCode, scale: u32, mode: RoundingMode) -> Result<Decimal, PricingError> {
+        let (from, to) = (self.lookup(from)?, self.lookup(to)?);
+        amount
+            .checked_mul(to.per_base)
+            .and_then(|amount| amount.checked_div(from.per_base, scale, mode))
+            .ok_or(PricingError::Overflow)
+    }
+}
+
+//=================================================================
+// How a category loses value, shares are fractions: 0.15 is 15%
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Depreciation {
+    // keeps its value
+    None,
+    // the same share of the new price every year, down to the residual share
+    StraightLine { yearly: Decimal, residual: Decimal },
+    // a share of what is left every year, rounded every year the way a ledger books it
+    DecliningBalance { yearly: Decimal },
+    // the share of the new price left after each year, after the last year the last share stays
+    Table(Vec<Decimal>)
+}
+
+impl Depreciation {
+    // the schedule a PricingEngine starts with
+    pub fn default_for(category: Category) -> Depreciation {
+        let share = |units| Decimal::new(units, 2);
+        match category {
+            Category::Automobile => Depreciation::DecliningBalance { yearly: share(15) },
+            Category::Watercraft => Depreciation::StraightLine { yearly: share(6), residual: share(25) },
+            Category::Amphibian => Depreciation::DecliningBalance { yearly: share(12) },
+            Category::Aircraft => Depreciation::Table([90, 82, 75, 70, 66, 63, 60].into_iter().map(share).collect())
+        }
+    }
+
+    // the value of a vehicle bought new at `price` after `years`, with `scale` digits
+    pub fn value_after(&self, price: Decimal, years: u32, scale: u32, mode: RoundingMode) -> Decimal {
+        self.checked_value_after(price, years, scale, mode).expect("decimal overflow")
+    }
+
+    // None when the value has more digits than a Decimal holds
+    pub fn checked_value_after(&self, price: Decimal, years: u32, scale: u32, mode: RoundingMode) -> Option<Decimal> {
+        if years == 0 {
+            return price.checked_round(scale, mode);
+        }
+        match self {
+            Depreciation::None => price.checked_round(scale, mode),
+            Depreciation::StraightLine { yearly, residual } => {
+                let left = Decimal::ONE.checked_sub(yearly.checked_mul(Decimal::from(years as i64))?)?.max(*residual);
+                price.checked_mul(left)?.checked_round(scale, mode)
+            }
+            Depreciation::DecliningBalance { yearly } => {
+                let kept = Decimal::ONE.checked_sub(*yearly)?;
+                (0..years).try_fold(price.checked_round(scale, mode)?, |value, _| value.checked_mul(kept)?.checked_round(scale, mode))
+            }
+            Depreciation::Table(shares) => {
+                let share = shares.get(years as usize - 1).or(shares.last()).copied().unwrap_or(Decimal::ONE);
+                price.checked_mul(share)?.checked_round(scale, mode)
+            }
+        }
+    }
+}
+
+fn percent(share: Decimal) -> String {
+    format!("{}%", (share * Decimal::from(100)).normalize())
+}
+
+impl fmt::Display for Depreciation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Depreciation::None => write!(f, "none"),
+            Depreciation::StraightLine { yearly, residual } => write!(f, "{} straight line, at least {} left", percent(*yearly), percent(*residual)),
+            Depreciation::DecliningBalance { yearly } => write!(f, "{} declining", percent(*yearly)),
+            Depreciation::Table(_) => write!(f, "per table")
+        }
+    }
+}
+
+//=================================================================
+// Fees and taxes, applied in the order they are added to the engine
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Basis {
+    // the depreciated value of the vehicle
+    Value,
+    // the value with every fee and tax before this one, VAT over a registration fee
+    Subtotal
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Charge {
+    Percentage { rate: Decimal, basis: Basis },
+    // in any currency of the rates table
+    Flat(Price),
+    // the rate on the part of the value above the threshold, a luxury tax
+    Above { threshold: Price, rate: Decimal }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rule {
+    pub name: String,
+    pub charge: Charge,
+    // the categories it applies to, all of them when empty
+    pub categories: Vec<Category>
+}
+
+impl Rule {
+    pub fn percentage(name: &str, rate: Decimal, basis: Basis) -> Self {
+        Self { name: name.to_string(), charge: Charge::Percentage { rate, basis }, categories: Vec::new() }
+    }
+
+    pub fn flat(name: &str, price: Price) -> Self {
+        Self { name: name.to_string(), charge: Charge::Flat(price), categories: Vec::new() }
+    }
+
+    pub fn above(name: &str, threshold: Price, rate: Decimal) -> Self {
+        Self { name: name.to_string(), charge: Charge::Above { threshold, rate }, categories: Vec::new() }
+    }
+
+    // limits the rule to a category, can be called once per category
+    pub fn only(mut self, category: Category) -> Self {
+        self.categories.push(category);
+        self
+    }
+
+    pub fn applies_to(&self, category: Category) -> bool {
+        self.categories.is_empty() || self.categories.contains(&category)
+    }
+
+    fn label(&self) -> String {
+        match &self.charge {
+            Charge::Percentage { rate, basis: Basis::Value } => format!("{} {}", self.name, percent(*rate)),
+            Charge::Percentage { rate, basis: Basis::Subtotal } => format!("{} {} of the subtotal", self.name, percent(*rate)),
+            Charge::Flat(_) => self.name.clone(),
+            Charge::Above { threshold, rate } => format!("{} {} above {}", self.name, percent(*rate), threshold)
+        }
+    }
+}
+
+//=================================================================
+// Quotes
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LineKind {
+    ListPrice,
+    Depreciation,
+    Charge
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Line {
+    pub kind: LineKind,
+    pub label: String,
+    // in the quote currency, rounded to its minor units; negative for depreciation
+    pub amount: Decimal
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Quote {
+    pub name: String,
+    pub kind: VehicleKind,
+    pub age: u32,
+    pub currency: CurrencyCode,
+    pub lines: Vec<Line>,
+    // the sum of the lines
+    pub total: Decimal
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FleetQuote {
+    pub quotes: Vec<Quote>,
+    pub currency: CurrencyCode,
+    pub total: Decimal
+}
+
+pub struct PricingEngine {
+    rates: RatesTable,
+    currency: CurrencyCode,
+    rounding: RoundingMode,
+    depreciation: Vec<(Category, Depreciation)>,
+    rules: Vec<Rule>
+}
+
+impl PricingEngine {
+    // quotes in `currency` with half even rounding, the default depreciation and no fees or taxes
+    pub fn new(rates: RatesTable, currency: CurrencyCode) -> Self {
+        let depreciation = [Category::Automobile, Category::Watercraft, Category::Amphibian, Category::Aircraft]
+            .into_iter()
+            .map(|category| (category, Depreciation::default_for(category)))
+            .collect();
+        Self { rates, currency, rounding: RoundingMode::HalfEven, depreciation, rules: Vec::new() }
+    }
+
+    pub fn rounding(mut self, mode: RoundingMode) -> Self {
+        self.rounding = mode;
+        self
+    }
+
+    // replaces the schedule of the category
+    pub fn depreciation(mut self, category: Category, schedule: Depreciation) -> Self {
+        self.depreciation.retain(|(other, _)| *other != category);
+        self.depreciation.push((category, schedule));
+        self
+    }
+
+    pub fn rule(mut self, rule: Rule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    pub fn schedule(&self, category: Category) -> &Depreciation {
+        self.depreciation.iter().find(|(other, _)| *other == category).map_or(&Depreciation::None, |(_, schedule)| schedule)
+    }
+
+    pub fn quote(&self, vehicle: &dyn Vehicle, age: u32) -> Result<Quote, PricingError> {
+        let kind = VehicleKind::of(vehicle).ok_or_else(|| PricingError::UnknownVehicle(vehicle.name().to_string()))?;
+        let category = spec_of(kind).category;
+        let scale = self.rates.minor_units(self.currency)?;
+        let convert = |price: Price| self.rates.convert(price.amount, price.currency, self.currency, scale, self.rounding);
+
+        let list_price = Price::from_money(vehicle.price())?;
+        let new_value = convert(list_price)?;
+        let mut lines = vec![Line { kind: LineKind::ListPrice, label: format!("list price {}", list_price), amount: new_value }];
+
+        let schedule = self.schedule(category);
+        let value = schedule.checked_value_after(new_value, age, scale, self.rounding).ok_or(PricingError::Overflow)?;
+        if age > 0 && *schedule != Depreciation::None {
+            let years = if age == 1 { "1 year".to_string() } else { format!("{} years", age) };
+            let amount = value.checked_sub(new_value).ok_or(PricingError::Overflow)?;
+            lines.push(Line { kind: LineKind::Depreciation, label: format!("depreciation after {}, {}", years, schedule), amount });
+        }
+
+        let share = |base: Decimal, rate: Decimal| base.checked_mul(rate).and_then(|amount| amount.checked_round(scale, self.rounding)).ok_or(PricingError::Overflow);
+        let mut subtotal = value;
+        for rule in self.rules.iter().filter(|rule| rule.applies_to(category)) {
+            let amount = match &rule.charge {
+                Charge::Percentage { rate, basis: Basis::Value } => share(value, *rate)?,
+                Charge::Percentage { rate, basis: Basis::Subtotal } => share(subtotal, *rate)?,
+                Charge::Flat(price) => convert(*price)?,
+                Charge::Above { threshold, rate } => {
+                    let above = value.checked_sub(convert(*threshold)?).ok_or(PricingError::Overflow)?;
+                    share(above.max(Decimal::ZERO), *rate)?
+                }
+            };
+            subtotal = subtotal.checked_add(amount).ok_or(PricingError::Overflow)?;
+            lines.push(Line { kind: LineKind::Charge, label: rule.label(), amount });
+        }
+        Ok(Quote { name: vehicle.name().to_string(), kind, age, currency: self.currency, lines, total: subtotal })
+    }
+
+    // one quote per vehicle with its age in years, and what the whole fleet costs
+    pub fn quote_fleet<'a>(&self, vehicles: impl IntoIterator<Item = (&'a Vehicles, u32)>) -> Result<FleetQuote, PricingError> {
+        let quotes = vehicles.into_iter().map(|(vehicle, age)| self.quote(vehicle, age)).collect::<Result<Vec<Quote>, PricingError>>()?;
+        let total = quotes.iter().try_fold(Decimal::ZERO, |total, quote| total.checked_add(quote.total)).ok_or(PricingError::Overflow)?;
+        Ok(FleetQuote { quotes, currency: self.currency, total })
+    }
+}
+
+impl fmt::Display for Quote {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let age = match self.age {
+            0 => "new".to_string(),
+            1 => "1 year old".to_string(),
+            age => format!("{} years old", age)
+        };
+        writeln!(f, "{} ({:?}, {})", self.name, self.kind, age)?;
+        for line in &self.lines {
+            writeln!(f, "    {:<45} {:>14} {}", line.label, line.amount, self.currency)?;
+        }
+        write!(f, "    {:<45} {:>14} {}", "total", self.total, self.currency)
+    }
+}
+
+impl fmt::Display for FleetQuote {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for quote in &self.quotes {
+            writeln!(f, "{}", quote)?;
+        }
+        write!(f, "{:<49} {:>14} {}", format!("{} vehicles together", self.quotes.len()), self.total, self.currency)
+    }
+}
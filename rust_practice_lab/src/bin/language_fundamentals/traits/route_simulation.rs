@@ -0,0 +1,248 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fmt;
+use std::time::Duration;
+use super::associated_constants::{Terrain, VehicleSpec};
+use super::default_type_parameters::Length;
+
+// Vehicles moving through a world instead of only printing that they drive
+//     World: places connected by roads, sea lanes and air corridors
+//     World::route: the fastest way from one place to another, only over the terrain a vehicle can go on,
+//                   at the speed from its specification (associated_constants.rs)
+//     simulate: follows a route in fixed ticks and logs what happens, the log is plain data to assert on
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PlaceId(usize);
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Edge {
+    pub from: PlaceId,
+    pub to: PlaceId,
+    pub terrain: Terrain,
+    pub length: Length
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct World {
+    places: Vec<String>,
+    edges: Vec<Edge>
+}
+
+impl World {
+    pub fn new() -> Self {
+        Self { places: Vec::new(), edges: Vec::new() }
+    }
+
+    pub fn add_place(&mut self, name: &str) -> PlaceId {
+        self.places.push(name.to_string());
+        PlaceId(self.places.len() - 1)
+    }
+
+    // edges go both ways, their length has to be finite and zero or more
+    pub fn connect(&mut self, a: PlaceId, b: PlaceId, terrain: Terrain, length: Length) -> Result<(), InvalidLength> {
+        if !(length.meters().is_finite() && length.meters() >= 0.0) {
+            return Err(InvalidLength(length));
+        }
+        self.edges.push(Edge { from: a, to: b, terrain, length });
+        Ok(())
+    }
+
+    pub fn place(&self, name: &str) -> Option<PlaceId> {
+        self.places.iter().position(|place| place == name).map(PlaceId)
+    }
+
+    pub fn name(&self, place: PlaceId) -> &str {
+        &self.places[place.0]
+    }
+
+    fn neighbours(&self, place: PlaceId) -> impl Iterator<Item = Edge> + '_ {
+        self.edges.iter().filter_map(move |edge| {
+            if edge.from == place {
+                Some(*edge)
+            } else if edge.to == place {
+                Some(Edge { from: edge.to, to: edge.from, ..*edge })
+            } else {
+                None
+            }
+        })
+    }
+
+    // Dijkstra over travel time, edges the vehicle can't use are skipped,
+    // like edges so long that their travel time doesn't fit in a Duration
+    pub fn route(&self, spec: &VehicleSpec, from: PlaceId, to: PlaceId) -> Result<Route, RouteError> {
+        let mut fastest: Vec<Option<Duration>> = vec![None; self.places.len()];
+        let mut previous: Vec<Option<Leg>> = vec![None; self.places.len()];
+        let mut queue = BinaryHeap::new();
+        fastest[from.0] = Some(Duration::ZERO);
+        queue.push(Reverse((Duration::ZERO, from)));
+
+        while let Some(Reverse((time, place))) = queue.pop() {
+            if place == to {
+                break;
+            }
+            // an older, slower entry for a place that was reached faster in the meantime
+            if fastest[place.0].is_some_and(|best| time > best) {
+                continue;
+            }
+            for edge in self.neighbours(place) {
+                let Some(speed) = spec.speed_on(edge.terrain) else {
+                    continue;
+                };
+                let Some(duration) = edge.length.checked_div(speed) else {
+                    continue;
+                };
+                let Some(arrival) = time.checked_add(duration) else {
+                    continue;
+                };
+                if fastest[edge.to.0].is_none_or(|best| arrival < best) {
+                    fastest[edge.to.0] = Some(arrival);
+                    previous[edge.to.0] = Some(Leg { from: place, to: edge.to, terrain: edge.terrain, length: edge.length, duration });
+                    queue.push(Reverse((arrival, edge.to)));
+                }
+            }
+        }
+
+        if fastest[to.0].is_none() {
+            return Err(RouteError { model: spec.model, from: self.name(from).to_string(), to: self.name(to).to_string() });
+        }
+        let mut legs = Vec::new();
+        let mut place = to;
+        while place != from {
+            let leg = previous[place.0].expect("every reached place except the start has a leg to it");
+            place = leg.from;
+            legs.push(leg);
+        }
+        legs.reverse();
+        Ok(Route { model: spec.model, legs })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RouteError {
+    pub model: &'static str,
+    pub from: String,
+    pub to: String
+}
+
+impl fmt::Display for RouteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a {} can't get from {} to {}", self.model, self.from, self.to)
+    }
+}
+
+impl std::error::Error for RouteError {}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct InvalidLength(pub Length);
+
+impl fmt::Display for InvalidLength {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} is not the length of a connection, it has to be finite and zero or more", self.0)
+    }
+}
+
+impl std::error::Error for InvalidLength {}
+
+//=================================================================
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Leg {
+    pub from: PlaceId,
+    pub to: PlaceId,
+    pub terrain: Terrain,
+    pub length: Length,
+    pub duration: Duration
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Route {
+    pub model: &'static str,
+    pub legs: Vec<Leg>
+}
+
+impl Route {
+    pub fn duration(&self) -> Duration {
+        self.legs.iter().map(|leg| leg.duration).sum()
+    }
+
+    pub fn length(&self) -> Length {
+        self.legs.iter().map(|leg| leg.length).sum()
+    }
+
+    // every place on the route in order, the start included
+    pub fn places(&self) -> Vec<PlaceId> {
+        let mut places: Vec<PlaceId> = self.legs.iter().map(|leg| leg.from).take(1).collect();
+        places.extend(self.legs.iter().map(|leg| leg.to));
+        places
+    }
+}
+
+//=================================================================
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum EventKind {
+    // starts the leg with this index, over this terrain
+    Departed { place: PlaceId, leg: usize, terrain: Terrain },
+    // still on the leg at the end of the tick
+    Travelling { leg: usize, covered: Length },
+    Arrived { place: PlaceId },
+    Finished { place: PlaceId }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Event {
+    pub tick: u64,
+    pub kind: EventKind
+}
+
+// Follows the route in steps of `tick`: departures happen in tick 0, everything that happens during
+// a tick gets its number (arriving halfway through tick 3 is logged in tick 3), and the position at the
+// end of a tick is logged as Travelling. A route without legs logs nothing.
+pub fn simulate(route: &Route, tick: Duration) -> Vec<Event> {
+    assert!(!tick.is_zero(), "a tick has to take some time");
+    let mut events = Vec::new();
+    let Some(first) = route.legs.first() else {
+        return events;
+    };
+    events.push(Event { tick: 0, kind: EventKind::Departed { place: first.from, leg: 0, terrain: first.terrain } });
+
+    let mut leg = 0;
+    // time spent on the current leg
+    let mut elapsed = 0.0;
+    let mut tick_number = 0;
+    while leg < route.legs.len() {
+        tick_number += 1;
+        let mut remaining = tick.as_secs_f64();
+        while leg < route.legs.len() {
+            let current = route.legs[leg];
+            let left = current.duration.as_secs_f64() - elapsed;
+            if remaining < left {
+                elapsed += remaining;
+                let covered = current.length * (elapsed / current.duration.as_secs_f64());
+                events.push(Event { tick: tick_number, kind: EventKind::Travelling { leg, covered } });
+                break;
+            }
+            remaining -= left;
+            elapsed = 0.0;
+            events.push(Event { tick: tick_number, kind: EventKind::Arrived { place: current.to } });
+            leg += 1;
+            match route.legs.get(leg) {
+                Some(next) => events.push(Event { tick: tick_number, kind: EventKind::Departed { place: next.from, leg, terrain: next.terrain } }),
+                None => events.push(Event { tick: tick_number, kind: EventKind::Finished { place: current.to } })
+            }
+        }
+    }
+    events
+}
+
+// One line per event, for printing a log
+pub fn describe_event(world: &World, route: &Route, event: &Event) -> String {
+    let what = match event.kind {
+        EventKind::Departed { place, terrain, .. } => format!("leaves {} over {:?}", world.name(place), terrain),
+        EventKind::Travelling { leg, covered } => {
+            let leg = route.legs[leg];
+            format!("{:.1} of {:.1} km from {} to {}", covered.kilometers(), leg.length.kilometers(), world.name(leg.from), world.name(leg.to))
+        }
+        EventKind::Arrived { place } => format!("arrives in {}", world.name(place)),
+        EventKind::Finished { place } => format!("is done in {}", world.name(place))
+    };
+    format!("[tick {:>3}] {} {}", event.tick, route.model, what)
+}
@@ -0,0 +1,142 @@
+use std::any::Any;
+use super::traits::poly::{AirVehicle, LandVehicle, SeaVehicle, Vehicle};
+use super::traits::Vehicles;
+
+// The Vehicles enum has to know every vehicle type up front, a Vec<Box<dyn Vehicle>> doesn't
+// The price is that the concrete type is gone, so it has to be asked for at runtime:
+//     capabilities: vehicle.as_land(), as_sea() and as_air() (implemented by the vehicles themselves)
+//     concrete types: vehicle.downcast_ref::<Car>(), works because every Vehicle is also Any
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Capability {
+    Land,
+    Sea,
+    Air
+}
+
+impl Capability {
+    pub const ALL: [Capability; 3] = [Capability::Land, Capability::Sea, Capability::Air];
+}
+
+impl dyn Vehicle {
+    pub fn can(&self, capability: Capability) -> bool {
+        match capability {
+            Capability::Land => self.as_land().is_some(),
+            Capability::Sea => self.as_sea().is_some(),
+            Capability::Air => self.as_air().is_some()
+        }
+    }
+
+    pub fn capabilities(&self) -> Vec<Capability> {
+        Capability::ALL.into_iter().filter(|&capability| self.can(capability)).collect()
+    }
+
+    // &dyn Vehicle -> &dyn Any is trait upcasting, Any then compares the TypeId
+    pub fn is<T: Vehicle>(&self) -> bool {
+        (self as &dyn Any).is::<T>()
+    }
+
+    pub fn downcast_ref<T: Vehicle>(&self) -> Option<&T> {
+        (self as &dyn Any).downcast_ref::<T>()
+    }
+}
+
+// Gives the box back when it holds another type, so the vehicle isn't lost
+pub fn downcast<T: Vehicle>(vehicle: Box<dyn Vehicle>) -> Result<Box<T>, Box<dyn Vehicle>> {
+    if vehicle.is::<T>() {
+        let any: Box<dyn Any> = vehicle;
+        Ok(any.downcast::<T>().expect("the type was checked above"))
+    } else {
+        Err(vehicle)
+    }
+}
+
+// Every variant of the enum is a vehicle, so the enum converts into a trait object of the vehicle inside
+// (a Box::new(vehicles) would be a dyn Vehicle as well, but downcasts to Vehicles instead of Car)
+impl From<Vehicles> for Box<dyn Vehicle> {
+    fn from(vehicle: Vehicles) -> Self {
+        vehicle.into_vehicle()
+    }
+}
+
+//=================================================================
+// A collection of any kind of vehicle
+#[derive(Default)]
+pub struct Fleet {
+    vehicles: Vec<Box<dyn Vehicle>>
+}
+
+impl Fleet {
+    pub fn new() -> Self {
+        Self { vehicles: Vec::new() }
+    }
+
+    pub fn add(&mut self, vehicle: impl Vehicle) {
+        self.vehicles.push(Box::new(vehicle));
+    }
+
+    pub fn len(&self) -> usize {
+        self.vehicles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.vehicles.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &dyn Vehicle> {
+        self.vehicles.iter().map(|vehicle| vehicle.as_ref())
+    }
+
+    pub fn with(&self, capability: Capability) -> impl Iterator<Item = &dyn Vehicle> {
+        self.iter().filter(move |vehicle| vehicle.can(capability))
+    }
+
+    pub fn land(&self) -> impl Iterator<Item = &dyn LandVehicle> {
+        self.iter().filter_map(|vehicle| vehicle.as_land())
+    }
+
+    pub fn sea(&self) -> impl Iterator<Item = &dyn SeaVehicle> {
+        self.iter().filter_map(|vehicle| vehicle.as_sea())
+    }
+
+    pub fn air(&self) -> impl Iterator<Item = &dyn AirVehicle> {
+        self.iter().filter_map(|vehicle| vehicle.as_air())
+    }
+
+    // Every vehicle of one concrete type, e.g. fleet.of_type::<Car>()
+    pub fn of_type<T: Vehicle>(&self) -> impl Iterator<Item = &T> {
+        self.iter().filter_map(|vehicle| vehicle.downcast_ref::<T>())
+    }
+
+    // Takes the vehicles of one concrete type out of the fleet, the others stay
+    pub fn take<T: Vehicle>(&mut self) -> Vec<T> {
+        let mut taken = Vec::new();
+        let mut kept = Vec::new();
+        for vehicle in self.vehicles.drain(..) {
+            match downcast::<T>(vehicle) {
+                Ok(vehicle) => taken.push(*vehicle),
+                Err(vehicle) => kept.push(vehicle)
+            }
+        }
+        self.vehicles = kept;
+        taken
+    }
+}
+
+impl FromIterator<Box<dyn Vehicle>> for Fleet {
+    fn from_iter<I: IntoIterator<Item = Box<dyn Vehicle>>>(iter: I) -> Self {
+        Self { vehicles: iter.into_iter().collect() }
+    }
+}
+
+impl FromIterator<Vehicles> for Fleet {
+    fn from_iter<I: IntoIterator<Item = Vehicles>>(iter: I) -> Self {
+        iter.into_iter().map(Box::<dyn Vehicle>::from).collect()
+    }
+}
+
+impl Extend<Box<dyn Vehicle>> for Fleet {
+    fn extend<I: IntoIterator<Item = Box<dyn Vehicle>>>(&mut self, iter: I) {
+        self.vehicles.extend(iter);
+    }
+}
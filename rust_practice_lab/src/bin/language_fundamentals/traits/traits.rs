@@ -1,55 +1,190 @@
 use poly::Vehicle;
+use default_type_parameters::{Length, Mass, Money, Power};
+use vehicle_events::{render_text, VehicleEvent};
+
+// The vehicles store their numbers as quantities with a unit
+// traits/mod.rs re-exports this module, the polymorphism bin compiles this file on its own and needs it here
+#[path = "default_type_parameters.rs"]
+pub mod default_type_parameters;
+
+// The behaviour functions return what happened, the events and how they are printed live here
+#[path = "vehicle_events.rs"]
+pub mod vehicle_events;
 
 // A kind of polymorphism
+// The enum, its kinds and every match over the variants come from one list of vehicle types,
+// a new vehicle type only has to be added to the vehicles! call below
+macro_rules! vehicles {
+    ($($variant:ident),* $(,)?) => {
+        // serialized with the variant name in a "type" field, next to the fields of the vehicle (see vehicle_records.rs)
+        #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+        #[serde(tag = "type")]
+        pub enum Vehicles {
+            $($variant(poly::$variant)),*
+        }
+
+        #[derive(Debug, Copy, Clone, PartialEq, Eq, serde::Serialize)]
+        pub enum VehicleKind {
+            $($variant),*
+        }
+
+        impl Vehicles {
+            // The variant as a trait object, the Vehicle impl below dispatches through this
+            pub fn as_vehicle(&self) -> &dyn Vehicle {
+                match self {
+                    $(Vehicles::$variant(vehicle) => vehicle),*
+                }
+            }
+
+            pub fn into_vehicle(self) -> Box<dyn Vehicle> {
+                match self {
+                    $(Vehicles::$variant(vehicle) => Box::new(vehicle)),*
+                }
+            }
+
+            pub fn kind(&self) -> VehicleKind {
+                match self {
+                    $(Vehicles::$variant(_) => VehicleKind::$variant),*
+                }
+            }
+        }
+
+        impl VehicleKind {
+            pub const ALL: &'static [VehicleKind] = &[$(VehicleKind::$variant),*];
+
+            // The kind of the vehicle behind a trait object, also when it is a whole Vehicles enum
+            // None for a vehicle type that isn't in the list
+            pub fn of(vehicle: &dyn Vehicle) -> Option<VehicleKind> {
+                let vehicle = vehicle as &dyn std::any::Any;
+                if let Some(vehicles) = vehicle.downcast_ref::<Vehicles>() {
+                    return Some(vehicles.kind());
+                }
+                $(
+                    if vehicle.is::<poly::$variant>() {
+                        return Some(VehicleKind::$variant);
+                    }
+                )*
+                None
+            }
+        }
 
-#[derive(Debug)]
-pub enum Vehicles {
-    Car(poly::Car),
-    Boat(poly::Boat),
-    AmphibiousBoat(poly::AmphibiousBoat),
-    Airplane(poly::Airplane)
+        $(
+            impl From<poly::$variant> for Vehicles {
+                fn from(vehicle: poly::$variant) -> Self {
+                    Vehicles::$variant(vehicle)
+                }
+            }
+        )*
+    };
+}
+
+vehicles!(Car, Boat, AmphibiousBoat, Airplane);
+
+// The enum is a vehicle too, every call goes to the vehicle in the variant
+impl Vehicle for Vehicles {
+    fn describe(&self) -> VehicleEvent {
+        self.as_vehicle().describe()
+    }
+
+    fn drive(&self) -> VehicleEvent {
+        self.as_vehicle().drive()
+    }
+
+    fn abstract_vehicle(&self) -> &poly::AbstractVehicle {
+        self.as_vehicle().abstract_vehicle()
+    }
+
+    fn as_land(&self) -> Option<&dyn poly::LandVehicle> {
+        self.as_vehicle().as_land()
+    }
+
+    fn as_sea(&self) -> Option<&dyn poly::SeaVehicle> {
+        self.as_vehicle().as_sea()
+    }
+
+    fn as_air(&self) -> Option<&dyn poly::AirVehicle> {
+        self.as_vehicle().as_air()
+    }
 }
 
 pub mod poly {
+    use std::any::Any;
+    use super::default_type_parameters::{Length, Mass, Money, Power};
+    use serde::{Deserialize, Serialize};
+    use super::vehicle_events::{Description, TestDrive, VehicleEvent};
+    use super::VehicleKind;
 
     // Traits are used for shared behaviour
     // They are some kind of interface
-    pub trait Vehicle {
-        fn describe(&self);
-        fn drive(&self);
+    // Any lets a &dyn Vehicle be turned back into the concrete type (see trait_objects.rs)
+    // The behaviour functions don't print, they return what happened (see vehicle_events.rs)
+    pub trait Vehicle: Any {
+        fn describe(&self) -> VehicleEvent;
+        fn drive(&self) -> VehicleEvent;
+
+        // The name and price every vehicle has, wherever it keeps them
+        fn abstract_vehicle(&self) -> &AbstractVehicle;
+
+        fn name(&self) -> &str {
+            &self.abstract_vehicle().name
+        }
+
+        fn price(&self) -> Money {
+            self.abstract_vehicle().price
+        }
+
+        // A Box<dyn Vehicle> only knows about Vehicle, these ask at runtime what else it can do
+        // Every vehicle that implements one of the sub traits overrides the matching function
+        fn as_land(&self) -> Option<&dyn LandVehicle> {
+            None
+        }
+
+        fn as_sea(&self) -> Option<&dyn SeaVehicle> {
+            None
+        }
+
+        fn as_air(&self) -> Option<&dyn AirVehicle> {
+            None
+        }
     }
 
     // Different types of vehicles have generic vehicle behaviour (describe, drive)
     // But also a bit more specific behaviour
-    trait LandVehicle: Vehicle {
-        fn drift(&self);
+    pub trait LandVehicle: Vehicle {
+        fn drift(&self) -> VehicleEvent;
     }
 
-    trait SeaVehicle: Vehicle {
-        fn drop_sail(&self);
+    pub trait SeaVehicle: Vehicle {
+        fn drop_sail(&self) -> VehicleEvent;
     }
 
-    trait AirVehicle: Vehicle {
-        fn fly(&self); // air vehicles can drive and fly
+    pub trait AirVehicle: Vehicle {
+        fn fly(&self) -> VehicleEvent; // air vehicles can drive and fly
     }
 
     //=================================================================
-    #[derive(Debug, Copy, Clone)]
+    // Owned, so vehicles can be read from a file or built from user input
+    // A record with a field the struct doesn't have is rejected instead of silently dropped
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(deny_unknown_fields)]
     pub struct AbstractVehicle{
-        pub name: &'static str,
-        pub price: f64
+        pub name: String,
+        pub price: Money
     }
 
     //=================================================================
-    #[derive(Debug)]
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(deny_unknown_fields)]
     pub struct Car {
         pub vehicle: AbstractVehicle, // composition
-        pub horse_power: u16
+        // serialized in watts like every Power, the name says so
+        #[serde(rename = "power")]
+        pub horse_power: Power
     }
 
     impl Car {
         // constructor
-        pub fn new(vehicle: AbstractVehicle, horse_power: u16) -> Self {
+        pub fn new(vehicle: AbstractVehicle, horse_power: Power) -> Self {
             Self { vehicle, horse_power }
         }
     }
@@ -57,51 +192,69 @@ pub mod poly {
     // implement trait for car type, car gets the implemented trait functions
     // which means car objects can call these functions
     impl Vehicle for Car {
-        fn describe(&self) {
-            println!("I'm a Car! I am {}, have {} horse power and cost {} dollar", self.vehicle.name, self.horse_power, self.vehicle.price);
+        fn describe(&self) -> VehicleEvent {
+            VehicleEvent::Described(Description::Car { name: self.vehicle.name.clone(), price: self.vehicle.price, power: self.horse_power })
+        }
+
+        fn drive(&self) -> VehicleEvent {
+            VehicleEvent::Drove { kind: VehicleKind::Car, name: self.vehicle.name.clone() }
+        }
+
+        fn abstract_vehicle(&self) -> &AbstractVehicle {
+            &self.vehicle
         }
 
-        fn drive(&self) {
-            println!("{} (type Car) is now driving!", self.vehicle.name);
+        fn as_land(&self) -> Option<&dyn LandVehicle> {
+            Some(self)
         }
     }
 
     // A car is not just a vehicle, but also a land vehicle
     impl LandVehicle for Car {
-        fn drift(&self) {
-            println!("{} is now spinning and making donuts, get some new tires!", self.vehicle.name);
+        fn drift(&self) -> VehicleEvent {
+            VehicleEvent::Drifted { kind: VehicleKind::Car, name: self.vehicle.name.clone(), wheels: None }
         }
     }
     //=================================================================
-    #[derive(Debug, Copy, Clone)]
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(deny_unknown_fields)]
     pub struct Boat {
         pub vehicle: AbstractVehicle,
-        pub anchor_weight: u16
+        pub anchor_weight: Mass
     }
 
     impl Boat {
-        pub fn new(vehicle: AbstractVehicle, anchor_weight: u16) -> Self {
+        pub fn new(vehicle: AbstractVehicle, anchor_weight: Mass) -> Self {
             Self { vehicle, anchor_weight }
         }
     }
 
     impl Vehicle for Boat {
-        fn describe(&self) {
-            println!("I'm a Boat! I am anchored!");
+        fn describe(&self) -> VehicleEvent {
+            VehicleEvent::Described(Description::Boat { name: self.vehicle.name.clone(), price: self.vehicle.price })
         }
-        fn drive(&self) {
-            println!("{} (type Boat) is now driving at sea!", self.vehicle.name);
+        fn drive(&self) -> VehicleEvent {
+            VehicleEvent::Drove { kind: VehicleKind::Boat, name: self.vehicle.name.clone() }
+        }
+
+        fn abstract_vehicle(&self) -> &AbstractVehicle {
+            &self.vehicle
+        }
+
+        fn as_sea(&self) -> Option<&dyn SeaVehicle> {
+            Some(self)
         }
     }
 
     impl SeaVehicle for Boat {
-        fn drop_sail(&self) {
-            println!("Dropping the sail for more wind and speed!")
+        fn drop_sail(&self) -> VehicleEvent {
+            VehicleEvent::DroppedSail { kind: VehicleKind::Boat, name: self.vehicle.name.clone() }
         }
     }
 
     //=================================================================
-    #[derive(Debug)]
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(deny_unknown_fields)]
     pub struct AmphibiousBoat {
         pub boat: Boat, // and boat contains vehicle
         pub nr_wheels: u8
@@ -114,174 +267,229 @@ pub mod poly {
     }
 
     impl Vehicle for AmphibiousBoat {
-        fn describe(&self) {
-            println!("I'm a Amphibious Boat! I am anchored!");
+        fn describe(&self) -> VehicleEvent {
+            VehicleEvent::Described(Description::AmphibiousBoat { name: self.boat.vehicle.name.clone(), price: self.boat.vehicle.price, wheels: self.nr_wheels })
+        }
+        fn drive(&self) -> VehicleEvent {
+            VehicleEvent::Drove { kind: VehicleKind::AmphibiousBoat, name: self.boat.vehicle.name.clone() }
+        }
+
+        fn abstract_vehicle(&self) -> &AbstractVehicle {
+            &self.boat.vehicle
         }
-        fn drive(&self) {
-            println!("{} (type AmphibiousBoat) is now driving (or sailing)!", self.boat.vehicle.name);
+
+        fn as_land(&self) -> Option<&dyn LandVehicle> {
+            Some(self)
+        }
+
+        fn as_sea(&self) -> Option<&dyn SeaVehicle> {
+            Some(self)
         }
     }
 
     // This boat can also drive on land so it's a land vehicle and implements land vehicle functions
     impl LandVehicle for AmphibiousBoat {
-        fn drift(&self) {
-            println!("I may be a boat, but i have {} wheels and enough power to drift!", self.nr_wheels);
+        fn drift(&self) -> VehicleEvent {
+            VehicleEvent::Drifted { kind: VehicleKind::AmphibiousBoat, name: self.boat.vehicle.name.clone(), wheels: Some(self.nr_wheels) }
         }
     }
 
     // it is also a boat and of course implements the sea vehicle trait
     impl SeaVehicle for AmphibiousBoat {
-        fn drop_sail(&self) {
-            println!("Dropping the sail for more wind and speed!")
+        fn drop_sail(&self) -> VehicleEvent {
+            VehicleEvent::DroppedSail { kind: VehicleKind::AmphibiousBoat, name: self.boat.vehicle.name.clone() }
         }
     }
 
     //=================================================================
 
-    #[derive(Debug)]
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(deny_unknown_fields)]
     pub struct Airplane {
         pub vehicle: AbstractVehicle,
-        pub wing_length: u16
+        pub wing_length: Length
     }
 
     impl Airplane {
-        pub fn new(vehicle: AbstractVehicle, wing_length: u16) -> Self {
+        pub fn new(vehicle: AbstractVehicle, wing_length: Length) -> Self {
             Self { vehicle, wing_length }
         }
     }
 
     impl Vehicle for Airplane {
-        fn describe(&self) {
-            println!("I'm an airplane! My name is {}, i cost {} and my wings are {} meters tall!", self.vehicle.name, self.vehicle.price, self.wing_length);
+        fn describe(&self) -> VehicleEvent {
+            VehicleEvent::Described(Description::Airplane { name: self.vehicle.name.clone(), price: self.vehicle.price, wing_length: self.wing_length })
+        }
+
+        fn drive(&self) -> VehicleEvent {
+            VehicleEvent::Drove { kind: VehicleKind::Airplane, name: self.vehicle.name.clone() }
+        }
+
+        fn abstract_vehicle(&self) -> &AbstractVehicle {
+            &self.vehicle
         }
 
-        fn drive(&self) {
-            println!("{} (type Airplane) is now driving", self.vehicle.name);
+        fn as_land(&self) -> Option<&dyn LandVehicle> {
+            Some(self)
+        }
+
+        fn as_air(&self) -> Option<&dyn AirVehicle> {
+            Some(self)
         }
     }
 
     // Airplane can be driven on land
     impl LandVehicle for Airplane {
-        fn drift(&self) {
-            println!("I'm not so good at drifting, sorry!");
+        fn drift(&self) -> VehicleEvent {
+            VehicleEvent::Drifted { kind: VehicleKind::Airplane, name: self.vehicle.name.clone(), wheels: None }
         }
     }
 
     impl AirVehicle for Airplane {
-        fn fly(&self) {
-            println!("I'm very good at flying, see ya later!");
+        fn fly(&self) -> VehicleEvent {
+            VehicleEvent::Flew { kind: VehicleKind::Airplane, name: self.vehicle.name.clone() }
         }
     }
 
     //=================================================================
     // Let's create a function which accepts any type which implements the Vehicle trait
     // argument is a reference to anything which implements vehicle
-    pub fn test_drive_vehicle_v1(vehicle: &impl Vehicle) {
-        println!("Test drive v1");
-        vehicle.drive();
+    // a test drive returns everything that happened, in order
+    pub fn test_drive_vehicle_v1(vehicle: &impl Vehicle) -> Vec<VehicleEvent> {
+        vec![VehicleEvent::TestDrive(TestDrive::VehicleV1), vehicle.drive()]
     }
 
     // other syntax
-    pub fn test_drive_vehicle_v2<T: Vehicle>(vehicle: &T) {
-        println!("Test drive v2");
-        vehicle.drive();
+    pub fn test_drive_vehicle_v2<T: Vehicle>(vehicle: &T) -> Vec<VehicleEvent> {
+        vec![VehicleEvent::TestDrive(TestDrive::VehicleV2), vehicle.drive()]
     }
 
     // something which implements land vehicle
-    pub fn test_drive_land_vehicle_v1(land_vehicle: &impl LandVehicle) {
-        println!("Test land vehicle drive v1");
-        land_vehicle.describe();
-        land_vehicle.drive();
-        land_vehicle.drift();
+    pub fn test_drive_land_vehicle_v1(land_vehicle: &impl LandVehicle) -> Vec<VehicleEvent> {
+        vec![
+            VehicleEvent::TestDrive(TestDrive::LandVehicleV1),
+            land_vehicle.describe(),
+            land_vehicle.drive(),
+            land_vehicle.drift()
+        ]
     }
 
     // something which implements sea vehicle
-    pub fn test_drive_sea_vehicle_v1(sea_vehicle: &impl SeaVehicle) {
-        println!("Test sea vehicle drive v1");
-        sea_vehicle.describe();
-        sea_vehicle.drive();
-        sea_vehicle.drop_sail();
+    pub fn test_drive_sea_vehicle_v1(sea_vehicle: &impl SeaVehicle) -> Vec<VehicleEvent> {
+        vec![
+            VehicleEvent::TestDrive(TestDrive::SeaVehicleV1),
+            sea_vehicle.describe(),
+            sea_vehicle.drive(),
+            sea_vehicle.drop_sail()
+        ]
     }
 
     // something which implements more than one trait!
-    pub fn test_drive_hybrid_vehicle_v1<T: SeaVehicle + LandVehicle>(hybrid_vehicle: &T) {
-        println!("Test drive hybrid vehicle (sea and land vehicle)");
-        hybrid_vehicle.describe();
-        hybrid_vehicle.drive();
-        hybrid_vehicle.drift();         // it can do land vehicle stuff
-        hybrid_vehicle.drop_sail();     // and sea vehicle stuff
+    pub fn test_drive_hybrid_vehicle_v1<T: SeaVehicle + LandVehicle>(hybrid_vehicle: &T) -> Vec<VehicleEvent> {
+        vec![
+            VehicleEvent::TestDrive(TestDrive::HybridVehicleV1),
+            hybrid_vehicle.describe(),
+            hybrid_vehicle.drive(),
+            hybrid_vehicle.drift(),         // it can do land vehicle stuff
+            hybrid_vehicle.drop_sail()      // and sea vehicle stuff
+        ]
     }
 
-    pub fn test_drive_air_vehicle_v1<T: LandVehicle + AirVehicle>(air_vehicle: &T) {
-        println!("Test drive air vehicle");
-        air_vehicle.describe();
-        air_vehicle.drive();
-        air_vehicle.drift();
-        air_vehicle.fly();
+    pub fn test_drive_air_vehicle_v1<T: LandVehicle + AirVehicle>(air_vehicle: &T) -> Vec<VehicleEvent> {
+        vec![
+            VehicleEvent::TestDrive(TestDrive::AirVehicleV1),
+            air_vehicle.describe(),
+            air_vehicle.drive(),
+            air_vehicle.drift(),
+            air_vehicle.fly()
+        ]
     }
 }
 
 //=================================================================
 fn main() {
     let vc = poly::AbstractVehicle {
-        name: "Carry the Car",
-        price: 4500.0
+        name: "Carry the Car".to_string(),
+        price: Money::new(4500.0)
     };
 
     let vb = poly::AbstractVehicle {
-        name: "Bobby the Boat",
-        price: 63850.0
+        name: "Bobby the Boat".to_string(),
+        price: Money::new(63850.0)
     };
 
     let vab = poly::AbstractVehicle {
-        name: "Amber the Amphibious Boat",
-        price: 132000.0
+        name: "Amber the Amphibious Boat".to_string(),
+        price: Money::new(132000.0)
     };
 
     let va = poly::AbstractVehicle {
-        name: "Aimy the Airplane",
-        price: 4500000.0
+        name: "Aimy the Airplane".to_string(),
+        price: Money::new(4500000.0)
     };
 
-    let car = poly::Car::new(vc, 110);
-    let boat = poly::Boat::new(vb, 230);
+    let car = poly::Car::new(vc, Power::from_horse_power(110.0));
+    let boat = poly::Boat::new(vb, Mass::from_kilograms(230.0));
     let amphibious_boat = poly::AmphibiousBoat::new(poly::Boat {
-        vehicle: vab, anchor_weight: 3
+        vehicle: vab, anchor_weight: Mass::from_kilograms(3.0)
     }, 4);
-    let airplane = poly::Airplane::new(va, 30);
+    let airplane = poly::Airplane::new(va, Length::from_meters(30.0));
+
+    println!("{}", car.describe());
+    println!("{}", car.drive());
 
-    car.describe();
-    car.drive();
+    // Every number has a unit now, mixing them up doesn't compile
+    // let nonsense = car.vehicle.price + boat.anchor_weight;   // Money + Mass isn't implemented!
+    let fleet_price = car.vehicle.price + boat.vehicle.price;
+    let power_to_weight = car.horse_power / Mass::from_tonnes(1.2);
+    println!("Car and boat together cost {}, the car has {} with {}", fleet_price, car.horse_power, power_to_weight);
 
     println!("================================================================================\n");
     println!("Test driving Vehicles");
-    poly::test_drive_vehicle_v1(&car);                  // All vehicles
-    poly::test_drive_vehicle_v1(&boat);                 // All vehicles
-    poly::test_drive_vehicle_v1(&airplane);             // All vehicles
-    poly::test_drive_vehicle_v1(&amphibious_boat);      // All vehicles
+    print!("{}", render_text(&poly::test_drive_vehicle_v1(&car)));                  // All vehicles
+    print!("{}", render_text(&poly::test_drive_vehicle_v1(&boat)));                 // All vehicles
+    print!("{}", render_text(&poly::test_drive_vehicle_v1(&airplane)));             // All vehicles
+    print!("{}", render_text(&poly::test_drive_vehicle_v1(&amphibious_boat)));      // All vehicles
     println!("================================================================================\n");
     println!("Test driving Land Vehicles");
     // test_drive_land_vehicle_v1(&boat);                       // Not a land vehicle!
-    poly::test_drive_land_vehicle_v1(&car);
-    poly::test_drive_land_vehicle_v1(&airplane);
-    poly::test_drive_land_vehicle_v1(&amphibious_boat);
+    print!("{}", render_text(&poly::test_drive_land_vehicle_v1(&car)));
+    print!("{}", render_text(&poly::test_drive_land_vehicle_v1(&airplane)));
+    print!("{}", render_text(&poly::test_drive_land_vehicle_v1(&amphibious_boat)));
     println!("================================================================================\n");
     println!("Test driving Sea Vehicles");
     // test_drive_sea_vehicle_v1(&car);                         // Not a sea vehicle!
     // test_drive_sea_vehicle_v1(&airplane);                    // Not a sea vehicle!
-    poly::test_drive_sea_vehicle_v1(&amphibious_boat);
-    poly::test_drive_sea_vehicle_v1(&boat);
+    print!("{}", render_text(&poly::test_drive_sea_vehicle_v1(&amphibious_boat)));
+    print!("{}", render_text(&poly::test_drive_sea_vehicle_v1(&boat)));
     println!("================================================================================\n");
     println!("Test driving Vehicles which can be driven on land and sea");
     // test_drive_hybrid_vehicle_v1(&car);                      // Not a land AND sea vehicle!
     // test_drive_hybrid_vehicle_v1(&airplane);                 // Not a land AND sea vehicle!
     // test_drive_hybrid_vehicle_v1(&boat);                     // Not a land AND sea vehicle!
-    poly::test_drive_hybrid_vehicle_v1(&amphibious_boat);
+    print!("{}", render_text(&poly::test_drive_hybrid_vehicle_v1(&amphibious_boat)));
     println!("================================================================================\n");
     println!("Test driving Vehicles which can fly");
     // test_drive_air_vehicle_v1(&car);                         // Not a land AND air vehicle!
     // test_drive_air_vehicle_v1(&boat);                        // Not a land AND air vehicle!
     // test_drive_air_vehicle_v1(&amphibious_boat);             // Not a land AND air vehicle!
-    poly::test_drive_air_vehicle_v1(&airplane);
+    print!("{}", render_text(&poly::test_drive_air_vehicle_v1(&airplane)));
+    println!("================================================================================\n");
+    println!("Asking trait objects what they can do");
+    // In a Vec<Box<dyn Vehicle>> the concrete types are gone, the functions above can't be called anymore
+    // as_land, as_sea and as_air find out at runtime
+    let garage: Vec<Box<dyn Vehicle>> = vec![Box::new(car), Box::new(boat), Box::new(amphibious_boat), Box::new(airplane)];
+    for vehicle in &garage {
+        println!("{}", vehicle.describe());
+        if let Some(land_vehicle) = vehicle.as_land() {
+            println!("{}", land_vehicle.drift());
+        }
+        if let Some(sea_vehicle) = vehicle.as_sea() {
+            println!("{}", sea_vehicle.drop_sail());
+        }
+        if let Some(air_vehicle) = vehicle.as_air() {
+            println!("{}", air_vehicle.fly());
+        }
+    }
 
 }
\ No newline at end of file
@@ -0,0 +1,130 @@
+use std::fmt;
+use serde::Serialize;
+use super::default_type_parameters::{Length, Money, Power, Speed};
+use super::VehicleKind;
+
+// What vehicles do, as values
+// describe, drive, drift, drop_sail and fly return what happened instead of printing it,
+// the test drives return everything that happened in order. A caller can assert on the events,
+// print them as the text they always printed (Display, render_text) or hand them on as JSON (render_json).
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum VehicleEvent {
+    // every test drive starts by saying which one it is
+    TestDrive(TestDrive),
+    Described(Description),
+    Drove { kind: VehicleKind, name: String },
+    // only the amphibious boat counts its wheels
+    Drifted {
+        kind: VehicleKind,
+        name: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        wheels: Option<u8>
+    },
+    DroppedSail { kind: VehicleKind, name: String },
+    Flew { kind: VehicleKind, name: String },
+    // an amphibious land vehicle in a test drive that knows its specification
+    ContinuedOnWater { model: &'static str, speed: Speed }
+}
+
+// Every vehicle type tells something else about itself
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind")]
+pub enum Description {
+    Car { name: String, price: Money, power: Power },
+    Boat { name: String, price: Money },
+    AmphibiousBoat { name: String, price: Money, wheels: u8 },
+    Airplane { name: String, price: Money, wing_length: Length }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize)]
+#[serde(tag = "test", rename_all = "snake_case")]
+pub enum TestDrive {
+    VehicleV1,
+    VehicleV2,
+    LandVehicleV1,
+    SeaVehicleV1,
+    HybridVehicleV1,
+    AirVehicleV1,
+    // the v2 test drives in associated_constants.rs read the specification of the type
+    LandVehicleV2 { category: &'static str, wheels: u8, max_speed: Speed },
+    SeaVehicleV2 { category: &'static str, max_passengers: u16, max_speed: Speed },
+    AirVehicleV2 { category: &'static str, max_passengers: u16, max_speed: Speed }
+}
+
+impl fmt::Display for TestDrive {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TestDrive::VehicleV1 => write!(f, "Test drive v1"),
+            TestDrive::VehicleV2 => write!(f, "Test drive v2"),
+            TestDrive::LandVehicleV1 => write!(f, "Test land vehicle drive v1"),
+            TestDrive::SeaVehicleV1 => write!(f, "Test sea vehicle drive v1"),
+            TestDrive::HybridVehicleV1 => write!(f, "Test drive hybrid vehicle (sea and land vehicle)"),
+            TestDrive::AirVehicleV1 => write!(f, "Test drive air vehicle"),
+            TestDrive::LandVehicleV2 { category, wheels, max_speed } => {
+                write!(f, "Test land vehicle drive v2: a {} with {} wheels, at most {}", category, wheels, max_speed)
+            }
+            TestDrive::SeaVehicleV2 { category, max_passengers, max_speed } => {
+                write!(f, "Test sea vehicle drive v2: a {} for {} passengers, at most {:.0} knots", category, max_passengers, max_speed.knots())
+            }
+            TestDrive::AirVehicleV2 { category, max_passengers, max_speed } => {
+                write!(f, "Test air vehicle drive v2: a {} for {} passengers, at most {}", category, max_passengers, max_speed)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Description {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Description::Car { name, price, power } => {
+                write!(f, "I'm a Car! I am {}, have {:.0} horse power and cost {} dollar", name, power.horse_power(), price.amount())
+            }
+            Description::Boat { .. } => write!(f, "I'm a Boat! I am anchored!"),
+            Description::AmphibiousBoat { .. } => write!(f, "I'm a Amphibious Boat! I am anchored!"),
+            Description::Airplane { name, price, wing_length } => {
+                write!(f, "I'm an airplane! My name is {}, i cost {} and my wings are {} meters tall!", name, price.amount(), wing_length.meters())
+            }
+        }
+    }
+}
+
+// One line of text per event, the way the vehicles used to print it
+impl fmt::Display for VehicleEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VehicleEvent::TestDrive(test_drive) => write!(f, "{}", test_drive),
+            VehicleEvent::Described(description) => write!(f, "{}", description),
+            VehicleEvent::Drove { kind, name } => {
+                let how = match kind {
+                    VehicleKind::Car => "!",
+                    VehicleKind::Boat => " at sea!",
+                    VehicleKind::AmphibiousBoat => " (or sailing)!",
+                    VehicleKind::Airplane => ""
+                };
+                write!(f, "{} (type {:?}) is now driving{}", name, kind, how)
+            }
+            VehicleEvent::Drifted { kind, name, wheels } => match kind {
+                VehicleKind::Car => write!(f, "{} is now spinning and making donuts, get some new tires!", name),
+                VehicleKind::AmphibiousBoat => write!(f, "I may be a boat, but i have {} wheels and enough power to drift!", wheels.unwrap_or_default()),
+                _ => write!(f, "I'm not so good at drifting, sorry!")
+            },
+            VehicleEvent::DroppedSail { .. } => write!(f, "Dropping the sail for more wind and speed!"),
+            VehicleEvent::Flew { .. } => write!(f, "I'm very good at flying, see ya later!"),
+            VehicleEvent::ContinuedOnWater { model, speed } => write!(f, "    {} is amphibious, continuing on the water at {}", model, speed)
+        }
+    }
+}
+
+//=================================================================
+// Every event on its own line
+pub fn render_text(events: &[VehicleEvent]) -> String {
+    events.iter().map(|event| format!("{}\n", event)).collect()
+}
+
+// A JSON array with one object per event, the "event" field says which one.
+// Quantities are plain numbers in SI units (watt, meter, meter per second), money is an amount and a currency
+pub fn render_json(events: &[VehicleEvent]) -> String {
+    serde_json::to_string_pretty(events).expect("events only hold names and numbers, they always serialize")
+}
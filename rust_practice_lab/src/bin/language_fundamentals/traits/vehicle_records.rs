@@ -0,0 +1,230 @@
+use std::fmt;
+use serde::{Deserialize, Serialize};
+use super::default_type_parameters::{Length, Mass, Money, Power};
+use super::traits::poly::{AbstractVehicle, Airplane, AmphibiousBoat, Boat, Car};
+use super::traits::Vehicles;
+
+// Vehicles saved and loaded, as JSON or in a compact binary form
+//
+// JSON, every vehicle has its type next to its own fields, an AmphibiousBoat holds its whole Boat:
+//     {"version": 1, "vehicles": [
+//         {"type": "Car", "vehicle": {"name": "Carry", "price": {"amount": 4500.0, "currency": "USD"}}, "power": 82026.99},
+//         {"type": "AmphibiousBoat", "boat": {"vehicle": {...}, "anchor_weight": 3.0}, "nr_wheels": 4}]}
+// quantities are numbers in SI units, fields a vehicle doesn't have are an error instead of being dropped.
+//
+// Binary, the same vehicles without field names, numbers are little endian:
+//     "VHCL", version: u32, count: u32, then per vehicle a type tag: u8 and its fields in declaration order,
+//     a name is its length: u32 and UTF-8 bytes, money the amount in dollars, quantities an f64 in SI units
+//
+// Both start with the version. A record of another version is rejected before the vehicles in it are read,
+// they may look different there.
+
+pub const VERSION: u32 = 1;
+
+const MAGIC: &[u8; 4] = b"VHCL";
+
+const CAR: u8 = 0;
+const BOAT: u8 = 1;
+const AMPHIBIOUS_BOAT: u8 = 2;
+const AIRPLANE: u8 = 3;
+
+#[derive(Debug)]
+pub enum RecordError {
+    // the JSON doesn't parse or doesn't hold vehicles: unknown fields and types, missing fields, wrong currencies
+    Json(serde_json::Error),
+    UnsupportedVersion { found: u32 },
+    // binary data that doesn't start with MAGIC
+    NotARecord,
+    // the data stops in the middle of this field
+    Truncated { offset: usize, field: &'static str },
+    UnknownType { offset: usize, tag: u8 },
+    InvalidName { offset: usize },
+    TrailingBytes { offset: usize }
+}
+
+impl fmt::Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecordError::Json(error) => write!(f, "invalid vehicle record: {}", error),
+            RecordError::UnsupportedVersion { found } => write!(f, "unsupported record version {}, this build reads version {}", found, VERSION),
+            RecordError::NotARecord => write!(f, "not a vehicle record, it doesn't start with {:?}", std::str::from_utf8(MAGIC).unwrap_or_default()),
+            RecordError::Truncated { offset, field } => write!(f, "the record ends in the middle of the {} at byte {}", field, offset),
+            RecordError::UnknownType { offset, tag } => write!(f, "unknown vehicle type {} at byte {}", tag, offset),
+            RecordError::InvalidName { offset } => write!(f, "the name at byte {} isn't valid UTF-8", offset),
+            RecordError::TrailingBytes { offset } => write!(f, "unexpected data after the last vehicle at byte {}", offset)
+        }
+    }
+}
+
+impl std::error::Error for RecordError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RecordError::Json(error) => Some(error),
+            _ => None
+        }
+    }
+}
+
+impl From<serde_json::Error> for RecordError {
+    fn from(error: serde_json::Error) -> Self {
+        RecordError::Json(error)
+    }
+}
+
+//=================================================================
+// JSON
+
+#[derive(Serialize)]
+struct Record<'a> {
+    version: u32,
+    vehicles: &'a [Vehicles]
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct OwnedRecord {
+    // checked through Header before, listed so it isn't an unknown field
+    #[serde(rename = "version")]
+    _version: u32,
+    vehicles: Vec<Vehicles>
+}
+
+// only the version, everything else is skipped
+#[derive(Deserialize)]
+struct Header {
+    version: u32
+}
+
+pub fn to_json(vehicles: &[Vehicles]) -> String {
+    serde_json::to_string_pretty(&Record { version: VERSION, vehicles }).expect("vehicles only hold names and numbers, they always serialize")
+}
+
+pub fn from_json(json: &str) -> Result<Vec<Vehicles>, RecordError> {
+    let header: Header = serde_json::from_str(json)?;
+    if header.version != VERSION {
+        return Err(RecordError::UnsupportedVersion { found: header.version });
+    }
+    let record: OwnedRecord = serde_json::from_str(json)?;
+    Ok(record.vehicles)
+}
+
+//=================================================================
+// binary
+
+pub fn to_bytes(vehicles: &[Vehicles]) -> Vec<u8> {
+    let mut bytes = MAGIC.to_vec();
+    bytes.extend(VERSION.to_le_bytes());
+    bytes.extend(length(vehicles.len()).to_le_bytes());
+    for vehicle in vehicles {
+        match vehicle {
+            Vehicles::Car(car) => {
+                bytes.push(CAR);
+                write_abstract_vehicle(&mut bytes, &car.vehicle);
+                bytes.extend(car.horse_power.watts().to_le_bytes());
+            }
+            Vehicles::Boat(boat) => {
+                bytes.push(BOAT);
+                write_boat(&mut bytes, boat);
+            }
+            Vehicles::AmphibiousBoat(amphibious_boat) => {
+                bytes.push(AMPHIBIOUS_BOAT);
+                write_boat(&mut bytes, &amphibious_boat.boat);
+                bytes.push(amphibious_boat.nr_wheels);
+            }
+            Vehicles::Airplane(airplane) => {
+                bytes.push(AIRPLANE);
+                write_abstract_vehicle(&mut bytes, &airplane.vehicle);
+                bytes.extend(airplane.wing_length.meters().to_le_bytes());
+            }
+        }
+    }
+    bytes
+}
+
+fn length(length: usize) -> u32 {
+    u32::try_from(length).expect("a record holds at most u32::MAX vehicles and names of at most u32::MAX bytes")
+}
+
+fn write_abstract_vehicle(bytes: &mut Vec<u8>, vehicle: &AbstractVehicle) {
+    bytes.extend(length(vehicle.name.len()).to_le_bytes());
+    bytes.extend(vehicle.name.as_bytes());
+    bytes.extend(vehicle.price.amount().to_le_bytes());
+}
+
+fn write_boat(bytes: &mut Vec<u8>, boat: &Boat) {
+    write_abstract_vehicle(bytes, &boat.vehicle);
+    bytes.extend(boat.anchor_weight.kilograms().to_le_bytes());
+}
+
+pub fn from_bytes(bytes: &[u8]) -> Result<Vec<Vehicles>, RecordError> {
+    if !bytes.starts_with(MAGIC) {
+        return Err(RecordError::NotARecord);
+    }
+    let mut reader = Reader { bytes, offset: MAGIC.len() };
+    let version = reader.u32("version")?;
+    if version != VERSION {
+        return Err(RecordError::UnsupportedVersion { found: version });
+    }
+    let count = reader.u32("vehicle count")?;
+    // no capacity up front, the count comes from the data and may be anything
+    let mut vehicles = Vec::new();
+    for _ in 0..count {
+        let offset = reader.offset;
+        let vehicle = match reader.u8("vehicle type")? {
+            CAR => Car::new(reader.abstract_vehicle()?, Power::from_watts(reader.f64("power")?)).into(),
+            BOAT => reader.boat()?.into(),
+            AMPHIBIOUS_BOAT => AmphibiousBoat::new(reader.boat()?, reader.u8("number of wheels")?).into(),
+            AIRPLANE => Airplane::new(reader.abstract_vehicle()?, Length::from_meters(reader.f64("wing length")?)).into(),
+            tag => return Err(RecordError::UnknownType { offset, tag })
+        };
+        vehicles.push(vehicle);
+    }
+    if reader.offset != bytes.len() {
+        return Err(RecordError::TrailingBytes { offset: reader.offset });
+    }
+    Ok(vehicles)
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, count: usize, field: &'static str) -> Result<&'a [u8], RecordError> {
+        let end = self.offset.checked_add(count).filter(|&end| end <= self.bytes.len());
+        let Some(end) = end else {
+            return Err(RecordError::Truncated { offset: self.offset, field });
+        };
+        let taken = &self.bytes[self.offset..end];
+        self.offset = end;
+        Ok(taken)
+    }
+
+    fn array<const N: usize>(&mut self, field: &'static str) -> Result<[u8; N], RecordError> {
+        Ok(self.take(N, field)?.try_into().expect("take returns exactly N bytes"))
+    }
+
+    fn u8(&mut self, field: &'static str) -> Result<u8, RecordError> {
+        Ok(self.array::<1>(field)?[0])
+    }
+
+    fn u32(&mut self, field: &'static str) -> Result<u32, RecordError> {
+        Ok(u32::from_le_bytes(self.array(field)?))
+    }
+
+    fn f64(&mut self, field: &'static str) -> Result<f64, RecordError> {
+        Ok(f64::from_le_bytes(self.array(field)?))
+    }
+
+    fn abstract_vehicle(&mut self) -> Result<AbstractVehicle, RecordError> {
+        let length = self.u32("name length")? as usize;
+        let offset = self.offset;
+        let name = std::str::from_utf8(self.take(length, "name")?).map_err(|_| RecordError::InvalidName { offset })?;
+        Ok(AbstractVehicle { name: name.to_string(), price: Money::new(self.f64("price")?) })
+    }
+
+    fn boat(&mut self) -> Result<Boat, RecordError> {
+        Ok(Boat::new(self.abstract_vehicle()?, Mass::from_kilograms(self.f64("anchor weight")?)))
+    }
+}
@@ -0,0 +1,719 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant};
+use crate::model::checkable::{Condvar, Mutex, MutexGuard};
+
+// multi-producer multi-consumer channel built on a Mutex<VecDeque> and two condition variables
+// both Sender and Receiver can be cloned, every message is received by exactly one receiver
+//
+//     let (sender, receiver) = bounded(16);
+//     sender.send(1).unwrap();
+//     assert_eq!(receiver.recv(), Ok(1));
+//
+// async code can use send_async and recv_async instead, they wait by registering a Waker
+// instead of blocking the thread, so both kinds of users can share one channel
+
+//=================================================================
+// errors, named like the ones in std::sync::mpsc
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub struct SendError<T>(pub T);
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum TrySendError<T> {
+    Full(T),
+    Disconnected(T)
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum SendTimeoutError<T> {
+    Timeout(T),
+    Disconnected(T)
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct RecvError;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TryRecvError {
+    Empty,
+    Disconnected
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RecvTimeoutError {
+    Timeout,
+    Disconnected
+}
+
+// the messages inside the send errors don't have to be Debug, same as in std
+impl<T> fmt::Debug for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SendError { .. }")
+    }
+}
+
+impl<T> fmt::Debug for TrySendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrySendError::Full(_) => f.write_str("Full(..)"),
+            TrySendError::Disconnected(_) => f.write_str("Disconnected(..)")
+        }
+    }
+}
+
+impl<T> fmt::Debug for SendTimeoutError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SendTimeoutError::Timeout(_) => f.write_str("Timeout(..)"),
+            SendTimeoutError::Disconnected(_) => f.write_str("Disconnected(..)")
+        }
+    }
+}
+
+impl<T> fmt::Display for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("sending on a channel without receivers")
+    }
+}
+
+impl<T> fmt::Display for TrySendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrySendError::Full(_) => f.write_str("sending on a full channel"),
+            TrySendError::Disconnected(_) => f.write_str("sending on a channel without receivers")
+        }
+    }
+}
+
+impl<T> fmt::Display for SendTimeoutError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SendTimeoutError::Timeout(_) => f.write_str("timed out waiting for room in the channel"),
+            SendTimeoutError::Disconnected(_) => f.write_str("sending on a channel without receivers")
+        }
+    }
+}
+
+impl fmt::Display for RecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("receiving on an empty channel without senders")
+    }
+}
+
+impl fmt::Display for TryRecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryRecvError::Empty => f.write_str("receiving on an empty channel"),
+            TryRecvError::Disconnected => f.write_str("receiving on an empty channel without senders")
+        }
+    }
+}
+
+impl fmt::Display for RecvTimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecvTimeoutError::Timeout => f.write_str("timed out waiting for a message"),
+            RecvTimeoutError::Disconnected => f.write_str("receiving on an empty channel without senders")
+        }
+    }
+}
+
+impl<T> std::error::Error for SendError<T> {}
+impl<T> std::error::Error for TrySendError<T> {}
+impl<T> std::error::Error for SendTimeoutError<T> {}
+impl std::error::Error for RecvError {}
+impl std::error::Error for TryRecvError {}
+impl std::error::Error for RecvTimeoutError {}
+
+//=================================================================
+// shared state
+
+// None, waiting forever, when the timeout reaches past what an Instant can hold (Duration::MAX)
+fn deadline_after(timeout: Duration) -> Option<Instant> {
+    Instant::now().checked_add(timeout)
+}
+
+// wakes up a thread blocked in Select, registered on every channel it waits for
+struct Signal {
+    fired: Mutex<bool>,
+    condvar: Condvar
+}
+
+impl Signal {
+    fn new() -> Arc<Self> {
+        Arc::new(Self { fired: Mutex::new(false), condvar: Condvar::new() })
+    }
+
+    fn fire(&self) {
+        *self.fired.lock().unwrap() = true;
+        self.condvar.notify_one();
+    }
+
+    // false when the deadline passed first
+    fn wait(&self, deadline: Option<Instant>) -> bool {
+        let mut fired = self.fired.lock().unwrap();
+        while !*fired {
+            match deadline {
+                None => fired = self.condvar.wait(fired).unwrap(),
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return false;
+                    }
+                    fired = self.condvar.wait_timeout(fired, deadline - now).unwrap().0;
+                }
+            }
+        }
+        true
+    }
+}
+
+struct State<T> {
+    queue: VecDeque<T>,
+    // None for an unbounded channel
+    capacity: Option<usize>,
+    senders: usize,
+    receivers: usize,
+    selectors: Vec<Arc<Signal>>,
+    // async receivers waiting for a message, async senders waiting for room
+    recv_wakers: Vec<Waker>,
+    send_wakers: Vec<Waker>
+}
+
+impl<T> State<T> {
+    fn is_full(&self) -> bool {
+        self.capacity.is_some_and(|capacity| self.queue.len() >= capacity)
+    }
+
+    // a message arrived or the last sender left
+    fn wake_selectors(&mut self) {
+        for signal in self.selectors.drain(..) {
+            signal.fire();
+        }
+        self.recv_wakers.drain(..).for_each(Waker::wake);
+    }
+
+    // room was made or the last receiver left
+    fn wake_senders(&mut self) {
+        self.send_wakers.drain(..).for_each(Waker::wake);
+    }
+}
+
+fn register_waker(wakers: &mut Vec<Waker>, waker: &Waker) {
+    if !wakers.iter().any(|registered| registered.will_wake(waker)) {
+        wakers.push(waker.clone());
+    }
+}
+
+struct Channel<T> {
+    state: Mutex<State<T>>,
+    not_empty: Condvar,
+    not_full: Condvar
+}
+
+impl<T> Channel<T> {
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        // a panicking user can't leave the queue half updated, so a poisoned lock is still usable
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+fn new_channel<T>(capacity: Option<usize>) -> (Sender<T>, Receiver<T>) {
+    let channel = Arc::new(Channel {
+        state: Mutex::new(State {
+            queue: VecDeque::new(),
+            capacity,
+            senders: 1,
+            receivers: 1,
+            selectors: Vec::new(),
+            recv_wakers: Vec::new(),
+            send_wakers: Vec::new()
+        }),
+        not_empty: Condvar::new(),
+        not_full: Condvar::new()
+    });
+    (Sender { channel: Arc::clone(&channel) }, Receiver { channel })
+}
+
+// senders block in send() while the channel holds `capacity` messages
+// panics when capacity is 0, rendezvous channels are not supported
+pub fn bounded<T>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    assert!(capacity > 0, "a bounded channel needs room for at least one message");
+    new_channel(Some(capacity))
+}
+
+// send() never blocks, the queue grows as long as there is memory
+pub fn unbounded<T>() -> (Sender<T>, Receiver<T>) {
+    new_channel(None)
+}
+
+//=================================================================
+// sending side
+
+pub struct Sender<T> {
+    channel: Arc<Channel<T>>
+}
+
+impl<T> Sender<T> {
+    // blocks while a bounded channel is full, fails once every receiver is gone
+    pub fn send(&self, message: T) -> Result<(), SendError<T>> {
+        self.send_until(message, None).map_err(|error| match error {
+            SendTimeoutError::Timeout(message) | SendTimeoutError::Disconnected(message) => SendError(message)
+        })
+    }
+
+    pub fn send_timeout(&self, message: T, timeout: Duration) -> Result<(), SendTimeoutError<T>> {
+        self.send_until(message, deadline_after(timeout))
+    }
+
+    pub fn try_send(&self, message: T) -> Result<(), TrySendError<T>> {
+        let state = self.channel.lock();
+        if state.receivers == 0 {
+            return Err(TrySendError::Disconnected(message));
+        }
+        if state.is_full() {
+            return Err(TrySendError::Full(message));
+        }
+        self.push(state, message);
+        Ok(())
+    }
+
+    fn send_until(&self, message: T, deadline: Option<Instant>) -> Result<(), SendTimeoutError<T>> {
+        let mut state = self.channel.lock();
+        loop {
+            if state.receivers == 0 {
+                return Err(SendTimeoutError::Disconnected(message));
+            }
+            if !state.is_full() {
+                self.push(state, message);
+                return Ok(());
+            }
+            state = match deadline {
+                None => self.channel.not_full.wait(state).unwrap_or_else(|poisoned| poisoned.into_inner()),
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return Err(SendTimeoutError::Timeout(message));
+                    }
+                    self.channel.not_full.wait_timeout(state, deadline - now).unwrap_or_else(|poisoned| poisoned.into_inner()).0
+                }
+            };
+        }
+    }
+
+    // waits for room without blocking the thread, fails once every receiver is gone
+    pub fn send_async(&self, message: T) -> SendFuture<'_, T> {
+        SendFuture { sender: self, message: Some(message) }
+    }
+
+    fn push(&self, mut state: MutexGuard<'_, State<T>>, message: T) {
+        state.queue.push_back(message);
+        state.wake_selectors();
+        drop(state);
+        self.channel.not_empty.notify_one();
+    }
+
+    pub fn len(&self) -> usize {
+        self.channel.lock().queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn capacity(&self) -> Option<usize> {
+        self.channel.lock().capacity
+    }
+
+    pub fn is_disconnected(&self) -> bool {
+        self.channel.lock().receivers == 0
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.channel.lock().senders += 1;
+        Self { channel: Arc::clone(&self.channel) }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut state = self.channel.lock();
+        state.senders -= 1;
+        if state.senders == 0 {
+            // receivers blocked on an empty channel have to find out that nothing will come anymore
+            state.wake_selectors();
+            drop(state);
+            self.channel.not_empty.notify_all();
+        }
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Sender { .. }")
+    }
+}
+
+//=================================================================
+// receiving side
+
+pub struct Receiver<T> {
+    channel: Arc<Channel<T>>
+}
+
+impl<T> Receiver<T> {
+    // blocks until a message arrives, fails once the channel is empty and every sender is gone
+    pub fn recv(&self) -> Result<T, RecvError> {
+        self.recv_until(None).map_err(|_| RecvError)
+    }
+
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        self.recv_until(deadline_after(timeout))
+    }
+
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        let mut state = self.channel.lock();
+        match state.queue.pop_front() {
+            Some(message) => {
+                state.wake_senders();
+                drop(state);
+                self.channel.not_full.notify_one();
+                Ok(message)
+            }
+            None if state.senders == 0 => Err(TryRecvError::Disconnected),
+            None => Err(TryRecvError::Empty)
+        }
+    }
+
+    fn recv_until(&self, deadline: Option<Instant>) -> Result<T, RecvTimeoutError> {
+        let mut state = self.channel.lock();
+        loop {
+            if let Some(message) = state.queue.pop_front() {
+                state.wake_senders();
+                drop(state);
+                self.channel.not_full.notify_one();
+                return Ok(message);
+            }
+            if state.senders == 0 {
+                return Err(RecvTimeoutError::Disconnected);
+            }
+            state = match deadline {
+                None => self.channel.not_empty.wait(state).unwrap_or_else(|poisoned| poisoned.into_inner()),
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return Err(RecvTimeoutError::Timeout);
+                    }
+                    self.channel.not_empty.wait_timeout(state, deadline - now).unwrap_or_else(|poisoned| poisoned.into_inner()).0
+                }
+            };
+        }
+    }
+
+    // the async version of try_recv: registers the waker when the channel is empty
+    // Ready(Err(RecvError)) once the channel is empty and every sender is gone
+    pub fn poll_recv(&self, cx: &mut Context<'_>) -> Poll<Result<T, RecvError>> {
+        let mut state = self.channel.lock();
+        match state.queue.pop_front() {
+            Some(message) => {
+                state.wake_senders();
+                drop(state);
+                self.channel.not_full.notify_one();
+                Poll::Ready(Ok(message))
+            }
+            None if state.senders == 0 => Poll::Ready(Err(RecvError)),
+            None => {
+                register_waker(&mut state.recv_wakers, cx.waker());
+                Poll::Pending
+            }
+        }
+    }
+
+    pub fn recv_async(&self) -> RecvFuture<'_, T> {
+        RecvFuture { receiver: self }
+    }
+
+    // blocking iterator, ends when the channel is disconnected
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { receiver: self }
+    }
+
+    // takes what is in the channel right now, never blocks
+    pub fn try_iter(&self) -> TryIter<'_, T> {
+        TryIter { receiver: self }
+    }
+
+    pub fn len(&self) -> usize {
+        self.channel.lock().queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn capacity(&self) -> Option<usize> {
+        self.channel.lock().capacity
+    }
+
+    pub fn is_disconnected(&self) -> bool {
+        self.channel.lock().senders == 0
+    }
+}
+
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Self {
+        self.channel.lock().receivers += 1;
+        Self { channel: Arc::clone(&self.channel) }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        let mut state = self.channel.lock();
+        state.receivers -= 1;
+        if state.receivers == 0 {
+            state.wake_senders();
+            drop(state);
+            self.channel.not_full.notify_all();
+        }
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Receiver { .. }")
+    }
+}
+
+pub struct Iter<'a, T> {
+    receiver: &'a Receiver<T>
+}
+
+impl<T> Iterator for Iter<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.receiver.recv().ok()
+    }
+}
+
+pub struct TryIter<'a, T> {
+    receiver: &'a Receiver<T>
+}
+
+impl<T> Iterator for TryIter<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+pub struct IntoIter<T> {
+    receiver: Receiver<T>
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.receiver.recv().ok()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Receiver<T> {
+    type Item = T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<T> IntoIterator for Receiver<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter { receiver: self }
+    }
+}
+
+//=================================================================
+// futures returned by send_async and recv_async
+
+pub struct SendFuture<'a, T> {
+    sender: &'a Sender<T>,
+    message: Option<T>
+}
+
+// the message is only moved in and out, never pinned
+impl<T> Unpin for SendFuture<'_, T> {}
+
+impl<T> Future for SendFuture<'_, T> {
+    type Output = Result<(), SendError<T>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let message = self.message.take().expect("SendFuture polled after it completed");
+        let mut state = self.sender.channel.lock();
+        if state.receivers == 0 {
+            return Poll::Ready(Err(SendError(message)));
+        }
+        if state.is_full() {
+            // registered under the lock, so a receiver that makes room afterwards will see the waker
+            register_waker(&mut state.send_wakers, cx.waker());
+            drop(state);
+            self.message = Some(message);
+            return Poll::Pending;
+        }
+        self.sender.push(state, message);
+        Poll::Ready(Ok(()))
+    }
+}
+
+pub struct RecvFuture<'a, T> {
+    receiver: &'a Receiver<T>
+}
+
+impl<T> Future for RecvFuture<'_, T> {
+    type Output = Result<T, RecvError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+//=================================================================
+// select
+
+// what Select needs from a receiver, lets one Select wait on receivers of different message types
+trait Selectable {
+    // a receive would not block: there is a message or the channel is disconnected
+    fn is_ready(&self) -> bool;
+    fn register(&self, signal: &Arc<Signal>);
+    fn unregister(&self, signal: &Arc<Signal>);
+}
+
+impl<T> Selectable for Receiver<T> {
+    fn is_ready(&self) -> bool {
+        let state = self.channel.lock();
+        !state.queue.is_empty() || state.senders == 0
+    }
+
+    fn register(&self, signal: &Arc<Signal>) {
+        self.channel.lock().selectors.push(Arc::clone(signal));
+    }
+
+    fn unregister(&self, signal: &Arc<Signal>) {
+        self.channel.lock().selectors.retain(|registered| !Arc::ptr_eq(registered, signal));
+    }
+}
+
+// waits on several receivers at once
+//
+//     let mut select = Select::new();
+//     let numbers = select.recv(&number_receiver);
+//     let words = select.recv(&word_receiver);
+//     match select.ready() {
+//         index if index == numbers => ...number_receiver.try_recv()...
+//         index if index == words => ...word_receiver.try_recv()...
+//     }
+//
+// with more than one consumer per channel, another thread can take the message between ready()
+// and try_recv(), so handle TryRecvError::Empty by selecting again
+#[derive(Default)]
+pub struct Select<'a> {
+    receivers: Vec<&'a dyn Selectable>
+}
+
+impl<'a> Select<'a> {
+    pub fn new() -> Self {
+        Self { receivers: Vec::new() }
+    }
+
+    // adds a receiver, returns the index ready() reports for it
+    pub fn recv<T>(&mut self, receiver: &'a Receiver<T>) -> usize {
+        self.receivers.push(receiver);
+        self.receivers.len() - 1
+    }
+
+    pub fn try_ready(&self) -> Option<usize> {
+        self.receivers.iter().position(|receiver| receiver.is_ready())
+    }
+
+    // blocks until one of the receivers is ready, returns its index
+    // panics when no receivers were added, that would block forever
+    pub fn ready(&self) -> usize {
+        self.ready_until(None).expect("waiting without a deadline can't time out")
+    }
+
+    pub fn ready_timeout(&self, timeout: Duration) -> Option<usize> {
+        self.ready_until(deadline_after(timeout))
+    }
+
+    fn ready_until(&self, deadline: Option<Instant>) -> Option<usize> {
+        assert!(!self.receivers.is_empty(), "select without receivers would block forever");
+        loop {
+            if let Some(index) = self.try_ready() {
+                return Some(index);
+            }
+            let signal = Signal::new();
+            for receiver in &self.receivers {
+                receiver.register(&signal);
+            }
+            // a message may have arrived between the first check and registering
+            let ready = self.try_ready();
+            let fired = ready.is_some() || signal.wait(deadline);
+            for receiver in &self.receivers {
+                receiver.unregister(&signal);
+            }
+            if ready.is_some() {
+                return ready;
+            }
+            if !fired {
+                return None;
+            }
+        }
+    }
+}
+
+// receives from whichever receiver gets a message first, returns its index and the message
+// disconnected receivers are skipped, fails once all of them are disconnected
+pub fn select<T>(receivers: &[&Receiver<T>]) -> Result<(usize, T), RecvError> {
+    select_until(receivers, None).map_err(|_| RecvError)
+}
+
+pub fn select_timeout<T>(receivers: &[&Receiver<T>], timeout: Duration) -> Result<(usize, T), RecvTimeoutError> {
+    select_until(receivers, deadline_after(timeout))
+}
+
+fn select_until<T>(receivers: &[&Receiver<T>], deadline: Option<Instant>) -> Result<(usize, T), RecvTimeoutError> {
+    let mut open: Vec<usize> = (0..receivers.len()).collect();
+    while !open.is_empty() {
+        let mut select = Select::new();
+        for &index in &open {
+            select.recv(receivers[index]);
+        }
+        let position = match deadline {
+            None => select.ready(),
+            Some(deadline) => select.ready_until(Some(deadline)).ok_or(RecvTimeoutError::Timeout)?
+        };
+        let index = open[position];
+        match receivers[index].try_recv() {
+            Ok(message) => return Ok((index, message)),
+            Err(TryRecvError::Disconnected) => {
+                open.remove(position);
+            }
+            // somebody else was faster, wait again
+            Err(TryRecvError::Empty) => {}
+        }
+    }
+    Err(RecvTimeoutError::Disconnected)
+}
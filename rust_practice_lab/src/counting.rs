@@ -0,0 +1,104 @@
+use std::fmt;
+use std::io::Write;
+use tokio_stream::Stream;
+
+// counting further than this floods the terminal, so the counter refuses to do it
+pub const MAX_COUNTING_LIMIT: i32 = 50000;
+
+#[derive(Debug)]
+pub enum CountingError {
+    LimitTooHigh { limit: i32, max: i32 },
+    InvalidStep(i32),
+    Io(std::io::Error)
+}
+
+impl fmt::Display for CountingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CountingError::LimitTooHigh { limit, max } => write!(f, "Limit {} too high! Choose something below {}!", limit, max),
+            CountingError::InvalidStep(step) => write!(f, "Step {} is invalid, the step has to be at least 1", step),
+            CountingError::Io(error) => write!(f, "Could not write the count: {}", error)
+        }
+    }
+}
+
+impl std::error::Error for CountingError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CountingError::Io(error) => Some(error),
+            _ => None
+        }
+    }
+}
+
+impl From<std::io::Error> for CountingError {
+    fn from(error: std::io::Error) -> Self {
+        CountingError::Io(error)
+    }
+}
+
+// counts from start up to and including limit, in steps of step
+// the limit is validated before anything is produced, so a failing counter never writes half a count
+#[derive(Debug, Copy, Clone)]
+pub struct Counter {
+    pub start: i32,
+    pub limit: i32,
+    pub step: i32
+}
+
+impl Counter {
+    pub fn new(limit: i32) -> Self {
+        Self { start: 1, limit, step: 1 }
+    }
+
+    pub fn start(mut self, start: i32) -> Self {
+        self.start = start;
+        self
+    }
+
+    pub fn step(mut self, step: i32) -> Self {
+        self.step = step;
+        self
+    }
+
+    fn validate(&self) -> Result<(), CountingError> {
+        if self.limit > MAX_COUNTING_LIMIT {
+            return Err(CountingError::LimitTooHigh { limit: self.limit, max: MAX_COUNTING_LIMIT });
+        }
+        if self.step < 1 {
+            return Err(CountingError::InvalidStep(self.step));
+        }
+        Ok(())
+    }
+
+    pub fn iter(&self) -> Result<impl Iterator<Item = i32>, CountingError> {
+        self.validate()?;
+        Ok((self.start..=self.limit).step_by(self.step as usize))
+    }
+
+    // calls the callback once per number, returns how many numbers were counted
+    pub fn for_each<F: FnMut(i32)>(&self, mut callback: F) -> Result<usize, CountingError> {
+        let mut count = 0;
+        for number in self.iter()? {
+            callback(number);
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    // writes one number per line to anything that implements Write (stdout, a file, a Vec<u8>, ...)
+    pub fn write_to<W: Write>(&self, out: &mut W) -> Result<usize, CountingError> {
+        let mut count = 0;
+        for number in self.iter()? {
+            writeln!(out, "{}", number)?;
+            count += 1;
+        }
+        out.flush()?;
+        Ok(count)
+    }
+
+    // async version, the numbers are produced one at a time when the stream is polled
+    pub fn stream(&self) -> Result<impl Stream<Item = i32>, CountingError> {
+        Ok(tokio_stream::iter(self.iter()?))
+    }
+}
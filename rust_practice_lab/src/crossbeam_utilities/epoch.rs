@@ -0,0 +1,341 @@
+use std::cell::{Cell, RefCell};
+use std::ptr;
+use std::rc::Rc;
+use std::sync::atomic::{fence, AtomicBool, AtomicPtr, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+
+// epoch-based reclamation
+//
+// there is one global epoch counter. a thread pins itself by copying the global epoch into its
+// participant record, and unpins by clearing it. the global epoch only moves from e to e + 1 when
+// every pinned thread has seen e.
+// garbage is tagged with the global epoch at the time it was handed in. by then it is unlinked, so only
+// threads pinned at that epoch or earlier can still hold a pointer to it. two advances later all of
+// those threads have unpinned, and the garbage can be freed.
+//
+//     let guard = epoch::pin();
+//     let head = stack_head.load(Ordering::Acquire);   // safe to read while the guard lives
+//     ... unlink head with a compare_exchange ...
+//     unsafe { guard.defer_destroy(head) };            // freed once no pinned thread can see it
+//
+// every thread collects garbage into a local bag, full bags go to the collector which frees what is old enough
+
+// deferred functions per thread before the bag is handed to the collector
+const BAG_CAPACITY: usize = 64;
+
+// a type erased "run this later", either a boxed closure or a Box<T> to drop
+struct Deferred {
+    data: *mut (),
+    call: unsafe fn(*mut ())
+}
+
+// SAFETY: defer only takes Send closures, defer_destroy leaves it to its caller
+unsafe impl Send for Deferred {}
+
+impl Deferred {
+    fn new<F: FnOnce() + Send + 'static>(f: F) -> Self {
+        unsafe fn call<F: FnOnce()>(data: *mut ()) {
+            let f = Box::from_raw(data as *mut F);
+            f();
+        }
+        Self { data: Box::into_raw(Box::new(f)) as *mut (), call: call::<F> }
+    }
+
+    // SAFETY: ptr must come from Box::into_raw
+    unsafe fn destroy<T>(ptr: *mut T) -> Self {
+        unsafe fn drop_box<T>(data: *mut ()) {
+            drop(Box::from_raw(data as *mut T));
+        }
+        Self { data: ptr as *mut (), call: drop_box::<T> }
+    }
+
+    fn run(self) {
+        // SAFETY: data and call were created together above and every Deferred runs once
+        unsafe { (self.call)(self.data) }
+    }
+}
+
+// one per registered thread, the records are never freed while the collector lives, only reused
+struct Participant {
+    // (epoch << 1) | 1 while pinned, 0 while not pinned
+    state: AtomicUsize,
+    in_use: AtomicBool,
+    // set before the record is published, never changes afterwards
+    next: *mut Participant
+}
+
+struct Global {
+    epoch: AtomicUsize,
+    participants: AtomicPtr<Participant>,
+    // sealed bags with the epoch they were sealed in
+    // a lock keeps this part simple, it is only touched once per BAG_CAPACITY deferred functions
+    garbage: Mutex<Vec<(usize, Vec<Deferred>)>>
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    // nothing panics while holding the garbage lock, the deferred functions run after it is released
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+impl Global {
+    fn participants(&self) -> impl Iterator<Item = &Participant> {
+        let mut current = self.participants.load(Ordering::Acquire);
+        std::iter::from_fn(move || {
+            // SAFETY: records stay allocated until the Global is dropped
+            let participant = unsafe { current.as_ref()? };
+            current = participant.next;
+            Some(participant)
+        })
+    }
+
+    fn acquire_participant(&self) -> *const Participant {
+        // reuse the record of a thread that is gone
+        for participant in self.participants() {
+            if participant.in_use.compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed).is_ok() {
+                return participant;
+            }
+        }
+        let record = Box::into_raw(Box::new(Participant {
+            state: AtomicUsize::new(0),
+            in_use: AtomicBool::new(true),
+            next: ptr::null_mut()
+        }));
+        let mut head = self.participants.load(Ordering::Relaxed);
+        loop {
+            // SAFETY: the record isn't published until the compare_exchange succeeds
+            unsafe { (*record).next = head };
+            match self.participants.compare_exchange_weak(head, record, Ordering::Release, Ordering::Relaxed) {
+                Ok(_) => return record,
+                Err(current) => head = current
+            }
+        }
+    }
+
+    // moves the global epoch forward if every pinned thread has seen the current one, returns the epoch afterwards
+    fn try_advance(&self) -> usize {
+        let epoch = self.epoch.load(Ordering::Relaxed);
+        // pairs with the fence in pin: either we see the thread pinned, or it sees the epoch we read here
+        fence(Ordering::SeqCst);
+        for participant in self.participants() {
+            let state = participant.state.load(Ordering::Relaxed);
+            if state & 1 == 1 && state >> 1 != epoch {
+                return epoch;
+            }
+        }
+        fence(Ordering::Acquire);
+        // another thread may have advanced it already, that is just as good
+        match self.epoch.compare_exchange(epoch, epoch.wrapping_add(1), Ordering::Release, Ordering::Relaxed) {
+            Ok(_) => epoch.wrapping_add(1),
+            Err(current) => current
+        }
+    }
+
+    fn push_bag(&self, bag: Vec<Deferred>) {
+        if bag.is_empty() {
+            return;
+        }
+        // the objects in the bag were unlinked before this fence, so a thread that pins after it can't find them
+        fence(Ordering::SeqCst);
+        let epoch = self.epoch.load(Ordering::Relaxed);
+        lock(&self.garbage).push((epoch, bag));
+    }
+
+    // frees the garbage that no pinned thread can see anymore
+    fn collect(&self) {
+        let epoch = self.try_advance();
+        let ready: Vec<Deferred> = {
+            let mut garbage = lock(&self.garbage);
+            let (ready, waiting) = garbage.drain(..).partition(|(sealed, _)| epoch.wrapping_sub(*sealed) >= 2);
+            *garbage = waiting;
+            ready.into_iter().flat_map(|(_, bag)| bag).collect()
+        };
+        // outside of the lock, a destructor may defer more garbage
+        ready.into_iter().for_each(Deferred::run);
+    }
+}
+
+impl Drop for Global {
+    fn drop(&mut self) {
+        // the last handle is gone, nobody is pinned anymore
+        let garbage = std::mem::take(self.garbage.get_mut().unwrap_or_else(|poisoned| poisoned.into_inner()));
+        garbage.into_iter().flat_map(|(_, bag)| bag).for_each(Deferred::run);
+        let mut current = *self.participants.get_mut();
+        while !current.is_null() {
+            // SAFETY: exclusive access, every record came from Box::into_raw
+            let participant = unsafe { Box::from_raw(current) };
+            current = participant.next;
+        }
+    }
+}
+
+//=================================================================
+
+struct Local {
+    global: Arc<Global>,
+    participant: *const Participant,
+    guards: Cell<usize>,
+    bag: RefCell<Vec<Deferred>>
+}
+
+impl Local {
+    fn participant(&self) -> &Participant {
+        // SAFETY: the record lives as long as the Global, which we keep alive
+        unsafe { &*self.participant }
+    }
+
+    fn pin(&self) {
+        let guards = self.guards.get();
+        if guards == 0 {
+            let epoch = self.global.epoch.load(Ordering::Relaxed);
+            self.participant().state.store((epoch << 1) | 1, Ordering::Relaxed);
+            // no load from the data structure may move before the pin is visible, pairs with the fence in try_advance
+            fence(Ordering::SeqCst);
+        }
+        self.guards.set(guards + 1);
+    }
+
+    fn unpin(&self) {
+        let guards = self.guards.get() - 1;
+        self.guards.set(guards);
+        if guards == 0 {
+            // Release: our reads of the data structure happen before the collector sees us unpinned
+            self.participant().state.store(0, Ordering::Release);
+        }
+    }
+
+    fn defer(&self, deferred: Deferred) {
+        let full = {
+            let mut bag = self.bag.borrow_mut();
+            bag.push(deferred);
+            bag.len() >= BAG_CAPACITY
+        };
+        if full {
+            self.flush();
+        }
+    }
+
+    fn flush(&self) {
+        let bag = std::mem::take(&mut *self.bag.borrow_mut());
+        self.global.push_bag(bag);
+        self.global.collect();
+    }
+}
+
+impl Drop for Local {
+    fn drop(&mut self) {
+        let bag = std::mem::take(self.bag.get_mut());
+        self.global.push_bag(bag);
+        self.participant().state.store(0, Ordering::Release);
+        self.participant().in_use.store(false, Ordering::Release);
+    }
+}
+
+//=================================================================
+
+// owns the global epoch and the garbage, data structures can use their own collector or the default one
+#[derive(Clone)]
+pub struct Collector {
+    global: Arc<Global>
+}
+
+impl Collector {
+    pub fn new() -> Self {
+        Self {
+            global: Arc::new(Global {
+                epoch: AtomicUsize::new(0),
+                participants: AtomicPtr::new(ptr::null_mut()),
+                garbage: Mutex::new(Vec::new())
+            })
+        }
+    }
+
+    // a handle for the calling thread, pinning goes through it
+    pub fn register(&self) -> LocalHandle {
+        let participant = self.global.acquire_participant();
+        LocalHandle {
+            local: Rc::new(Local {
+                global: Arc::clone(&self.global),
+                participant,
+                guards: Cell::new(0),
+                bag: RefCell::new(Vec::new())
+            })
+        }
+    }
+
+    pub fn epoch(&self) -> usize {
+        self.global.epoch.load(Ordering::Relaxed)
+    }
+}
+
+impl Default for Collector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// a thread's registration with a collector, not Send: the pin state belongs to one thread
+// the garbage left in its bag goes to the collector when the handle is dropped
+pub struct LocalHandle {
+    local: Rc<Local>
+}
+
+impl LocalHandle {
+    pub fn pin(&self) -> Guard {
+        self.local.pin();
+        Guard { local: Rc::clone(&self.local) }
+    }
+
+    pub fn is_pinned(&self) -> bool {
+        self.local.guards.get() > 0
+    }
+}
+
+// while a guard lives, nothing unlinked after it was created is freed
+// guards nest, the thread is unpinned when the last one is dropped
+pub struct Guard {
+    local: Rc<Local>
+}
+
+impl Guard {
+    // runs f once no pinned thread can see what was unlinked before this call
+    pub fn defer<F: FnOnce() + Send + 'static>(&self, f: F) {
+        self.local.defer(Deferred::new(f));
+    }
+
+    /// Drops the `Box<T>` behind `ptr` once no thread pinned now can still be reading it.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must come from `Box::into_raw`, must already be unreachable for threads that pin from now on
+    /// and must be handed in only once. Dropping the `T` may happen on any thread.
+    pub unsafe fn defer_destroy<T>(&self, ptr: *mut T) {
+        self.local.defer(Deferred::destroy(ptr));
+    }
+
+    // hands this thread's garbage to the collector and frees whatever is old enough
+    pub fn flush(&self) {
+        self.local.flush();
+    }
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        self.local.unpin();
+    }
+}
+
+//=================================================================
+
+pub fn default_collector() -> &'static Collector {
+    static COLLECTOR: OnceLock<Collector> = OnceLock::new();
+    COLLECTOR.get_or_init(Collector::new)
+}
+
+thread_local! {
+    static HANDLE: LocalHandle = default_collector().register();
+}
+
+// pins the calling thread with the default collector
+pub fn pin() -> Guard {
+    HANDLE.with(|handle| handle.pin())
+}
@@ -0,0 +1,186 @@
+use std::collections::HashSet;
+use std::ptr;
+use std::sync::atomic::{fence, AtomicBool, AtomicPtr, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+// hazard pointers
+//
+// before dereferencing a shared pointer a thread writes it into one of its hazard slots and checks
+// that the pointer is still reachable. retired nodes are only freed when no slot holds them.
+// unlike epochs a stalled thread only keeps back the few nodes it announced, not all garbage.
+//
+//     let hazard = domain.hazard_pointer();
+//     let head = hazard.protect(&stack_head);       // safe to read until reset or drop
+//     ... unlink head with a compare_exchange ...
+//     drop(hazard);
+//     unsafe { domain.retire(head) };               // freed by a later scan that finds no hazard on it
+//
+// the slots are kept in a push-only list of records that get reused, like the epoch participants
+
+// retired nodes before a retire call scans the hazards
+const SCAN_THRESHOLD: usize = 64;
+
+struct HazardRecord {
+    pointer: AtomicPtr<()>,
+    in_use: AtomicBool,
+    // set before the record is published, never changes afterwards
+    next: *mut HazardRecord
+}
+
+struct Retired {
+    pointer: *mut (),
+    drop: unsafe fn(*mut ())
+}
+
+// SAFETY: see the contract of Domain::retire
+unsafe impl Send for Retired {}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    // nothing panics while holding the retired lock, the destructors run after it is released
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+pub struct Domain {
+    records: AtomicPtr<HazardRecord>,
+    retired: Mutex<Vec<Retired>>
+}
+
+impl Domain {
+    pub fn new() -> Self {
+        Self { records: AtomicPtr::new(ptr::null_mut()), retired: Mutex::new(Vec::new()) }
+    }
+
+    fn records(&self) -> impl Iterator<Item = &HazardRecord> {
+        let mut current = self.records.load(Ordering::Acquire);
+        std::iter::from_fn(move || {
+            // SAFETY: records stay allocated until the domain is dropped
+            let record = unsafe { current.as_ref()? };
+            current = record.next;
+            Some(record)
+        })
+    }
+
+    // a free hazard slot for the calling thread, released again when the HazardPointer is dropped
+    pub fn hazard_pointer(&self) -> HazardPointer<'_> {
+        for record in self.records() {
+            if record.in_use.compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed).is_ok() {
+                return HazardPointer { record };
+            }
+        }
+        let record = Box::into_raw(Box::new(HazardRecord {
+            pointer: AtomicPtr::new(ptr::null_mut()),
+            in_use: AtomicBool::new(true),
+            next: ptr::null_mut()
+        }));
+        let mut head = self.records.load(Ordering::Relaxed);
+        loop {
+            // SAFETY: the record isn't published until the compare_exchange succeeds
+            unsafe { (*record).next = head };
+            match self.records.compare_exchange_weak(head, record, Ordering::Release, Ordering::Relaxed) {
+                // SAFETY: freed only when the domain is dropped, which the returned borrow prevents
+                Ok(_) => return HazardPointer { record: unsafe { &*record } },
+                Err(current) => head = current
+            }
+        }
+    }
+
+    /// Drops the `Box<T>` behind `ptr` as soon as no hazard pointer protects it anymore.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must come from `Box::into_raw`, must already be unreachable from the data structure
+    /// and must be retired only once. Dropping the `T` may happen on any thread.
+    pub unsafe fn retire<T>(&self, ptr: *mut T) {
+        unsafe fn drop_box<T>(pointer: *mut ()) {
+            drop(Box::from_raw(pointer as *mut T));
+        }
+        let scan = {
+            let mut retired = lock(&self.retired);
+            retired.push(Retired { pointer: ptr as *mut (), drop: drop_box::<T> });
+            retired.len() >= SCAN_THRESHOLD
+        };
+        if scan {
+            self.reclaim();
+        }
+    }
+
+    // frees every retired node no hazard pointer points at, returns how many were freed
+    pub fn reclaim(&self) -> usize {
+        let retired = std::mem::take(&mut *lock(&self.retired));
+        // pairs with the fence in protect: either we see the hazard, or the protecting thread sees the node unlinked
+        fence(Ordering::SeqCst);
+        let protected: HashSet<*mut ()> = self.records().map(|record| record.pointer.load(Ordering::Acquire)).collect();
+        let (still_protected, free): (Vec<Retired>, Vec<Retired>) =
+            retired.into_iter().partition(|node| protected.contains(&node.pointer));
+        lock(&self.retired).extend(still_protected);
+        let freed = free.len();
+        for node in free {
+            // SAFETY: retired once, unreachable and not protected by anyone
+            unsafe { (node.drop)(node.pointer) };
+        }
+        freed
+    }
+
+    pub fn retired_count(&self) -> usize {
+        lock(&self.retired).len()
+    }
+}
+
+impl Default for Domain {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Domain {
+    fn drop(&mut self) {
+        // no HazardPointer can outlive the domain, so nothing is protected anymore
+        let retired = std::mem::take(self.retired.get_mut().unwrap_or_else(|poisoned| poisoned.into_inner()));
+        for node in retired {
+            unsafe { (node.drop)(node.pointer) };
+        }
+        let mut current = *self.records.get_mut();
+        while !current.is_null() {
+            // SAFETY: exclusive access, every record came from Box::into_raw
+            let record = unsafe { Box::from_raw(current) };
+            current = record.next;
+        }
+    }
+}
+
+//=================================================================
+
+// one hazard slot, protects at most one pointer at a time
+pub struct HazardPointer<'domain> {
+    record: &'domain HazardRecord
+}
+
+impl HazardPointer<'_> {
+    // loads the pointer in source and protects it
+    // the result is safe to dereference until the next protect, reset or drop, as long as the structure
+    // only retires nodes after unlinking them from wherever source lives
+    pub fn protect<T>(&self, source: &AtomicPtr<T>) -> *mut T {
+        let mut pointer = source.load(Ordering::Relaxed);
+        loop {
+            self.record.pointer.store(pointer as *mut (), Ordering::Relaxed);
+            // the hazard has to be visible before we check that the node is still reachable
+            fence(Ordering::SeqCst);
+            let current = source.load(Ordering::Acquire);
+            if current == pointer {
+                return pointer;
+            }
+            pointer = current;
+        }
+    }
+
+    pub fn reset(&self) {
+        self.record.pointer.store(ptr::null_mut(), Ordering::Release);
+    }
+}
+
+impl Drop for HazardPointer<'_> {
+    fn drop(&mut self) {
+        self.reset();
+        self.record.in_use.store(false, Ordering::Release);
+    }
+}
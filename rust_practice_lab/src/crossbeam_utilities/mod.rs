@@ -0,0 +1,14 @@
+// safe memory reclamation for lock-free data structures, written out instead of pulling in crossbeam
+//
+// the problem: a thread pops a node and wants to free it, but another thread may have loaded the
+// same pointer a moment earlier and is about to read it. two classic answers:
+//     epoch.rs: threads "pin" themselves while they work, garbage is freed once every pinned thread has moved on
+//               pinning is cheap, but one thread that stays pinned holds back all garbage
+//     hazard.rs: threads announce every single pointer they are about to read, garbage is freed when nobody announced it
+//                reading costs a fence per pointer, but the amount of garbage stays bounded
+// queue.rs has a Michael-Scott queue on hazard pointers, atomics::MsQueue and atomics::TreiberStack use epochs
+pub mod epoch;
+pub mod hazard;
+pub mod queue;
+
+pub use queue::HazardQueue;
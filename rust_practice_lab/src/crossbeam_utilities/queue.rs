@@ -0,0 +1,118 @@
+use std::mem::MaybeUninit;
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, Ordering};
+use super::hazard::Domain;
+
+// Michael-Scott queue with hazard pointers, the same algorithm as atomics::MsQueue
+// (see there for the orderings) but dequeued nodes are freed right away unless a reader announced them
+//
+// hazards: enqueue protects the tail, dequeue protects the head and the node after it
+// a node is retired when it stops being the dummy at the front, so a node that is still the head
+// or the tail is never retired, which is what makes the check in protect enough
+
+struct Node<T> {
+    value: MaybeUninit<T>,
+    next: AtomicPtr<Node<T>>
+}
+
+impl<T> Node<T> {
+    fn new(value: MaybeUninit<T>) -> *mut Self {
+        Box::into_raw(Box::new(Self { value, next: AtomicPtr::new(ptr::null_mut()) }))
+    }
+}
+
+pub struct HazardQueue<T> {
+    head: AtomicPtr<Node<T>>,
+    tail: AtomicPtr<Node<T>>,
+    domain: Domain
+}
+
+unsafe impl<T: Send> Send for HazardQueue<T> {}
+unsafe impl<T: Send> Sync for HazardQueue<T> {}
+
+impl<T> HazardQueue<T> {
+    pub fn new() -> Self {
+        let dummy = Node::new(MaybeUninit::uninit());
+        Self { head: AtomicPtr::new(dummy), tail: AtomicPtr::new(dummy), domain: Domain::new() }
+    }
+
+    pub fn enqueue(&self, value: T) {
+        let node = Node::new(MaybeUninit::new(value));
+        let hazard = self.domain.hazard_pointer();
+        loop {
+            let tail = hazard.protect(&self.tail);
+            // SAFETY: protected while it was still the tail, so it isn't freed
+            let next = unsafe { (*tail).next.load(Ordering::Acquire) };
+            if next.is_null() {
+                let linked = unsafe { (*tail).next.compare_exchange(ptr::null_mut(), node, Ordering::Release, Ordering::Relaxed) };
+                if linked.is_ok() {
+                    let _ = self.tail.compare_exchange(tail, node, Ordering::Release, Ordering::Relaxed);
+                    return;
+                }
+            } else {
+                let _ = self.tail.compare_exchange(tail, next, Ordering::Release, Ordering::Relaxed);
+            }
+        }
+    }
+
+    pub fn dequeue(&self) -> Option<T> {
+        let head_hazard = self.domain.hazard_pointer();
+        let next_hazard = self.domain.hazard_pointer();
+        loop {
+            let head = head_hazard.protect(&self.head);
+            // SAFETY: protected while it was still the head
+            let next = next_hazard.protect(unsafe { &(*head).next });
+            // if head moved on in the meantime, next may already be retired
+            if self.head.load(Ordering::Acquire) != head {
+                continue;
+            }
+            if next.is_null() {
+                return None;
+            }
+            let tail = self.tail.load(Ordering::Acquire);
+            if head == tail {
+                let _ = self.tail.compare_exchange(tail, next, Ordering::Release, Ordering::Relaxed);
+                continue;
+            }
+            if self.head.compare_exchange(head, next, Ordering::AcqRel, Ordering::Relaxed).is_ok() {
+                // next is the new dummy and protected, winning the compare_exchange makes its value ours
+                let value = unsafe { (*next).value.assume_init_read() };
+                drop(head_hazard);
+                drop(next_hazard);
+                // SAFETY: head is unlinked and only the winner of the compare_exchange retires it
+                unsafe { self.domain.retire(head) };
+                return Some(value);
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        let hazard = self.domain.hazard_pointer();
+        let head = hazard.protect(&self.head);
+        unsafe { (*head).next.load(Ordering::Acquire).is_null() }
+    }
+
+    // number of dequeued nodes that are still waiting for their hazards to go away
+    pub fn retired_count(&self) -> usize {
+        self.domain.retired_count()
+    }
+}
+
+impl<T> Default for HazardQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for HazardQueue<T> {
+    fn drop(&mut self) {
+        // the retired nodes are freed by the domain, their values were moved out already
+        let dummy = *self.head.get_mut();
+        let mut node = unsafe { Box::from_raw(dummy) }.next.into_inner();
+        while !node.is_null() {
+            let mut boxed = unsafe { Box::from_raw(node) };
+            unsafe { boxed.value.assume_init_drop() };
+            node = boxed.next.into_inner();
+        }
+    }
+}
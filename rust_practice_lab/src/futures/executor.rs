@@ -0,0 +1,211 @@
+use std::future::Future;
+use std::pin::pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+use crate::channels::{unbounded, Receiver, Select, Sender};
+use super::task::{JoinHandle, Task};
+
+// the executors keep their runnable tasks in one of our own unbounded channels:
+// waking a task sends it, a worker receives it and polls it once
+
+// wakes the thread that is blocked in block_on
+struct ThreadWaker {
+    thread: Thread,
+    woken: AtomicBool
+}
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.woken.store(true, Ordering::Release);
+        self.thread.unpark();
+    }
+}
+
+// runs a future to completion on the calling thread, parking the thread while the future waits
+// spawned tasks don't run here, use Executor::block_on for that
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let thread_waker = Arc::new(ThreadWaker { thread: thread::current(), woken: AtomicBool::new(false) });
+    let waker = Waker::from(Arc::clone(&thread_waker));
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+        // park can wake up spuriously, so wait for the flag
+        while !thread_waker.woken.swap(false, Ordering::Acquire) {
+            thread::park();
+        }
+    }
+}
+
+//=================================================================
+
+// wakes the future passed to Executor::block_on by sending a message the executor is selecting on
+struct MainWaker {
+    sender: Sender<()>
+}
+
+impl Wake for MainWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        // one pending wake-up is enough
+        if self.sender.is_empty() {
+            let _ = self.sender.send(());
+        }
+    }
+}
+
+// single-threaded executor: spawned tasks only run on the thread that calls block_on
+pub struct Executor {
+    sender: Sender<Arc<Task>>,
+    receiver: Receiver<Arc<Task>>
+}
+
+impl Executor {
+    pub fn new() -> Self {
+        let (sender, receiver) = unbounded();
+        Self { sender, receiver }
+    }
+
+    pub fn spawn<F>(&self, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static
+    {
+        Task::spawn(future, &self.sender)
+    }
+
+    // runs the spawned tasks until `future` is done
+    // tasks that are still pending afterwards stay in the executor for the next block_on
+    pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+        let mut future = pin!(future);
+        let (main_sender, main_receiver) = unbounded();
+        let waker = Waker::from(Arc::new(MainWaker { sender: main_sender }));
+        let mut cx = Context::from_waker(&waker);
+        let mut main_woken = true;
+        loop {
+            if main_woken {
+                if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                    return output;
+                }
+            }
+            // run what is runnable right now, tasks woken meanwhile wait for the next round
+            let runnable: Vec<Arc<Task>> = self.receiver.try_iter().collect();
+            let ran_something = !runnable.is_empty();
+            runnable.into_iter().for_each(Task::run);
+            main_woken = main_receiver.try_iter().count() > 0;
+            if !main_woken && !ran_something {
+                // nothing to do until a task or the main future gets woken
+                let mut select = Select::new();
+                select.recv(&self.receiver);
+                let main = select.recv(&main_receiver);
+                main_woken = select.ready() == main && main_receiver.try_iter().count() > 0;
+            }
+        }
+    }
+
+    // runs tasks until none of them is runnable, for tasks that only wait on each other
+    pub fn run_until_stalled(&self) {
+        while let Ok(task) = self.receiver.try_recv() {
+            task.run();
+        }
+    }
+}
+
+impl Default for Executor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Executor {
+    fn drop(&mut self) {
+        // queued tasks hold a sender to this channel, drop them so the channel can go away
+        self.receiver.try_iter().for_each(drop);
+    }
+}
+
+//=================================================================
+
+// multi-threaded executor: a fixed number of worker threads take tasks from the shared queue
+// a task can run on a different worker every time it is woken
+pub struct ThreadPoolExecutor {
+    sender: Sender<Arc<Task>>,
+    receiver: Receiver<Arc<Task>>,
+    // dropped to tell the workers to stop, they select on it next to the task queue
+    shutdown: Option<Sender<()>>,
+    workers: Vec<thread::JoinHandle<()>>
+}
+
+impl ThreadPoolExecutor {
+    // panics when the operating system refuses to start the worker threads
+    pub fn new(threads: usize) -> Self {
+        let (sender, receiver) = unbounded::<Arc<Task>>();
+        let (shutdown, shutdown_receiver) = unbounded::<()>();
+        let workers = (0..threads.max(1))
+            .map(|index| {
+                let receiver = receiver.clone();
+                let shutdown_receiver = shutdown_receiver.clone();
+                thread::Builder::new()
+                    .name(format!("executor-worker-{}", index))
+                    .spawn(move || worker_loop(&receiver, &shutdown_receiver))
+                    .expect("could not start an executor thread")
+            })
+            .collect();
+        Self { sender, receiver, shutdown: Some(shutdown), workers }
+    }
+
+    pub fn threads(&self) -> usize {
+        self.workers.len()
+    }
+
+    pub fn spawn<F>(&self, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static
+    {
+        Task::spawn(future, &self.sender)
+    }
+
+    // blocks the calling thread on `future` while the workers run the spawned tasks
+    pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+        block_on(future)
+    }
+}
+
+fn worker_loop(tasks: &Receiver<Arc<Task>>, shutdown: &Receiver<()>) {
+    loop {
+        if let Ok(task) = tasks.try_recv() {
+            task.run();
+            continue;
+        }
+        let mut select = Select::new();
+        select.recv(tasks);
+        let stop = select.recv(shutdown);
+        if select.ready() == stop {
+            // the shutdown sender is never used to send, ready means it was dropped
+            return;
+        }
+    }
+}
+
+impl Drop for ThreadPoolExecutor {
+    fn drop(&mut self) {
+        // tasks that are still waiting are dropped, like tokio does when its runtime goes away
+        self.shutdown.take();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+        self.receiver.try_iter().for_each(drop);
+    }
+}
@@ -0,0 +1,21 @@
+// a small async runtime written by hand, to see what tokio does for us
+//     task.rs: spawned futures, the wakers that reschedule them and JoinHandle
+//     executor.rs: block_on, a single-threaded Executor and a multi-threaded ThreadPoolExecutor
+//     timer.rs: a hashed timer wheel on a background thread, the sleep and timeout futures on top of it
+//               and a MockTimer whose clock only moves when told to
+//
+//     let executor = Executor::new();
+//     let handle = executor.spawn(async {
+//         sleep(Duration::from_millis(10)).await;
+//         42
+//     });
+//     assert_eq!(executor.block_on(handle), 42);
+//
+// spawned futures have to be Send + 'static in both executors, this keeps a single task type
+pub mod task;
+pub mod executor;
+pub mod timer;
+
+pub use executor::{block_on, Executor, ThreadPoolExecutor};
+pub use task::JoinHandle;
+pub use timer::{sleep, sleep_until, timeout, Elapsed, MockTimer, Sleep, Timeout};
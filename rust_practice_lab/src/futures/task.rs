@@ -0,0 +1,130 @@
+use std::future::Future;
+use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread;
+use crate::channels::Sender;
+
+type BoxFuture = Pin<Box<dyn Future<Output = ()> + Send + 'static>>;
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    // a panic while polling is caught and handed to the JoinHandle, the state behind the lock stays usable
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+// a spawned future plus the queue it goes back to when it is woken
+// the Arc<Task> itself is the waker: waking it sends it to the run queue of its executor
+pub(crate) struct Task {
+    future: Mutex<Option<BoxFuture>>,
+    queue: Sender<Arc<Task>>,
+    // true while the task sits in the queue, so ten wakes don't queue it ten times
+    scheduled: AtomicBool
+}
+
+impl Task {
+    pub(crate) fn spawn<F>(future: F, queue: &Sender<Arc<Task>>) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static
+    {
+        let state = Arc::new(Mutex::new(JoinState { result: None, waker: None }));
+        let task_state = Arc::clone(&state);
+        let mut future = Box::pin(future);
+        // the task only ever produces (), the real output goes to the JoinHandle
+        let wrapped = std::future::poll_fn(move |cx| {
+            let result = match catch_unwind(AssertUnwindSafe(|| future.as_mut().poll(cx))) {
+                Ok(Poll::Pending) => return Poll::Pending,
+                Ok(Poll::Ready(output)) => Ok(output),
+                Err(panic) => Err(panic)
+            };
+            let waker = {
+                let mut state = lock(&task_state);
+                state.result = Some(result);
+                state.waker.take()
+            };
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+            Poll::Ready(())
+        });
+        let task = Arc::new(Task {
+            future: Mutex::new(Some(Box::pin(wrapped))),
+            queue: queue.clone(),
+            scheduled: AtomicBool::new(false)
+        });
+        task.schedule();
+        JoinHandle { state }
+    }
+
+    fn schedule(self: &Arc<Self>) {
+        if !self.scheduled.swap(true, Ordering::AcqRel) {
+            // fails when the executor is gone, then the task is dropped with the message
+            let _ = self.queue.send(Arc::clone(self));
+        }
+    }
+
+    // polls the future once, called by the executor that took the task from its queue
+    pub(crate) fn run(self: Arc<Self>) {
+        // cleared before polling: a wake during the poll has to queue the task again
+        self.scheduled.store(false, Ordering::Release);
+        let waker = Waker::from(Arc::clone(&self));
+        let mut cx = Context::from_waker(&waker);
+        let mut slot = lock(&self.future);
+        if let Some(future) = slot.as_mut() {
+            if future.as_mut().poll(&mut cx).is_ready() {
+                // drop the finished future now, not when the last waker goes away
+                *slot = None;
+            }
+        }
+    }
+}
+
+impl Wake for Task {
+    fn wake(self: Arc<Self>) {
+        self.schedule();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.schedule();
+    }
+}
+
+//=================================================================
+
+struct JoinState<T> {
+    result: Option<thread::Result<T>>,
+    waker: Option<Waker>
+}
+
+// resolves to the output of a spawned future, a panic in the future is rethrown where the handle is awaited
+// dropping the handle doesn't cancel the task, it just runs to completion unobserved
+pub struct JoinHandle<T> {
+    state: Arc<Mutex<JoinState<T>>>
+}
+
+impl<T> JoinHandle<T> {
+    pub fn is_finished(&self) -> bool {
+        lock(&self.state).result.is_some()
+    }
+}
+
+impl<T> Future for JoinHandle<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        let mut state = lock(&self.state);
+        match state.result.take() {
+            Some(Ok(output)) => Poll::Ready(output),
+            Some(Err(panic)) => {
+                drop(state);
+                resume_unwind(panic)
+            }
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
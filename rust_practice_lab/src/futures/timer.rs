@@ -0,0 +1,362 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, OnceLock};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// hashed timer wheel: time is cut into ticks of TICK, and a timer that expires in tick t goes into
+// slot t % SLOTS. a background thread moves from tick to tick and only looks at the slot of the current
+// tick, so registering and firing cost the same no matter how many timers there are.
+// timers further away than SLOTS ticks share a slot with nearer ones, they stay until their own tick comes around.
+//
+// the precision is one tick, a sleep never ends early but can end up to a tick late.
+// the thread sleeps until the earliest timer is due, not from tick to tick
+
+const TICK: Duration = Duration::from_millis(1);
+const SLOTS: usize = 256;
+
+// shared by a Sleep future and its entry in the wheel
+struct SleepState {
+    fired: AtomicBool,
+    waker: Mutex<Option<Waker>>
+}
+
+impl SleepState {
+    fn fire(&self) {
+        self.fired.store(true, Ordering::Release);
+        let waker = lock(&self.waker).take();
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    // no user code runs while these locks are held, wakers are called after releasing them
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+struct Entry {
+    tick: u64,
+    state: Arc<SleepState>
+}
+
+struct Wheel {
+    slots: Vec<Vec<Entry>>,
+    // every tick up to and including this one has been fired
+    current_tick: u64,
+    pending: usize,
+    // no later than the earliest tick with a timer, None when there are no timers.
+    // the thread sleeps until then instead of visiting the empty ticks in between
+    next_tick: Option<u64>,
+    // Some for a MockTimer, its clock only moves when it is advanced
+    mocked_elapsed: Option<Duration>
+}
+
+impl Wheel {
+    // fires everything due by `now` (the last tick that fully passed) and returns it, to be fired outside the lock.
+    // catching up more than SLOTS ticks visits every slot once
+    fn take_due(&mut self, now: u64) -> Vec<Entry> {
+        if now <= self.current_tick {
+            return Vec::new();
+        }
+        let visits = (now - self.current_tick).min(SLOTS as u64);
+        let mut due = Vec::new();
+        for tick in self.current_tick + 1..=self.current_tick + visits {
+            let slot = &mut self.slots[tick as usize % SLOTS];
+            let (expired, later): (Vec<Entry>, Vec<Entry>) = slot.drain(..).partition(|entry| entry.tick <= now);
+            *slot = later;
+            due.extend(expired);
+        }
+        self.pending -= due.len();
+        self.current_tick = now;
+        if self.next_tick.is_some_and(|next_tick| next_tick <= now) {
+            self.next_tick = self.slots.iter().flatten().map(|entry| entry.tick).min();
+        }
+        due
+    }
+}
+
+struct TimerWheel {
+    start: Instant,
+    wheel: Mutex<Wheel>,
+    changed: Condvar
+}
+
+impl TimerWheel {
+    fn new(mocked: bool) -> Self {
+        Self {
+            start: Instant::now(),
+            wheel: Mutex::new(Wheel {
+                slots: (0..SLOTS).map(|_| Vec::new()).collect(),
+                current_tick: 0,
+                pending: 0,
+                next_tick: None,
+                mocked_elapsed: mocked.then_some(Duration::ZERO)
+            }),
+            changed: Condvar::new()
+        }
+    }
+
+    fn now(&self) -> Instant {
+        self.now_locked(&lock(&self.wheel))
+    }
+
+    fn now_locked(&self, wheel: &Wheel) -> Instant {
+        match wheel.mocked_elapsed {
+            Some(elapsed) => deadline_after(self.start, elapsed),
+            None => Instant::now()
+        }
+    }
+
+    // the first tick that ends at or after the instant
+    fn tick_at(&self, instant: Instant) -> u64 {
+        let elapsed = instant.saturating_duration_since(self.start);
+        elapsed.as_nanos().div_ceil(TICK.as_nanos()) as u64
+    }
+
+    // the last tick that has fully passed
+    fn tick_passed(&self, wheel: &Wheel) -> u64 {
+        (self.now_locked(wheel).saturating_duration_since(self.start).as_nanos() / TICK.as_nanos()) as u64
+    }
+
+    fn tick_start(&self, tick: u64) -> Instant {
+        self.start + Duration::from_nanos(tick * TICK.as_nanos() as u64)
+    }
+
+    // false when the deadline is already in a tick that was fired, the caller is done sleeping then
+    fn register(&self, deadline: Instant, state: &Arc<SleepState>) -> bool {
+        let tick = self.tick_at(deadline);
+        let mut wheel = lock(&self.wheel);
+        if tick <= wheel.current_tick {
+            return false;
+        }
+        wheel.slots[tick as usize % SLOTS].push(Entry { tick, state: Arc::clone(state) });
+        wheel.pending += 1;
+        // the thread only needs to know when it has to wake up earlier than planned
+        if wheel.next_tick.is_none_or(|next_tick| tick < next_tick) {
+            wheel.next_tick = Some(tick);
+            self.changed.notify_one();
+        }
+        true
+    }
+
+    // takes the timer of a dropped Sleep out of the wheel, nothing to do when it fired already
+    fn cancel(&self, deadline: Instant, state: &Arc<SleepState>) {
+        let tick = self.tick_at(deadline);
+        let mut wheel = lock(&self.wheel);
+        let slot = &mut wheel.slots[tick as usize % SLOTS];
+        if let Some(position) = slot.iter().position(|entry| Arc::ptr_eq(&entry.state, state)) {
+            slot.swap_remove(position);
+            wheel.pending -= 1;
+            // next_tick may now be early, that only costs the thread one wake-up
+            if wheel.pending == 0 {
+                wheel.next_tick = None;
+            }
+        }
+    }
+
+    fn run(&self) {
+        let mut wheel = lock(&self.wheel);
+        loop {
+            let now = self.tick_passed(&wheel);
+            let expired = wheel.take_due(now);
+            if !expired.is_empty() {
+                drop(wheel);
+                expired.iter().for_each(|entry| entry.state.fire());
+                wheel = lock(&self.wheel);
+                continue;
+            }
+            wheel = match wheel.next_tick {
+                // nothing to do until a timer is registered, then catch up with the ticks that passed meanwhile
+                None => self.changed.wait(wheel).unwrap_or_else(|poisoned| poisoned.into_inner()),
+                Some(next_tick) => {
+                    let timeout = self.tick_start(next_tick).saturating_duration_since(Instant::now());
+                    self.changed.wait_timeout(wheel, timeout).unwrap_or_else(|poisoned| poisoned.into_inner()).0
+                }
+            };
+        }
+    }
+}
+
+// started the first time something sleeps, runs until the process ends
+fn timer_wheel() -> &'static Arc<TimerWheel> {
+    static WHEEL: OnceLock<Arc<TimerWheel>> = OnceLock::new();
+    WHEEL.get_or_init(|| {
+        let wheel = Arc::new(TimerWheel::new(false));
+        let runner = Arc::clone(&wheel);
+        thread::Builder::new()
+            .name("timer-wheel".to_string())
+            .spawn(move || runner.run())
+            .expect("could not start the timer thread");
+        wheel
+    })
+}
+
+// about 30 years away, what a deadline or a mocked clock becomes when the duration reaches past what an Instant can hold
+fn deadline_after(now: Instant, duration: Duration) -> Instant {
+    now.checked_add(duration).unwrap_or_else(|| now + Duration::from_secs(60 * 60 * 24 * 365 * 30))
+}
+
+//=================================================================
+
+// completes once the deadline has passed, works on any executor (ours, tokio's or block_on)
+// the timer is registered on the first poll, so creating a Sleep without awaiting it costs nothing.
+// dropping it before the deadline takes the timer out of the wheel again
+pub struct Sleep {
+    deadline: Instant,
+    // None until the first poll for the global wheel, a MockTimer sets its own
+    wheel: Option<Arc<TimerWheel>>,
+    state: Option<Arc<SleepState>>
+}
+
+pub fn sleep(duration: Duration) -> Sleep {
+    sleep_until(deadline_after(Instant::now(), duration))
+}
+
+pub fn sleep_until(deadline: Instant) -> Sleep {
+    Sleep { deadline, wheel: None, state: None }
+}
+
+impl Sleep {
+    pub fn deadline(&self) -> Instant {
+        self.deadline
+    }
+
+    pub fn is_elapsed(&self) -> bool {
+        self.state.as_ref().is_some_and(|state| state.fired.load(Ordering::Acquire))
+    }
+}
+
+impl Drop for Sleep {
+    fn drop(&mut self) {
+        if let (Some(wheel), Some(state)) = (&self.wheel, &self.state) {
+            if !state.fired.load(Ordering::Acquire) {
+                wheel.cancel(self.deadline, state);
+            }
+        }
+    }
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.state.is_none() {
+            let wheel = Arc::clone(self.wheel.get_or_insert_with(|| Arc::clone(timer_wheel())));
+            let state = Arc::new(SleepState { fired: AtomicBool::new(false), waker: Mutex::new(None) });
+            if wheel.now() >= self.deadline || !wheel.register(self.deadline, &state) {
+                state.fired.store(true, Ordering::Release);
+            }
+            self.state = Some(state);
+        }
+        let state = self.state.as_ref().expect("set above");
+        // store the waker first and check afterwards, fire sets the flag first and takes the waker afterwards,
+        // so one of the two sees the other
+        *lock(&state.waker) = Some(cx.waker().clone());
+        if state.fired.load(Ordering::Acquire) {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+//=================================================================
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Elapsed;
+
+impl std::fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("The deadline passed before the future completed")
+    }
+}
+
+impl std::error::Error for Elapsed {}
+
+crate::pin_project! {
+    // the future is polled in place, so it doesn't have to be Unpin like the stream combinators want
+    #[project(TimeoutProjection)]
+    pub struct Timeout<F> {
+        #[pin]
+        future: F,
+        deadline: Sleep
+    }
+}
+
+// runs the future until it completes or until the duration has passed, whichever comes first
+// when the time is up the future is dropped with the Timeout
+pub fn timeout<F: Future>(duration: Duration, future: F) -> Timeout<F> {
+    Timeout { future, deadline: sleep(duration) }
+}
+
+impl<F: Future> Future for Timeout<F> {
+    type Output = Result<F::Output, Elapsed>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        if let Poll::Ready(output) = this.future.poll(cx) {
+            return Poll::Ready(Ok(output));
+        }
+        Pin::new(this.deadline).poll(cx).map(|_| Err(Elapsed))
+    }
+}
+
+//=================================================================
+
+// a timer wheel of its own whose clock stands still until advance is called, so tests can check
+// deadlines without waiting for them. sleeps created here don't care about the system clock
+//
+//     let timer = MockTimer::new();
+//     let handle = executor.spawn(timer.sleep(Duration::from_secs(60)));
+//     timer.advance(Duration::from_secs(60));
+//     executor.block_on(handle);
+// clones share the clock
+#[derive(Clone)]
+pub struct MockTimer {
+    wheel: Arc<TimerWheel>
+}
+
+impl MockTimer {
+    pub fn new() -> Self {
+        Self { wheel: Arc::new(TimerWheel::new(true)) }
+    }
+
+    pub fn now(&self) -> Instant {
+        self.wheel.now()
+    }
+
+    pub fn sleep(&self, duration: Duration) -> Sleep {
+        self.sleep_until(deadline_after(self.now(), duration))
+    }
+
+    pub fn sleep_until(&self, deadline: Instant) -> Sleep {
+        Sleep { deadline, wheel: Some(Arc::clone(&self.wheel)), state: None }
+    }
+
+    // the number of sleeps waiting in the wheel
+    pub fn pending(&self) -> usize {
+        lock(&self.wheel.wheel).pending
+    }
+
+    // moves the clock forward and wakes every sleep whose deadline it passed, on the calling thread
+    pub fn advance(&self, duration: Duration) {
+        let expired = {
+            let mut wheel = lock(&self.wheel.wheel);
+            let elapsed = wheel.mocked_elapsed.expect("a MockTimer's wheel is mocked");
+            wheel.mocked_elapsed = Some(elapsed.saturating_add(duration));
+            let now = self.wheel.tick_passed(&wheel);
+            wheel.take_due(now)
+        };
+        expired.iter().for_each(|entry| entry.state.fire());
+    }
+}
+
+impl Default for MockTimer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,171 @@
+use std::any::Any;
+use std::fmt;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::mpsc::{channel, sync_channel, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::knapsack::{validate_knapsack_problem, Item};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KnapsackProblem {
+    pub items: Vec<Item>,
+    pub weight_limit: i32
+}
+
+impl KnapsackProblem {
+    pub fn new(items: Vec<Item>, weight_limit: i32) -> Self {
+        Self { items, weight_limit }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JobError {
+    InvalidProblem(String),
+    // the solver panicked, the other jobs in the batch keep running
+    Panicked(String)
+}
+
+impl fmt::Display for JobError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JobError::InvalidProblem(message) => write!(f, "invalid knapsack problem: {}", message),
+            JobError::Panicked(message) => write!(f, "solver panicked: {}", message)
+        }
+    }
+}
+
+impl std::error::Error for JobError {}
+
+#[derive(Debug, Clone)]
+pub struct JobReport {
+    // position of the problem in the submitted batch
+    pub index: usize,
+    pub result: Result<Vec<Item>, JobError>,
+    // only the time the solver ran, not the time the job waited in the queue
+    pub elapsed: Duration
+}
+
+impl JobReport {
+    pub fn total_value(&self) -> Option<i32> {
+        self.result.as_ref().ok().map(|items| items.iter().map(|item| item.value).sum())
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ResultOrder {
+    Submission,
+    Completion
+}
+
+// runs many knapsack problems on a fixed number of worker threads
+// problems are pulled from the input lazily through a bounded queue, so when the workers fall behind
+// the producer waits instead of loading the whole batch into memory (back-pressure)
+#[derive(Debug, Copy, Clone)]
+pub struct BatchSolver {
+    workers: usize,
+    queue_capacity: usize
+}
+
+impl BatchSolver {
+    pub fn new(workers: usize) -> Self {
+        let workers = workers.max(1);
+        Self { workers, queue_capacity: workers }
+    }
+
+    pub fn queue_capacity(mut self, queue_capacity: usize) -> Self {
+        self.queue_capacity = queue_capacity;
+        self
+    }
+
+    pub fn workers(&self) -> usize {
+        self.workers
+    }
+
+    pub fn solve<I, F>(&self, problems: I, solver: F, order: ResultOrder) -> Vec<JobReport>
+    where
+        I: IntoIterator<Item = KnapsackProblem>,
+        I::IntoIter: Send,
+        F: Fn(&mut Vec<Item>, i32) -> Vec<Item> + Sync
+    {
+        let mut reports = Vec::new();
+        self.for_each_completed(problems, solver, |report| reports.push(report));
+        if order == ResultOrder::Submission {
+            reports.sort_by_key(|report| report.index);
+        }
+        reports
+    }
+
+    // calls on_report on the calling thread as soon as a job is done
+    pub fn for_each_completed<I, F, C>(&self, problems: I, solver: F, mut on_report: C)
+    where
+        I: IntoIterator<Item = KnapsackProblem>,
+        I::IntoIter: Send,
+        F: Fn(&mut Vec<Item>, i32) -> Vec<Item> + Sync,
+        C: FnMut(JobReport)
+    {
+        let problems = problems.into_iter();
+        let solver = &solver;
+
+        thread::scope(|scope| {
+            let (job_sender, job_receiver) = sync_channel::<(usize, KnapsackProblem)>(self.queue_capacity);
+            let job_receiver = Arc::new(Mutex::new(job_receiver));
+            let (report_sender, report_receiver) = channel::<JobReport>();
+
+            for _ in 0..self.workers {
+                let job_receiver = Arc::clone(&job_receiver);
+                let report_sender = report_sender.clone();
+                scope.spawn(move || {
+                    while let Some((index, problem)) = next_job(&job_receiver) {
+                        if report_sender.send(run_job(index, problem, solver)).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+            drop(report_sender);
+
+            // send blocks while the queue is full, that is where the back-pressure comes from
+            scope.spawn(move || {
+                for job in problems.enumerate() {
+                    if job_sender.send(job).is_err() {
+                        break;
+                    }
+                }
+            });
+
+            for report in report_receiver {
+                on_report(report);
+            }
+        });
+    }
+}
+
+fn next_job(job_receiver: &Mutex<Receiver<(usize, KnapsackProblem)>>) -> Option<(usize, KnapsackProblem)> {
+    // the lock is only held while waiting for the next job, not while solving it
+    job_receiver.lock().unwrap().recv().ok()
+}
+
+fn run_job<F>(index: usize, problem: KnapsackProblem, solver: &F) -> JobReport
+where
+    F: Fn(&mut Vec<Item>, i32) -> Vec<Item>
+{
+    let start = Instant::now();
+    let KnapsackProblem { mut items, weight_limit } = problem;
+    let result = match validate_knapsack_problem(&items, weight_limit) {
+        Err(message) => Err(JobError::InvalidProblem(message)),
+        Ok(()) => catch_unwind(AssertUnwindSafe(|| solver(&mut items, weight_limit)))
+            .map_err(|payload| JobError::Panicked(panic_message(payload)))
+    };
+    JobReport { index, result, elapsed: start.elapsed() }
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
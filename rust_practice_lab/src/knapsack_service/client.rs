@@ -0,0 +1,144 @@
+use std::collections::{HashMap, VecDeque};
+use std::io;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{TcpStream, ToSocketAddrs};
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+use crate::knapsack::{Item, KnapsackProgress};
+use super::protocol::*;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum JobEvent {
+    Progress(KnapsackProgress),
+    Finished { items: Vec<Item>, total_weight: i32, total_value: i32 },
+    Cancelled,
+    Failed(String)
+}
+
+impl JobEvent {
+    // finished, cancelled and failed are the last event a job sends
+    pub fn is_final(&self) -> bool {
+        !matches!(self, JobEvent::Progress(_))
+    }
+}
+
+// one submitted job, the events arrive in the order the server sent them
+pub struct JobHandle {
+    pub job_id: u64,
+    events: mpsc::UnboundedReceiver<JobEvent>
+}
+
+impl JobHandle {
+    // None once the final event has been received or the connection is gone
+    pub async fn next_event(&mut self) -> Option<JobEvent> {
+        self.events.recv().await
+    }
+
+    // skips the progress updates and waits for the final event
+    pub async fn outcome(mut self) -> io::Result<JobEvent> {
+        while let Some(event) = self.next_event().await {
+            if event.is_final() {
+                return Ok(event);
+            }
+        }
+        Err(io::Error::new(io::ErrorKind::UnexpectedEof, "connection closed before the job finished"))
+    }
+}
+
+type PendingSubmit = oneshot::Sender<Result<JobHandle, String>>;
+
+#[derive(Default)]
+struct Routing {
+    pending_submits: VecDeque<PendingSubmit>,
+    jobs: HashMap<u64, mpsc::UnboundedSender<JobEvent>>
+}
+
+// async client for the knapsack service, one connection can run many jobs at the same time
+pub struct KnapsackClient {
+    writer: tokio::sync::Mutex<OwnedWriteHalf>,
+    routing: Arc<Mutex<Routing>>,
+    reader_task: JoinHandle<()>
+}
+
+impl KnapsackClient {
+    pub async fn connect<A: ToSocketAddrs>(address: A) -> io::Result<Self> {
+        let (reader, writer) = TcpStream::connect(address).await?.into_split();
+        let routing = Arc::new(Mutex::new(Routing::default()));
+        let reader_task = tokio::spawn(route_messages(reader, Arc::clone(&routing)));
+        Ok(Self { writer: tokio::sync::Mutex::new(writer), routing, reader_task })
+    }
+
+    pub async fn submit(&self, items: Vec<Item>, weight_limit: i32, solver: Solver) -> io::Result<JobHandle> {
+        let (sender, receiver) = oneshot::channel();
+        {
+            // the server answers submits in order, so queueing and sending happen under the same lock
+            let mut writer = self.writer.lock().await;
+            self.routing.lock().unwrap().pending_submits.push_back(sender);
+            let line = encode_line(&ClientMessage::Submit { items, weight_limit, solver });
+            writer.write_all(line.as_bytes()).await?;
+        }
+        match receiver.await {
+            Ok(Ok(handle)) => Ok(handle),
+            Ok(Err(message)) => Err(io::Error::new(io::ErrorKind::InvalidInput, message)),
+            Err(_) => Err(io::Error::new(io::ErrorKind::UnexpectedEof, "connection closed before the job was accepted"))
+        }
+    }
+
+    // the job's handle receives JobEvent::Cancelled once the server has stopped it
+    pub async fn cancel(&self, job_id: u64) -> io::Result<()> {
+        let line = encode_line(&ClientMessage::Cancel { job_id });
+        self.writer.lock().await.write_all(line.as_bytes()).await
+    }
+}
+
+impl Drop for KnapsackClient {
+    fn drop(&mut self) {
+        self.reader_task.abort();
+    }
+}
+
+async fn route_messages(reader: OwnedReadHalf, routing: Arc<Mutex<Routing>>) {
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        let Ok(message) = serde_json::from_str::<ServerMessage>(&line) else {
+            continue;
+        };
+        let mut routing = routing.lock().unwrap();
+        let event = match message {
+            ServerMessage::Accepted { job_id } => {
+                let (sender, events) = mpsc::unbounded_channel();
+                routing.jobs.insert(job_id, sender);
+                if let Some(pending) = routing.pending_submits.pop_front() {
+                    let _ = pending.send(Ok(JobHandle { job_id, events }));
+                }
+                continue;
+            }
+            ServerMessage::Rejected { message } => {
+                if let Some(pending) = routing.pending_submits.pop_front() {
+                    let _ = pending.send(Err(message));
+                }
+                continue;
+            }
+            ServerMessage::Error { .. } => continue,
+            ServerMessage::Progress { job_id, progress } => (job_id, JobEvent::Progress(progress)),
+            ServerMessage::Finished { job_id, items, total_weight, total_value } => (job_id, JobEvent::Finished { items, total_weight, total_value }),
+            ServerMessage::Cancelled { job_id } => (job_id, JobEvent::Cancelled),
+            ServerMessage::Failed { job_id, message } => (job_id, JobEvent::Failed(message))
+        };
+
+        let (job_id, event) = event;
+        let is_final = event.is_final();
+        if let Some(sender) = routing.jobs.get(&job_id) {
+            let _ = sender.send(event);
+        }
+        if is_final {
+            routing.jobs.remove(&job_id);
+        }
+    }
+    // dropping the senders ends every JobHandle that is still waiting
+    let mut routing = routing.lock().unwrap();
+    routing.jobs.clear();
+    routing.pending_submits.clear();
+}
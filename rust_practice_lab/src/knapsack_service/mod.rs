@@ -0,0 +1,9 @@
+// knapsack solving as a service: other processes send jobs over a local TCP socket
+// see protocol.rs for the line-delimited JSON messages
+pub mod protocol;
+pub mod server;
+pub mod client;
+
+pub use protocol::{ClientMessage, ServerMessage, Solver};
+pub use server::serve;
+pub use client::{JobEvent, JobHandle, KnapsackClient};
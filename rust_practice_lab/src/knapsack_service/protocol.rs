@@ -0,0 +1,97 @@
+use serde::{Deserialize, Serialize};
+use crate::knapsack::{Item, KnapsackProgress};
+
+// every message is one JSON object on its own line, the "type" field tells which message it is
+//
+// client -> server
+//     {"type":"submit","items":[{"weight":10,"value":20}],"weight_limit":50,"solver":"exhaustive"}
+//     {"type":"cancel","job_id":1}
+//
+// server -> client
+//     {"type":"accepted","job_id":1}
+//     {"type":"rejected","message":"..."}
+//     {"type":"progress","job_id":1,"subset_size":2,"max_subset_size":4,"best_value":20}
+//     {"type":"finished","job_id":1,"items":[...],"total_weight":10,"total_value":20}
+//     {"type":"cancelled","job_id":1}
+//     {"type":"failed","job_id":1,"message":"..."}
+//     {"type":"error","message":"..."}
+//
+// a submit is always answered with accepted or rejected, in the order the submits were sent
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Solver {
+    // get_knapsack_items, the only solver that reports progress and stops right away when cancelled
+    #[default]
+    Exhaustive,
+    ParallelThreads,
+    ParallelIter,
+    ValueWeightRatio
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    Submit {
+        items: Vec<Item>,
+        weight_limit: i32,
+        #[serde(default)]
+        solver: Solver
+    },
+    Cancel {
+        job_id: u64
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    Accepted {
+        job_id: u64
+    },
+    Rejected {
+        message: String
+    },
+    Progress {
+        job_id: u64,
+        #[serde(flatten)]
+        progress: KnapsackProgress
+    },
+    Finished {
+        job_id: u64,
+        items: Vec<Item>,
+        total_weight: i32,
+        total_value: i32
+    },
+    Cancelled {
+        job_id: u64
+    },
+    Failed {
+        job_id: u64,
+        message: String
+    },
+    // a line the server could not make sense of, or a cancel for a job this connection isn't running
+    Error {
+        message: String
+    }
+}
+
+impl ServerMessage {
+    pub fn job_id(&self) -> Option<u64> {
+        match self {
+            ServerMessage::Accepted { job_id }
+            | ServerMessage::Progress { job_id, .. }
+            | ServerMessage::Finished { job_id, .. }
+            | ServerMessage::Cancelled { job_id }
+            | ServerMessage::Failed { job_id, .. } => Some(*job_id),
+            ServerMessage::Rejected { .. } | ServerMessage::Error { .. } => None
+        }
+    }
+}
+
+// serialises a message into a single protocol line, including the trailing newline
+pub fn encode_line<T: Serialize>(message: &T) -> String {
+    let mut line = serde_json::to_string(message).expect("protocol messages always serialise");
+    line.push('\n');
+    line
+}
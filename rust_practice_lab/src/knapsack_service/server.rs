@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+use std::io;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{mpsc, Notify};
+use crate::knapsack::*;
+use super::protocol::*;
+
+// shared between the job task, the solver thread and whoever wants to cancel the job
+struct JobControl {
+    cancelled: AtomicBool,
+    notify: Notify
+}
+
+impl JobControl {
+    fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+        // notify_one keeps a permit when nobody is waiting yet, so the job task can't miss it
+        self.notify.notify_one();
+    }
+}
+
+// state of the whole server, the job ids are unique across connections
+#[derive(Default)]
+struct ServiceState {
+    next_job_id: AtomicU64,
+    jobs: Mutex<HashMap<u64, Arc<JobControl>>>
+}
+
+// accepts connections until the listener fails, every connection is handled on its own task
+// bind the listener to 127.0.0.1, the protocol has no authentication
+pub async fn serve(listener: TcpListener) -> io::Result<()> {
+    let state = Arc::new(ServiceState::default());
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let state = Arc::clone(&state);
+        tokio::spawn(async move {
+            if let Err(error) = handle_connection(stream, state).await {
+                eprintln!("knapsack service: connection closed with error: {}", error);
+            }
+        });
+    }
+}
+
+async fn handle_connection(stream: TcpStream, state: Arc<ServiceState>) -> io::Result<()> {
+    let (reader, mut writer) = stream.into_split();
+
+    // all messages for this client go through one channel, so lines from different jobs never mix
+    let (sender, mut receiver) = mpsc::unbounded_channel::<ServerMessage>();
+    let writer_task = tokio::spawn(async move {
+        while let Some(message) = receiver.recv().await {
+            writer.write_all(encode_line(&message).as_bytes()).await?;
+        }
+        writer.shutdown().await
+    });
+
+    let mut own_jobs: Vec<u64> = Vec::new();
+    let mut lines = BufReader::new(reader).lines();
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<ClientMessage>(&line) {
+            Ok(ClientMessage::Submit { items, weight_limit, solver }) => {
+                if let Err(message) = validate_knapsack_problem(&items, weight_limit) {
+                    let _ = sender.send(ServerMessage::Rejected { message });
+                    continue;
+                }
+                let job_id = state.next_job_id.fetch_add(1, Ordering::Relaxed) + 1;
+                let control = Arc::new(JobControl { cancelled: AtomicBool::new(false), notify: Notify::new() });
+                state.jobs.lock().unwrap().insert(job_id, Arc::clone(&control));
+                own_jobs.push(job_id);
+
+                let _ = sender.send(ServerMessage::Accepted { job_id });
+                tokio::spawn(run_job(job_id, items, weight_limit, solver, control, Arc::clone(&state), sender.clone()));
+            }
+            Ok(ClientMessage::Cancel { job_id }) => {
+                // a connection can only cancel the jobs it submitted itself
+                let control = if own_jobs.contains(&job_id) { state.jobs.lock().unwrap().get(&job_id).cloned() } else { None };
+                match control {
+                    Some(control) => control.cancel(),
+                    None => {
+                        let _ = sender.send(ServerMessage::Error { message: format!("no running job with id {}", job_id) });
+                    }
+                }
+            }
+            Err(error) => {
+                let _ = sender.send(ServerMessage::Error { message: format!("invalid message: {}", error) });
+            }
+        }
+    }
+
+    // the client is gone, nobody would receive the results of its jobs anymore
+    for job_id in own_jobs {
+        if let Some(control) = state.jobs.lock().unwrap().get(&job_id) {
+            control.cancel();
+        }
+    }
+    drop(sender);
+    writer_task.await.unwrap_or(Ok(()))
+}
+
+async fn run_job(
+    job_id: u64,
+    items: Vec<Item>,
+    weight_limit: i32,
+    solver: Solver,
+    control: Arc<JobControl>,
+    state: Arc<ServiceState>,
+    sender: mpsc::UnboundedSender<ServerMessage>
+) {
+    // the solvers are plain blocking code, so they run on tokio's blocking thread pool
+    let solver_control = Arc::clone(&control);
+    let progress_sender = sender.clone();
+    let solving = tokio::task::spawn_blocking(move || {
+        let mut items = items;
+        match solver {
+            Solver::Exhaustive => get_knapsack_items_with_progress(&items, weight_limit, &solver_control.cancelled, |progress| {
+                if !solver_control.cancelled.load(Ordering::Relaxed) {
+                    let _ = progress_sender.send(ServerMessage::Progress { job_id, progress });
+                }
+            }),
+            Solver::ParallelThreads => Some(get_knapsack_items_par_threads(&items, weight_limit)),
+            Solver::ParallelIter => Some(get_knapsack_items_par_iter(&items, weight_limit)),
+            Solver::ValueWeightRatio => Some(get_suboptimal_knapsack_items_val_weight_ratio(&mut items, weight_limit))
+        }
+    });
+
+    // a cancelled job is reported right away, even when its solver can't be interrupted and keeps running
+    let message = tokio::select! {
+        result = solving => match result {
+            Ok(Some(items)) => ServerMessage::Finished {
+                job_id,
+                total_weight: items.iter().map(|item| item.weight).sum(),
+                total_value: items.iter().map(|item| item.value).sum(),
+                items
+            },
+            Ok(None) => ServerMessage::Cancelled { job_id },
+            Err(error) => ServerMessage::Failed { job_id, message: error.to_string() }
+        },
+        _ = control.notify.notified() => ServerMessage::Cancelled { job_id }
+    };
+
+    state.jobs.lock().unwrap().remove(&job_id);
+    let _ = sender.send(message);
+}
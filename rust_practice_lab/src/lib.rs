@@ -1,5 +1,6 @@
 
 use cxx::*;
+use std::collections::HashMap;
 use std::error::Error;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
@@ -8,13 +9,37 @@ use itertools::Itertools;
 use rayon::prelude::*;
 use colored::*;
 use rand::Rng;
+use counting::{Counter, CountingError};
+
+pub mod counting;
+pub mod async_fibonacci;
+pub mod knapsack_service;
+pub mod knapsack_batch;
+pub mod channels;
+pub mod threads;
+pub mod mutexes;
+pub mod atomics;
+pub mod crossbeam_utilities;
+pub mod futures;
+pub mod async_stream;
+pub mod pinning;
+pub mod rayon_parallel;
+pub mod model;
 
 
 #[cxx::bridge]
 mod to_cpp {
+    unsafe extern "C++" {
+        include!("rust_practice_lab/include/counting.h");
+
+        type CountingCallback;
+        fn on_number(self: &CountingCallback, number: i32);
+    }
+
     extern "Rust" {
         fn hello();
-        fn counting_until(limit: i32);
+        fn counting_until(limit: i32) -> Result<()>;
+        fn counting_with_callback(start: i32, limit: i32, step: i32, callback: &CountingCallback) -> Result<()>;
         fn fibonacci_recursive(n: i64) -> i64;
         fn fibonacci_iterative(n: i64) -> i64;
     }
@@ -24,13 +49,16 @@ pub fn hello() {
     println!("Hello from Rust!");
 }
 
-pub fn counting_until(limit: i32) {
-    if limit > 50000 {
-        println!("Limit too high! Choose something below 50k!");
-    }
-    for i in 1..=limit {
-        println!("{}", i);
-    }
+pub fn counting_until(limit: i32) -> Result<(), CountingError> {
+    Counter::new(limit).write_to(&mut std::io::stdout().lock())?;
+    Ok(())
+}
+
+// C++ passes a callback (see include/counting.h) which gets called for every number
+// errors end up as a rust::Error exception on the C++ side
+pub fn counting_with_callback(start: i32, limit: i32, step: i32, callback: &to_cpp::CountingCallback) -> Result<(), CountingError> {
+    Counter::new(limit).start(start).step(step).for_each(|number| callback.on_number(number))?;
+    Ok(())
 }
 
 pub fn fibonacci_recursive(n: i64) -> i64 {
@@ -56,13 +84,48 @@ pub fn fibonacci_iterative(n: i64) -> i64 {
     return current_number;
 }
 
+// same recursion as fibonacci_recursive, but every computed value is stored in a cache
+// the cache sits behind a mutex, so one cache can be shared between calls and threads
+pub fn fibonacci_memoized(n: i64, cache: &Mutex<HashMap<i64, i64>>) -> i64 {
+    if n < 2 {
+        return n;
+    }
+    if let Some(&value) = cache.lock().unwrap().get(&n) {
+        return value;
+    }
+    // don't hold the lock while recursing, the recursive calls need it too
+    let value = fibonacci_memoized(n - 1, cache) + fibonacci_memoized(n - 2, cache);
+    cache.lock().unwrap().insert(n, value);
+    value
+}
+
+// fork-join version, both branches are handed to rayon which may run them on different threads
+// below the cutoff the overhead of splitting is bigger than the work, so we fall back to the sequential version
+pub fn fibonacci_parallel(n: i64, sequential_cutoff: i64) -> i64 {
+    if n <= sequential_cutoff || n < 2 {
+        return fibonacci_recursive(n);
+    }
+    let (first_number, second_number) = rayon::join(
+        || fibonacci_parallel(n - 1, sequential_cutoff),
+        || fibonacci_parallel(n - 2, sequential_cutoff)
+    );
+    first_number + second_number
+}
+
 
 
 pub mod knapsack {
     use std::cmp::Ordering;
+    use std::sync::atomic::AtomicBool;
+    use serde::{Deserialize, Serialize};
     use super::*;
+    use crate::knapsack_batch::{BatchSolver, KnapsackProblem, ResultOrder};
+    use crate::threads::global_pool;
+    use crate::mutexes::{self, RawLock};
+    use crate::atomics::AtomicMax;
+    use crate::model;
 
-    #[derive(Debug, Copy, Clone)]
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
     pub struct Item {
         pub weight: i32,
         pub value: i32
@@ -80,15 +143,7 @@ pub mod knapsack {
     }
 
     pub fn get_knapsack_items(items: &mut Vec<Item>, weight_limit: i32) -> Vec<Item> {
-        let mut sum = 0;
-        let max_possible_combinations = items
-            .iter()
-            .sorted_by(|a, b| a.weight.cmp(&b.weight))
-            .take_while(|&&item| {
-                sum += item.weight;
-                sum < weight_limit
-            })
-            .count();
+        let max_possible_combinations = max_subset_size(items, weight_limit);
 
         println!("\nMax {} possible combinations\n", max_possible_combinations);
 
@@ -108,69 +163,42 @@ pub mod knapsack {
     }
 
     pub fn get_knapsack_items_par_threads(items: &Vec<Item>, weight_limit: i32) -> Vec<Item> {
-        let items = Arc::new(items.clone());
-
-        let mut sum = 0;
-        let max_possible_combinations = items
-            .iter()
-            .sorted_by(|a, b| a.weight.cmp(&b.weight))
-            .take_while(|&&item| {
-                sum += item.weight;
-                sum < weight_limit
-            })
-            .count();
+        let max_possible_combinations = max_subset_size(items, weight_limit);
 
         println!("\nMax {} possible combinations\n", max_possible_combinations);
 
-        let highest_combined_value: Arc<Mutex<i32>> = Arc::new(Mutex::new(0));
-        let mut knapsack_items: Arc<Mutex<Vec<Item>>> = Arc::new(Mutex::new(Vec::<Item>::new()));
-
-        let mut handles = vec![];
-
-        for i in 1..=max_possible_combinations {
-            // atomic reference count clone, for every thread
-            let items = Arc::clone(&items);
-            let highest_combined_value = Arc::clone(&highest_combined_value);
-            let knapsack_items = Arc::clone(&knapsack_items);
-
-            // make thread and execute
-            let thread = std::thread::spawn(move || {
-                for combination in items.iter().combinations(i) {
-                    let current_combined_value: i32 = combination.iter().map(|item| item.value).sum();
-                    let current_combined_weight: i32 = combination.iter().map(|item| item.weight).sum();
-                    let mut highest_combined_value = highest_combined_value.lock().unwrap();
-                    if current_combined_value > *highest_combined_value  && current_combined_weight <= weight_limit {
-                        *highest_combined_value = current_combined_value;
-                        let mut knapsack_items = knapsack_items.lock().unwrap();
-                        *knapsack_items = combination.into_iter().cloned().collect();
+        let highest_combined_value: Mutex<i32> = Mutex::new(0);
+        let knapsack_items: Mutex<Vec<Item>> = Mutex::new(Vec::<Item>::new());
+
+        // one job per subset size on the work-stealing pool, instead of one OS thread per subset size
+        // the jobs run inside a scope, so they can borrow the items and the shared best instead of cloning Arcs
+        global_pool().scope(|scope| {
+            for i in 1..=max_possible_combinations {
+                let highest_combined_value = &highest_combined_value;
+                let knapsack_items = &knapsack_items;
+                scope.spawn(move |_| {
+                    for combination in items.iter().combinations(i) {
+                        let current_combined_value: i32 = combination.iter().map(|item| item.value).sum();
+                        let current_combined_weight: i32 = combination.iter().map(|item| item.weight).sum();
+                        let mut highest_combined_value = highest_combined_value.lock().unwrap();
+                        if current_combined_value > *highest_combined_value  && current_combined_weight <= weight_limit {
+                            *highest_combined_value = current_combined_value;
+                            let mut knapsack_items = knapsack_items.lock().unwrap();
+                            *knapsack_items = combination.into_iter().cloned().collect();
+                        }
                     }
-                }
-                highest_combined_value
-            });
-
-            handles.push(thread);
-        }
-
-        for handle in handles {
-            handle.join().unwrap();
-        }
+                });
+            }
+        });
 
-        Arc::try_unwrap(knapsack_items).unwrap().into_inner().unwrap()
+        knapsack_items.into_inner().unwrap()
     }
 
 
     pub fn get_knapsack_items_par_iter(items: &Vec<Item>, weight_limit: i32) -> Vec<Item> {
         let items = Arc::new(items.clone());
 
-        let mut sum = 0;
-        let max_possible_combinations = items
-            .iter()
-            .sorted_by(|a, b| a.weight.cmp(&b.weight))
-            .take_while(|&&item| {
-                sum += item.weight;
-                sum < weight_limit
-            })
-            .count();
+        let max_possible_combinations = max_subset_size(&items, weight_limit);
 
         println!("\nMax {} possible combinations\n", max_possible_combinations);
 
@@ -198,6 +226,249 @@ pub mod knapsack {
         Arc::try_unwrap(knapsack_items).unwrap().into_inner().unwrap()
     }
 
+    // snapshot of a running search, sent after every subset size that has been fully checked
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct KnapsackProgress {
+        pub subset_size: usize,
+        pub max_subset_size: usize,
+        pub best_value: i32
+    }
+
+    // get_knapsack_items_par_iter with the std mutex swapped for one of the locks in the mutexes module
+    // the best value and the best items sit behind the same lock, so every combination takes it once
+    pub fn get_knapsack_items_par_iter_with_lock<L: RawLock>(items: &[Item], weight_limit: i32) -> Vec<Item> {
+        let max_possible_combinations = max_subset_size(items, weight_limit);
+
+        let best: mutexes::Mutex<(i32, Vec<Item>), L> = mutexes::Mutex::new((0, Vec::new()));
+
+        (1..=max_possible_combinations).into_par_iter().for_each(|i| {
+            for combination in items.iter().combinations(i) {
+                let current_combined_value: i32 = combination.iter().map(|item| item.value).sum();
+                let current_combined_weight: i32 = combination.iter().map(|item| item.weight).sum();
+                let mut best = best.lock().unwrap();
+                if current_combined_value > best.0 && current_combined_weight <= weight_limit {
+                    *best = (current_combined_value, combination.into_iter().cloned().collect());
+                }
+            }
+        });
+
+        best.into_inner().unwrap().1
+    }
+
+    // the best combination found so far, shared by all threads of a parallel search
+    // the highest value lives in an atomic max register, so most combinations are rejected without any lock,
+    // only a combination that raised the maximum takes the mutex to store its items.
+    // both are model::sync types with the model feature, so tests/model.rs can check every interleaving of the update
+    pub struct SharedBest {
+        highest_value: AtomicMax,
+        best: model::checkable::Mutex<(i32, Vec<Item>)>
+    }
+
+    impl SharedBest {
+        pub fn new() -> Self {
+            Self { highest_value: AtomicMax::new(0), best: model::checkable::Mutex::new((0, Vec::new())) }
+        }
+
+        // returns true when the combination became the new best, its items are only collected in that case
+        pub fn offer<F: FnOnce() -> Vec<Item>>(&self, value: i32, items: F) -> bool {
+            if !self.highest_value.update(value as i64) {
+                return false;
+            }
+            // a thread with an even better combination may have been here first, so check again
+            let mut best = self.best.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            if value > best.0 {
+                *best = (value, items());
+                true
+            } else {
+                false
+            }
+        }
+
+        pub fn value(&self) -> i32 {
+            self.best.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).0
+        }
+
+        pub fn into_items(self) -> Vec<Item> {
+            self.best.into_inner().unwrap_or_else(|poisoned| poisoned.into_inner()).1
+        }
+    }
+
+    impl Default for SharedBest {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    pub fn get_knapsack_items_par_iter_atomic(items: &[Item], weight_limit: i32) -> Vec<Item> {
+        let max_possible_combinations = max_subset_size(items, weight_limit);
+
+        let best = SharedBest::new();
+
+        (1..=max_possible_combinations).into_par_iter().for_each(|i| {
+            for combination in items.iter().combinations(i) {
+                let current_combined_value: i32 = combination.iter().map(|item| item.value).sum();
+                let current_combined_weight: i32 = combination.iter().map(|item| item.weight).sum();
+                if current_combined_weight <= weight_limit {
+                    best.offer(current_combined_value, || combination.into_iter().cloned().collect());
+                }
+            }
+        });
+
+        best.into_items()
+    }
+
+    // the exhaustive solvers look at up to 2^items subsets, 2^30 is about a billion and already takes minutes
+    pub const MAX_KNAPSACK_ITEMS: usize = 30;
+
+    // checks the things the solvers silently assume
+    pub fn validate_knapsack_problem(items: &[Item], weight_limit: i32) -> Result<(), std::string::String> {
+        if weight_limit <= 0 {
+            return Err(format!("weight limit has to be positive, got {}", weight_limit));
+        }
+        if items.len() > MAX_KNAPSACK_ITEMS {
+            return Err(format!("at most {} items can be searched, got {}", MAX_KNAPSACK_ITEMS, items.len()));
+        }
+        if let Some(item) = items.iter().find(|item| item.weight <= 0) {
+            return Err(format!("item weights have to be positive, got {:?}", item));
+        }
+        // the solvers add up weights and values as i32, with these totals no subset can overflow
+        if items.iter().try_fold(0i32, |sum, item| sum.checked_add(item.weight)).is_none() {
+            return Err("the item weights add up to more than an i32 can hold".to_string());
+        }
+        if items.iter().try_fold(0i32, |sum, item| sum.checked_add(item.value.checked_abs()?)).is_none() {
+            return Err("the item values add up to more than an i32 can hold".to_string());
+        }
+        Ok(())
+    }
+
+    // the dynamic programming solver takes a step per item and per weight (or value) it can add up to,
+    // and keeps one byte per step to find the chosen items again
+    pub const MAX_DYNAMIC_KNAPSACK_STEPS: usize = 1 << 25;
+
+    // (search by value, last position): the solver counts up to the weight limit, or to the sum of the values
+    // when that is smaller, items worth nothing are never taken and don't count
+    fn dynamic_knapsack_range(items: &[Item], weight_limit: i32) -> (bool, usize) {
+        let value_sum: i64 = items.iter().map(|item| item.value.max(0) as i64).sum();
+        if value_sum < weight_limit as i64 {
+            (true, value_sum as usize)
+        } else {
+            (false, weight_limit.max(0) as usize)
+        }
+    }
+
+    pub fn dynamic_knapsack_steps(items: &[Item], weight_limit: i32) -> usize {
+        items.len().saturating_mul(dynamic_knapsack_range(items, weight_limit).1 + 1)
+    }
+
+    // get_knapsack_items_dynamic doesn't look at subsets, so any number of items is fine, the steps are what's limited
+    pub fn validate_dynamic_knapsack_problem(items: &[Item], weight_limit: i32) -> Result<(), std::string::String> {
+        if weight_limit <= 0 {
+            return Err(format!("weight limit has to be positive, got {}", weight_limit));
+        }
+        if let Some(item) = items.iter().find(|item| item.weight <= 0) {
+            return Err(format!("item weights have to be positive, got {:?}", item));
+        }
+        let steps = dynamic_knapsack_steps(items, weight_limit);
+        if steps > MAX_DYNAMIC_KNAPSACK_STEPS {
+            return Err(format!("the search would take {} steps, at most {} are allowed", steps, MAX_DYNAMIC_KNAPSACK_STEPS));
+        }
+        Ok(())
+    }
+
+    // 0/1 knapsack as a dynamic program, items × weight limit steps instead of 2^items subsets
+    // best[position] is the most value of a combination weighing exactly `position`, or, when searching by value,
+    // the least weight of a combination worth exactly `position`. sums are kept in i64 so they can't overflow
+    pub fn get_knapsack_items_dynamic(items: &[Item], weight_limit: i32) -> Vec<Item> {
+        let (by_value, range) = dynamic_knapsack_range(items, weight_limit);
+        let mut best: Vec<Option<i64>> = vec![None; range + 1];
+        best[0] = Some(0);
+        // taken[i][position]: item i improved the position, walking these back gives the chosen items
+        let mut taken: Vec<Vec<bool>> = Vec::with_capacity(items.len());
+        for item in items {
+            let mut took = vec![false; range + 1];
+            if item.value > 0 && item.weight > 0 {
+                let (step, gain) = if by_value { (item.value as usize, item.weight as i64) } else { (item.weight as usize, item.value as i64) };
+                // from the top down, so every item is counted at most once
+                for position in (step..=range).rev() {
+                    let Some(score) = best[position - step] else { continue };
+                    let score = score + gain;
+                    if by_value && score > weight_limit as i64 {
+                        continue;
+                    }
+                    if best[position].is_none_or(|current| if by_value { score < current } else { score > current }) {
+                        best[position] = Some(score);
+                        took[position] = true;
+                    }
+                }
+            }
+            taken.push(took);
+        }
+
+        // by value the highest reachable value wins, by weight the most value, the lightest of equal ones
+        let mut position = if by_value {
+            (0..=range).rev().find(|&position| best[position].is_some()).unwrap_or(0)
+        } else {
+            (0..=range).fold(0, |found, position| if best[position] > best[found] { position } else { found })
+        };
+        let mut chosen = Vec::new();
+        for i in (0..items.len()).rev() {
+            if taken[i][position] {
+                chosen.push(items[i]);
+                position -= if by_value { items[i].value as usize } else { items[i].weight as usize };
+            }
+        }
+        chosen.reverse();
+        chosen
+    }
+
+    // the largest number of items that can fit, found by filling the knapsack with the lightest items first
+    // no combination with more items than this can stay under the weight limit, one that fills it exactly can
+    pub fn max_subset_size(items: &[Item], weight_limit: i32) -> usize {
+        // in i64, the lightest items can add up to more than an i32 before the limit stops them
+        let mut sum = 0i64;
+        items
+            .iter()
+            .sorted_by(|a, b| a.weight.cmp(&b.weight))
+            .take_while(|&&item| {
+                sum += item.weight as i64;
+                sum <= weight_limit as i64
+            })
+            .count()
+    }
+
+    // same search as get_knapsack_items, but it can be watched and stopped from another thread
+    // returns None when `cancelled` was set before the search finished
+    pub fn get_knapsack_items_with_progress<F: FnMut(KnapsackProgress)>(
+        items: &[Item],
+        weight_limit: i32,
+        cancelled: &AtomicBool,
+        mut on_progress: F
+    ) -> Option<Vec<Item>> {
+        let max_possible_combinations = max_subset_size(items, weight_limit);
+
+        let mut highest_combined_value = 0;
+        let mut knapsack_items: Vec<Item> = Vec::new();
+        for i in 1..=max_possible_combinations {
+            for combination in items.iter().combinations(i) {
+                if cancelled.load(std::sync::atomic::Ordering::Relaxed) {
+                    return None;
+                }
+                let current_combined_value: i32 = combination.iter().map(|item| item.value).sum();
+                let current_combined_weight: i32 = combination.iter().map(|item| item.weight).sum();
+                if current_combined_value > highest_combined_value && current_combined_weight <= weight_limit {
+                    highest_combined_value = current_combined_value;
+                    knapsack_items = combination.into_iter().cloned().collect();
+                }
+            }
+            on_progress(KnapsackProgress {
+                subset_size: i,
+                max_subset_size: max_possible_combinations,
+                best_value: highest_combined_value
+            });
+        }
+        Some(knapsack_items)
+    }
+
     pub fn get_suboptimal_knapsack_items_val_weight_ratio(items: &mut Vec<Item>, weight_limit: i32) -> Vec<Item> {
         let mut sum = 0;
         items
@@ -225,39 +496,38 @@ pub mod knapsack {
     pub fn test_parallel_knapsack_threads(items: &Vec<Item>, weight_limit: i32) -> Vec<Item> {
         println!("Using {} execution and {} inside function", "parallel".green(), "parallel processing with spawning threads".green());
 
-        const KNAPSACK_ITEM_COUNT: u8 = 20;
-
-        let knapsack_one = items.clone();
-        let knapsack_two = items.clone();
-
-        let knapsack_first = std::thread::spawn(
-            move || {
-                let mut knapsack_clone = knapsack_one;
-                let best_items = get_knapsack_items_par_threads(&mut knapsack_clone, weight_limit);
-                best_items
-            }
-        );
-
-        let knapsack_second = std::thread::spawn(
-            move || {
-                let mut knapsack_clone = knapsack_two;
-                let best_items = get_knapsack_items_par_threads(&mut knapsack_clone, weight_limit);
-                best_items
-            }
-        );
+        // the same instance twice as one batch on two workers, both runs have to agree on the best value
+        let problems = vec![
+            KnapsackProblem::new(items.clone(), weight_limit),
+            KnapsackProblem::new(items.clone(), weight_limit)
+        ];
         let start = Instant::now();
-        let result_one = knapsack_first.join().unwrap();
-        let result_two = knapsack_second.join().unwrap();
+        let mut reports = BatchSolver::new(2).solve(
+            problems,
+            |items, weight_limit| get_knapsack_items_par_threads(items, weight_limit),
+            ResultOrder::Submission
+        );
         let elapsed = start.elapsed().as_secs();
+        for report in &reports {
+            match &report.result {
+                Ok(_) => println!("Job {} took {:?}, total value {}", report.index, report.elapsed, report.total_value().unwrap_or(0)),
+                Err(error) => println!("Job {} failed after {:?}: {}", report.index, report.elapsed, error)
+            }
+        }
+        if reports[0].total_value() != reports[1].total_value() {
+            println!("{}", "The two runs found a different best value!".red());
+        }
+
+        let best_items = reports.remove(0).result.unwrap_or_default();
         println!("\nBest items:");
-        for (i, item) in result_two.iter().enumerate() {
+        for (i, item) in best_items.iter().enumerate() {
             println!("    Item {}: {:?}", i, item);
         }
-        println!("Total weight: {}", result_two.iter().fold(0, |acc, item| acc + item.weight));
-        println!("Total value: {}", result_two.iter().fold(0, |acc, item| acc + item.value));
+        println!("Total weight: {}", best_items.iter().fold(0, |acc, item| acc + item.weight));
+        println!("Total value: {}", best_items.iter().fold(0, |acc, item| acc + item.value));
         println!("Execution time in seconds, {}: {}", "parallel".green(), elapsed);
 
-        result_two
+        best_items
     }
 
 
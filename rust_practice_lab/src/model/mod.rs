@@ -0,0 +1,220 @@
+// a deterministic scheduler for testing concurrent code, in the spirit of loom
+//     sync.rs: Mutex, Condvar and atomics that behave like std, and become scheduling points inside a model run
+//     thread.rs: spawn, join and yield_now for model threads
+//     scheduler.rs: runs one thread at a time and decides who goes next at every scheduling point
+//
+// code that should be checkable uses model::checkable instead of std::sync (channels and the knapsack shared best do),
+// those are the shims with the `model` feature and std::sync without it, so a normal build doesn't pay for them.
+// a check runs the closure over and over, every time with a different interleaving of its threads:
+//
+//     model::check(|| {
+//         let counter = Arc::new(model::sync::AtomicUsize::new(0));
+//         let other = Arc::clone(&counter);
+//         let handle = model::thread::spawn(move || { other.fetch_add(1, Ordering::SeqCst); });
+//         counter.fetch_add(1, Ordering::SeqCst);
+//         handle.join().unwrap();
+//         assert_eq!(counter.load(Ordering::SeqCst), 2);
+//     });
+//
+// exhaustive mode tries every interleaving with at most `preemption_bound` preemptions (switching away from
+// a thread that could have continued), most concurrency bugs need only one or two. random mode picks
+// schedules from a seed instead, for tests with too many interleavings. either way a failure reports the
+// schedule it failed on, and Builder::replay runs exactly that schedule again.
+//
+// the closure has to be deterministic apart from the scheduling: no clocks, no randomness, no real threads
+pub mod sync;
+pub mod thread;
+mod scheduler;
+
+pub(crate) mod checkable {
+    #[cfg(any(test, feature = "model"))]
+    pub(crate) use super::sync::{AtomicI64, Condvar, Mutex, MutexGuard};
+    #[cfg(not(any(test, feature = "model")))]
+    pub(crate) use std::sync::{atomic::AtomicI64, Condvar, Mutex, MutexGuard};
+}
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use scheduler::{lock, Chooser, Context, Decision, Execution, Shared, SplitMix};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Strategy {
+    Exhaustive,
+    Random { seed: u64 },
+    Replay(Schedule)
+}
+
+// the choice made at every point where more than one thread could run, written as "0.2.1"
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Schedule(Vec<usize>);
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let choices: Vec<String> = self.0.iter().map(|choice| choice.to_string()).collect();
+        write!(f, "{}", choices.join("."))
+    }
+}
+
+impl FromStr for Schedule {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        if text.is_empty() {
+            return Ok(Schedule::default());
+        }
+        text.split('.').map(str::parse).collect::<Result<Vec<usize>, _>>().map(Schedule)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    pub executions: usize,
+    // every schedule within the preemption bound was tried, only in exhaustive mode
+    pub complete: bool
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Failure {
+    pub message: String,
+    // which execution failed, counting from 0
+    pub execution: usize,
+    pub schedule: Schedule,
+    // in random mode, the seed that gives this schedule as its first execution
+    pub seed: Option<u64>
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (execution {}), replay it with Builder::new().replay(\"{}\")", self.message, self.execution, self.schedule)?;
+        if let Some(seed) = self.seed {
+            write!(f, " or Builder::new().random(0x{:x})", seed)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Failure {}
+
+#[derive(Debug, Clone)]
+pub struct Builder {
+    preemption_bound: usize,
+    max_executions: usize,
+    max_steps: usize,
+    strategy: Strategy
+}
+
+impl Builder {
+    pub fn new() -> Self {
+        Self { preemption_bound: 2, max_executions: 100_000, max_steps: 10_000, strategy: Strategy::Exhaustive }
+    }
+
+    pub fn preemption_bound(mut self, preemption_bound: usize) -> Self {
+        self.preemption_bound = preemption_bound;
+        self
+    }
+
+    // exhaustive mode stops here even when it isn't done, random mode runs exactly this many
+    pub fn max_executions(mut self, max_executions: usize) -> Self {
+        self.max_executions = max_executions;
+        self
+    }
+
+    // scheduling points per execution, an execution that takes more fails as a livelock
+    pub fn max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    // random schedules, execution i uses seed + i
+    pub fn random(mut self, seed: u64) -> Self {
+        self.strategy = Strategy::Random { seed };
+        self
+    }
+
+    // runs one execution with the schedule of a failure, panics when the text isn't a schedule
+    pub fn replay(mut self, schedule: &str) -> Self {
+        self.strategy = Strategy::Replay(schedule.parse().expect("a schedule looks like 0.2.1"));
+        self
+    }
+
+    // runs the closure until an execution fails or every schedule has been tried
+    pub fn explore<F: Fn() + Send + Sync + 'static>(&self, f: F) -> Result<Report, Failure> {
+        let f = Arc::new(f);
+        let mut prefix = Vec::new();
+        for execution in 0..self.max_executions {
+            let (chooser, seed) = match &self.strategy {
+                Strategy::Exhaustive => (Chooser::Exhaustive(std::mem::take(&mut prefix)), None),
+                Strategy::Random { seed } => {
+                    let seed = seed.wrapping_add(execution as u64);
+                    (Chooser::Random(SplitMix::new(seed)), Some(seed))
+                }
+                Strategy::Replay(schedule) => (Chooser::Replay(schedule.0.clone()), None)
+            };
+            let (decisions, failure) = run_execution(Execution::new(chooser, self.preemption_bound, self.max_steps), &f);
+            if let Some(message) = failure {
+                let schedule = Schedule(decisions.iter().map(|decision| decision.chosen).collect());
+                return Err(Failure { message, execution, schedule, seed });
+            }
+            match self.strategy {
+                Strategy::Exhaustive => match next_prefix(decisions) {
+                    Some(next) => prefix = next,
+                    None => return Ok(Report { executions: execution + 1, complete: true })
+                },
+                Strategy::Replay(_) => return Ok(Report { executions: 1, complete: true }),
+                Strategy::Random { .. } => {}
+            }
+        }
+        Ok(Report { executions: self.max_executions, complete: false })
+    }
+
+    // explore, but a failure panics with the schedule to replay
+    pub fn check<F: Fn() + Send + Sync + 'static>(&self, f: F) -> Report {
+        self.explore(f).unwrap_or_else(|failure| panic!("model check failed: {}", failure))
+    }
+}
+
+impl Default for Builder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// exhaustive check with the default settings
+pub fn check<F: Fn() + Send + Sync + 'static>(f: F) -> Report {
+    Builder::new().check(f)
+}
+
+// depth first: the last decision that still has an untried option gets the next one, everything after it is new
+fn next_prefix(mut decisions: Vec<Decision>) -> Option<Vec<Decision>> {
+    while let Some(last) = decisions.pop() {
+        if last.chosen + 1 < last.options {
+            decisions.push(Decision { chosen: last.chosen + 1, options: last.options });
+            return Some(decisions);
+        }
+    }
+    None
+}
+
+fn run_execution<F: Fn() + Send + Sync + 'static>(execution: Execution, f: &Arc<F>) -> (Vec<Decision>, Option<String>) {
+    let shared = Shared::new(execution);
+    let main = Context::new(&shared, 0);
+    let f = Arc::clone(f);
+    let main_thread = std::thread::Builder::new()
+        .name("model thread 0".to_string())
+        .spawn(move || main.run(|| f()))
+        .expect("could not start a model thread");
+    let _ = main_thread.join();
+    // a thread that is still running has its handle in the list, and pushes the handles of its children before it ends
+    loop {
+        let os_thread = lock(&shared.os_threads).pop();
+        match os_thread {
+            Some(os_thread) => {
+                let _ = os_thread.join();
+            }
+            None => break
+        }
+    }
+    let mut execution = lock(&shared.execution);
+    (std::mem::take(&mut execution.decisions), execution.failure.take())
+}
@@ -0,0 +1,461 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread;
+
+// the scheduler behind the shim types
+//
+// every model thread is a real OS thread, but only the active one runs. at every visible operation
+// (lock, atomic access, notify, spawn, join) the active thread asks the scheduler who goes next and
+// parks until it is its turn again. the answers come from a Chooser, so the same choices give the same run.
+
+pub(crate) type ThreadId = usize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Resource {
+    Mutex(usize),
+    Condvar(usize),
+    Join(ThreadId)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Runnable,
+    // timed waits (Condvar::wait_timeout) time out when nothing else can run anymore
+    Blocked { on: Resource, timed: bool },
+    Finished
+}
+
+struct ThreadState {
+    status: Status,
+    timed_out: bool
+}
+
+// one point where more than one thread could run next
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Decision {
+    pub(crate) chosen: usize,
+    pub(crate) options: usize
+}
+
+pub(crate) enum Chooser {
+    // follows the prefix, and takes the first option (no preemption) after it
+    Exhaustive(Vec<Decision>),
+    Random(SplitMix),
+    Replay(Vec<usize>)
+}
+
+// splitmix64, a tiny generator that gives the same numbers for the same seed on every platform
+pub(crate) struct SplitMix(u64);
+
+impl SplitMix {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+}
+
+// a random schedule keeps the current thread running most of the time, otherwise every preemption
+// would be spent in the first few steps
+const RANDOM_PREEMPTION_ODDS: usize = 8;
+
+pub(crate) struct Execution {
+    threads: Vec<ThreadState>,
+    active: ThreadId,
+    mutex_owners: HashMap<usize, ThreadId>,
+    condvar_waiters: HashMap<usize, VecDeque<ThreadId>>,
+    chooser: Chooser,
+    pub(crate) decisions: Vec<Decision>,
+    preemptions: usize,
+    preemption_bound: usize,
+    steps: usize,
+    max_steps: usize,
+    pub(crate) failure: Option<String>,
+    aborted: bool
+}
+
+impl Execution {
+    pub(crate) fn new(chooser: Chooser, preemption_bound: usize, max_steps: usize) -> Self {
+        Self {
+            threads: vec![ThreadState { status: Status::Runnable, timed_out: false }],
+            active: 0,
+            mutex_owners: HashMap::new(),
+            condvar_waiters: HashMap::new(),
+            chooser,
+            decisions: Vec::new(),
+            preemptions: 0,
+            preemption_bound,
+            steps: 0,
+            max_steps,
+            failure: None,
+            aborted: false
+        }
+    }
+
+    fn fail(&mut self, message: String) {
+        if self.failure.is_none() {
+            self.failure = Some(message);
+        }
+        self.aborted = true;
+    }
+
+    // picks one of `options` threads, the first option is the one that doesn't cost a preemption
+    fn choose(&mut self, options: usize, stay_is_free: bool) -> usize {
+        if options == 1 {
+            return 0;
+        }
+        let position = self.decisions.len();
+        let mut chosen = match &mut self.chooser {
+            Chooser::Exhaustive(prefix) => match prefix.get(position) {
+                Some(decision) if decision.options != options => {
+                    let message = format!(
+                        "the test isn't deterministic: step {} had {} runnable threads before and {} now",
+                        self.steps, decision.options, options
+                    );
+                    self.fail(message);
+                    0
+                }
+                Some(decision) => decision.chosen,
+                None => 0
+            },
+            Chooser::Random(rng) => {
+                if stay_is_free && rng.below(RANDOM_PREEMPTION_ODDS) != 0 {
+                    0
+                } else {
+                    rng.below(options)
+                }
+            }
+            Chooser::Replay(choices) => choices.get(position).copied().unwrap_or(0)
+        };
+        if chosen >= options {
+            let message = format!("the schedule doesn't fit this test: choice {} at step {} of {} threads", chosen, self.steps, options);
+            self.fail(message);
+            chosen = 0;
+        }
+        self.decisions.push(Decision { chosen, options });
+        chosen
+    }
+
+    fn runnable_except(&self, except: Option<ThreadId>) -> Vec<ThreadId> {
+        (0..self.threads.len())
+            .filter(|&id| Some(id) != except && self.threads[id].status == Status::Runnable)
+            .collect()
+    }
+
+    // the active thread is about to do something other threads can see
+    fn yield_point(&mut self, me: ThreadId) {
+        self.steps += 1;
+        if self.steps > self.max_steps {
+            let message = format!(
+                "more than {} steps, probably a loop that waits for another thread without blocking (use model::thread::yield_now)",
+                self.max_steps
+            );
+            self.fail(message);
+            return;
+        }
+        let mut options = vec![me];
+        if self.preemptions < self.preemption_bound {
+            options.extend(self.runnable_except(Some(me)));
+        }
+        let chosen = self.choose(options.len(), true);
+        if chosen > 0 {
+            self.preemptions += 1;
+        }
+        self.active = options[chosen];
+    }
+
+    // the active thread wants the others to go first, that isn't counted as a preemption
+    fn yield_now(&mut self, me: ThreadId) {
+        let others = self.runnable_except(Some(me));
+        if !others.is_empty() {
+            self.active = others[self.choose(others.len(), false)];
+        }
+    }
+
+    // the active thread blocked or finished, somebody else has to continue
+    fn switch_away(&mut self) {
+        let mut runnable = self.runnable_except(None);
+        if runnable.is_empty() {
+            // nothing can happen anymore, that is when a timed wait runs out
+            let timed = (0..self.threads.len()).find(|&id| matches!(self.threads[id].status, Status::Blocked { timed: true, .. }));
+            match timed {
+                Some(id) => {
+                    if let Status::Blocked { on: Resource::Condvar(condvar), .. } = self.threads[id].status {
+                        if let Some(waiters) = self.condvar_waiters.get_mut(&condvar) {
+                            waiters.retain(|&waiter| waiter != id);
+                        }
+                    }
+                    self.threads[id] = ThreadState { status: Status::Runnable, timed_out: true };
+                    runnable.push(id);
+                }
+                None if self.threads.iter().all(|thread| thread.status == Status::Finished) => return,
+                None => {
+                    let blocked: Vec<String> = self
+                        .threads
+                        .iter()
+                        .enumerate()
+                        .filter_map(|(id, thread)| match thread.status {
+                            Status::Blocked { on, .. } => Some(format!("thread {} waits for {:?}", id, on)),
+                            _ => None
+                        })
+                        .collect();
+                    self.fail(format!("deadlock: {}", blocked.join(", ")));
+                    return;
+                }
+            }
+        }
+        self.active = runnable[self.choose(runnable.len(), false)];
+    }
+
+    fn block(&mut self, me: ThreadId, on: Resource, timed: bool) {
+        self.threads[me] = ThreadState { status: Status::Blocked { on, timed }, timed_out: false };
+        self.switch_away();
+    }
+
+    fn unblock_where(&mut self, on: Resource) {
+        for thread in &mut self.threads {
+            if matches!(thread.status, Status::Blocked { on: blocked_on, .. } if blocked_on == on) {
+                thread.status = Status::Runnable;
+            }
+        }
+    }
+}
+
+//=================================================================
+
+// returned when an execution has been given up, unwinds the model threads that are still in it
+pub(crate) struct Aborted;
+
+pub(crate) struct Shared {
+    pub(crate) execution: Mutex<Execution>,
+    turn: Condvar,
+    // every OS thread of this execution, the checker joins them before starting the next one
+    pub(crate) os_threads: Mutex<Vec<thread::JoinHandle<()>>>
+}
+
+impl Shared {
+    pub(crate) fn new(execution: Execution) -> Arc<Self> {
+        Arc::new(Self { execution: Mutex::new(execution), turn: Condvar::new(), os_threads: Mutex::new(Vec::new()) })
+    }
+}
+
+pub(crate) fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    // a model thread that panics is caught before it could leave the execution half updated
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+thread_local! {
+    static CONTEXT: RefCell<Option<Context>> = const { RefCell::new(None) };
+}
+
+// the model thread running on this OS thread, None outside of a model run
+pub(crate) fn current() -> Option<Context> {
+    CONTEXT.with(|context| context.borrow().clone())
+}
+
+#[derive(Clone)]
+pub(crate) struct Context {
+    shared: Arc<Shared>,
+    id: ThreadId
+}
+
+impl Context {
+    pub(crate) fn new(shared: &Arc<Shared>, id: ThreadId) -> Self {
+        Self { shared: Arc::clone(shared), id }
+    }
+
+    fn execution(&self) -> MutexGuard<'_, Execution> {
+        lock(&self.shared.execution)
+    }
+
+    // hands the turn to whoever is active now and waits until it comes back to us
+    // returns false when the execution was aborted while unwinding, the caller carries on without the scheduler
+    fn wait_turn(&self, mut execution: MutexGuard<'_, Execution>) -> bool {
+        self.shared.turn.notify_all();
+        while execution.active != self.id && !execution.aborted {
+            execution = self.shared.turn.wait(execution).unwrap_or_else(|poisoned| poisoned.into_inner());
+        }
+        if execution.aborted {
+            drop(execution);
+            self.shared.turn.notify_all();
+            // a second panic while unwinding would abort the process, so drop code just runs unscheduled
+            if !thread::panicking() {
+                panic::resume_unwind(Box::new(Aborted));
+            }
+            return false;
+        }
+        true
+    }
+
+    // a visible operation, returns false when the scheduler is gone (see wait_turn)
+    pub(crate) fn step(&self) -> bool {
+        let mut execution = self.execution();
+        if !execution.aborted {
+            execution.yield_point(self.id);
+        }
+        self.wait_turn(execution)
+    }
+
+    pub(crate) fn yield_now(&self) {
+        let mut execution = self.execution();
+        if !execution.aborted {
+            execution.yield_now(self.id);
+        }
+        self.wait_turn(execution);
+    }
+
+    pub(crate) fn fail(&self, message: String) {
+        self.execution().fail(message);
+        self.shared.turn.notify_all();
+    }
+
+    // the model side of Mutex::lock, returns false when the caller should lock without the scheduler
+    pub(crate) fn acquire(&self, mutex: usize) -> bool {
+        if !self.step() {
+            return false;
+        }
+        loop {
+            let mut execution = self.execution();
+            if execution.aborted {
+                return self.wait_turn(execution);
+            }
+            if let std::collections::hash_map::Entry::Vacant(entry) = execution.mutex_owners.entry(mutex) {
+                entry.insert(self.id);
+                return true;
+            }
+            execution.block(self.id, Resource::Mutex(mutex), false);
+            if !self.wait_turn(execution) {
+                return false;
+            }
+        }
+    }
+
+    pub(crate) fn try_acquire(&self, mutex: usize) -> Option<bool> {
+        if !self.step() {
+            return None;
+        }
+        let mut execution = self.execution();
+        if let std::collections::hash_map::Entry::Vacant(entry) = execution.mutex_owners.entry(mutex) {
+            entry.insert(self.id);
+            return Some(true);
+        }
+        Some(false)
+    }
+
+    pub(crate) fn release(&self, mutex: usize) {
+        let mut execution = self.execution();
+        if execution.aborted {
+            return;
+        }
+        execution.mutex_owners.remove(&mutex);
+        execution.unblock_where(Resource::Mutex(mutex));
+    }
+
+    // blocks until a notify (or for a timed wait: until nothing else can run), returns whether it timed out
+    // the mutex has been released by the caller, nothing ran in between because nothing runs without the turn
+    pub(crate) fn wait_condvar(&self, condvar: usize, timed: bool) -> Option<bool> {
+        let mut execution = self.execution();
+        if execution.aborted {
+            return if self.wait_turn(execution) { Some(false) } else { None };
+        }
+        execution.condvar_waiters.entry(condvar).or_default().push_back(self.id);
+        execution.block(self.id, Resource::Condvar(condvar), timed);
+        if !self.wait_turn(execution) {
+            return None;
+        }
+        let mut execution = self.execution();
+        let timed_out = execution.threads[self.id].timed_out;
+        execution.threads[self.id].timed_out = false;
+        Some(timed_out)
+    }
+
+    // wakes the longest waiting thread, or all of them
+    pub(crate) fn notify(&self, condvar: usize, all: bool) {
+        if !self.step() {
+            return;
+        }
+        let mut execution = self.execution();
+        let woken: Vec<ThreadId> = match execution.condvar_waiters.get_mut(&condvar) {
+            Some(waiters) if all => waiters.drain(..).collect(),
+            Some(waiters) => waiters.pop_front().into_iter().collect(),
+            None => Vec::new()
+        };
+        for id in woken {
+            execution.threads[id].status = Status::Runnable;
+        }
+    }
+
+    // registers a new model thread, it starts running when the scheduler picks it
+    pub(crate) fn register_thread(&self) -> Context {
+        let mut execution = self.execution();
+        execution.threads.push(ThreadState { status: Status::Runnable, timed_out: false });
+        Context { shared: Arc::clone(&self.shared), id: execution.threads.len() - 1 }
+    }
+
+    pub(crate) fn push_os_thread(&self, handle: thread::JoinHandle<()>) {
+        lock(&self.shared.os_threads).push(handle);
+    }
+
+    pub(crate) fn join(&self, target: ThreadId) {
+        if !self.step() {
+            return;
+        }
+        let mut execution = self.execution();
+        if execution.aborted || execution.threads[target].status == Status::Finished {
+            return;
+        }
+        execution.block(self.id, Resource::Join(target), false);
+        self.wait_turn(execution);
+    }
+
+    pub(crate) fn id(&self) -> ThreadId {
+        self.id
+    }
+
+    // runs the body of a model thread on the current OS thread
+    pub(crate) fn run(self, body: impl FnOnce()) {
+        CONTEXT.with(|context| *context.borrow_mut() = Some(self.clone()));
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            // wait for the scheduler to pick us for the first time
+            let execution = self.execution();
+            self.wait_turn(execution);
+            body();
+        }));
+        if let Err(payload) = result {
+            if !payload.is::<Aborted>() {
+                self.fail(format!("thread {} panicked: {}", self.id, panic_message(&*payload)));
+            }
+        }
+        let mut execution = self.execution();
+        execution.threads[self.id].status = Status::Finished;
+        if !execution.aborted {
+            execution.unblock_where(Resource::Join(self.id));
+            execution.switch_away();
+        }
+        drop(execution);
+        self.shared.turn.notify_all();
+        CONTEXT.with(|context| *context.borrow_mut() = None);
+    }
+}
+
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "(no message)".to_string()
+    }
+}
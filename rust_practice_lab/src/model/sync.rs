@@ -0,0 +1,328 @@
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::{LockResult, PoisonError, TryLockError, TryLockResult};
+use std::time::Duration;
+use super::scheduler::current;
+
+pub use std::sync::atomic::Ordering;
+
+// drop-in replacements for std::sync::{Mutex, MutexGuard, Condvar} and the atomics
+//
+// outside of a model run they just call the std type they wrap. inside one, every operation is a point
+// where the scheduler may switch threads, and blocking is done by the scheduler instead of the OS.
+// the std type still does the actual work, so the data is protected the same way in both cases.
+//
+// only one model thread runs at a time, so the atomics behave as if every access was SeqCst.
+// the harness finds bugs in the order of operations, not bugs that need weaker memory orderings.
+
+// objects are told apart by address, they can't move while somebody locks or waits on them
+fn address<T: ?Sized>(value: &T) -> usize {
+    value as *const T as *const () as usize
+}
+
+pub struct Mutex<T: ?Sized> {
+    inner: std::sync::Mutex<T>
+}
+
+pub struct MutexGuard<'a, T: ?Sized> {
+    mutex: &'a Mutex<T>,
+    // None only after Condvar::wait took it out
+    inner: Option<std::sync::MutexGuard<'a, T>>,
+    // locked through the scheduler, so unlocking has to tell it as well
+    modelled: bool
+}
+
+impl<T> Mutex<T> {
+    pub const fn new(value: T) -> Self {
+        Self { inner: std::sync::Mutex::new(value) }
+    }
+
+    pub fn into_inner(self) -> LockResult<T> {
+        self.inner.into_inner()
+    }
+}
+
+impl<T: ?Sized> Mutex<T> {
+    pub fn lock(&self) -> LockResult<MutexGuard<'_, T>> {
+        let modelled = current().is_some_and(|context| context.acquire(address(self)));
+        self.wrap(self.inner.lock(), modelled)
+    }
+
+    pub fn try_lock(&self) -> TryLockResult<MutexGuard<'_, T>> {
+        match current().and_then(|context| context.try_acquire(address(self))) {
+            Some(false) => Err(TryLockError::WouldBlock),
+            // the scheduler knows the lock is free, so the std lock is too
+            Some(true) => self.wrap(self.inner.lock(), true).map_err(TryLockError::Poisoned),
+            None => match self.inner.try_lock() {
+                Ok(guard) => Ok(self.guard(guard, false)),
+                Err(TryLockError::Poisoned(poisoned)) => Err(TryLockError::Poisoned(PoisonError::new(self.guard(poisoned.into_inner(), false)))),
+                Err(TryLockError::WouldBlock) => Err(TryLockError::WouldBlock)
+            }
+        }
+    }
+
+    pub fn get_mut(&mut self) -> LockResult<&mut T> {
+        self.inner.get_mut()
+    }
+
+    pub fn is_poisoned(&self) -> bool {
+        self.inner.is_poisoned()
+    }
+
+    fn guard<'a>(&'a self, inner: std::sync::MutexGuard<'a, T>, modelled: bool) -> MutexGuard<'a, T> {
+        MutexGuard { mutex: self, inner: Some(inner), modelled }
+    }
+
+    fn wrap<'a>(&'a self, result: LockResult<std::sync::MutexGuard<'a, T>>, modelled: bool) -> LockResult<MutexGuard<'a, T>> {
+        match result {
+            Ok(guard) => Ok(self.guard(guard, modelled)),
+            Err(poisoned) => Err(PoisonError::new(self.guard(poisoned.into_inner(), modelled)))
+        }
+    }
+}
+
+impl<T: Default> Default for Mutex<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for Mutex<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+impl<T: ?Sized> Deref for MutexGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.inner.as_ref().expect("the guard is only emptied by Condvar::wait")
+    }
+}
+
+impl<T: ?Sized> DerefMut for MutexGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.inner.as_mut().expect("the guard is only emptied by Condvar::wait")
+    }
+}
+
+impl<T: ?Sized> Drop for MutexGuard<'_, T> {
+    fn drop(&mut self) {
+        // the std lock goes first, the next owner takes it as soon as the scheduler lets it run
+        if self.inner.take().is_some() && self.modelled {
+            if let Some(context) = current() {
+                context.release(address(self.mutex));
+            }
+        }
+    }
+}
+
+//=================================================================
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct WaitTimeoutResult(bool);
+
+impl WaitTimeoutResult {
+    pub fn timed_out(&self) -> bool {
+        self.0
+    }
+}
+
+// in a model run notify_one wakes the thread that waits the longest, there are no spurious wakeups,
+// and wait_timeout only times out when no other thread can run anymore (time doesn't pass in a model)
+#[derive(Debug, Default)]
+pub struct Condvar {
+    inner: std::sync::Condvar
+}
+
+impl Condvar {
+    pub const fn new() -> Self {
+        Self { inner: std::sync::Condvar::new() }
+    }
+
+    pub fn wait<'a, T>(&self, guard: MutexGuard<'a, T>) -> LockResult<MutexGuard<'a, T>> {
+        self.wait_inner(guard, None).map(|(guard, _)| guard).map_err(|poisoned| PoisonError::new(poisoned.into_inner().0))
+    }
+
+    pub fn wait_timeout<'a, T>(&self, guard: MutexGuard<'a, T>, timeout: Duration) -> LockResult<(MutexGuard<'a, T>, WaitTimeoutResult)> {
+        self.wait_inner(guard, Some(timeout))
+    }
+
+    fn wait_inner<'a, T>(&self, mut guard: MutexGuard<'a, T>, timeout: Option<Duration>) -> LockResult<(MutexGuard<'a, T>, WaitTimeoutResult)> {
+        let mutex = guard.mutex;
+        let context = match current() {
+            Some(context) if guard.modelled => context,
+            // locked after the execution was given up, pretend a spurious wakeup
+            Some(_) => return Ok((guard, WaitTimeoutResult(false))),
+            None => {
+                let inner = guard.inner.take().expect("the guard is only emptied by Condvar::wait");
+                drop(guard);
+                return match timeout {
+                    None => match self.inner.wait(inner) {
+                        Ok(inner) => Ok((mutex.guard(inner, false), WaitTimeoutResult(false))),
+                        Err(poisoned) => Err(PoisonError::new((mutex.guard(poisoned.into_inner(), false), WaitTimeoutResult(false))))
+                    },
+                    Some(timeout) => match self.inner.wait_timeout(inner, timeout) {
+                        Ok((inner, result)) => Ok((mutex.guard(inner, false), WaitTimeoutResult(result.timed_out()))),
+                        Err(poisoned) => {
+                            let (inner, result) = poisoned.into_inner();
+                            Err(PoisonError::new((mutex.guard(inner, false), WaitTimeoutResult(result.timed_out()))))
+                        }
+                    }
+                };
+            }
+        };
+        // unlock, wait for a notify, lock again: the same steps std takes, but through the scheduler
+        drop(guard);
+        // None: the execution was given up while unwinding, that looks like a spurious wakeup
+        let timed_out = WaitTimeoutResult(context.wait_condvar(address(self), timeout.is_some()).unwrap_or(false));
+        match mutex.lock() {
+            Ok(guard) => Ok((guard, timed_out)),
+            Err(poisoned) => Err(PoisonError::new((poisoned.into_inner(), timed_out)))
+        }
+    }
+
+    pub fn notify_one(&self) {
+        match current() {
+            Some(context) => context.notify(address(self), false),
+            None => self.inner.notify_one()
+        }
+    }
+
+    pub fn notify_all(&self) {
+        match current() {
+            Some(context) => context.notify(address(self), true),
+            None => self.inner.notify_all()
+        }
+    }
+}
+
+//=================================================================
+
+fn step() {
+    if let Some(context) = current() {
+        context.step();
+    }
+}
+
+macro_rules! atomic_integer {
+    ($name:ident, $std:ty, $integer:ty) => {
+        #[derive(Default)]
+        pub struct $name {
+            inner: $std
+        }
+
+        impl $name {
+            pub const fn new(value: $integer) -> Self {
+                Self { inner: <$std>::new(value) }
+            }
+
+            pub fn load(&self, order: Ordering) -> $integer {
+                step();
+                self.inner.load(order)
+            }
+
+            pub fn store(&self, value: $integer, order: Ordering) {
+                step();
+                self.inner.store(value, order)
+            }
+
+            pub fn swap(&self, value: $integer, order: Ordering) -> $integer {
+                step();
+                self.inner.swap(value, order)
+            }
+
+            pub fn compare_exchange(&self, current: $integer, new: $integer, success: Ordering, failure: Ordering) -> Result<$integer, $integer> {
+                step();
+                self.inner.compare_exchange(current, new, success, failure)
+            }
+
+            // never fails spuriously in a model run, loops around it are checked all the same
+            pub fn compare_exchange_weak(&self, current: $integer, new: $integer, success: Ordering, failure: Ordering) -> Result<$integer, $integer> {
+                step();
+                self.inner.compare_exchange_weak(current, new, success, failure)
+            }
+
+            pub fn fetch_add(&self, value: $integer, order: Ordering) -> $integer {
+                step();
+                self.inner.fetch_add(value, order)
+            }
+
+            pub fn fetch_sub(&self, value: $integer, order: Ordering) -> $integer {
+                step();
+                self.inner.fetch_sub(value, order)
+            }
+
+            pub fn fetch_max(&self, value: $integer, order: Ordering) -> $integer {
+                step();
+                self.inner.fetch_max(value, order)
+            }
+
+            pub fn fetch_min(&self, value: $integer, order: Ordering) -> $integer {
+                step();
+                self.inner.fetch_min(value, order)
+            }
+
+            pub fn get_mut(&mut self) -> &mut $integer {
+                self.inner.get_mut()
+            }
+
+            pub fn into_inner(self) -> $integer {
+                self.inner.into_inner()
+            }
+        }
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.inner.fmt(f)
+            }
+        }
+    };
+}
+
+atomic_integer!(AtomicI32, std::sync::atomic::AtomicI32, i32);
+atomic_integer!(AtomicI64, std::sync::atomic::AtomicI64, i64);
+atomic_integer!(AtomicU64, std::sync::atomic::AtomicU64, u64);
+atomic_integer!(AtomicUsize, std::sync::atomic::AtomicUsize, usize);
+
+#[derive(Default)]
+pub struct AtomicBool {
+    inner: std::sync::atomic::AtomicBool
+}
+
+impl AtomicBool {
+    pub const fn new(value: bool) -> Self {
+        Self { inner: std::sync::atomic::AtomicBool::new(value) }
+    }
+
+    pub fn load(&self, order: Ordering) -> bool {
+        step();
+        self.inner.load(order)
+    }
+
+    pub fn store(&self, value: bool, order: Ordering) {
+        step();
+        self.inner.store(value, order)
+    }
+
+    pub fn swap(&self, value: bool, order: Ordering) -> bool {
+        step();
+        self.inner.swap(value, order)
+    }
+
+    pub fn compare_exchange(&self, current: bool, new: bool, success: Ordering, failure: Ordering) -> Result<bool, bool> {
+        step();
+        self.inner.compare_exchange(current, new, success, failure)
+    }
+
+    pub fn into_inner(self) -> bool {
+        self.inner.into_inner()
+    }
+}
+
+impl fmt::Debug for AtomicBool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
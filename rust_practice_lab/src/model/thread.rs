@@ -0,0 +1,63 @@
+use std::sync::{Arc, Mutex};
+use std::thread;
+use super::scheduler::{current, lock};
+
+// std::thread::spawn, join and yield_now, going through the scheduler inside a model run
+
+pub struct JoinHandle<T> {
+    inner: Inner<T>
+}
+
+enum Inner<T> {
+    Std(thread::JoinHandle<T>),
+    // the checker joins the OS thread itself, the result is handed over through the slot
+    Model { id: usize, result: Arc<Mutex<Option<T>>> }
+}
+
+pub fn spawn<F, T>(f: F) -> JoinHandle<T>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static
+{
+    let Some(context) = current() else {
+        return JoinHandle { inner: Inner::Std(thread::spawn(f)) };
+    };
+    let child = context.register_thread();
+    let id = child.id();
+    let result = Arc::new(Mutex::new(None));
+    let slot = Arc::clone(&result);
+    let os_thread = thread::Builder::new()
+        .name(format!("model thread {}", id))
+        .spawn(move || child.run(|| *lock(&slot) = Some(f())))
+        .expect("could not start a model thread");
+    context.push_os_thread(os_thread);
+    // the child may run first
+    context.step();
+    JoinHandle { inner: Inner::Model { id, result } }
+}
+
+impl<T> JoinHandle<T> {
+    // in a model run a panicking thread fails the whole execution, so this only returns Err outside of one
+    pub fn join(self) -> thread::Result<T> {
+        match self.inner {
+            Inner::Std(handle) => handle.join(),
+            Inner::Model { id, result } => {
+                // the handle may have been sent to another thread, that one is waiting now
+                if let Some(context) = current() {
+                    context.join(id);
+                }
+                let value = lock(&result).take();
+                value.ok_or_else(|| Box::new("the model thread did not finish") as Box<dyn std::any::Any + Send>)
+            }
+        }
+    }
+}
+
+// lets the other threads go first, loops that wait for another thread should call this in every round,
+// otherwise the scheduler only leaves such a loop when it runs out of preemptions or steps
+pub fn yield_now() {
+    match current() {
+        Some(context) => context.yield_now(),
+        None => thread::yield_now()
+    }
+}
@@ -0,0 +1,550 @@
+use std::cell::UnsafeCell;
+use std::fmt;
+use std::hint::spin_loop;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+use std::ptr;
+use std::sync::atomic::{AtomicBool, AtomicPtr, AtomicUsize, Ordering};
+use std::sync::{LockResult, PoisonError, TryLockError, TryLockResult};
+use std::thread;
+use crate::pinning::WaitQueue;
+
+// a collection of hand-written locks, all of them usable through the RawLock trait
+// Mutex<T, L> turns any of them into a safe, poison-aware mutex around some data
+//
+//     let best: Mutex<i32, TicketLock> = Mutex::new(0);
+//     *best.lock().unwrap() = 42;
+
+// after this many spins a waiting thread starts giving its time slice away
+const SPINS_BEFORE_YIELD: u32 = 64;
+
+fn backoff(spins: &mut u32) {
+    if *spins < SPINS_BEFORE_YIELD {
+        *spins += 1;
+        spin_loop();
+    } else {
+        thread::yield_now();
+    }
+}
+
+/// A lock without data, see `Mutex` for the safe version.
+///
+/// # Safety
+///
+/// Between `lock()` (or a successful `try_lock()`) and the matching `unlock()` no other thread
+/// may get the lock, and acquiring has to synchronise-with the previous unlock.
+pub unsafe trait RawLock: Default + Send + Sync {
+    // whatever the lock needs to remember between lock and unlock, the MCS lock keeps its queue node here
+    type Token;
+
+    fn lock(&self) -> Self::Token;
+    fn try_lock(&self) -> Option<Self::Token>;
+
+    /// # Safety
+    ///
+    /// The token has to come from `lock()`/`try_lock()` on this same lock.
+    unsafe fn unlock(&self, token: Self::Token);
+
+    fn name() -> &'static str;
+}
+
+//=================================================================
+// test-and-test-and-set spinlock, the simplest lock there is
+// waiting threads only read the flag while it is taken, so they don't fight over the cache line
+
+#[derive(Default)]
+pub struct SpinLock {
+    locked: AtomicBool
+}
+
+unsafe impl RawLock for SpinLock {
+    type Token = ();
+
+    fn lock(&self) {
+        let mut spins = 0;
+        loop {
+            if self.try_lock().is_some() {
+                return;
+            }
+            while self.locked.load(Ordering::Relaxed) {
+                backoff(&mut spins);
+            }
+        }
+    }
+
+    fn try_lock(&self) -> Option<()> {
+        // Acquire: everything the previous owner wrote before unlocking is visible to us
+        self.locked.compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed).ok().map(|_| ())
+    }
+
+    unsafe fn unlock(&self, _token: ()) {
+        self.locked.store(false, Ordering::Release);
+    }
+
+    fn name() -> &'static str {
+        "SpinLock"
+    }
+}
+
+//=================================================================
+// ticket lock, like the queue at the butcher: take a number and wait until it is called
+// unlike the spinlock it is fair, threads get the lock in the order they asked for it
+
+#[derive(Default)]
+pub struct TicketLock {
+    next_ticket: AtomicUsize,
+    now_serving: AtomicUsize
+}
+
+unsafe impl RawLock for TicketLock {
+    type Token = ();
+
+    fn lock(&self) {
+        let ticket = self.next_ticket.fetch_add(1, Ordering::Relaxed);
+        let mut spins = 0;
+        while self.now_serving.load(Ordering::Acquire) != ticket {
+            backoff(&mut spins);
+        }
+    }
+
+    fn try_lock(&self) -> Option<()> {
+        // only take a ticket when it would be served right away
+        let serving = self.now_serving.load(Ordering::Acquire);
+        self.next_ticket
+            .compare_exchange(serving, serving.wrapping_add(1), Ordering::Acquire, Ordering::Relaxed)
+            .ok()
+            .map(|_| ())
+    }
+
+    unsafe fn unlock(&self, _token: ()) {
+        // only the owner writes now_serving, so a plain load + store is enough
+        let next = self.now_serving.load(Ordering::Relaxed).wrapping_add(1);
+        self.now_serving.store(next, Ordering::Release);
+    }
+
+    fn name() -> &'static str {
+        "TicketLock"
+    }
+}
+
+//=================================================================
+// MCS queue lock (Mellor-Crummey and Scott)
+// waiting threads form a linked list and every thread spins on a flag in its own node,
+// so a release only touches the cache line of the next waiter instead of all of them
+
+struct McsNode {
+    locked: AtomicBool,
+    next: AtomicPtr<McsNode>
+}
+
+#[derive(Default)]
+pub struct McsLock {
+    tail: AtomicPtr<McsNode>
+}
+
+// the node lives on the heap so its address stays the same while other threads point to it
+// it is freed again in unlock
+pub struct McsToken(*mut McsNode);
+
+fn new_mcs_node(locked: bool) -> *mut McsNode {
+    Box::into_raw(Box::new(McsNode { locked: AtomicBool::new(locked), next: AtomicPtr::new(ptr::null_mut()) }))
+}
+
+unsafe impl RawLock for McsLock {
+    type Token = McsToken;
+
+    fn lock(&self) -> McsToken {
+        let node = new_mcs_node(true);
+        // AcqRel: Acquire to see the previous tail's initialisation, Release to publish our own node
+        let previous = self.tail.swap(node, Ordering::AcqRel);
+        if !previous.is_null() {
+            // SAFETY: the previous owner can't free its node before it has handed the lock to us,
+            // and it can only do that after it has seen this next pointer
+            unsafe { (*previous).next.store(node, Ordering::Release) };
+            let mut spins = 0;
+            // SAFETY: our own node, only freed by our own unlock
+            while unsafe { (*node).locked.load(Ordering::Acquire) } {
+                backoff(&mut spins);
+            }
+        }
+        McsToken(node)
+    }
+
+    fn try_lock(&self) -> Option<McsToken> {
+        let node = new_mcs_node(false);
+        match self.tail.compare_exchange(ptr::null_mut(), node, Ordering::AcqRel, Ordering::Relaxed) {
+            Ok(_) => Some(McsToken(node)),
+            Err(_) => {
+                // SAFETY: the node was never published
+                drop(unsafe { Box::from_raw(node) });
+                None
+            }
+        }
+    }
+
+    unsafe fn unlock(&self, token: McsToken) {
+        let node = token.0;
+        let mut next = (*node).next.load(Ordering::Acquire);
+        if next.is_null() {
+            // nobody queued behind us, try to leave the lock empty
+            if self.tail.compare_exchange(node, ptr::null_mut(), Ordering::Release, Ordering::Relaxed).is_ok() {
+                drop(Box::from_raw(node));
+                return;
+            }
+            // somebody swapped the tail but hasn't linked to us yet, wait for it
+            let mut spins = 0;
+            loop {
+                next = (*node).next.load(Ordering::Acquire);
+                if !next.is_null() {
+                    break;
+                }
+                backoff(&mut spins);
+            }
+        }
+        // the next thread stops spinning on its own node, ours isn't referenced by anybody anymore
+        (*next).locked.store(false, Ordering::Release);
+        drop(Box::from_raw(node));
+    }
+
+    fn name() -> &'static str {
+        "McsLock"
+    }
+}
+
+//=================================================================
+// reader-writer spinlock that prefers writers
+// as soon as a writer is waiting new readers stay out, so a steady stream of readers can't starve writers
+
+const WRITER: usize = 1 << (usize::BITS - 1);
+
+#[derive(Default)]
+pub struct RawRwLock {
+    // number of readers, or WRITER when a writer holds the lock
+    state: AtomicUsize,
+    waiting_writers: AtomicUsize
+}
+
+impl RawRwLock {
+    pub fn lock_shared(&self) {
+        let mut spins = 0;
+        loop {
+            if self.try_lock_shared() {
+                return;
+            }
+            backoff(&mut spins);
+        }
+    }
+
+    pub fn try_lock_shared(&self) -> bool {
+        if self.waiting_writers.load(Ordering::Relaxed) > 0 {
+            return false;
+        }
+        let state = self.state.load(Ordering::Relaxed);
+        state & WRITER == 0
+            && self.state.compare_exchange_weak(state, state + 1, Ordering::Acquire, Ordering::Relaxed).is_ok()
+    }
+
+    /// # Safety
+    ///
+    /// The caller has to hold a shared lock taken with `lock_shared()`/`try_lock_shared()`.
+    pub unsafe fn unlock_shared(&self) {
+        self.state.fetch_sub(1, Ordering::Release);
+    }
+}
+
+// the exclusive (writer) side of the lock
+unsafe impl RawLock for RawRwLock {
+    type Token = ();
+
+    fn lock(&self) {
+        self.waiting_writers.fetch_add(1, Ordering::Relaxed);
+        let mut spins = 0;
+        while self.state.compare_exchange_weak(0, WRITER, Ordering::Acquire, Ordering::Relaxed).is_err() {
+            backoff(&mut spins);
+        }
+        self.waiting_writers.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    fn try_lock(&self) -> Option<()> {
+        self.state.compare_exchange(0, WRITER, Ordering::Acquire, Ordering::Relaxed).ok().map(|_| ())
+    }
+
+    unsafe fn unlock(&self, _token: ()) {
+        self.state.store(0, Ordering::Release);
+    }
+
+    fn name() -> &'static str {
+        "RwLock (writer)"
+    }
+}
+
+//=================================================================
+// a lock that parks its waiters instead of spinning, they queue up in a pinning::WaitQueue
+// after a short spin a thread that can't get the lock goes to sleep, unlock wakes the longest waiting one.
+// the woken thread competes with newcomers for the lock, so this isn't fair, but nobody burns a core while waiting
+
+#[derive(Default)]
+pub struct QueueLock {
+    locked: AtomicBool,
+    waiters: WaitQueue
+}
+
+unsafe impl RawLock for QueueLock {
+    type Token = ();
+
+    fn lock(&self) {
+        for _ in 0..SPINS_BEFORE_YIELD {
+            if self.try_lock().is_some() {
+                return;
+            }
+            spin_loop();
+        }
+        loop {
+            if self.try_lock().is_some() {
+                return;
+            }
+            // the check runs with the queue locked, an unlock in between either makes it false
+            // or comes after we are queued and wakes us
+            self.waiters.wait_blocking_if(|| self.locked.load(Ordering::Relaxed));
+        }
+    }
+
+    fn try_lock(&self) -> Option<()> {
+        self.locked.compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed).ok().map(|_| ())
+    }
+
+    unsafe fn unlock(&self, _token: ()) {
+        self.locked.store(false, Ordering::Release);
+        self.waiters.notify_one();
+    }
+
+    fn name() -> &'static str {
+        "QueueLock"
+    }
+}
+
+//=================================================================
+// poison-aware mutex on top of any RawLock, with the same API as std::sync::Mutex
+// when a thread panics while holding the guard the mutex is marked poisoned,
+// later lock() calls still get the data but wrapped in a PoisonError
+
+pub struct Mutex<T: ?Sized, L: RawLock = SpinLock> {
+    raw: L,
+    poisoned: AtomicBool,
+    data: UnsafeCell<T>
+}
+
+unsafe impl<T: ?Sized + Send, L: RawLock> Send for Mutex<T, L> {}
+unsafe impl<T: ?Sized + Send, L: RawLock> Sync for Mutex<T, L> {}
+
+/// Like std's guard it is `!Send`, and only `Sync` when `T` is: `&guard` hands out `&T`,
+/// so sharing a guard of a `Cell` between threads has to be a compile error.
+///
+/// ```compile_fail
+/// use std::cell::Cell;
+/// use rust_practice_lab::mutexes::{Mutex, MutexGuard, SpinLock};
+///
+/// fn assert_sync<T: Sync>(_: &T) {}
+/// let mutex: Mutex<Cell<i32>, SpinLock> = Mutex::new(Cell::new(0));
+/// let guard: MutexGuard<'_, Cell<i32>, SpinLock> = mutex.lock().unwrap();
+/// assert_sync(&guard);
+/// ```
+///
+/// ```compile_fail
+/// use rust_practice_lab::mutexes::{Mutex, MutexGuard, SpinLock};
+///
+/// fn assert_send<T: Send>(_: &T) {}
+/// let mutex: Mutex<i32, SpinLock> = Mutex::new(0);
+/// let guard: MutexGuard<'_, i32, SpinLock> = mutex.lock().unwrap();
+/// assert_send(&guard);
+/// ```
+pub struct MutexGuard<'a, T: ?Sized, L: RawLock> {
+    mutex: &'a Mutex<T, L>,
+    token: Option<L::Token>,
+    // a guard taken while already unwinding shouldn't poison the mutex again
+    panicking: bool,
+    // the auto traits would follow &Mutex, which is Send + Sync for any T: Send.
+    // a raw pointer takes both away, Sync comes back below for T: Sync only
+    not_send: PhantomData<*const ()>
+}
+
+// SAFETY: a shared guard only gives out &T, which is fine to share when T is Sync
+unsafe impl<T: ?Sized + Sync, L: RawLock> Sync for MutexGuard<'_, T, L> {}
+
+impl<T, L: RawLock> Mutex<T, L> {
+    pub fn new(data: T) -> Self {
+        Self { raw: L::default(), poisoned: AtomicBool::new(false), data: UnsafeCell::new(data) }
+    }
+
+    pub fn into_inner(self) -> LockResult<T> {
+        let poisoned = self.is_poisoned();
+        let data = self.data.into_inner();
+        if poisoned { Err(PoisonError::new(data)) } else { Ok(data) }
+    }
+}
+
+impl<T: ?Sized, L: RawLock> Mutex<T, L> {
+    fn guard(&self, token: L::Token) -> MutexGuard<'_, T, L> {
+        MutexGuard { mutex: self, token: Some(token), panicking: thread::panicking(), not_send: PhantomData }
+    }
+
+    pub fn lock(&self) -> LockResult<MutexGuard<'_, T, L>> {
+        let guard = self.guard(self.raw.lock());
+        if self.is_poisoned() { Err(PoisonError::new(guard)) } else { Ok(guard) }
+    }
+
+    pub fn try_lock(&self) -> TryLockResult<MutexGuard<'_, T, L>> {
+        match self.raw.try_lock() {
+            None => Err(TryLockError::WouldBlock),
+            Some(token) if self.is_poisoned() => Err(TryLockError::Poisoned(PoisonError::new(self.guard(token)))),
+            Some(token) => Ok(self.guard(token))
+        }
+    }
+
+    pub fn is_poisoned(&self) -> bool {
+        self.poisoned.load(Ordering::Relaxed)
+    }
+
+    pub fn clear_poison(&self) {
+        self.poisoned.store(false, Ordering::Relaxed);
+    }
+
+    pub fn get_mut(&mut self) -> LockResult<&mut T> {
+        let poisoned = self.is_poisoned();
+        let data = self.data.get_mut();
+        if poisoned { Err(PoisonError::new(data)) } else { Ok(data) }
+    }
+}
+
+impl<T: Default, L: RawLock> Default for Mutex<T, L> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: ?Sized + fmt::Debug, L: RawLock> fmt::Debug for Mutex<T, L> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("Mutex");
+        debug.field("lock", &L::name());
+        match self.try_lock() {
+            Ok(guard) => debug.field("data", &&*guard),
+            Err(TryLockError::Poisoned(error)) => debug.field("data", &&**error.get_ref()),
+            Err(TryLockError::WouldBlock) => debug.field("data", &format_args!("<locked>"))
+        };
+        debug.field("poisoned", &self.is_poisoned()).finish()
+    }
+}
+
+impl<T: ?Sized, L: RawLock> Deref for MutexGuard<'_, T, L> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: holding the guard means holding the lock
+        unsafe { &*self.mutex.data.get() }
+    }
+}
+
+impl<T: ?Sized, L: RawLock> DerefMut for MutexGuard<'_, T, L> {
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: holding the guard means holding the lock
+        unsafe { &mut *self.mutex.data.get() }
+    }
+}
+
+impl<T: ?Sized, L: RawLock> Drop for MutexGuard<'_, T, L> {
+    fn drop(&mut self) {
+        if !self.panicking && thread::panicking() {
+            self.mutex.poisoned.store(true, Ordering::Relaxed);
+        }
+        if let Some(token) = self.token.take() {
+            // SAFETY: the token came from this mutex's raw lock when the guard was made
+            unsafe { self.mutex.raw.unlock(token) };
+        }
+    }
+}
+
+//=================================================================
+// safe reader-writer lock around some data
+
+pub struct RwLock<T: ?Sized> {
+    raw: RawRwLock,
+    data: UnsafeCell<T>
+}
+
+unsafe impl<T: ?Sized + Send> Send for RwLock<T> {}
+unsafe impl<T: ?Sized + Send + Sync> Sync for RwLock<T> {}
+
+pub struct RwLockReadGuard<'a, T: ?Sized> {
+    lock: &'a RwLock<T>
+}
+
+pub struct RwLockWriteGuard<'a, T: ?Sized> {
+    lock: &'a RwLock<T>
+}
+
+impl<T> RwLock<T> {
+    pub fn new(data: T) -> Self {
+        Self { raw: RawRwLock::default(), data: UnsafeCell::new(data) }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+}
+
+impl<T: ?Sized> RwLock<T> {
+    pub fn read(&self) -> RwLockReadGuard<'_, T> {
+        self.raw.lock_shared();
+        RwLockReadGuard { lock: self }
+    }
+
+    pub fn write(&self) -> RwLockWriteGuard<'_, T> {
+        self.raw.lock();
+        RwLockWriteGuard { lock: self }
+    }
+}
+
+impl<T: Default> Default for RwLock<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: ?Sized> Deref for RwLockReadGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: no writer can get in while a read guard exists
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for RwLockReadGuard<'_, T> {
+    fn drop(&mut self) {
+        // SAFETY: the guard was made after lock_shared()
+        unsafe { self.lock.raw.unlock_shared() };
+    }
+}
+
+impl<T: ?Sized> Deref for RwLockWriteGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: the write guard is the only way in
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for RwLockWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: the write guard is the only way in
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for RwLockWriteGuard<'_, T> {
+    fn drop(&mut self) {
+        // SAFETY: the guard was made after lock()
+        unsafe { self.lock.raw.unlock(()) };
+    }
+}
@@ -0,0 +1,22 @@
+// pinning: values that must not move anymore once something points into them
+//     wait_queue.rs: an intrusive linked list of waiting futures, the nodes live inside the pinned futures
+//     parser.rs: a token buffer whose tokens point into its own inline buffer
+//     projection.rs: the pin_project! macro, to get at the fields of a pinned struct without unsafe
+//
+//     let queue = WaitQueue::new();
+//     let ready = AtomicBool::new(false);
+//     // somewhere else: ready.store(true, ...); queue.notify_all();
+//     block_on(async {
+//         while !ready.load(Ordering::Acquire) {
+//             queue.wait_if(|| !ready.load(Ordering::Acquire)).await;
+//         }
+//     });
+//
+// the tests in tests/pinning.rs also run under Miri, which checks the raw pointers in here
+pub mod wait_queue;
+pub mod parser;
+pub mod projection;
+
+pub use wait_queue::{Wait, WaitQueue};
+pub use parser::{BufferFull, TokenBuffer};
+pub use projection::AlwaysUnpin;
@@ -0,0 +1,94 @@
+use std::fmt;
+use std::marker::PhantomPinned;
+use std::pin::Pin;
+
+// a self-referential type: the text sits in an inline array, and the tokens are pointers into that same array.
+// moving the struct would move the array but not the pointers, so it only works behind a Pin.
+// PhantomPinned makes it !Unpin, after pinning safe code can't move it anymore.
+//
+//     let mut buffer = Box::pin(TokenBuffer::<64>::new());
+//     buffer.as_mut().push_str("let x = 1").unwrap();
+//     let tokens: Vec<&str> = buffer.as_ref().tokens().collect();
+//
+// or without a heap allocation: let mut buffer = std::pin::pin!(TokenBuffer::<64>::new());
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct BufferFull {
+    pub needed: usize,
+    pub available: usize
+}
+
+impl fmt::Display for BufferFull {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "The text needs {} bytes but only {} are left in the buffer", self.needed, self.available)
+    }
+}
+
+impl std::error::Error for BufferFull {}
+
+pub struct TokenBuffer<const N: usize> {
+    buffer: [u8; N],
+    filled: usize,
+    // the whitespace separated words of buffer[..filled], pointing into buffer
+    tokens: Vec<*const str>,
+    _pinned: PhantomPinned
+}
+
+// SAFETY: the token pointers only point into the buffer that is owned by the same struct
+unsafe impl<const N: usize> Send for TokenBuffer<N> {}
+unsafe impl<const N: usize> Sync for TokenBuffer<N> {}
+
+impl<const N: usize> TokenBuffer<N> {
+    // there are no tokens yet, so it can still be moved until it gets pinned
+    pub fn new() -> Self {
+        Self { buffer: [0; N], filled: 0, tokens: Vec::new(), _pinned: PhantomPinned }
+    }
+
+    // appends the text and splits everything in the buffer into tokens again, so a word can be pushed in pieces
+    pub fn push_str(self: Pin<&mut Self>, text: &str) -> Result<(), BufferFull> {
+        // SAFETY: we only write into the fields, nothing is moved out
+        let this = unsafe { self.get_unchecked_mut() };
+        let available = N - this.filled;
+        if text.len() > available {
+            return Err(BufferFull { needed: text.len(), available });
+        }
+        this.buffer[this.filled..this.filled + text.len()].copy_from_slice(text.as_bytes());
+        this.filled += text.len();
+        this.tokenize();
+        Ok(())
+    }
+
+    fn tokenize(&mut self) {
+        let text = std::str::from_utf8(&self.buffer[..self.filled]).expect("only whole strings are copied in");
+        self.tokens = text.split_whitespace().map(|token| token as *const str).collect();
+    }
+
+    // the tokens borrow the pinned buffer, they stay valid as long as it isn't changed
+    pub fn tokens(self: Pin<&Self>) -> impl Iterator<Item = &str> {
+        // SAFETY: the pointers were taken from self.buffer, which hasn't moved since (it is pinned)
+        // and hasn't changed since (that needs a Pin<&mut Self>, which the borrow on self rules out)
+        self.get_ref().tokens.iter().map(|&token| unsafe { &*token })
+    }
+
+    pub fn as_str(self: Pin<&Self>) -> &str {
+        let this = self.get_ref();
+        std::str::from_utf8(&this.buffer[..this.filled]).expect("only whole strings are copied in")
+    }
+
+    pub fn remaining(&self) -> usize {
+        N - self.filled
+    }
+
+    pub fn clear(self: Pin<&mut Self>) {
+        // SAFETY: nothing is moved out
+        let this = unsafe { self.get_unchecked_mut() };
+        this.tokens.clear();
+        this.filled = 0;
+    }
+}
+
+impl<const N: usize> Default for TokenBuffer<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,96 @@
+use std::marker::PhantomData;
+
+// pin projection: turning a Pin<&mut Struct> into a pinned reference to some fields and a plain &mut to the others
+//
+//     pin_project! {
+//         #[project(TimeoutProjection)]
+//         pub struct Timeout<F> {
+//             #[pin]
+//             future: F,
+//             deadline: Sleep
+//         }
+//     }
+//
+// generates the struct, a TimeoutProjection<'_, F> with `future: Pin<&mut F>` and `deadline: &mut Sleep`,
+// and `fn project(self: Pin<&mut Self>) -> TimeoutProjection<'_, F>`.
+//
+// that projection is only sound when the struct keeps three promises, the macro makes sure it does:
+//     - it is Unpin only when all #[pin] fields are Unpin, the other fields don't matter
+//     - it has no Drop impl, drop() gets a &mut Self and could move a pinned field out
+//     - it isn't #[repr(packed)], references to packed fields may be moved to align them (not supported here)
+//
+// the generic parameters can't have bounds in the struct itself, put them on the impls instead
+
+// stands in for an unpinned field when deciding whether the whole struct is Unpin
+#[doc(hidden)]
+pub struct AlwaysUnpin<T: ?Sized>(PhantomData<T>);
+
+impl<T: ?Sized> Unpin for AlwaysUnpin<T> {}
+
+#[macro_export]
+macro_rules! pin_project {
+    // the type and the value of a field in the projection
+    (@field_type pin; $lifetime:lifetime; $ty:ty) => { ::core::pin::Pin<&$lifetime mut $ty> };
+    (@field_type ; $lifetime:lifetime; $ty:ty) => { &$lifetime mut $ty };
+    (@unpin_type pin; $ty:ty) => { $ty };
+    (@unpin_type ; $ty:ty) => { $crate::pinning::AlwaysUnpin<$ty> };
+    (@project pin; $field:expr) => {
+        // SAFETY: the struct is pinned and only Unpin when this field is, and it has no Drop impl
+        // that could move the field, so the field stays where it is until it is dropped
+        unsafe { ::core::pin::Pin::new_unchecked($field) }
+    };
+    (@project ; $field:expr) => { $field };
+
+    (
+        #[project($projection:ident)]
+        $(#[$attribute:meta])*
+        $vis:vis struct $name:ident $(<$($generic:ident),* $(,)?>)? {
+            $(
+                $(#[$pin:ident])?
+                $field_vis:vis $field:ident: $ty:ty
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$attribute])*
+        $vis struct $name $(<$($generic),*>)? {
+            $($field_vis $field: $ty),*
+        }
+
+        $vis struct $projection<'__pin $(, $($generic),*)?> {
+            $($field_vis $field: $crate::pin_project!(@field_type $($pin)?; '__pin; $ty)),*
+        }
+
+        impl $(<$($generic),*>)? $name $(<$($generic),*>)? {
+            $vis fn project<'__pin>(self: ::core::pin::Pin<&'__pin mut Self>) -> $projection<'__pin $(, $($generic),*)?> {
+                // SAFETY: nothing is moved out, every field is either pinned again or handed out unpinned on purpose
+                let this = unsafe { self.get_unchecked_mut() };
+                $projection {
+                    $($field: $crate::pin_project!(@project $($pin)?; &mut this.$field)),*
+                }
+            }
+        }
+
+        const _: () = {
+            // a copy of the struct where the unpinned fields are always Unpin, the real struct is Unpin
+            // exactly when this one is. the lifetime keeps the compiler from deciding that bound too early
+            #[allow(dead_code)]
+            struct __Origin<'__pin $(, $($generic),*)?> {
+                __lifetime: ::core::marker::PhantomData<&'__pin ()>,
+                $($field: $crate::pin_project!(@unpin_type $($pin)?; $ty)),*
+            }
+
+            #[allow(private_bounds)]
+            impl<'__pin $(, $($generic),*)?> ::core::marker::Unpin for $name $(<$($generic),*>)?
+            where
+                __Origin<'__pin $(, $($generic),*)?>: ::core::marker::Unpin
+            {}
+
+            // a Drop impl on the struct would make it implement this trait twice, which doesn't compile
+            #[allow(dead_code)]
+            trait MustNotImplDrop {}
+            #[allow(drop_bounds)]
+            impl<T: ::core::ops::Drop> MustNotImplDrop for T {}
+            impl $(<$($generic),*>)? MustNotImplDrop for $name $(<$($generic),*>)? {}
+        };
+    };
+}
@@ -0,0 +1,232 @@
+use std::cell::{Cell, UnsafeCell};
+use std::future::Future;
+use std::marker::PhantomPinned;
+use std::pin::Pin;
+use std::ptr;
+use std::sync::{Mutex, MutexGuard};
+use std::task::{Context, Poll, Waker};
+
+// a FIFO queue of waiting futures, for locks and other things that have to wait without spinning
+//
+// the queue doesn't allocate: every waiting future carries its own list node, and the list links those nodes
+// together (an intrusive list). other waiters point at our node, so the future must not move while it waits.
+// that is what the Pin in Future::poll promises, and Wait is !Unpin so safe code can't take that back.
+// dropping a waiting future unlinks its node again, so the list never points at freed memory.
+//
+// the waiting side works with any waker: an executor task is rescheduled, block_on just unparks its thread
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    // not polled yet
+    Idle,
+    // linked into the list
+    Waiting,
+    // taken out of the list by a notify, the next poll completes
+    Notified,
+    // completed
+    Done
+}
+
+struct Node {
+    prev: *mut Node,
+    next: *mut Node,
+    waker: Option<Waker>,
+    state: State
+}
+
+// every node in here belongs to a pinned Wait that is still alive, Wait::drop unlinks it first
+// nodes are only read or written with the list locked
+struct List {
+    head: *mut Node,
+    tail: *mut Node
+}
+
+// SAFETY: the nodes are only touched through the list, which sits behind a mutex
+unsafe impl Send for List {}
+
+impl List {
+    // SAFETY: node has to be valid and not linked yet
+    unsafe fn push_back(&mut self, node: *mut Node) {
+        (*node).prev = self.tail;
+        (*node).next = ptr::null_mut();
+        if self.tail.is_null() {
+            self.head = node;
+        } else {
+            (*self.tail).next = node;
+        }
+        self.tail = node;
+    }
+
+    // SAFETY: node has to be linked into this list
+    unsafe fn unlink(&mut self, node: *mut Node) {
+        let (prev, next) = ((*node).prev, (*node).next);
+        if prev.is_null() {
+            self.head = next;
+        } else {
+            (*prev).next = next;
+        }
+        if next.is_null() {
+            self.tail = prev;
+        } else {
+            (*next).prev = prev;
+        }
+        (*node).prev = ptr::null_mut();
+        (*node).next = ptr::null_mut();
+    }
+
+    // marks the oldest waiter as notified and hands out its waker, None when nobody waits
+    fn pop_front(&mut self) -> Option<Waker> {
+        let node = self.head;
+        if node.is_null() {
+            return None;
+        }
+        // SAFETY: a linked node belongs to a live Wait, and we hold the lock
+        unsafe {
+            self.unlink(node);
+            (*node).state = State::Notified;
+            (*node).waker.take()
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    // the list is consistent again before any code that could panic runs, wakers are called after releasing the lock
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+pub struct WaitQueue {
+    list: Mutex<List>
+}
+
+impl WaitQueue {
+    pub fn new() -> Self {
+        Self { list: Mutex::new(List { head: ptr::null_mut(), tail: ptr::null_mut() }) }
+    }
+
+    // a future that completes after a notify, or right away when `condition` returns false
+    // the condition runs on the first poll with the queue locked, so a notify can't slip in between the
+    // check and the moment we are queued. it must not use this queue itself
+    pub fn wait_if<C: FnOnce() -> bool>(&self, condition: C) -> Wait<'_, C> {
+        Wait {
+            queue: self,
+            condition: Cell::new(Some(condition)),
+            node: UnsafeCell::new(Node { prev: ptr::null_mut(), next: ptr::null_mut(), waker: None, state: State::Idle }),
+            _pinned: PhantomPinned
+        }
+    }
+
+    // wait_if for code outside of async, parks the thread until it is notified
+    pub fn wait_blocking_if<C: FnOnce() -> bool>(&self, condition: C) {
+        crate::futures::block_on(self.wait_if(condition));
+    }
+
+    // wakes the waiter that has been waiting the longest, returns false when nobody waits
+    pub fn notify_one(&self) -> bool {
+        let waker = lock(&self.list).pop_front();
+        match waker {
+            Some(waker) => {
+                waker.wake();
+                true
+            }
+            None => false
+        }
+    }
+
+    // wakes everybody that waits right now, returns how many that were
+    pub fn notify_all(&self) -> usize {
+        let mut wakers = Vec::new();
+        {
+            let mut list = lock(&self.list);
+            while let Some(waker) = list.pop_front() {
+                wakers.push(waker);
+            }
+        }
+        let count = wakers.len();
+        wakers.into_iter().for_each(Waker::wake);
+        count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        lock(&self.list).head.is_null()
+    }
+}
+
+impl Default for WaitQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+//=================================================================
+
+// returned by WaitQueue::wait_if, holds the list node while it waits
+pub struct Wait<'a, C> {
+    queue: &'a WaitQueue,
+    // taken on the first poll
+    condition: Cell<Option<C>>,
+    // other nodes and the queue point at this one, it is only accessed with the list locked
+    node: UnsafeCell<Node>,
+    _pinned: PhantomPinned
+}
+
+// SAFETY: the node is only touched with the list locked, whichever thread does it
+unsafe impl<C: Send> Send for Wait<'_, C> {}
+
+impl<C: FnOnce() -> bool> Future for Wait<'_, C> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        // only shared access from here on, the node is changed through its UnsafeCell
+        let this = self.into_ref().get_ref();
+        let node = this.node.get();
+        let mut list = lock(&this.queue.list);
+        // SAFETY: we hold the lock, nobody else looks at the node right now
+        let node_ref = unsafe { &mut *node };
+        match node_ref.state {
+            State::Idle => {
+                let condition = this.condition.take().expect("the condition is only taken once");
+                if !condition() {
+                    node_ref.state = State::Done;
+                    return Poll::Ready(());
+                }
+                node_ref.waker = Some(cx.waker().clone());
+                node_ref.state = State::Waiting;
+                // SAFETY: the future is pinned, so the node stays at this address until drop unlinks it
+                unsafe { list.push_back(node) };
+                Poll::Pending
+            }
+            State::Waiting => {
+                // the future may have moved to another task since the last poll
+                if !node_ref.waker.as_ref().is_some_and(|waker| waker.will_wake(cx.waker())) {
+                    node_ref.waker = Some(cx.waker().clone());
+                }
+                Poll::Pending
+            }
+            State::Notified | State::Done => {
+                node_ref.state = State::Done;
+                Poll::Ready(())
+            }
+        }
+    }
+}
+
+impl<C> Drop for Wait<'_, C> {
+    fn drop(&mut self) {
+        let node = self.node.get();
+        let mut list = lock(&self.queue.list);
+        // SAFETY: we hold the lock
+        match unsafe { (*node).state } {
+            // SAFETY: a waiting node is linked into this queue's list
+            State::Waiting => unsafe { list.unlink(node) },
+            State::Notified => {
+                // we were picked by a notify but never saw it, pass it on so the notify isn't lost
+                let waker = list.pop_front();
+                drop(list);
+                if let Some(waker) = waker {
+                    waker.wake();
+                }
+            }
+            State::Idle | State::Done => {}
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use rayon::prelude::*;
+
+// both parallel versions let every rayon task fill a private partial result (fold) and combine the partial
+// results pairwise afterwards (reduce), so there is no lock or atomic in the hot loop
+
+// counts how many values land in each bucket, bucket_of has to return something below `buckets`
+pub fn histogram<T, F: Fn(&T) -> usize>(values: &[T], buckets: usize, bucket_of: F) -> Vec<usize> {
+    let mut counts = vec![0; buckets];
+    for value in values {
+        counts[bucket_of(value)] += 1;
+    }
+    counts
+}
+
+pub fn par_histogram<T, F>(values: &[T], buckets: usize, bucket_of: F) -> Vec<usize>
+where
+    T: Sync,
+    F: Fn(&T) -> usize + Sync
+{
+    values
+        .par_iter()
+        .fold(
+            || vec![0; buckets],
+            |mut counts, value| {
+                counts[bucket_of(value)] += 1;
+                counts
+            }
+        )
+        .reduce(
+            || vec![0; buckets],
+            |mut counts, other| {
+                counts.iter_mut().zip(other).for_each(|(count, other)| *count += other);
+                counts
+            }
+        )
+}
+
+//=================================================================
+
+// combines the values of every key with op, like a GROUP BY key with op as the aggregate
+pub fn reduce_by_key<K, V, F>(pairs: &[(K, V)], op: F) -> HashMap<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    F: Fn(V, V) -> V
+{
+    let mut reduced = HashMap::new();
+    for (key, value) in pairs {
+        combine(&mut reduced, key.clone(), value.clone(), &op);
+    }
+    reduced
+}
+
+// rayon's reduce keeps the partial results in order, so op only has to be associative, not commutative
+pub fn par_reduce_by_key<K, V, F>(pairs: &[(K, V)], op: F) -> HashMap<K, V>
+where
+    K: Hash + Eq + Clone + Send + Sync,
+    V: Clone + Send + Sync,
+    F: Fn(V, V) -> V + Sync
+{
+    pairs
+        .par_iter()
+        .fold(HashMap::new, |mut reduced, (key, value)| {
+            combine(&mut reduced, key.clone(), value.clone(), &op);
+            reduced
+        })
+        .reduce(HashMap::new, |mut reduced, later| {
+            for (key, value) in later {
+                combine(&mut reduced, key, value, &op);
+            }
+            reduced
+        })
+}
+
+fn combine<K: Hash + Eq, V, F: Fn(V, V) -> V>(reduced: &mut HashMap<K, V>, key: K, value: V, op: &F) {
+    let value = match reduced.remove(&key) {
+        Some(earlier) => op(earlier, value),
+        None => value
+    };
+    reduced.insert(key, value);
+}
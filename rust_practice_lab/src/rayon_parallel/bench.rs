@@ -0,0 +1,28 @@
+use std::time::{Duration, Instant};
+
+// 1, 2, 4, ... up to the number of cores, and the number of cores itself when that isn't a power of two
+pub fn thread_counts() -> Vec<usize> {
+    let max_threads = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    let mut thread_counts: Vec<usize> = vec![1];
+    while thread_counts[thread_counts.len() - 1] * 2 <= max_threads {
+        thread_counts.push(thread_counts[thread_counts.len() - 1] * 2);
+    }
+    if thread_counts[thread_counts.len() - 1] != max_threads {
+        thread_counts.push(max_threads);
+    }
+    thread_counts
+}
+
+// runs f on a new rayon pool with this many threads, everything it calls in rayon uses that pool
+// only f itself is timed, not starting the pool
+pub fn time_on_threads<R: Send, F: FnOnce() -> R + Send>(threads: usize, f: F) -> (R, Duration) {
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .expect("could not build thread pool");
+    pool.install(|| {
+        let start = Instant::now();
+        let result = f();
+        (result, start.elapsed())
+    })
+}
@@ -0,0 +1,28 @@
+// parallel algorithms on top of rayon, each one next to the plain sequential version it has to agree with
+//     scan.rs: inclusive prefix scan (prefix sums) in three passes over chunks
+//     sort.rs: merge sort with a parallel merge, and sample sort
+//     aggregate.rs: histogram and reduce-by-key, with per-thread partial results that are combined at the end
+//     bench.rs: runs a closure on rayon pools of different sizes and times it
+//
+//     let sums = par_scan(&[1, 2, 3, 4], 0, |a, b| a + b);
+//     assert_eq!(sums, scan(&[1, 2, 3, 4], 0, |a, b| a + b));
+//
+// the parallel versions run on whatever rayon pool they are called from, the global one unless
+// they are inside a pool.install() like the benchmark does
+pub mod scan;
+pub mod sort;
+pub mod aggregate;
+pub mod bench;
+
+pub use scan::{par_prefix_sum, par_scan, prefix_sum, scan};
+pub use sort::{merge_sort, par_merge_sort, par_sample_sort};
+pub use aggregate::{histogram, par_histogram, par_reduce_by_key, reduce_by_key};
+pub use bench::{thread_counts, time_on_threads};
+
+// below this many elements splitting the work costs more than it saves
+const SEQUENTIAL_CUTOFF: usize = 4096;
+
+// a few chunks per thread so a slow thread doesn't hold everybody up, but never tiny ones
+fn chunk_size(len: usize) -> usize {
+    (len / (rayon::current_num_threads() * 4)).max(SEQUENTIAL_CUTOFF)
+}
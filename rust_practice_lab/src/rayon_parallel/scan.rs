@@ -0,0 +1,63 @@
+use rayon::prelude::*;
+use super::chunk_size;
+
+// inclusive scan: output[i] = values[0] op values[1] op ... op values[i]
+// `identity` is the neutral element of op (0 for +, 1 for *, i64::MIN for max)
+pub fn scan<T: Copy, F: Fn(T, T) -> T>(values: &[T], identity: T, op: F) -> Vec<T> {
+    let mut running = identity;
+    values
+        .iter()
+        .map(|&value| {
+            running = op(running, value);
+            running
+        })
+        .collect()
+}
+
+// the same scan in three passes:
+//     1. every chunk is reduced to its total, in parallel
+//     2. a sequential scan over those totals gives every chunk the total of everything before it
+//     3. every chunk is scanned again starting from that offset, in parallel
+// it combines the values in a different order than scan, so op has to be associative
+pub fn par_scan<T, F>(values: &[T], identity: T, op: F) -> Vec<T>
+where
+    T: Copy + Send + Sync,
+    F: Fn(T, T) -> T + Sync
+{
+    let chunk_size = chunk_size(values.len());
+    let chunk_totals: Vec<T> = values
+        .par_chunks(chunk_size)
+        .map(|chunk| chunk.iter().fold(identity, |total, &value| op(total, value)))
+        .collect();
+
+    // only a few chunks per thread, not worth doing in parallel
+    let mut offsets = Vec::with_capacity(chunk_totals.len());
+    let mut running = identity;
+    for total in chunk_totals {
+        offsets.push(running);
+        running = op(running, total);
+    }
+
+    let mut output = vec![identity; values.len()];
+    output
+        .par_chunks_mut(chunk_size)
+        .zip(values.par_chunks(chunk_size))
+        .zip(offsets.into_par_iter())
+        .for_each(|((output, chunk), offset)| {
+            let mut running = offset;
+            for (output, &value) in output.iter_mut().zip(chunk) {
+                running = op(running, value);
+                *output = running;
+            }
+        });
+    output
+}
+
+// the running totals of the values, wrapping around on overflow like both versions do
+pub fn prefix_sum(values: &[u64]) -> Vec<u64> {
+    scan(values, 0, u64::wrapping_add)
+}
+
+pub fn par_prefix_sum(values: &[u64]) -> Vec<u64> {
+    par_scan(values, 0, u64::wrapping_add)
+}
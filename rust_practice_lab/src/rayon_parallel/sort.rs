@@ -0,0 +1,164 @@
+use std::mem;
+use rand::Rng;
+use rayon::prelude::*;
+use super::{chunk_size, SEQUENTIAL_CUTOFF};
+
+// both merge sorts are stable, sample sort isn't
+
+// top-down merge sort, the sequential reference for both parallel sorts
+pub fn merge_sort<T: Ord + Copy>(values: &mut [T]) {
+    let mut buffer = values.to_vec();
+    merge_sort_with_buffer(values, &mut buffer);
+}
+
+// sorts values, buffer is scratch space of the same length
+fn merge_sort_with_buffer<T: Ord + Copy>(values: &mut [T], buffer: &mut [T]) {
+    if values.len() <= INSERTION_SORT_CUTOFF {
+        insertion_sort(values);
+        return;
+    }
+    let middle = values.len() / 2;
+    {
+        let (left, right) = values.split_at_mut(middle);
+        let (left_buffer, right_buffer) = buffer.split_at_mut(middle);
+        merge_sort_with_buffer(left, left_buffer);
+        merge_sort_with_buffer(right, right_buffer);
+        merge(left, right, buffer);
+    }
+    values.copy_from_slice(buffer);
+}
+
+// short slices are sorted faster by shifting values into place than by splitting them further
+const INSERTION_SORT_CUTOFF: usize = 16;
+
+fn insertion_sort<T: Ord + Copy>(values: &mut [T]) {
+    for i in 1..values.len() {
+        let value = values[i];
+        let mut j = i;
+        while j > 0 && values[j - 1] > value {
+            values[j] = values[j - 1];
+            j -= 1;
+        }
+        values[j] = value;
+    }
+}
+
+// merges two sorted slices into output, on equal values the left one goes first
+fn merge<T: Ord + Copy>(left: &[T], right: &[T], output: &mut [T]) {
+    let (mut l, mut r) = (0, 0);
+    for slot in output.iter_mut() {
+        if r == right.len() || (l < left.len() && left[l] <= right[r]) {
+            *slot = left[l];
+            l += 1;
+        } else {
+            *slot = right[r];
+            r += 1;
+        }
+    }
+}
+
+//=================================================================
+
+// the two halves are sorted in parallel, and the merge is split up as well, otherwise the last merge
+// would go through all values on a single thread
+pub fn par_merge_sort<T: Ord + Copy + Send + Sync>(values: &mut [T]) {
+    let mut buffer = values.to_vec();
+    par_merge_sort_with_buffer(values, &mut buffer);
+}
+
+fn par_merge_sort_with_buffer<T: Ord + Copy + Send + Sync>(values: &mut [T], buffer: &mut [T]) {
+    if values.len() <= SEQUENTIAL_CUTOFF {
+        merge_sort_with_buffer(values, buffer);
+        return;
+    }
+    let middle = values.len() / 2;
+    {
+        let (left, right) = values.split_at_mut(middle);
+        let (left_buffer, right_buffer) = buffer.split_at_mut(middle);
+        rayon::join(
+            || par_merge_sort_with_buffer(left, left_buffer),
+            || par_merge_sort_with_buffer(right, right_buffer)
+        );
+        par_merge(left, right, buffer);
+    }
+    let chunk_size = chunk_size(values.len());
+    values
+        .par_chunks_mut(chunk_size)
+        .zip(buffer.par_chunks(chunk_size))
+        .for_each(|(values, buffer)| values.copy_from_slice(buffer));
+}
+
+// splits the longer slice in the middle and the other one where that middle value would go,
+// then both halves are merged independently into their own part of the output
+fn par_merge<T: Ord + Copy + Send + Sync>(left: &[T], right: &[T], output: &mut [T]) {
+    if left.len() + right.len() <= SEQUENTIAL_CUTOFF {
+        merge(left, right, output);
+        return;
+    }
+    // equal values from the left slice have to stay in front of those from the right one
+    let (left_split, right_split) = if left.len() >= right.len() {
+        let middle = left.len() / 2;
+        (middle, right.partition_point(|value| *value < left[middle]))
+    } else {
+        let middle = right.len() / 2;
+        (left.partition_point(|value| *value <= right[middle]), middle)
+    };
+    let (low_output, high_output) = output.split_at_mut(left_split + right_split);
+    rayon::join(
+        || par_merge(&left[..left_split], &right[..right_split], low_output),
+        || par_merge(&left[left_split..], &right[right_split..], high_output)
+    );
+}
+
+//=================================================================
+
+// random samples per bucket, more samples give buckets of a more even size
+const OVERSAMPLING: usize = 8;
+
+// sample sort: a random sample picks splitters that cut the value range into one bucket per task,
+// every value is moved to its bucket, and then all buckets are sorted at the same time.
+// unlike merge sort the values move only once, but a bad sample gives uneven buckets
+// (lots of equal values end up in one bucket, it is still correct but slower)
+pub fn par_sample_sort<T: Ord + Copy + Send + Sync>(values: &mut [T]) {
+    if values.len() <= SEQUENTIAL_CUTOFF {
+        values.sort_unstable();
+        return;
+    }
+    let buckets = rayon::current_num_threads() * 4;
+    let mut rng = rand::thread_rng();
+    let mut samples: Vec<T> = (0..buckets * OVERSAMPLING).map(|_| values[rng.gen_range(0..values.len())]).collect();
+    samples.sort_unstable();
+    let splitters: Vec<T> = (1..buckets).map(|bucket| samples[bucket * OVERSAMPLING]).collect();
+
+    // every chunk sorts its values into buckets of its own, so nothing is shared while distributing
+    let partitions: Vec<Vec<Vec<T>>> = values
+        .par_chunks(chunk_size(values.len()))
+        .map(|chunk| {
+            let mut partition = vec![Vec::new(); buckets];
+            for &value in chunk {
+                partition[splitters.partition_point(|splitter| *splitter <= value)].push(value);
+            }
+            partition
+        })
+        .collect();
+
+    // bucket b gets the part of the output right after buckets 0..b
+    let mut bucket_slices = Vec::with_capacity(buckets);
+    let mut rest = values;
+    for bucket in 0..buckets {
+        let size = partitions.iter().map(|partition| partition[bucket].len()).sum();
+        let (bucket_slice, tail) = mem::take(&mut rest).split_at_mut(size);
+        bucket_slices.push(bucket_slice);
+        rest = tail;
+    }
+
+    bucket_slices.into_par_iter().enumerate().for_each(|(bucket, slice)| {
+        let mut filled = 0;
+        for partition in &partitions {
+            let part = &partition[bucket];
+            slice[filled..filled + part.len()].copy_from_slice(part);
+            filled += part.len();
+        }
+        slice.sort_unstable();
+    });
+}
@@ -0,0 +1,324 @@
+use std::any::Any;
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::marker::PhantomData;
+use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, OnceLock};
+use std::thread;
+use std::time::Duration;
+
+// work-stealing thread pool
+//
+// every worker has its own deque: it pushes and pops jobs at the back (newest first, good for the cache)
+// and other workers steal from the front (oldest first, those tend to be the biggest pieces of work)
+// jobs spawned from outside the pool go to a global injector queue that every worker checks
+//
+//     let pool = ThreadPool::new(4);
+//     let handle = pool.spawn(|| 6 * 7);
+//     assert_eq!(handle.join(), 42);
+//
+//     let numbers = vec![1, 2, 3];
+//     let (sum, max) = pool.join(|| numbers.iter().sum::<i32>(), || numbers.iter().max().copied());
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+type Panic = Box<dyn Any + Send + 'static>;
+
+static NEXT_POOL_ID: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    // (pool id, worker index) when the current thread is a pool worker
+    static CURRENT_WORKER: Cell<Option<(usize, usize)>> = const { Cell::new(None) };
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    // jobs never panic while holding one of these locks, the panics are caught inside the job
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+struct Inner {
+    id: usize,
+    injector: Mutex<VecDeque<Job>>,
+    deques: Vec<Mutex<VecDeque<Job>>>,
+    // number of jobs sitting in any queue, lets sleeping workers check for work without locking every deque
+    queued: AtomicUsize,
+    shutting_down: AtomicBool,
+    sleep: Mutex<()>,
+    wakeup: Condvar
+}
+
+impl Inner {
+    fn current_worker(&self) -> Option<usize> {
+        match CURRENT_WORKER.with(|worker| worker.get()) {
+            Some((pool_id, index)) if pool_id == self.id => Some(index),
+            _ => None
+        }
+    }
+
+    fn push(&self, job: Job) {
+        match self.current_worker() {
+            Some(index) => lock(&self.deques[index]).push_back(job),
+            None => lock(&self.injector).push_back(job)
+        }
+        self.queued.fetch_add(1, Ordering::SeqCst);
+        // taking the sleep lock makes sure a worker that just saw `queued == 0` is already waiting
+        let _sleep = lock(&self.sleep);
+        self.wakeup.notify_one();
+    }
+
+    fn find_job(&self, index: Option<usize>) -> Option<Job> {
+        let job = index
+            .and_then(|index| lock(&self.deques[index]).pop_back())
+            .or_else(|| lock(&self.injector).pop_front())
+            .or_else(|| {
+                // steal, starting with the next worker so not everybody robs worker 0
+                let start = index.map_or(0, |index| index + 1);
+                (0..self.deques.len())
+                    .map(|offset| (start + offset) % self.deques.len())
+                    .filter(|&victim| Some(victim) != index)
+                    .find_map(|victim| lock(&self.deques[victim]).pop_front())
+            });
+        if job.is_some() {
+            self.queued.fetch_sub(1, Ordering::SeqCst);
+        }
+        job
+    }
+
+    fn worker_loop(&self, index: usize) {
+        CURRENT_WORKER.with(|worker| worker.set(Some((self.id, index))));
+        loop {
+            if let Some(job) = self.find_job(Some(index)) {
+                job();
+                continue;
+            }
+            let sleep = lock(&self.sleep);
+            if self.queued.load(Ordering::SeqCst) > 0 {
+                continue;
+            }
+            // graceful shutdown: only stop once every queued job has run
+            if self.shutting_down.load(Ordering::SeqCst) {
+                break;
+            }
+            drop(self.wakeup.wait(sleep));
+        }
+        CURRENT_WORKER.with(|worker| worker.set(None));
+    }
+
+    // blocks until `done` returns true
+    // a worker thread keeps running other jobs meanwhile, otherwise nested joins could deadlock the pool
+    fn wait_until<F: Fn() -> bool>(&self, done: F, wait: impl Fn(Duration)) {
+        let index = self.current_worker();
+        while !done() {
+            match index.and_then(|index| self.find_job(Some(index))) {
+                Some(job) => job(),
+                None => wait(Duration::from_millis(1))
+            }
+        }
+    }
+}
+
+pub struct ThreadPool {
+    inner: Arc<Inner>,
+    workers: Vec<thread::JoinHandle<()>>
+}
+
+impl ThreadPool {
+    // panics when the operating system refuses to start the worker threads
+    pub fn new(threads: usize) -> Self {
+        let threads = threads.max(1);
+        let id = NEXT_POOL_ID.fetch_add(1, Ordering::Relaxed);
+        let inner = Arc::new(Inner {
+            id,
+            injector: Mutex::new(VecDeque::new()),
+            deques: (0..threads).map(|_| Mutex::new(VecDeque::new())).collect(),
+            queued: AtomicUsize::new(0),
+            shutting_down: AtomicBool::new(false),
+            sleep: Mutex::new(()),
+            wakeup: Condvar::new()
+        });
+        let workers = (0..threads)
+            .map(|index| {
+                let inner = Arc::clone(&inner);
+                thread::Builder::new()
+                    .name(format!("pool-{}-worker-{}", id, index))
+                    .spawn(move || inner.worker_loop(index))
+                    .expect("could not start a worker thread")
+            })
+            .collect();
+        Self { inner, workers }
+    }
+
+    pub fn threads(&self) -> usize {
+        self.workers.len()
+    }
+
+    pub fn spawn<F, T>(&self, f: F) -> JoinHandle<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static
+    {
+        let state = Arc::new(JoinState { result: Mutex::new(None), finished: Condvar::new() });
+        let job_state = Arc::clone(&state);
+        self.inner.push(Box::new(move || {
+            let result = catch_unwind(AssertUnwindSafe(f));
+            *lock(&job_state.result) = Some(result);
+            job_state.finished.notify_all();
+        }));
+        JoinHandle { state, pool: Arc::clone(&self.inner) }
+    }
+
+    // runs f, every job spawned on the scope may borrow from outside of it
+    // returns after f and all of those jobs are done, then rethrows the first panic if there was one
+    pub fn scope<'scope, F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&Scope<'scope>) -> R
+    {
+        let scope = Scope {
+            pool: Arc::clone(&self.inner),
+            state: Arc::new(ScopeState { pending: Mutex::new(0), all_done: Condvar::new(), panic: Mutex::new(None) }),
+            _marker: PhantomData
+        };
+        let result = catch_unwind(AssertUnwindSafe(|| f(&scope)));
+
+        // the jobs borrow things that only live until this function returns, so wait even when f panicked
+        let state = &scope.state;
+        self.inner.wait_until(
+            || *lock(&state.pending) == 0,
+            |timeout| {
+                let pending = lock(&state.pending);
+                if *pending > 0 {
+                    drop(state.all_done.wait_timeout(pending, timeout));
+                }
+            }
+        );
+
+        let job_panic = lock(&state.panic).take();
+        match (result, job_panic) {
+            (Err(panic), _) => resume_unwind(panic),
+            (Ok(_), Some(panic)) => resume_unwind(panic),
+            (Ok(result), None) => result
+        }
+    }
+
+    // runs a and b, possibly in parallel, and returns both results
+    // b is offered to the pool while the calling thread works on a
+    pub fn join<A, B, RA, RB>(&self, a: A, b: B) -> (RA, RB)
+    where
+        A: FnOnce() -> RA + Send,
+        B: FnOnce() -> RB + Send,
+        RA: Send,
+        RB: Send
+    {
+        let mut result_b = None;
+        let result_a = self.scope(|scope| {
+            scope.spawn(|_| result_b = Some(b()));
+            a()
+        });
+        (result_a, result_b.expect("the scope waits for every job"))
+    }
+
+    // lets the workers finish everything that is queued and waits for them to exit
+    // dropping the pool does the same
+    pub fn shutdown(mut self) {
+        self.stop();
+    }
+
+    fn stop(&mut self) {
+        self.inner.shutting_down.store(true, Ordering::SeqCst);
+        {
+            let _sleep = lock(&self.inner.sleep);
+            self.inner.wakeup.notify_all();
+        }
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl Drop for ThreadPool {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+// a pool with one worker per core, created the first time it is used
+pub fn global_pool() -> &'static ThreadPool {
+    static POOL: OnceLock<ThreadPool> = OnceLock::new();
+    POOL.get_or_init(|| ThreadPool::new(thread::available_parallelism().map(|n| n.get()).unwrap_or(4)))
+}
+
+//=================================================================
+
+struct JoinState<T> {
+    result: Mutex<Option<thread::Result<T>>>,
+    finished: Condvar
+}
+
+pub struct JoinHandle<T> {
+    state: Arc<JoinState<T>>,
+    pool: Arc<Inner>
+}
+
+impl<T> JoinHandle<T> {
+    pub fn is_finished(&self) -> bool {
+        lock(&self.state.result).is_some()
+    }
+
+    // waits for the job and returns its result, a panic in the job is rethrown here
+    pub fn join(self) -> T {
+        let state = &self.state;
+        self.pool.wait_until(
+            || lock(&state.result).is_some(),
+            |timeout| {
+                let result = lock(&state.result);
+                if result.is_none() {
+                    drop(state.finished.wait_timeout(result, timeout));
+                }
+            }
+        );
+        match lock(&state.result).take().expect("checked above") {
+            Ok(value) => value,
+            Err(panic) => resume_unwind(panic)
+        }
+    }
+}
+
+//=================================================================
+
+struct ScopeState {
+    pending: Mutex<usize>,
+    all_done: Condvar,
+    panic: Mutex<Option<Panic>>
+}
+
+pub struct Scope<'scope> {
+    pool: Arc<Inner>,
+    state: Arc<ScopeState>,
+    // invariant over 'scope, same trick as std::thread::Scope
+    _marker: PhantomData<&'scope mut &'scope ()>
+}
+
+impl<'scope> Scope<'scope> {
+    // the job gets the scope as argument, so it can spawn more jobs on it
+    pub fn spawn<F>(&self, f: F)
+    where
+        F: FnOnce(&Scope<'scope>) + Send + 'scope
+    {
+        *lock(&self.state.pending) += 1;
+        let scope = Scope { pool: Arc::clone(&self.pool), state: Arc::clone(&self.state), _marker: PhantomData };
+        let job: Box<dyn FnOnce() + Send + 'scope> = Box::new(move || {
+            if let Err(panic) = catch_unwind(AssertUnwindSafe(|| f(&scope))) {
+                lock(&scope.state.panic).get_or_insert(panic);
+            }
+            let mut pending = lock(&scope.state.pending);
+            *pending -= 1;
+            if *pending == 0 {
+                scope.state.all_done.notify_all();
+            }
+        });
+        // SAFETY: ThreadPool::scope doesn't return before `pending` is back to 0,
+        // so the job has finished before anything it borrows for 'scope goes away
+        let job: Job = unsafe { std::mem::transmute::<Box<dyn FnOnce() + Send + 'scope>, Job>(job) };
+        self.pool.push(job);
+    }
+}
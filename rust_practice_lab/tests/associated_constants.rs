@@ -0,0 +1,105 @@
+mod common;
+
+use common::{on_sale, traits};
+use traits::associated_constants::{
+    carrying, covers, fastest_on, spec_of, trips_needed, Category, Specification, Terrain, VehicleSpec, MOST_PASSENGERS, REGISTRY
+};
+use traits::default_type_parameters::Speed;
+use traits::traits::poly::{Airplane, AmphibiousBoat, Boat, Car};
+use traits::traits::VehicleKind;
+
+fn models<'a>(specs: impl Iterator<Item = &'a VehicleSpec>) -> Vec<&'static str> {
+    specs.map(|spec| spec.model).collect()
+}
+
+//=================================================================
+// the constants of every type
+
+#[test]
+fn the_registry_holds_every_vehicle_kind_in_order() {
+    assert_eq!(REGISTRY.map(|spec| spec.kind), VehicleKind::ALL);
+    assert_eq!(REGISTRY[0], VehicleSpec::of::<Car>());
+    assert_eq!(REGISTRY[1], VehicleSpec::of::<Boat>());
+    assert_eq!(REGISTRY[2], VehicleSpec::of::<AmphibiousBoat>());
+    assert_eq!(REGISTRY[3], VehicleSpec::of::<Airplane>());
+}
+
+#[test]
+fn the_registry_can_be_read_at_compile_time() {
+    const AIRPLANE: VehicleSpec = REGISTRY[3];
+    const WHEELS: [u8; 4] = [REGISTRY[0].wheels, REGISTRY[1].wheels, REGISTRY[2].wheels, REGISTRY[3].wheels];
+    assert_eq!(AIRPLANE.model, "Airplane");
+    assert_eq!(WHEELS, [4, 0, 4, 10]);
+    const _: () = assert!(REGISTRY[3].covers(Terrain::Air));
+    assert_eq!(MOST_PASSENGERS, VehicleSpec::of::<Airplane>());
+}
+
+#[test]
+fn types_that_dont_override_a_default_get_the_default() {
+    // one terrain, so the secondary speed is the max speed
+    assert_eq!(Car::SECONDARY_SPEED, Car::MAX_SPEED);
+    assert_eq!(Boat::SECONDARY_SPEED, Boat::MAX_SPEED);
+    assert_eq!(Boat::WHEELS, 0);
+    assert_ne!(AmphibiousBoat::SECONDARY_SPEED, AmphibiousBoat::MAX_SPEED);
+}
+
+#[test]
+fn amphibious_is_computed_from_the_terrain() {
+    // a const assert, like the ones next to the impls, a wrong constant fails the build
+    const _: () = assert!(AmphibiousBoat::AMPHIBIOUS && !Car::AMPHIBIOUS && !Boat::AMPHIBIOUS && !Airplane::AMPHIBIOUS);
+    assert!(spec_of(VehicleKind::AmphibiousBoat).covers(Terrain::Water) && spec_of(VehicleKind::AmphibiousBoat).covers(Terrain::Road));
+    assert!(covers(&[Terrain::Water, Terrain::Road], Terrain::Road));
+    assert!(!covers(&[], Terrain::Road));
+}
+
+#[test]
+fn categories_have_a_name() {
+    let names: Vec<&str> = REGISTRY.iter().map(|spec| spec.category.name()).collect();
+    assert_eq!(names, ["Automobile", "Watercraft", "Amphibian", "Aircraft"]);
+    assert_eq!(spec_of(VehicleKind::AmphibiousBoat).category, Category::Amphibian);
+}
+
+//=================================================================
+// looking specifications up at runtime
+
+#[test]
+fn a_vehicle_only_known_at_runtime_finds_its_specification() {
+    let specs: Vec<&str> = on_sale().iter().map(|vehicle| spec_of(vehicle.kind()).model).collect();
+    assert_eq!(specs, ["Car", "Car", "Car", "Boat", "AmphibiousBoat", "Airplane"]);
+    for kind in VehicleKind::ALL {
+        assert_eq!(spec_of(*kind).kind, *kind);
+    }
+}
+
+#[test]
+fn the_first_terrain_gets_the_max_speed_the_others_the_secondary_speed() {
+    let airplane = spec_of(VehicleKind::Airplane);
+    assert_eq!(airplane.speed_on(Terrain::Air), Some(Speed::from_kilometers_per_hour(900.0)));
+    assert_eq!(airplane.speed_on(Terrain::Road), Some(Speed::from_kilometers_per_hour(50.0)));
+    assert_eq!(airplane.speed_on(Terrain::Water), None);
+    assert_eq!(spec_of(VehicleKind::AmphibiousBoat).speed_on(Terrain::Water), Some(Speed::from_knots(6.0)));
+}
+
+#[test]
+fn the_fastest_vehicle_depends_on_the_terrain() {
+    assert_eq!(fastest_on(Terrain::Road).unwrap().model, "Car");
+    assert_eq!(fastest_on(Terrain::Water).unwrap().model, "Boat");
+    assert_eq!(fastest_on(Terrain::Air).unwrap().model, "Airplane");
+}
+
+#[test]
+fn vehicles_carrying_a_group() {
+    assert_eq!(models(carrying(1)), ["Car", "Boat", "AmphibiousBoat", "Airplane"]);
+    assert_eq!(models(carrying(6)), ["Boat", "AmphibiousBoat", "Airplane"]);
+    assert_eq!(models(carrying(13)), ["Airplane"]);
+    assert_eq!(carrying(181).count(), 0);
+}
+
+#[test]
+fn trips_needed_rounds_up() {
+    assert_eq!(trips_needed::<Car>(0), 0);
+    assert_eq!(trips_needed::<Car>(5), 1);
+    assert_eq!(trips_needed::<Car>(6), 2);
+    assert_eq!(trips_needed::<Boat>(25), 3);
+    assert_eq!(trips_needed::<Airplane>(180), 1);
+}
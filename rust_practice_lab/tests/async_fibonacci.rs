@@ -0,0 +1,58 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+use tokio_stream::StreamExt;
+use rust_practice_lab::async_fibonacci::fibonacci_stream;
+
+#[tokio::test]
+async fn yields_the_same_values_as_the_example_loop() {
+    let values: Vec<u64> = fibonacci_stream(8, Duration::ZERO).collect().await;
+    assert_eq!(values, vec![1, 2, 3, 5, 8, 13, 21, 34]);
+}
+
+#[tokio::test]
+async fn ends_before_overflowing() {
+    let values: Vec<u64> = fibonacci_stream(1000, Duration::ZERO).collect().await;
+    assert_eq!(values.len(), 92);
+    assert_eq!(values[values.len() - 1], 12200160415121876738);
+}
+
+// with the clock paused tokio jumps straight to the next timer, so the test takes no real time
+// but the order in which the sleeps finish is still the same as on a real clock
+#[tokio::test(start_paused = true)]
+async fn concurrent_streams_interleave() {
+    let log: Arc<Mutex<Vec<(&str, u64)>>> = Arc::new(Mutex::new(Vec::new()));
+    let start = Instant::now();
+
+    let consume = |name: &'static str, delay: u64| {
+        let log = Arc::clone(&log);
+        async move {
+            let mut values = fibonacci_stream(3, Duration::from_millis(delay));
+            while let Some(value) = values.next().await {
+                log.lock().unwrap().push((name, value));
+            }
+        }
+    };
+    tokio::join!(consume("a", 100), consume("b", 150));
+
+    let log = log.lock().unwrap().clone();
+    assert_eq!(log, vec![("a", 1), ("b", 1), ("a", 2), ("b", 2), ("a", 3), ("b", 3)]);
+    // 3 values with 150ms between them, run one after another this would have been 500ms
+    assert_eq!(start.elapsed(), Duration::from_millis(300));
+}
+
+#[tokio::test(start_paused = true)]
+async fn dropping_the_stream_cancels_it() {
+    let start = Instant::now();
+    let mut values = fibonacci_stream(60, Duration::from_secs(1));
+    assert_eq!(values.next().await, Some(1));
+    assert_eq!(values.next().await, Some(2));
+    drop(values);
+    assert_eq!(start.elapsed(), Duration::from_secs(1));
+
+    // a stream that is still waiting can be cut off by a timeout, which drops it
+    let mut values = fibonacci_stream(60, Duration::from_secs(1));
+    values.next().await;
+    let waited = tokio::time::timeout(Duration::from_millis(10), values.next()).await;
+    assert!(waited.is_err());
+}
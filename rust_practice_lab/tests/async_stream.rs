@@ -0,0 +1,233 @@
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+use std::time::Duration;
+use rust_practice_lab::async_stream::*;
+use rust_practice_lab::channels::{bounded, unbounded, SendError};
+use rust_practice_lab::futures::{block_on, MockTimer};
+
+// polled by hand, nothing has to be woken because the tests poll again themselves
+fn poll<S: Stream + Unpin>(stream: &mut S) -> Poll<Option<S::Item>> {
+    Pin::new(stream).poll_next(&mut Context::from_waker(Waker::noop()))
+}
+
+// every item with the mocked second it came out in, a second passes whenever the stream is pending
+fn drain<S: Stream + Unpin>(mut stream: S, timer: &MockTimer) -> Vec<(u64, S::Item)> {
+    let started = timer.now();
+    let mut items = Vec::new();
+    loop {
+        match poll(&mut stream) {
+            Poll::Ready(Some(item)) => items.push(((timer.now() - started).as_secs(), item)),
+            Poll::Ready(None) => return items,
+            Poll::Pending => {
+                assert!(timer.now() - started < Duration::from_secs(1000), "the stream never ended");
+                timer.advance(Duration::from_secs(1));
+            }
+        }
+    }
+}
+
+fn collect<S: Stream + Unpin>(mut stream: S) -> Vec<S::Item> {
+    block_on(async {
+        let mut items = Vec::new();
+        while let Some(item) = next(&mut stream).await {
+            items.push(item);
+        }
+        items
+    })
+}
+
+//=================================================================
+// generators
+
+#[test]
+fn a_generator_yields_in_emit_order_and_only_runs_when_polled() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let generator_log = Arc::clone(&log);
+    let mut numbers = stream(move |emitter| async move {
+        for i in 0..3 {
+            generator_log.lock().unwrap().push(format!("emit {}", i));
+            emitter.emit(i).await;
+        }
+        generator_log.lock().unwrap().push("done".to_string());
+    });
+    assert!(log.lock().unwrap().is_empty());
+    while let Poll::Ready(Some(i)) = poll(&mut numbers) {
+        log.lock().unwrap().push(format!("got {}", i));
+    }
+    assert_eq!(*log.lock().unwrap(), ["emit 0", "got 0", "emit 1", "got 1", "emit 2", "got 2", "done"]);
+    // an ended generator stays ended
+    assert_eq!(poll(&mut numbers), Poll::Ready(None));
+}
+
+#[test]
+fn map_and_filter_keep_the_order() {
+    let numbers = stream(|emitter| async move {
+        for i in 0..10 {
+            emitter.emit(i).await;
+        }
+    });
+    assert_eq!(collect(numbers.filter(|i| i % 3 == 0).map(|i| i * 10)), [0, 30, 60, 90]);
+}
+
+#[test]
+fn the_fibonacci_feed_ends_before_overflowing() {
+    assert_eq!(collect(fibonacci(6, Duration::ZERO)), [1, 2, 3, 5, 8, 13]);
+    let all = collect(fibonacci(u64::MAX, Duration::ZERO));
+    assert_eq!(all.len(), 92);
+    assert!(all.last().unwrap().checked_add(all[all.len() - 2]).is_none());
+}
+
+//=================================================================
+// buffer_unordered
+
+#[test]
+fn buffer_unordered_never_runs_more_than_its_limit_and_yields_as_they_finish() {
+    let timer = MockTimer::new();
+    let running = Arc::new(AtomicUsize::new(0));
+    let most = Arc::new(AtomicUsize::new(0));
+    let jobs = [5, 1, 3, 2, 4, 1].into_iter().enumerate().map(|(job, seconds)| {
+        let (timer, running, most) = (timer.clone(), Arc::clone(&running), Arc::clone(&most));
+        async move {
+            most.fetch_max(running.fetch_add(1, Ordering::SeqCst) + 1, Ordering::SeqCst);
+            timer.sleep(Duration::from_secs(seconds)).await;
+            running.fetch_sub(1, Ordering::SeqCst);
+            job
+        }
+    });
+    let finished = drain(tokio_stream::iter(jobs).buffer_unordered(2), &timer);
+    // two at a time: 1 ends at 1s, 2 starts and ends at 4s, 0 at 5s, 3 at 6s, 5 at 7s and 4 at 9s
+    assert_eq!(finished, [(1, 1), (4, 2), (5, 0), (6, 3), (7, 5), (9, 4)]);
+    assert_eq!(most.load(Ordering::SeqCst), 2);
+    assert_eq!(running.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+#[should_panic(expected = "buffer_unordered needs room for at least one future")]
+fn buffer_unordered_needs_a_limit() {
+    let _ = tokio_stream::iter([async { 1 }]).buffer_unordered(0);
+}
+
+//=================================================================
+// throttle and chunks_timeout, on a mocked clock
+
+#[test]
+fn throttle_lets_one_item_through_per_period() {
+    let timer = MockTimer::new();
+    let items = drain(tokio_stream::iter(0..4).throttle(Duration::from_secs(10)).timer(&timer), &timer);
+    assert_eq!(items, [(0, 0), (10, 1), (20, 2), (30, 3)]);
+    // the pause after the last item is over too
+    assert_eq!(timer.pending(), 0);
+}
+
+#[test]
+fn chunks_are_flushed_when_full_when_their_time_is_up_and_at_the_end() {
+    let timer = MockTimer::new();
+    let source_timer = timer.clone();
+    let values = stream(move |emitter| async move {
+        for value in 1..=4 {
+            emitter.emit(value).await;
+        }
+        source_timer.sleep(Duration::from_secs(10)).await;
+        emitter.emit(5).await;
+        emitter.emit(6).await;
+    });
+    let chunks = drain(values.chunks_timeout(3, Duration::from_secs(5)).timer(&timer), &timer);
+    // full at once, 4 waits for the timeout, 5 and 6 are what is left when the stream ends
+    assert_eq!(chunks, [(0, vec![1, 2, 3]), (5, vec![4]), (10, vec![5, 6])]);
+}
+
+#[test]
+fn an_empty_stream_has_no_chunks() {
+    let timer = MockTimer::new();
+    let chunks = drain(tokio_stream::iter(Vec::<u32>::new()).chunks_timeout(3, Duration::from_secs(5)).timer(&timer), &timer);
+    assert!(chunks.is_empty());
+}
+
+//=================================================================
+// merge
+
+#[test]
+fn merge_takes_turns_when_both_sides_are_ready() {
+    let mut merged = tokio_stream::iter(std::iter::repeat('a')).merge(tokio_stream::iter(std::iter::repeat('b')));
+    let taken: Vec<char> = (0..8).map(|_| poll(&mut merged)).map(|item| match item {
+        Poll::Ready(Some(side)) => side,
+        other => panic!("{:?}", other)
+    }).collect();
+    assert!(taken.windows(2).all(|pair| pair[0] != pair[1]), "{:?}", taken);
+}
+
+#[test]
+fn merge_ends_only_when_both_sides_have_ended() {
+    let merged = collect(tokio_stream::iter(0..2).merge(tokio_stream::iter(10..15)));
+    let mut sorted = merged.clone();
+    sorted.sort_unstable();
+    assert_eq!(sorted, [0, 1, 10, 11, 12, 13, 14]);
+    // each side keeps its own order
+    assert_eq!(merged.iter().filter(|&&i| i >= 10).copied().collect::<Vec<_>>(), [10, 11, 12, 13, 14]);
+
+    // a side that is only waiting keeps the merge open
+    let (sender, receiver) = unbounded();
+    let mut merged = tokio_stream::iter(0..2).merge(from_receiver(receiver));
+    assert_eq!((poll(&mut merged), poll(&mut merged)), (Poll::Ready(Some(0)), Poll::Ready(Some(1))));
+    assert_eq!(poll(&mut merged), Poll::Pending);
+    sender.send(7).unwrap();
+    assert_eq!(poll(&mut merged), Poll::Ready(Some(7)));
+    drop(sender);
+    assert_eq!(poll(&mut merged), Poll::Ready(None));
+}
+
+//=================================================================
+// channel adapters
+
+#[test]
+fn a_receiver_stream_ends_once_every_sender_is_gone_and_the_queue_is_empty() {
+    let (sender, receiver) = unbounded();
+    let mut messages = from_receiver(receiver);
+    assert_eq!(poll(&mut messages), Poll::Pending);
+    let second = sender.clone();
+    sender.send(1).unwrap();
+    second.send(2).unwrap();
+    drop(sender);
+    assert_eq!(poll(&mut messages), Poll::Ready(Some(1)));
+    // one sender is still there
+    assert_eq!(poll(&mut messages), Poll::Ready(Some(2)));
+    assert_eq!(poll(&mut messages), Poll::Pending);
+    second.send(3).unwrap();
+    drop(second);
+    // what was sent before the last sender went away still comes out
+    assert_eq!(poll(&mut messages), Poll::Ready(Some(3)));
+    assert_eq!(poll(&mut messages), Poll::Ready(None));
+    assert!(messages.into_inner().is_disconnected());
+}
+
+#[test]
+fn a_receiver_stream_is_fed_by_a_thread() {
+    let (sender, receiver) = bounded(2);
+    let producer = thread::spawn(move || {
+        for i in 0..20 {
+            sender.send(i).unwrap();
+        }
+    });
+    assert_eq!(collect(from_receiver(receiver)), (0..20).collect::<Vec<_>>());
+    producer.join().unwrap();
+}
+
+#[test]
+fn forward_pours_a_stream_into_a_bounded_channel() {
+    let (sender, receiver) = bounded(1);
+    let consumer = thread::spawn(move || receiver.iter().collect::<Vec<u32>>());
+    block_on(forward(tokio_stream::iter(0..10), &sender)).unwrap();
+    drop(sender);
+    assert_eq!(consumer.join().unwrap(), (0..10).collect::<Vec<_>>());
+}
+
+#[test]
+fn forward_stops_when_nobody_receives() {
+    let (sender, receiver) = bounded(1);
+    drop(receiver);
+    let error = block_on(forward(tokio_stream::iter(["first", "second"]), &sender)).unwrap_err();
+    assert!(error == SendError("first"));
+}
@@ -0,0 +1,319 @@
+use std::collections::{HashSet, VecDeque};
+use std::hash::Hash;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use rand::Rng;
+use rust_practice_lab::atomics::*;
+
+//=================================================================
+// history recording and a brute-force linearisability check (Wing & Gong)
+//
+// every operation gets an invoke and a response timestamp from one shared clock
+// a history is linearisable when the operations can be put in one sequential order that
+//     1. keeps a before b whenever a responded before b was invoked
+//     2. gives every operation the result it actually returned, when replayed on the sequential spec
+
+#[derive(Debug, Clone)]
+struct Event<C, R> {
+    call: C,
+    result: R,
+    invoke: u64,
+    response: u64
+}
+
+struct Recorder<C, R> {
+    clock: AtomicU64,
+    events: Mutex<Vec<Event<C, R>>>
+}
+
+impl<C, R> Recorder<C, R> {
+    fn new() -> Self {
+        Self { clock: AtomicU64::new(0), events: Mutex::new(Vec::new()) }
+    }
+
+    fn record<F: FnOnce() -> R>(&self, call: C, operation: F) -> R
+    where
+        R: Clone
+    {
+        let invoke = self.clock.fetch_add(1, Ordering::SeqCst);
+        let result = operation();
+        let response = self.clock.fetch_add(1, Ordering::SeqCst);
+        self.events.lock().unwrap().push(Event { call, result: result.clone(), invoke, response });
+        result
+    }
+
+    fn into_history(self) -> Vec<Event<C, R>> {
+        self.events.into_inner().unwrap()
+    }
+}
+
+fn is_linearizable<S, C, R, F>(history: &[Event<C, R>], initial: S, step: F) -> bool
+where
+    S: Clone + Hash + Eq,
+    R: PartialEq,
+    F: Fn(&mut S, &C) -> R
+{
+    assert!(history.len() <= 64, "the search keeps the remaining operations in a u64");
+    fn search<S, C, R, F>(history: &[Event<C, R>], remaining: u64, state: S, step: &F, seen: &mut HashSet<(u64, S)>) -> bool
+    where
+        S: Clone + Hash + Eq,
+        R: PartialEq,
+        F: Fn(&mut S, &C) -> R
+    {
+        if remaining == 0 {
+            return true;
+        }
+        if !seen.insert((remaining, state.clone())) {
+            return false;
+        }
+        let pending = |i: usize| remaining & (1 << i) != 0;
+        for candidate in (0..history.len()).filter(|&i| pending(i)) {
+            // an operation can go next only if nothing still pending finished before it started
+            let blocked = (0..history.len()).any(|other| pending(other) && history[other].response < history[candidate].invoke);
+            if blocked {
+                continue;
+            }
+            let mut next_state = state.clone();
+            if step(&mut next_state, &history[candidate].call) == history[candidate].result
+                && search(history, remaining & !(1 << candidate), next_state, step, seen)
+            {
+                return true;
+            }
+        }
+        false
+    }
+    let all = if history.len() == 64 { u64::MAX } else { (1u64 << history.len()) - 1 };
+    search(history, all, initial, &step, &mut HashSet::new())
+}
+
+const HISTORY_THREADS: usize = 3;
+const OPERATIONS_PER_HISTORY_THREAD: usize = 5;
+const HISTORIES: usize = 300;
+
+// runs small random workloads on a fresh structure and checks every recorded history
+fn check_histories<D, C, R, S, New, Run, Spec>(new: New, initial: S, run: Run, spec: Spec)
+where
+    D: Sync,
+    C: Send + Clone + std::fmt::Debug,
+    R: Send + Clone + PartialEq + std::fmt::Debug,
+    S: Clone + Hash + Eq,
+    New: Fn() -> D,
+    Run: Fn(&D, &Recorder<C, R>, usize) + Sync,
+    Spec: Fn(&mut S, &C) -> R
+{
+    for _ in 0..HISTORIES {
+        let structure = new();
+        let recorder = Recorder::new();
+        thread::scope(|scope| {
+            for t in 0..HISTORY_THREADS {
+                let (structure, recorder, run) = (&structure, &recorder, &run);
+                scope.spawn(move || run(structure, recorder, t));
+            }
+        });
+        let history = recorder.into_history();
+        assert!(is_linearizable(&history, initial.clone(), &spec), "history is not linearizable: {:#?}", history);
+    }
+}
+
+//=================================================================
+
+#[derive(Debug, Clone, Copy)]
+enum StackCall {
+    Push(u32),
+    Pop
+}
+
+#[test]
+fn treiber_stack_histories_are_linearizable() {
+    check_histories(
+        TreiberStack::new,
+        Vec::<u32>::new(),
+        |stack, recorder, t| {
+            for i in 0..OPERATIONS_PER_HISTORY_THREAD {
+                if rand::thread_rng().gen_bool(0.5) {
+                    let value = (t * 100 + i) as u32;
+                    recorder.record(StackCall::Push(value), || { stack.push(value); None });
+                } else {
+                    recorder.record(StackCall::Pop, || stack.pop());
+                }
+            }
+        },
+        |state, call| match call {
+            StackCall::Push(value) => { state.push(*value); None }
+            StackCall::Pop => state.pop()
+        }
+    );
+}
+
+#[derive(Debug, Clone, Copy)]
+enum QueueCall {
+    Enqueue(u32),
+    Dequeue
+}
+
+#[test]
+fn ms_queue_histories_are_linearizable() {
+    check_histories(
+        MsQueue::new,
+        VecDeque::<u32>::new(),
+        |queue, recorder, t| {
+            for i in 0..OPERATIONS_PER_HISTORY_THREAD {
+                if rand::thread_rng().gen_bool(0.5) {
+                    let value = (t * 100 + i) as u32;
+                    recorder.record(QueueCall::Enqueue(value), || { queue.enqueue(value); None });
+                } else {
+                    recorder.record(QueueCall::Dequeue, || queue.dequeue());
+                }
+            }
+        },
+        |state, call| match call {
+            QueueCall::Enqueue(value) => { state.push_back(*value); None }
+            QueueCall::Dequeue => state.pop_front()
+        }
+    );
+}
+
+#[derive(Debug, Clone, Copy)]
+enum MaxCall {
+    Update(i64),
+    Get
+}
+
+#[test]
+fn atomic_max_histories_are_linearizable() {
+    check_histories(
+        || AtomicMax::new(0),
+        0i64,
+        |register, recorder, _| {
+            for _ in 0..OPERATIONS_PER_HISTORY_THREAD {
+                if rand::thread_rng().gen_bool(0.6) {
+                    let candidate = rand::thread_rng().gen_range(0..1000);
+                    recorder.record(MaxCall::Update(candidate), || register.update(candidate) as i64);
+                } else {
+                    recorder.record(MaxCall::Get, || register.get());
+                }
+            }
+        },
+        |state, call| match call {
+            MaxCall::Update(candidate) if candidate > state => { *state = *candidate; 1 }
+            MaxCall::Update(_) => 0,
+            MaxCall::Get => *state
+        }
+    );
+}
+
+//=================================================================
+// bigger runs without the history check, they look for lost or duplicated values
+
+const STRESS_THREADS: usize = 8;
+const STRESS_OPERATIONS: usize = 20000;
+
+#[test]
+fn stack_stress_keeps_every_value() {
+    let stack = TreiberStack::new();
+    let popped: Vec<usize> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..STRESS_THREADS)
+            .map(|t| {
+                let stack = &stack;
+                scope.spawn(move || {
+                    let mut popped = Vec::new();
+                    for i in 0..STRESS_OPERATIONS {
+                        stack.push(t * STRESS_OPERATIONS + i);
+                        if i % 2 == 0 {
+                            popped.extend(stack.pop());
+                        }
+                    }
+                    popped
+                })
+            })
+            .collect();
+        workers.into_iter().flat_map(|worker| worker.join().unwrap()).collect()
+    });
+    let mut all: Vec<usize> = popped.into_iter().chain(std::iter::from_fn(|| stack.pop())).collect();
+    all.sort_unstable();
+    assert_eq!(all, (0..STRESS_THREADS * STRESS_OPERATIONS).collect::<Vec<_>>());
+}
+
+#[test]
+fn queue_stress_keeps_every_value_and_producer_order() {
+    let queue = MsQueue::new();
+    let consumed: Vec<Vec<(usize, usize)>> = thread::scope(|scope| {
+        for producer in 0..STRESS_THREADS / 2 {
+            let queue = &queue;
+            scope.spawn(move || (0..STRESS_OPERATIONS).for_each(|i| queue.enqueue((producer, i))));
+        }
+        let consumers: Vec<_> = (0..STRESS_THREADS / 2)
+            .map(|_| scope.spawn(|| (0..STRESS_OPERATIONS).filter_map(|_| queue.dequeue()).collect::<Vec<_>>()))
+            .collect();
+        consumers.into_iter().map(|consumer| consumer.join().unwrap()).collect()
+    });
+
+    // FIFO: every consumer sees the values of one producer in increasing order
+    for values in &consumed {
+        for producer in 0..STRESS_THREADS / 2 {
+            let from_producer: Vec<usize> = values.iter().filter(|(p, _)| *p == producer).map(|(_, i)| *i).collect();
+            assert!(from_producer.windows(2).all(|pair| pair[0] < pair[1]));
+        }
+    }
+    let total = consumed.iter().map(Vec::len).sum::<usize>() + std::iter::from_fn(|| queue.dequeue()).count();
+    assert_eq!(total, STRESS_THREADS / 2 * STRESS_OPERATIONS);
+}
+
+#[test]
+fn dropping_the_structures_drops_the_values_left_inside() {
+    let value = Arc::new(());
+    {
+        let stack = TreiberStack::new();
+        let queue = MsQueue::new();
+        for _ in 0..10 {
+            stack.push(Arc::clone(&value));
+            queue.enqueue(Arc::clone(&value));
+        }
+        drop(stack.pop());
+        drop(queue.dequeue());
+        assert_eq!(Arc::strong_count(&value), 19);
+    }
+    assert_eq!(Arc::strong_count(&value), 1);
+}
+
+#[test]
+fn sharded_counter_sum_stays_within_bounds() {
+    // sum() isn't linearisable, but it can't be lower than what finished before it started
+    // or higher than what started before it returned
+    let counter = ShardedCounter::new(4);
+    let started = AtomicU64::new(0);
+    let finished = AtomicU64::new(0);
+    thread::scope(|scope| {
+        for _ in 0..STRESS_THREADS {
+            scope.spawn(|| {
+                for _ in 0..STRESS_OPERATIONS {
+                    started.fetch_add(1, Ordering::SeqCst);
+                    counter.increment();
+                    finished.fetch_add(1, Ordering::SeqCst);
+                }
+            });
+        }
+        scope.spawn(|| {
+            for _ in 0..1000 {
+                let lower = finished.load(Ordering::SeqCst);
+                let sum = counter.sum();
+                let upper = started.load(Ordering::SeqCst);
+                assert!(lower <= sum && sum <= upper, "{} <= {} <= {}", lower, sum, upper);
+            }
+        });
+    });
+    assert_eq!(counter.sum(), (STRESS_THREADS * STRESS_OPERATIONS) as u64);
+}
+
+#[test]
+fn knapsack_with_atomic_max_finds_the_same_value() {
+    use rust_practice_lab::knapsack::*;
+    for _ in 0..5 {
+        let mut items: Vec<Item> = (0..14).map(|_| { let mut item = *Item::new(0, 0); item.randomize(); item }).collect();
+        let expected: i32 = get_knapsack_items(&mut items, 250).iter().map(|item| item.value).sum();
+        let found = get_knapsack_items_par_iter_atomic(&items, 250);
+        assert_eq!(found.iter().map(|item| item.value).sum::<i32>(), expected);
+        assert!(found.iter().map(|item| item.weight).sum::<i32>() <= 250);
+    }
+}
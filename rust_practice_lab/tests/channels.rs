@@ -0,0 +1,227 @@
+use std::collections::HashSet;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+use rust_practice_lab::channels::*;
+
+const PRODUCERS: usize = 8;
+const CONSUMERS: usize = 4;
+const MESSAGES_PER_PRODUCER: usize = 20000;
+
+// every producer sends its own range of numbers, so lost or duplicated messages are easy to spot
+fn producer_messages(producer: usize) -> impl Iterator<Item = usize> {
+    producer * MESSAGES_PER_PRODUCER..(producer + 1) * MESSAGES_PER_PRODUCER
+}
+
+fn run_ours(sender: Sender<usize>, receiver: Receiver<usize>) -> Vec<usize> {
+    let producers: Vec<_> = (0..PRODUCERS)
+        .map(|producer| {
+            let sender = sender.clone();
+            thread::spawn(move || producer_messages(producer).for_each(|message| sender.send(message).unwrap()))
+        })
+        .collect();
+    drop(sender);
+    let consumers: Vec<_> = (0..CONSUMERS)
+        .map(|_| {
+            let receiver = receiver.clone();
+            thread::spawn(move || receiver.iter().collect::<Vec<usize>>())
+        })
+        .collect();
+    drop(receiver);
+
+    producers.into_iter().for_each(|producer| producer.join().unwrap());
+    consumers.into_iter().flat_map(|consumer| consumer.join().unwrap()).collect()
+}
+
+// std's receiver can't be cloned, so the std run has a single consumer
+fn run_std() -> Vec<usize> {
+    let (sender, receiver) = mpsc::sync_channel(16);
+    let producers: Vec<_> = (0..PRODUCERS)
+        .map(|producer| {
+            let sender = sender.clone();
+            thread::spawn(move || producer_messages(producer).for_each(|message| sender.send(message).unwrap()))
+        })
+        .collect();
+    drop(sender);
+    let received = receiver.iter().collect();
+    producers.into_iter().for_each(|producer| producer.join().unwrap());
+    received
+}
+
+fn assert_exactly_once(mut received: Vec<usize>) {
+    assert_eq!(received.len(), PRODUCERS * MESSAGES_PER_PRODUCER);
+    received.sort_unstable();
+    assert!(received.iter().enumerate().all(|(i, &message)| i == message));
+}
+
+#[test]
+fn mpmc_stress_delivers_every_message_exactly_once() {
+    for capacity in [Some(1), Some(16), None] {
+        let (sender, receiver) = match capacity {
+            Some(capacity) => bounded(capacity),
+            None => unbounded()
+        };
+        let start = Instant::now();
+        let ours = run_ours(sender, receiver);
+        let our_time = start.elapsed();
+
+        let start = Instant::now();
+        let theirs = run_std();
+        let std_time = start.elapsed();
+        println!("capacity {:?}: ours {:?}, std::sync::mpsc {:?}", capacity, our_time, std_time);
+
+        assert_exactly_once(ours);
+        assert_exactly_once(theirs);
+    }
+}
+
+#[test]
+fn per_producer_order_is_kept() {
+    let (sender, receiver) = bounded(4);
+    let producers: Vec<_> = (0..PRODUCERS)
+        .map(|producer| {
+            let sender = sender.clone();
+            thread::spawn(move || producer_messages(producer).for_each(|message| sender.send((producer, message)).unwrap()))
+        })
+        .collect();
+    drop(sender);
+
+    let mut last_seen = [None; PRODUCERS];
+    for (producer, message) in receiver {
+        assert!(last_seen[producer].is_none_or(|last| last < message));
+        last_seen[producer] = Some(message);
+    }
+    producers.into_iter().for_each(|producer| producer.join().unwrap());
+}
+
+#[test]
+fn bounded_channel_applies_back_pressure() {
+    let (sender, receiver) = bounded(2);
+    sender.try_send(1).unwrap();
+    sender.try_send(2).unwrap();
+    assert!(matches!(sender.try_send(3), Err(TrySendError::Full(3))));
+    assert!(matches!(sender.send_timeout(3, Duration::from_millis(20)), Err(SendTimeoutError::Timeout(3))));
+
+    let blocked = thread::spawn(move || {
+        let start = Instant::now();
+        sender.send(3).unwrap();
+        start.elapsed()
+    });
+    thread::sleep(Duration::from_millis(50));
+    assert_eq!(receiver.recv(), Ok(1));
+    assert!(blocked.join().unwrap() >= Duration::from_millis(40));
+    assert_eq!(receiver.try_iter().collect::<Vec<_>>(), vec![2, 3]);
+}
+
+#[test]
+fn disconnect_is_detected_on_both_sides() {
+    let (sender, receiver) = unbounded();
+    let second_sender = sender.clone();
+    sender.send(1).unwrap();
+    drop(sender);
+    assert!(!receiver.is_disconnected());
+    drop(second_sender);
+    assert!(receiver.is_disconnected());
+    // messages that were already sent can still be received
+    assert_eq!(receiver.recv(), Ok(1));
+    assert_eq!(receiver.recv(), Err(RecvError));
+    assert_eq!(receiver.try_recv(), Err(TryRecvError::Disconnected));
+    assert_eq!(receiver.recv_timeout(Duration::from_millis(10)), Err(RecvTimeoutError::Disconnected));
+
+    let (sender, receiver) = bounded(1);
+    sender.send(1).unwrap();
+    // a sender blocked on a full channel wakes up when the last receiver goes away
+    let blocked = thread::spawn(move || sender.send(2));
+    thread::sleep(Duration::from_millis(20));
+    drop(receiver);
+    assert_eq!(blocked.join().unwrap(), Err(SendError(2)));
+}
+
+#[test]
+fn blocked_receivers_wake_up_on_disconnect() {
+    let (sender, receiver) = unbounded::<u8>();
+    let waiting: Vec<_> = (0..CONSUMERS)
+        .map(|_| {
+            let receiver = receiver.clone();
+            thread::spawn(move || receiver.recv())
+        })
+        .collect();
+    thread::sleep(Duration::from_millis(20));
+    drop(sender);
+    for receiver in waiting {
+        assert_eq!(receiver.join().unwrap(), Err(RecvError));
+    }
+}
+
+#[test]
+fn recv_timeout_waits_at_least_the_timeout() {
+    let (_sender, receiver) = unbounded::<u8>();
+    let start = Instant::now();
+    assert_eq!(receiver.recv_timeout(Duration::from_millis(30)), Err(RecvTimeoutError::Timeout));
+    assert!(start.elapsed() >= Duration::from_millis(30));
+}
+
+#[test]
+fn timeouts_too_long_for_an_instant_wait_without_a_deadline() {
+    let (sender, receiver) = bounded::<u8>(1);
+    sender.send(0).unwrap();
+    let (other_sender, other_receiver) = unbounded::<u8>();
+    let helper = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(20));
+        assert_eq!(receiver.recv_timeout(Duration::MAX), Ok(0));
+        assert_eq!(receiver.recv_timeout(Duration::MAX), Ok(1));
+        let mut select = Select::new();
+        select.recv(&receiver);
+        assert_eq!(select.ready_timeout(Duration::MAX), Some(0));
+        assert_eq!(select_timeout(&[&receiver], Duration::MAX), Ok((0, 2)));
+        other_sender.send(3).unwrap();
+    });
+    // the channel is full, this waits for the helper to make room
+    sender.send_timeout(1, Duration::MAX).unwrap();
+    thread::sleep(Duration::from_millis(20));
+    sender.send(2).unwrap();
+    assert_eq!(other_receiver.recv_timeout(Duration::MAX), Ok(3));
+    helper.join().unwrap();
+}
+
+#[test]
+fn select_receives_from_every_channel_until_all_are_disconnected() {
+    let (senders, receivers): (Vec<Sender<usize>>, Vec<Receiver<usize>>) = (0..4).map(|_| bounded(8)).unzip();
+    let producers: Vec<_> = senders
+        .into_iter()
+        .enumerate()
+        .map(|(channel, sender)| thread::spawn(move || (0..1000).for_each(|i| sender.send(channel * 1000 + i).unwrap())))
+        .collect();
+    let receivers: Vec<&Receiver<usize>> = receivers.iter().collect();
+
+    let mut seen = HashSet::new();
+    while let Ok((index, message)) = select(&receivers) {
+        assert_eq!(message / 1000, index);
+        assert!(seen.insert(message));
+    }
+    assert_eq!(seen.len(), 4000);
+    producers.into_iter().for_each(|producer| producer.join().unwrap());
+}
+
+#[test]
+fn select_over_different_message_types() {
+    let (number_sender, numbers) = unbounded::<u32>();
+    let (word_sender, words) = unbounded::<&str>();
+
+    let mut select = Select::new();
+    let number_index = select.recv(&numbers);
+    let word_index = select.recv(&words);
+    assert_eq!(select.try_ready(), None);
+    assert_eq!(select.ready_timeout(Duration::from_millis(10)), None);
+
+    thread::spawn(move || {
+        thread::sleep(Duration::from_millis(20));
+        word_sender.send("hello").unwrap();
+    });
+    assert_eq!(select.ready(), word_index);
+    assert_eq!(words.try_recv(), Ok("hello"));
+
+    number_sender.send(7).unwrap();
+    assert_eq!(select.ready(), number_index);
+    assert_eq!(numbers.try_recv(), Ok(7));
+}
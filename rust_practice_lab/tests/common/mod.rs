@@ -0,0 +1,32 @@
+// shared by the tests of the vehicle model, every test file gets it with `mod common;`
+// not every test file uses every fixture
+#![allow(dead_code)]
+
+// the vehicle model lives with the language_fundamentals binaries, it is included the same way iterators.rs does
+#[allow(clippy::module_inception)]
+#[path = "../../src/bin/language_fundamentals/traits/mod.rs"]
+pub mod traits;
+
+use traits::default_type_parameters::{Length, Mass, Money, Power};
+use traits::traits::poly::{AbstractVehicle, Airplane, AmphibiousBoat, Boat, Car};
+use traits::traits::Vehicles;
+
+pub fn vehicle(name: &str, price: f64) -> AbstractVehicle {
+    AbstractVehicle { name: name.to_string(), price: Money::new(price) }
+}
+
+pub fn car(name: &str, price: f64, horse_power: f64) -> Vehicles {
+    Car::new(vehicle(name, price), Power::from_horse_power(horse_power)).into()
+}
+
+// the same vehicles the iterators demo puts on sale, minus the jet
+pub fn on_sale() -> Vec<Vehicles> {
+    vec![
+        car("Family Car", 32000.0, 150.0),
+        car("Sports Car", 95000.0, 450.0),
+        car("Old Van", 8000.0, 90.0),
+        Boat::new(vehicle("Fishing Boat", 45000.0), Mass::from_kilograms(40.0)).into(),
+        AmphibiousBoat::new(Boat::new(vehicle("Duck Tour", 120000.0), Mass::from_kilograms(20.0)), 6).into(),
+        Airplane::new(vehicle("Crop Duster", 180000.0), Length::from_meters(12.0)).into()
+    ]
+}
@@ -0,0 +1,86 @@
+use std::error::Error;
+use std::io::{self, Write};
+use tokio_stream::StreamExt;
+use rust_practice_lab::counting::*;
+
+#[test]
+fn counts_from_one_up_to_and_including_the_limit() {
+    assert_eq!(Counter::new(5).iter().unwrap().collect::<Vec<i32>>(), [1, 2, 3, 4, 5]);
+    assert_eq!(Counter::new(10).start(3).step(3).iter().unwrap().collect::<Vec<i32>>(), [3, 6, 9]);
+    assert_eq!(Counter::new(-2).start(-4).iter().unwrap().collect::<Vec<i32>>(), [-4, -3, -2]);
+    // a start past the limit counts nothing, that is not an error
+    assert_eq!(Counter::new(1).start(2).iter().unwrap().count(), 0);
+}
+
+#[test]
+fn the_limit_is_capped() {
+    assert_eq!(Counter::new(MAX_COUNTING_LIMIT).iter().unwrap().count(), MAX_COUNTING_LIMIT as usize);
+    let error = Counter::new(MAX_COUNTING_LIMIT + 1).iter().err().unwrap();
+    assert!(matches!(error, CountingError::LimitTooHigh { limit, max } if limit == MAX_COUNTING_LIMIT + 1 && max == MAX_COUNTING_LIMIT));
+    assert_eq!(error.to_string(), format!("Limit {} too high! Choose something below {}!", MAX_COUNTING_LIMIT + 1, MAX_COUNTING_LIMIT));
+    assert!(error.source().is_none());
+}
+
+#[test]
+fn the_step_has_to_be_positive() {
+    for step in [0, -1, i32::MIN] {
+        let error = Counter::new(10).step(step).iter().err().unwrap();
+        assert!(matches!(error, CountingError::InvalidStep(invalid) if invalid == step));
+    }
+    assert_eq!(Counter::new(10).step(0).iter().err().unwrap().to_string(), "Step 0 is invalid, the step has to be at least 1");
+    // the limit is checked first
+    assert!(matches!(Counter::new(i32::MAX).step(0).iter(), Err(CountingError::LimitTooHigh { .. })));
+}
+
+#[test]
+fn for_each_calls_back_once_per_number() {
+    let mut seen = Vec::new();
+    assert_eq!(Counter::new(9).step(4).for_each(|number| seen.push(number)).unwrap(), 3);
+    assert_eq!(seen, [1, 5, 9]);
+
+    let mut called = false;
+    assert!(Counter::new(10).step(0).for_each(|_| called = true).is_err());
+    assert!(!called);
+}
+
+#[test]
+fn write_to_writes_one_number_per_line() {
+    let mut out = Vec::new();
+    assert_eq!(Counter::new(3).write_to(&mut out).unwrap(), 3);
+    assert_eq!(String::from_utf8(out).unwrap(), "1\n2\n3\n");
+
+    // an invalid counter writes nothing at all
+    let mut out = Vec::new();
+    assert!(Counter::new(MAX_COUNTING_LIMIT + 1).write_to(&mut out).is_err());
+    assert!(out.is_empty());
+}
+
+struct BrokenPipe;
+
+impl Write for BrokenPipe {
+    fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+        Err(io::Error::new(io::ErrorKind::BrokenPipe, "reader went away"))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn write_errors_come_back_as_io_errors() {
+    let error = Counter::new(3).write_to(&mut BrokenPipe).unwrap_err();
+    let CountingError::Io(io_error) = &error else { panic!("expected an io error, got {:?}", error) };
+    assert_eq!(io_error.kind(), io::ErrorKind::BrokenPipe);
+    assert_eq!(error.to_string(), "Could not write the count: reader went away");
+    assert_eq!(error.source().unwrap().to_string(), "reader went away");
+}
+
+#[tokio::test]
+async fn stream_yields_the_same_numbers_as_iter() {
+    let counter = Counter::new(20).start(2).step(5);
+    let streamed: Vec<i32> = counter.stream().unwrap().collect().await;
+    assert_eq!(streamed, counter.iter().unwrap().collect::<Vec<i32>>());
+    assert_eq!(streamed, [2, 7, 12, 17]);
+    assert!(Counter::new(5).step(-3).stream().is_err());
+}
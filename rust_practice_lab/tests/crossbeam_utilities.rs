@@ -0,0 +1,262 @@
+use std::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use rust_practice_lab::atomics::{MsQueue, TreiberStack};
+use rust_practice_lab::crossbeam_utilities::epoch::Collector;
+use rust_practice_lab::crossbeam_utilities::hazard::Domain;
+use rust_practice_lab::crossbeam_utilities::HazardQueue;
+
+// these tests also run under Miri (cargo +nightly miri test --test crossbeam_utilities), which reports
+// use-after-free and leaked allocations on its own. Miri is slow, so it gets smaller workloads
+const fn scaled(operations: usize) -> usize {
+    if cfg!(miri) { operations / 100 + 1 } else { operations }
+}
+
+const THREADS: usize = 4;
+
+// counts its drops and poisons its memory, a reader that finds a dead canary has read freed memory
+struct Canary {
+    alive: usize,
+    drops: Arc<AtomicUsize>
+}
+
+const ALIVE: usize = 0xC0FFEE;
+
+impl Canary {
+    fn new(drops: &Arc<AtomicUsize>) -> Self {
+        Self { alive: ALIVE, drops: Arc::clone(drops) }
+    }
+}
+
+impl Drop for Canary {
+    fn drop(&mut self) {
+        assert_eq!(self.alive, ALIVE, "dropped twice");
+        self.alive = 0;
+        self.drops.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+//=================================================================
+// epochs
+
+#[test]
+fn epoch_frees_all_garbage_when_the_collector_goes_away() {
+    let drops = Arc::new(AtomicUsize::new(0));
+    let per_thread = scaled(1000);
+    {
+        let collector = Collector::new();
+        thread::scope(|scope| {
+            for _ in 0..THREADS {
+                let (collector, drops) = (&collector, &drops);
+                scope.spawn(move || {
+                    let handle = collector.register();
+                    for _ in 0..per_thread {
+                        let guard = handle.pin();
+                        let canary = Box::into_raw(Box::new(Canary::new(drops)));
+                        unsafe { guard.defer_destroy(canary) };
+                    }
+                });
+            }
+        });
+    }
+    assert_eq!(drops.load(Ordering::SeqCst), THREADS * per_thread);
+}
+
+#[test]
+fn epoch_pinned_thread_holds_back_garbage() {
+    let collector = Collector::new();
+    let drops = Arc::new(AtomicUsize::new(0));
+    let reader = collector.register();
+    let writer = collector.register();
+
+    let reader_guard = reader.pin();
+    for _ in 0..200 {
+        let guard = writer.pin();
+        unsafe { guard.defer_destroy(Box::into_raw(Box::new(Canary::new(&drops)))) };
+    }
+    for _ in 0..5 {
+        writer.pin().flush();
+    }
+    assert_eq!(drops.load(Ordering::SeqCst), 0, "garbage was freed while a thread that could see it was pinned");
+
+    drop(reader_guard);
+    for _ in 0..5 {
+        writer.pin().flush();
+    }
+    assert_eq!(drops.load(Ordering::SeqCst), 200);
+}
+
+#[test]
+fn epoch_guards_nest() {
+    let collector = Collector::new();
+    let handle = collector.register();
+    let outer = handle.pin();
+    let inner = handle.pin();
+    drop(outer);
+    assert!(handle.is_pinned());
+    drop(inner);
+    assert!(!handle.is_pinned());
+}
+
+#[test]
+fn epoch_deferred_closures_run() {
+    let ran = Arc::new(AtomicUsize::new(0));
+    {
+        let collector = Collector::new();
+        let handle = collector.register();
+        for _ in 0..10 {
+            let ran = Arc::clone(&ran);
+            handle.pin().defer(move || { ran.fetch_add(1, Ordering::SeqCst); });
+        }
+    }
+    assert_eq!(ran.load(Ordering::SeqCst), 10);
+}
+
+// writers keep replacing a shared canary, readers check that the one they loaded is still alive
+#[test]
+fn epoch_readers_never_see_freed_memory() {
+    let drops = Arc::new(AtomicUsize::new(0));
+    let swaps = scaled(5000);
+    {
+        let collector = Collector::new();
+        let shared = AtomicPtr::new(Box::into_raw(Box::new(Canary::new(&drops))));
+        thread::scope(|scope| {
+            for t in 0..THREADS {
+                let (collector, shared, drops) = (&collector, &shared, &drops);
+                scope.spawn(move || {
+                    let handle = collector.register();
+                    for _ in 0..swaps {
+                        let guard = handle.pin();
+                        if t % 2 == 0 {
+                            let old = shared.swap(Box::into_raw(Box::new(Canary::new(drops))), Ordering::AcqRel);
+                            unsafe { guard.defer_destroy(old) };
+                        } else {
+                            let current = shared.load(Ordering::Acquire);
+                            assert_eq!(unsafe { (*current).alive }, ALIVE);
+                        }
+                    }
+                });
+            }
+        });
+        drop(unsafe { Box::from_raw(shared.into_inner()) });
+    }
+    assert_eq!(drops.load(Ordering::SeqCst), THREADS / 2 * swaps + 1);
+}
+
+//=================================================================
+// hazard pointers
+
+#[test]
+fn hazard_protected_node_is_not_freed() {
+    let drops = Arc::new(AtomicUsize::new(0));
+    let domain = Domain::new();
+    let shared = AtomicPtr::new(Box::into_raw(Box::new(Canary::new(&drops))));
+
+    let hazard = domain.hazard_pointer();
+    let protected = hazard.protect(&shared);
+    let old = shared.swap(Box::into_raw(Box::new(Canary::new(&drops))), Ordering::AcqRel);
+    assert_eq!(old, protected);
+    unsafe { domain.retire(old) };
+    assert_eq!(domain.reclaim(), 0);
+    assert_eq!(unsafe { (*protected).alive }, ALIVE);
+
+    hazard.reset();
+    assert_eq!(domain.reclaim(), 1);
+    assert_eq!(drops.load(Ordering::SeqCst), 1);
+    drop(unsafe { Box::from_raw(shared.into_inner()) });
+}
+
+#[test]
+fn hazard_domain_frees_everything_retired_when_dropped() {
+    let drops = Arc::new(AtomicUsize::new(0));
+    {
+        let domain = Domain::new();
+        for _ in 0..10 {
+            unsafe { domain.retire(Box::into_raw(Box::new(Canary::new(&drops)))) };
+        }
+        assert_eq!(domain.retired_count(), 10);
+    }
+    assert_eq!(drops.load(Ordering::SeqCst), 10);
+}
+
+#[test]
+fn hazard_readers_never_see_freed_memory() {
+    let drops = Arc::new(AtomicUsize::new(0));
+    let swaps = scaled(5000);
+    {
+        let domain = Domain::new();
+        let shared = AtomicPtr::new(Box::into_raw(Box::new(Canary::new(&drops))));
+        thread::scope(|scope| {
+            for t in 0..THREADS {
+                let (domain, shared, drops) = (&domain, &shared, &drops);
+                scope.spawn(move || {
+                    let hazard = domain.hazard_pointer();
+                    for _ in 0..swaps {
+                        if t % 2 == 0 {
+                            let old = shared.swap(Box::into_raw(Box::new(Canary::new(drops))), Ordering::AcqRel);
+                            unsafe { domain.retire(old) };
+                        } else {
+                            let current = hazard.protect(shared);
+                            assert_eq!(unsafe { (*current).alive }, ALIVE);
+                            hazard.reset();
+                        }
+                    }
+                });
+            }
+        });
+        drop(unsafe { Box::from_raw(shared.into_inner()) });
+    }
+    assert_eq!(drops.load(Ordering::SeqCst), THREADS / 2 * swaps + 1);
+}
+
+//=================================================================
+// the queues built on top
+
+#[test]
+fn hazard_queue_keeps_every_value() {
+    let per_thread = scaled(20000);
+    let queue = HazardQueue::new();
+    let consumed: usize = thread::scope(|scope| {
+        for producer in 0..THREADS / 2 {
+            let queue = &queue;
+            scope.spawn(move || (0..per_thread).for_each(|i| queue.enqueue(producer * per_thread + i)));
+        }
+        let consumers: Vec<_> = (0..THREADS / 2)
+            .map(|_| scope.spawn(|| (0..per_thread).filter_map(|_| queue.dequeue()).count()))
+            .collect();
+        consumers.into_iter().map(|consumer| consumer.join().unwrap()).sum()
+    });
+    let left_over = std::iter::from_fn(|| queue.dequeue()).count();
+    assert_eq!(consumed + left_over, THREADS / 2 * per_thread);
+    assert!(queue.is_empty());
+}
+
+#[test]
+fn queues_drop_every_value_exactly_once() {
+    let drops = Arc::new(AtomicUsize::new(0));
+    let per_thread = scaled(2000);
+    {
+        let hazard_queue = HazardQueue::new();
+        let epoch_queue = MsQueue::new();
+        let stack = TreiberStack::new();
+        thread::scope(|scope| {
+            for _ in 0..THREADS {
+                let (hazard_queue, epoch_queue, stack, drops) = (&hazard_queue, &epoch_queue, &stack, &drops);
+                scope.spawn(move || {
+                    for i in 0..per_thread {
+                        hazard_queue.enqueue(Canary::new(drops));
+                        epoch_queue.enqueue(Canary::new(drops));
+                        stack.push(Canary::new(drops));
+                        // pop half, the other half is dropped together with the structures
+                        if i % 2 == 0 {
+                            for canary in [hazard_queue.dequeue(), epoch_queue.dequeue(), stack.pop()].into_iter().flatten() {
+                                assert_eq!(canary.alive, ALIVE);
+                            }
+                        }
+                    }
+                });
+            }
+        });
+    }
+    assert_eq!(drops.load(Ordering::SeqCst), 3 * THREADS * per_thread);
+}
@@ -0,0 +1,169 @@
+mod common;
+
+use std::time::Duration;
+use serde_json::json;
+use common::{on_sale, traits};
+use traits::default_type_parameters::{Eur, ExchangeRate, Length, Mass, Money, Power, Speed, Usd};
+use traits::traits::poly::Vehicle;
+
+// unit conversions go through an f64 factor, so they are compared with a little room
+fn assert_close(actual: f64, expected: f64) {
+    assert!((actual - expected).abs() <= 1e-9 * expected.abs().max(1.0), "{} != {}", actual, expected);
+}
+
+//=================================================================
+// unit conversions
+
+#[test]
+fn power_converts_between_watts_kilowatts_and_horse_power() {
+    assert_close(Power::from_horse_power(1.0).watts(), 745.699872);
+    assert_close(Power::from_kilowatts(1.5).watts(), 1500.0);
+    assert_close(Power::from_watts(745.699872 * 150.0).horse_power(), 150.0);
+    assert_close(Power::from_horse_power(450.0).kilowatts(), 335.5649424);
+}
+
+#[test]
+fn mass_converts_between_kilograms_pounds_and_tonnes() {
+    assert_close(Mass::from_pounds(1.0).kilograms(), 0.45359237);
+    assert_close(Mass::from_tonnes(2.5).kilograms(), 2500.0);
+    assert_close(Mass::from_kilograms(0.45359237 * 40.0).pounds(), 40.0);
+    assert_close(Mass::from_kilograms(1200.0).tonnes(), 1.2);
+}
+
+#[test]
+fn length_converts_between_metric_imperial_and_nautical_units() {
+    assert_close(Length::from_feet(1.0).meters(), 0.3048);
+    assert_close(Length::from_miles(1.0).meters(), 1609.344);
+    assert_close(Length::from_nautical_miles(1.0).meters(), 1852.0);
+    assert_close(Length::from_kilometers(3.0).meters(), 3000.0);
+    assert_close(Length::from_meters(1852.0).nautical_miles(), 1.0);
+    assert_close(Length::from_meters(3048.0).feet(), 10000.0);
+    assert_close(Length::from_meters(1609.344 * 26.2).miles(), 26.2);
+    assert_close(Length::from_meters(42195.0).kilometers(), 42.195);
+}
+
+#[test]
+fn speed_converts_between_every_unit() {
+    assert_close(Speed::from_kilometers_per_hour(36.0).meters_per_second(), 10.0);
+    assert_close(Speed::from_miles_per_hour(60.0).kilometers_per_hour(), 96.56064);
+    assert_close(Speed::from_knots(10.0).kilometers_per_hour(), 18.52);
+    assert_close(Speed::from_meters_per_second(10.0).knots(), 10.0 * 3600.0 / 1852.0);
+    assert_close(Speed::from_kilometers_per_hour(96.56064).miles_per_hour(), 60.0);
+}
+
+#[test]
+fn a_round_trip_through_another_unit_keeps_the_value() {
+    for value in [0.0, 1.0, 12.5, 4500.0] {
+        assert_close(Power::from_horse_power(Power::from_watts(value).horse_power()).watts(), value);
+        assert_close(Mass::from_pounds(Mass::from_kilograms(value).pounds()).kilograms(), value);
+        assert_close(Length::from_feet(Length::from_meters(value).feet()).meters(), value);
+        assert_close(Speed::from_knots(Speed::from_meters_per_second(value).knots()).meters_per_second(), value);
+    }
+}
+
+//=================================================================
+// arithmetic on one quantity
+
+#[test]
+fn quantities_add_subtract_and_scale() {
+    assert_eq!(Mass::from_kilograms(20.0) + Mass::from_kilograms(40.0), Mass::from_kilograms(60.0));
+    assert_eq!(Length::from_meters(12.0) - Length::from_meters(2.0), Length::from_meters(10.0));
+    assert_eq!(Power::from_watts(300.0) * 2.0, Power::from_watts(600.0));
+    assert_eq!(Speed::from_meters_per_second(30.0) / 3.0, Speed::from_meters_per_second(10.0));
+}
+
+#[test]
+fn dividing_a_quantity_by_itself_gives_a_plain_number() {
+    let ratio: f64 = Mass::from_kilograms(40.0) / Mass::from_kilograms(20.0);
+    assert_eq!(ratio, 2.0);
+    assert_eq!(Length::from_kilometers(1.0) / Length::from_meters(250.0), 4.0);
+}
+
+#[test]
+fn quantities_sum_and_an_empty_sum_is_zero() {
+    let total: Power = [150.0, 450.0, 90.0].into_iter().map(Power::from_watts).sum();
+    assert_eq!(total, Power::from_watts(690.0));
+    assert_eq!(std::iter::empty::<Mass>().sum::<Mass>(), Mass::default());
+}
+
+//=================================================================
+// derived units
+
+#[test]
+fn length_over_time_is_a_speed_and_back() {
+    let speed = Length::from_kilometers(100.0) / Duration::from_secs(3600);
+    assert_close(speed.kilometers_per_hour(), 100.0);
+    assert_close((speed * Duration::from_secs(1800)).kilometers(), 50.0);
+    assert_eq!(Length::from_meters(100.0) / Speed::from_meters_per_second(4.0), Duration::from_secs(25));
+}
+
+#[test]
+fn a_time_that_is_no_duration_is_none_when_checked() {
+    let speed = Speed::from_meters_per_second(4.0);
+    assert_eq!(Length::from_meters(100.0).checked_div(speed), Some(Duration::from_secs(25)));
+    assert_eq!(Length::from_meters(0.0).checked_div(speed), Some(Duration::ZERO));
+    assert_eq!(Length::from_meters(-100.0).checked_div(speed), None);
+    assert_eq!(Length::from_meters(f64::NAN).checked_div(speed), None);
+    assert_eq!(Length::from_meters(100.0).checked_div(Speed::from_meters_per_second(0.0)), None);
+    assert_eq!(Length::from_meters(0.0).checked_div(Speed::from_meters_per_second(0.0)), None);
+    assert_eq!(Length::from_meters(f64::MAX).checked_div(speed), None);
+}
+
+#[test]
+#[should_panic(expected = "a travel time has to be a finite number of seconds, zero or more")]
+fn dividing_by_a_zero_speed_panics() {
+    let _ = Length::from_meters(100.0) / Speed::from_meters_per_second(0.0);
+}
+
+#[test]
+fn power_over_mass_is_a_power_to_weight_ratio() {
+    let ratio = Power::from_horse_power(150.0) / Mass::from_tonnes(1.5);
+    assert_close(ratio.horse_power_per_tonne(), 100.0);
+    assert_close(ratio.watts_per_kilogram(), 745.699872 * 150.0 / 1500.0);
+    assert!(Power::from_horse_power(450.0) / Mass::from_tonnes(1.5) > ratio);
+}
+
+//=================================================================
+// money
+
+#[test]
+fn money_defaults_to_dollars() {
+    let price: Money = Money::new(4500.0);
+    assert_eq!(price.currency(), "USD");
+    assert_eq!(Money::<Eur>::new(4500.0).currency(), "EUR");
+    assert_eq!(price.to_string(), "4500 USD");
+}
+
+#[test]
+fn money_adds_scales_and_sums_in_one_currency() {
+    assert_eq!(Money::<Eur>::new(100.0) + Money::new(50.0), Money::new(150.0));
+    assert_eq!(Money::<Usd>::new(100.0) - Money::new(30.0), Money::new(70.0));
+    assert_eq!(Money::<Usd>::new(100.0) * 1.5, Money::new(150.0));
+    assert_eq!(Money::<Usd>::new(100.0) / 4.0, Money::new(25.0));
+    let total: Money = on_sale().iter().map(|vehicle| vehicle.price()).sum();
+    assert_eq!(total, Money::new(480000.0));
+}
+
+#[test]
+fn an_exchange_rate_converts_to_the_other_currency() {
+    let usd_to_eur = ExchangeRate::<Usd, Eur>::new(0.5);
+    let euros: Money<Eur> = Money::<Usd>::new(300.0) * usd_to_eur;
+    assert_eq!(euros, Money::new(150.0));
+    let dollars: Money<Usd> = euros * usd_to_eur.inverse();
+    assert_eq!(dollars, Money::new(300.0));
+}
+
+#[test]
+fn money_is_serialized_with_its_currency() {
+    assert_eq!(serde_json::to_value(Money::<Eur>::new(99.5)).unwrap(), json!({ "amount": 99.5, "currency": "EUR" }));
+    let read: Money<Eur> = serde_json::from_value(json!({ "amount": 99.5, "currency": "EUR" })).unwrap();
+    assert_eq!(read, Money::new(99.5));
+    let error = serde_json::from_value::<Money<Eur>>(json!({ "amount": 99.5, "currency": "USD" })).unwrap_err();
+    assert_eq!(error.to_string(), "expected an amount in EUR, got USD");
+}
+
+#[test]
+fn quantities_are_serialized_as_the_bare_si_number() {
+    assert_eq!(serde_json::to_value(Mass::from_tonnes(1.5)).unwrap(), json!(1500.0));
+    assert_eq!(serde_json::from_value::<Length>(json!(12.0)).unwrap(), Length::from_meters(12.0));
+}
@@ -0,0 +1,198 @@
+mod common;
+
+use std::time::{Duration, Instant};
+use rust_practice_lab::knapsack;
+use common::{car, on_sale, traits};
+use traits::default_type_parameters::Money;
+use traits::fleet_planning::{FleetPlanner, Plan, PlanError, Requirement, Utility};
+use traits::trait_objects::Capability;
+use traits::traits::poly::Vehicle;
+use traits::traits::{VehicleKind, Vehicles};
+
+// a car's value is its horse power, everything else is worth nothing
+fn horse_power_only() -> FleetPlanner {
+    FleetPlanner::new().utility(Utility::new().per_horse_power(1.0))
+}
+
+fn names(plan: &Plan) -> Vec<String> {
+    plan.chosen.iter().map(|vehicle| vehicle.name().to_string()).collect()
+}
+
+// a few dozen cars with prices and horse power spread out without being random
+fn showroom(count: usize) -> Vec<Vehicles> {
+    (0..count)
+        .map(|i| car(&format!("Car {}", i), ((i * 7919) % 97 + 1) as f64 * 1000.0, ((i * 104729) % 301 + 50) as f64))
+        .collect()
+}
+
+#[test]
+fn the_most_valuable_fleet_within_the_budget_is_chosen() {
+    let vehicles = on_sale();
+    let plan = horse_power_only().plan(&vehicles, Money::new(250000.0)).unwrap();
+    assert_eq!(names(&plan), ["Family Car", "Sports Car", "Old Van"]);
+    assert_eq!(plan.total_score, 690.0);
+    assert_eq!(plan.total_price, Money::new(135000.0));
+}
+
+#[test]
+fn the_default_search_agrees_with_the_exhaustive_solver() {
+    let vehicles = showroom(14);
+    for budget in [5000.0, 40000.0, 120000.0, 300000.0] {
+        let budget = Money::new(budget);
+        let searched = horse_power_only().plan(&vehicles, budget).unwrap();
+        let exhaustive = horse_power_only().solver(knapsack::get_knapsack_items_par_iter_atomic).plan(&vehicles, budget).unwrap();
+        assert_eq!(searched.total_score, exhaustive.total_score, "budget {}", budget);
+        assert!(searched.total_price.amount() <= budget.amount());
+    }
+}
+
+#[test]
+fn spending_exactly_the_budget_is_allowed() {
+    let vehicles = vec![car("A", 30000.0, 100.0), car("B", 20000.0, 100.0), car("C", 10000.0, 100.0), car("D", 50000.0, 150.0)];
+    let budget = Money::new(60000.0);
+    let searched = horse_power_only().plan(&vehicles, budget).unwrap();
+    assert_eq!(names(&searched), ["A", "B", "C"]);
+    assert_eq!(searched.total_price, budget);
+    let exhaustive = horse_power_only()
+        .solver(|items: &[knapsack::Item], limit| knapsack::get_knapsack_items(&mut items.to_vec(), limit))
+        .plan(&vehicles, budget)
+        .unwrap();
+    assert_eq!(names(&exhaustive), ["A", "B", "C"]);
+}
+
+#[test]
+fn requirements_are_met_even_when_they_are_worth_nothing() {
+    let vehicles = on_sale();
+    let budget = Money::new(250000.0);
+    for planner in [horse_power_only().require(Capability::Air), horse_power_only().require_kind(VehicleKind::Airplane)] {
+        let plan = planner.plan(&vehicles, budget).unwrap();
+        assert_eq!(names(&plan), ["Family Car", "Old Van", "Crop Duster"]);
+        assert_eq!(plan.total_score, 240.0);
+    }
+    let both = horse_power_only().require(Capability::Sea).require_kind(VehicleKind::Car);
+    let plan = both.plan(&vehicles, Money::new(60000.0)).unwrap();
+    assert!(plan.chosen.iter().any(|vehicle| vehicle.as_vehicle().can(Capability::Sea)));
+    assert!(plan.chosen.iter().any(|vehicle| vehicle.kind() == VehicleKind::Car));
+    assert_eq!(names(&plan), ["Old Van", "Fishing Boat"]);
+}
+
+#[test]
+fn requirements_give_the_same_plan_with_a_solver() {
+    let vehicles = on_sale();
+    let searched = horse_power_only().require(Capability::Sea).require(Capability::Air).plan(&vehicles, Money::new(400000.0)).unwrap();
+    let solved = horse_power_only()
+        .require(Capability::Sea)
+        .require(Capability::Air)
+        .solver(knapsack::get_knapsack_items_par_iter_atomic)
+        .plan(&vehicles, Money::new(400000.0))
+        .unwrap();
+    assert_eq!(names(&searched), names(&solved));
+}
+
+#[test]
+fn a_requirement_nothing_meets_is_unsatisfiable() {
+    let vehicles = showroom(5);
+    let error = horse_power_only().require(Capability::Air).plan(&vehicles, Money::new(1e6)).unwrap_err();
+    assert_eq!(error, PlanError::Unsatisfiable(Requirement::Capability(Capability::Air)));
+    assert_eq!(error.to_string(), "no vehicle on offer meets the requirement at least one Air vehicle");
+}
+
+#[test]
+fn requirements_beyond_the_budget_report_the_cheapest_way_to_meet_them() {
+    let vehicles = on_sale();
+    let planner = horse_power_only().require(Capability::Air).require_kind(VehicleKind::AmphibiousBoat);
+    let error = planner.plan(&vehicles, Money::new(150000.0)).unwrap_err();
+    assert_eq!(error, PlanError::OverBudget { cheapest: Money::new(300000.0), budget: Money::new(150000.0) });
+    let solved = planner.solver(knapsack::get_knapsack_items_par_iter_atomic).plan(&vehicles, Money::new(150000.0));
+    assert_eq!(solved.unwrap_err(), error);
+}
+
+#[test]
+fn a_price_that_does_not_fit_a_weight_is_too_large() {
+    let vehicles = vec![car("Cheap", 1000.0, 50.0), car("Gold Plated", 5e12, 500.0)];
+    let error = horse_power_only().plan(&vehicles, Money::new(1e6)).unwrap_err();
+    assert_eq!(error, PlanError::PriceTooLarge(Money::new(5e12)));
+    // in millions it fits
+    let plan = horse_power_only().price_unit(1e6).plan(&vehicles, Money::new(1e13)).unwrap();
+    assert_eq!(plan.chosen.len(), 2);
+}
+
+#[test]
+fn price_units_have_to_be_positive_numbers() {
+    let vehicles = on_sale();
+    for price_unit in [0.0, -100.0, f64::NAN, f64::INFINITY] {
+        let error = horse_power_only().price_unit(price_unit).plan(&vehicles, Money::new(250000.0)).unwrap_err();
+        assert!(matches!(error, PlanError::InvalidPriceUnit(unit) if unit.to_bits() == price_unit.to_bits()), "{:?}", error);
+    }
+}
+
+#[test]
+fn large_showrooms_are_planned_quickly() {
+    let vehicles: Vec<Vehicles> = showroom(200).into_iter().chain(on_sale()).collect();
+    let started = Instant::now();
+    let plan = horse_power_only()
+        .require(Capability::Air)
+        .require(Capability::Sea)
+        .price_unit(100.0)
+        .plan(&vehicles, Money::new(1_000_000.0))
+        .unwrap();
+    assert!(started.elapsed() < Duration::from_secs(10), "took {:?}", started.elapsed());
+    assert!(plan.total_price.amount() <= 1_000_000.0);
+    assert!(plan.chosen.iter().any(|vehicle| vehicle.as_vehicle().can(Capability::Air)));
+}
+
+#[test]
+fn a_search_too_large_to_finish_is_refused() {
+    let vehicles = showroom(40);
+    let planner = FleetPlanner::new().utility(Utility::new().per_horse_power(1e6));
+    let error = planner.plan(&vehicles, Money::new(1e9)).unwrap_err();
+    assert!(matches!(&error, PlanError::Unsolvable(reason) if reason.starts_with("the search would take")), "{:?}", error);
+}
+
+#[test]
+fn problems_a_solver_would_overflow_on_are_refused() {
+    let vehicles = vec![car("A", 1.0, 100.0), car("B", 2e9, 100.0), car("C", 2e9, 100.0)];
+    let error = horse_power_only()
+        .solver(knapsack::get_knapsack_items_par_iter_atomic)
+        .plan(&vehicles, Money::new(2e9 + 1.0))
+        .unwrap_err();
+    assert_eq!(error, PlanError::Unsolvable("the item weights add up to more than an i32 can hold".to_string()));
+    // the default solver adds up in i64 and is fine with it
+    let plan = horse_power_only().price_unit(1e6).plan(&vehicles, Money::new(2e9 + 1.0)).unwrap();
+    assert_eq!(plan.chosen.len(), 1);
+}
+
+#[test]
+fn too_many_vehicles_for_an_exhaustive_solver_are_refused() {
+    let vehicles = showroom(knapsack::MAX_KNAPSACK_ITEMS + 1);
+    let error = horse_power_only().solver(knapsack::get_knapsack_items_par_iter_atomic).plan(&vehicles, Money::new(1e5)).unwrap_err();
+    assert!(matches!(error, PlanError::Unsolvable(_)), "{:?}", error);
+}
+
+#[test]
+fn budgets_have_to_be_zero_or_more() {
+    let vehicles = on_sale();
+    for budget in [-1.0, f64::NAN, f64::INFINITY] {
+        let error = horse_power_only().plan(&vehicles, Money::new(budget)).unwrap_err();
+        assert!(matches!(error, PlanError::InvalidBudget(money) if money.amount().to_bits() == budget.to_bits()), "{:?}", error);
+    }
+    let plan = horse_power_only().plan(&vehicles, Money::new(0.0)).unwrap();
+    assert!(plan.chosen.is_empty());
+}
+
+#[test]
+fn vehicles_that_are_never_the_better_pick_are_left_out_of_the_picks() {
+    // every car meets the requirement, only the ones nothing beats are tried as the forced pick
+    let vehicles = showroom(200);
+    let plan = horse_power_only().require(Capability::Land).price_unit(100.0).plan(&vehicles, Money::new(300000.0)).unwrap();
+    let unrequired = horse_power_only().price_unit(100.0).plan(&vehicles, Money::new(300000.0)).unwrap();
+    assert_eq!(plan.total_score, unrequired.total_score);
+}
+
+#[test]
+fn requirements_met_in_too_many_ways_are_refused() {
+    // every car costs more and is worth more than the one before, none of them beats another
+    let vehicles: Vec<Vehicles> = (0..20).map(|i| car(&format!("Car {}", i), 1000.0 + i as f64, 100.0 + i as f64)).collect();
+    let error = horse_power_only().require(Capability::Land).require_kind(VehicleKind::Car).plan(&vehicles, Money::new(1e5)).unwrap_err();
+    assert_eq!(error, PlanError::TooManyPicks { picks: 400 });
+}
@@ -0,0 +1,223 @@
+mod common;
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+use common::{on_sale, traits, vehicle};
+use traits::default_type_parameters::{Mass, Power};
+use traits::fleet_query::{parse, Aggregate, Comparison, Condition, ErrorKind, Field, Query, QueryError, QueryResult, Row, Span, Value};
+use traits::trait_objects::{Capability, Fleet};
+use traits::traits::poly::{Boat, Car};
+use traits::traits::VehicleKind;
+
+fn fleet() -> Fleet {
+    on_sale().into_iter().collect()
+}
+
+fn names(fleet: &Fleet, query: &str) -> Vec<String> {
+    match fleet.query(query).unwrap() {
+        QueryResult::Vehicles(vehicles) => vehicles.iter().map(|vehicle| vehicle.name().to_string()).collect(),
+        rows => panic!("{} returned rows {:?}", query, rows)
+    }
+}
+
+fn rows(fleet: &Fleet, query: &str) -> Vec<Row> {
+    match fleet.query(query).unwrap() {
+        QueryResult::Rows { rows, .. } => rows,
+        vehicles => panic!("{} returned vehicles {:?}", query, vehicles)
+    }
+}
+
+fn error(query: &str) -> QueryError {
+    parse(query).unwrap_err()
+}
+
+// the part of the query the error points at
+fn pointed_at(query: &str) -> &str {
+    let Span { start, end } = error(query).span;
+    &query[start..end]
+}
+
+//=================================================================
+// parsing
+
+#[test]
+fn and_binds_stronger_than_or() {
+    let compare = |field, comparison, value| Condition::Compare { field, comparison, value };
+    let query = parse("can Air or kind = Car and price < 65_000").unwrap();
+    assert_eq!(
+        query.condition,
+        Some(Condition::Or(
+            Box::new(Condition::Can(Capability::Air)),
+            Box::new(Condition::And(
+                Box::new(compare(Field::Kind, Comparison::Equal, Value::Kind(VehicleKind::Car))),
+                Box::new(compare(Field::Price, Comparison::Less, Value::Number(65000.0)))
+            ))
+        ))
+    );
+    let grouped = parse("(can Air or kind = Car) and price < 65000").unwrap();
+    assert!(matches!(grouped.condition, Some(Condition::And(..))));
+}
+
+#[test]
+fn every_part_is_optional() {
+    let empty = Query { aggregates: vec![], condition: None, group_by: None, order: None, limit: None };
+    assert_eq!(parse("").unwrap(), empty);
+    assert_eq!(parse("  where ").unwrap(), empty);
+    assert_eq!(parse("limit 2").unwrap().limit, Some(2));
+    assert_eq!(parse("count, sum(price)").unwrap().aggregates, [Aggregate::Count, Aggregate::Sum(Field::Price)]);
+}
+
+#[test]
+fn group_by_alone_counts() {
+    let query = parse("group by kind").unwrap();
+    assert_eq!(query.aggregates, [Aggregate::Count]);
+    assert_eq!(query.group_by, Some(Field::Kind));
+}
+
+//=================================================================
+// errors point at what is wrong
+
+#[test]
+fn unknown_names_are_pointed_at() {
+    assert_eq!(error("colour = 3").kind, ErrorKind::UnknownField("colour".to_string()));
+    assert_eq!(pointed_at("price < 10 and colour = 3"), "colour");
+    assert_eq!(error("kind = Bike").kind, ErrorKind::UnknownKind("Bike".to_string()));
+    assert_eq!(pointed_at("kind = Bike"), "Bike");
+    assert_eq!(error("can Swim").kind, ErrorKind::UnknownCapability("Swim".to_string()));
+}
+
+#[test]
+fn values_have_to_fit_their_field() {
+    assert_eq!(error("price < \"cheap\"").kind, ErrorKind::TypeMismatch { field: Field::Price, expected: "a number" });
+    assert_eq!(pointed_at("price < \"cheap\""), "\"cheap\"");
+    assert!(matches!(error("name = Jet").kind, ErrorKind::TypeMismatch { field: Field::Name, .. }));
+    assert_eq!(error("kind < Car").kind, ErrorKind::Unordered { field: Field::Kind, comparison: Comparison::Less });
+    assert_eq!(pointed_at("kind < Car"), "<");
+    assert_eq!(error("sum(name)").kind, ErrorKind::NotANumber { aggregate: "sum", field: Field::Name });
+    assert_eq!(pointed_at("count, max(kind)"), "kind");
+}
+
+#[test]
+fn broken_syntax_says_what_was_expected() {
+    assert_eq!(error("price <").kind, ErrorKind::Expected { expected: "a number", found: "the end of the query".to_string() });
+    assert_eq!(error("price <").span, Span { start: 7, end: 7 });
+    assert_eq!(error("(price < 3").kind, ErrorKind::Expected { expected: "`)`", found: "the end of the query".to_string() });
+    assert!(matches!(error("price 3").kind, ErrorKind::Expected { expected: "a comparison like = or <", .. }));
+    assert!(matches!(error("order price").kind, ErrorKind::Expected { expected: "`by` after `order`", .. }));
+    assert!(matches!(error("price < 3 price > 1").kind, ErrorKind::Expected { .. }));
+    assert_eq!(pointed_at("price < 3 price > 1"), "price");
+    assert_eq!(error("name = \"Jet").kind, ErrorKind::UnterminatedString);
+    assert_eq!(error("price < 3 & wheels = 4").kind, ErrorKind::UnexpectedCharacter('&'));
+    assert_eq!(error("price < 1.2.3").kind, ErrorKind::InvalidNumber("1.2.3".to_string()));
+    assert_eq!(error("limit 2.5").kind, ErrorKind::InvalidLimit("2.50".to_string()));
+}
+
+#[test]
+fn orders_have_to_fit_the_query() {
+    assert!(matches!(error("kind = Car order by sum(price)").kind, ErrorKind::InvalidOrder(_)));
+    assert_eq!(pointed_at("kind = Car order by sum(price)"), "sum(price)");
+    assert!(matches!(error("count group by kind order by price").kind, ErrorKind::InvalidOrder(_)));
+}
+
+#[test]
+fn render_underlines_the_span() {
+    let query = "kind = Bike";
+    assert_eq!(error(query).render(query), "kind = Bike\n       ^^^^ unknown vehicle kind `Bike`, the kinds are [Car, Boat, AmphibiousBoat, Airplane]");
+    // columns count characters, not bytes
+    let query = "name = \"Ä\" and wheels = x";
+    assert!(error(query).render(query).ends_with("\n                        ^ wheels has to be compared with a number"));
+    assert_eq!(error("wheels =").to_string(), "expected a number, found the end of the query at 8..8");
+}
+
+//=================================================================
+// running
+
+#[test]
+fn conditions_filter_the_fleet() {
+    let fleet = fleet();
+    assert_eq!(names(&fleet, "kind = Car and price < 65000"), ["Family Car", "Old Van"]);
+    assert_eq!(names(&fleet, "can Sea and not kind = Boat"), ["Duck Tour"]);
+    assert_eq!(names(&fleet, "name = \"Old Van\" or horse_power >= 400"), ["Sports Car", "Old Van"]);
+    assert_eq!(names(&fleet, "").len(), fleet.len());
+}
+
+#[test]
+fn missing_values_match_no_comparison() {
+    let fleet = fleet();
+    // only cars have horse power, the boats are neither above nor below it
+    assert_eq!(names(&fleet, "horse_power < 100 or horse_power >= 100").len(), 3);
+    assert_eq!(names(&fleet, "horse_power != 90"), ["Family Car", "Sports Car"]);
+}
+
+#[test]
+fn order_and_limit() {
+    let fleet = fleet();
+    assert_eq!(names(&fleet, "order by price desc limit 3"), ["Crop Duster", "Duck Tour", "Sports Car"]);
+    assert_eq!(names(&fleet, "kind = Car order by price asc"), ["Old Van", "Family Car", "Sports Car"]);
+    // vehicles without a value come last in both directions
+    assert_eq!(names(&fleet, "order by horse_power desc")[3..].len(), 3);
+    assert_eq!(names(&fleet, "order by horse_power")[..3], ["Old Van", "Family Car", "Sports Car"]);
+    assert!(names(&fleet, "limit 0").is_empty());
+}
+
+#[test]
+fn aggregates_over_the_matching_vehicles() {
+    let fleet = fleet();
+    let row = |values: &[Option<f64>]| Row { group: None, values: values.to_vec() };
+    assert_eq!(rows(&fleet, "sum(price) where kind = Car"), [row(&[Some(135000.0)])]);
+    assert_eq!(rows(&fleet, "count, avg(price), min(price), max(horse_power) kind = Car"), [row(&[Some(3.0), Some(45000.0), Some(8000.0), Some(450.0)])]);
+    // nothing to take the average of
+    assert_eq!(rows(&fleet, "count, sum(price), avg(price) where price > 1000000"), [row(&[Some(0.0), Some(0.0), None])]);
+}
+
+#[test]
+fn group_by_makes_a_row_per_value() {
+    let fleet = fleet();
+    let grouped = rows(&fleet, "count, sum(price) group by kind");
+    let groups: Vec<(Option<Value>, Vec<Option<f64>>)> = grouped.into_iter().map(|row| (row.group, row.values)).collect();
+    assert_eq!(
+        groups,
+        [
+            (Some(Value::Kind(VehicleKind::Car)), vec![Some(3.0), Some(135000.0)]),
+            (Some(Value::Kind(VehicleKind::Boat)), vec![Some(1.0), Some(45000.0)]),
+            (Some(Value::Kind(VehicleKind::AmphibiousBoat)), vec![Some(1.0), Some(120000.0)]),
+            (Some(Value::Kind(VehicleKind::Airplane)), vec![Some(1.0), Some(180000.0)])
+        ]
+    );
+    let by_sum: Vec<Option<Value>> = rows(&fleet, "group by kind order by sum(price) desc limit 2").into_iter().map(|row| row.group).collect();
+    assert_eq!(by_sum, [Some(Value::Kind(VehicleKind::Airplane)), Some(Value::Kind(VehicleKind::Car))]);
+    assert_eq!(
+        fleet.query("count, sum(price) price < 50000 group by kind").unwrap().to_string(),
+        "Car: count = 2, sum(price) = 40000\nBoat: count = 1, sum(price) = 45000"
+    );
+}
+
+#[test]
+fn boxed_vehicles_are_queried_like_the_enum() {
+    let mut fleet = Fleet::new();
+    fleet.add(Car::new(vehicle("Boxed Car", 10000.0), Power::from_horse_power(70.0)));
+    fleet.add(Boat::new(vehicle("Boxed Boat", 20000.0), Mass::from_kilograms(10.0)));
+    assert_eq!(names(&fleet, "kind = Car and horse_power < 80"), ["Boxed Car"]);
+    assert_eq!(names(&fleet, "wheels = 0"), ["Boxed Boat"]);
+}
+
+//=================================================================
+// the fleet binary
+
+#[test]
+fn the_binary_runs_queries_from_arguments_and_stdin() {
+    let output = Command::new(env!("CARGO_BIN_EXE_fleet")).args(["kind", "=", "Airplane"]).output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap().lines().count(), 2);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_fleet")).arg("kind = Bike").output().unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8(output.stderr).unwrap().contains("       ^^^^ unknown vehicle kind `Bike`"));
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_fleet")).stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::piped()).spawn().unwrap();
+    child.stdin.take().unwrap().write_all(b"# the cars\ncount where kind = Car\n\nwheels > x\nsum(price) where can Air\n").unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "count = 3\n\n\nsum(price) = 2680000\n\n");
+    assert!(String::from_utf8(output.stderr).unwrap().contains("wheels has to be compared with a number"));
+}
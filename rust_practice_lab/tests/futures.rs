@@ -0,0 +1,258 @@
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use rust_practice_lab::futures::*;
+
+// pending once, so the task goes back to the end of the run queue
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            return Poll::Ready(());
+        }
+        self.0 = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+fn yield_now() -> YieldNow {
+    YieldNow(false)
+}
+
+fn panic_message(panic: Box<dyn std::any::Any + Send>) -> String {
+    match panic.downcast::<&str>() {
+        Ok(message) => message.to_string(),
+        Err(panic) => *panic.downcast::<String>().expect("a panic with a message")
+    }
+}
+
+//=================================================================
+// executors
+
+#[test]
+fn block_on_runs_a_future_on_the_calling_thread() {
+    let thread = std::thread::current().id();
+    assert_eq!(block_on(async move { (std::thread::current().id() == thread, 6 * 7) }), (true, 42));
+}
+
+#[test]
+fn spawned_tasks_wait_for_block_on_and_run_in_spawn_order() {
+    let executor = Executor::new();
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let handles: Vec<JoinHandle<usize>> = (0..3)
+        .map(|task| {
+            let log = Arc::clone(&log);
+            executor.spawn(async move {
+                log.lock().unwrap().push(format!("{} starts", task));
+                yield_now().await;
+                log.lock().unwrap().push(format!("{} ends", task));
+                task
+            })
+        })
+        .collect();
+    // a single-threaded executor only runs tasks inside block_on
+    assert!(log.lock().unwrap().is_empty());
+    assert!(handles.iter().all(|handle| !handle.is_finished()));
+
+    let results = executor.block_on(async {
+        let mut results = Vec::new();
+        for handle in handles {
+            results.push(handle.await);
+        }
+        results
+    });
+    assert_eq!(results, [0, 1, 2]);
+    // every task ran up to its yield before any of them was polled again
+    assert_eq!(*log.lock().unwrap(), ["0 starts", "1 starts", "2 starts", "0 ends", "1 ends", "2 ends"]);
+}
+
+#[test]
+fn tasks_left_pending_run_in_the_next_block_on() {
+    let executor = Executor::new();
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let handle = executor.spawn(async move {
+        yield_now().await;
+        sender.send("ran").unwrap();
+    });
+    executor.block_on(async {});
+    executor.run_until_stalled();
+    assert_eq!(receiver.try_recv(), Ok("ran"));
+    executor.block_on(handle);
+}
+
+#[test]
+fn a_panicking_task_rethrows_where_it_is_awaited() {
+    let executor = Executor::new();
+    let failing = executor.spawn(async {
+        yield_now().await;
+        panic!("the task failed");
+    });
+    let fine = executor.spawn(async { "still here" });
+    let panic = panic::catch_unwind(AssertUnwindSafe(|| executor.block_on(failing))).unwrap_err();
+    assert_eq!(panic_message(panic), "the task failed");
+    // the panic stayed inside its task, the executor and the other tasks carry on
+    assert_eq!(executor.block_on(fine), "still here");
+    assert_eq!(executor.block_on(executor.spawn(async { 7 })), 7);
+}
+
+#[test]
+fn a_panicking_task_does_not_take_down_a_pool_worker() {
+    let executor = ThreadPoolExecutor::new(1);
+    let failing = executor.spawn(async { panic!("on a worker") });
+    let panic = panic::catch_unwind(AssertUnwindSafe(|| executor.block_on(failing))).unwrap_err();
+    assert_eq!(panic_message(panic), "on a worker");
+    let handles: Vec<JoinHandle<u32>> = (0..10).map(|n| executor.spawn(async move { n * 2 })).collect();
+    let doubled: Vec<u32> = executor.block_on(async {
+        let mut doubled = Vec::new();
+        for handle in handles {
+            doubled.push(handle.await);
+        }
+        doubled
+    });
+    assert_eq!(doubled, (0..10).map(|n| n * 2).collect::<Vec<u32>>());
+}
+
+//=================================================================
+// timers
+
+#[test]
+fn a_sleep_never_ends_before_its_deadline() {
+    let start = Instant::now();
+    block_on(sleep(Duration::from_millis(20)));
+    assert!(start.elapsed() >= Duration::from_millis(20));
+}
+
+#[test]
+fn a_sleep_ends_exactly_when_the_mocked_clock_passes_its_deadline() {
+    let timer = MockTimer::new();
+    let executor = Executor::new();
+    let started = timer.now();
+    let sleep = timer.sleep(Duration::from_secs(60));
+    assert_eq!(sleep.deadline(), started + Duration::from_secs(60));
+    let handle = executor.spawn(async move {
+        sleep.await;
+        "woke up"
+    });
+    executor.run_until_stalled();
+    assert_eq!(timer.pending(), 1);
+
+    timer.advance(Duration::from_secs(59));
+    executor.run_until_stalled();
+    assert!(!handle.is_finished());
+    timer.advance(Duration::from_secs(1));
+    executor.run_until_stalled();
+    assert!(handle.is_finished());
+    assert_eq!(timer.pending(), 0);
+    assert_eq!(executor.block_on(handle), "woke up");
+    // no real time had to pass
+    assert!(started.elapsed() < Duration::from_secs(59));
+}
+
+#[test]
+fn sleeps_wake_in_deadline_order_under_a_mocked_clock() {
+    let timer = MockTimer::new();
+    let executor = Executor::new();
+    let woken = Arc::new(Mutex::new(Vec::new()));
+    // far apart, so they share slots of the wheel and have to wait for their own round
+    for seconds in [3600, 5, 600, 1] {
+        let sleep = timer.sleep(Duration::from_secs(seconds));
+        let woken = Arc::clone(&woken);
+        executor.spawn(async move {
+            sleep.await;
+            woken.lock().unwrap().push(seconds);
+        });
+    }
+    executor.run_until_stalled();
+    for _ in 0..3600 {
+        timer.advance(Duration::from_secs(1));
+        executor.run_until_stalled();
+    }
+    assert_eq!(*woken.lock().unwrap(), [1, 5, 600, 3600]);
+}
+
+#[test]
+fn dropping_a_sleep_takes_it_out_of_the_wheel() {
+    let timer = MockTimer::new();
+    let executor = Executor::new();
+    let mut sleeps: Vec<Sleep> = (1..=3).map(|seconds| timer.sleep(Duration::from_secs(seconds))).collect();
+    // a sleep is only registered once it is polled
+    assert_eq!(timer.pending(), 0);
+    executor.block_on(async {
+        for sleep in &mut sleeps {
+            let polled = std::future::poll_fn(|cx| Poll::Ready(Pin::new(&mut *sleep).poll(cx))).await;
+            assert!(polled.is_pending());
+        }
+    });
+    assert_eq!(timer.pending(), 3);
+    drop(sleeps.remove(1));
+    assert_eq!(timer.pending(), 2);
+    drop(sleeps);
+    assert_eq!(timer.pending(), 0);
+    // nothing is left to fire
+    timer.advance(Duration::from_secs(10));
+    assert_eq!(timer.pending(), 0);
+}
+
+#[test]
+fn a_cancelled_sleep_never_wakes_its_task() {
+    let timer = MockTimer::new();
+    let executor = Executor::new();
+    let sleep = timer.sleep(Duration::from_secs(1));
+    let handle = executor.spawn(async move {
+        let mut sleep = Some(sleep);
+        // poll the sleep once to register it, then cancel it by dropping
+        let registered = std::future::poll_fn(|cx| Poll::Ready(Pin::new(sleep.as_mut().unwrap()).poll(cx))).await;
+        drop(sleep.take());
+        registered.is_pending()
+    });
+    assert!(executor.block_on(handle));
+    assert_eq!(timer.pending(), 0);
+    timer.advance(Duration::from_secs(2));
+    assert_eq!(timer.pending(), 0);
+}
+
+#[test]
+fn sleeping_for_longer_than_an_instant_can_hold_does_not_overflow() {
+    let forever = sleep(Duration::MAX);
+    assert!(forever.deadline() > Instant::now() + Duration::from_secs(60 * 60 * 24 * 365));
+    let timer = MockTimer::new();
+    let mut mocked = timer.sleep(Duration::MAX);
+    assert!(block_on(std::future::poll_fn(|cx| Poll::Ready(Pin::new(&mut mocked).poll(cx)))).is_pending());
+    assert_eq!(timer.pending(), 1);
+    drop(mocked);
+    assert_eq!(timer.pending(), 0);
+}
+
+#[test]
+fn advancing_a_mocked_clock_past_what_an_instant_can_hold_does_not_overflow() {
+    let timer = MockTimer::new();
+    let started = timer.now();
+    let executor = Executor::new();
+    let handle = executor.spawn(timer.sleep(Duration::from_secs(60)));
+    executor.run_until_stalled();
+    timer.advance(Duration::MAX);
+    timer.advance(Duration::from_secs(1));
+    assert!(timer.now() > started + Duration::from_secs(60 * 60 * 24 * 365));
+    executor.run_until_stalled();
+    assert!(handle.is_finished());
+    assert!(timer.sleep(Duration::MAX).deadline() >= timer.now());
+}
+
+#[test]
+fn timeout_gives_up_on_a_slow_future() {
+    let slow = async {
+        sleep(Duration::from_secs(60)).await;
+        "done"
+    };
+    let start = Instant::now();
+    assert_eq!(block_on(timeout(Duration::from_millis(10), slow)), Err(Elapsed));
+    assert!(start.elapsed() < Duration::from_secs(30));
+    assert_eq!(block_on(timeout(Duration::from_secs(60), async { "done" })), Ok("done"));
+}
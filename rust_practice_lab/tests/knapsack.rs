@@ -0,0 +1,130 @@
+use std::sync::atomic::AtomicBool;
+use rust_practice_lab::knapsack::*;
+use rust_practice_lab::mutexes::SpinLock;
+
+// the three light items weigh exactly the limit together and beat every pair,
+// a bound that only counts subsets strictly under the limit stops at pairs and misses them
+fn exact_fill() -> Vec<Item> {
+    vec![
+        Item { weight: 30, value: 20 },
+        Item { weight: 50, value: 35 },
+        Item { weight: 10, value: 20 },
+        Item { weight: 20, value: 20 }
+    ]
+}
+
+const LIMIT: i32 = 60;
+
+fn value(items: &[Item]) -> i32 {
+    items.iter().map(|item| item.value).sum()
+}
+
+fn weight(items: &[Item]) -> i32 {
+    items.iter().map(|item| item.weight).sum()
+}
+
+#[test]
+fn max_subset_size_counts_a_subset_that_fills_the_limit() {
+    assert_eq!(max_subset_size(&exact_fill(), LIMIT), 3);
+    assert_eq!(max_subset_size(&exact_fill(), LIMIT - 1), 2);
+}
+
+#[test]
+fn every_exhaustive_solver_finds_the_exact_fill() {
+    let mut items = exact_fill();
+    let found = [
+        get_knapsack_items(&mut items, LIMIT),
+        get_knapsack_items_par_threads(&items, LIMIT),
+        get_knapsack_items_par_iter(&items, LIMIT),
+        get_knapsack_items_par_iter_with_lock::<SpinLock>(&items, LIMIT),
+        get_knapsack_items_par_iter_atomic(&items, LIMIT),
+        get_knapsack_items_with_progress(&items, LIMIT, &AtomicBool::new(false), |_| {}).unwrap()
+    ];
+    for solution in found {
+        assert_eq!((value(&solution), weight(&solution)), (60, LIMIT), "{:?}", solution);
+    }
+}
+
+#[test]
+fn validation_rejects_what_the_solvers_cannot_handle() {
+    assert_eq!(validate_knapsack_problem(&exact_fill(), LIMIT), Ok(()));
+    assert!(validate_knapsack_problem(&exact_fill(), 0).is_err());
+    assert!(validate_knapsack_problem(&[Item { weight: 0, value: 5 }], LIMIT).is_err());
+
+    let items = vec![Item { weight: 1, value: 1 }; MAX_KNAPSACK_ITEMS];
+    assert_eq!(validate_knapsack_problem(&items, LIMIT), Ok(()));
+    let items = vec![Item { weight: 1, value: 1 }; MAX_KNAPSACK_ITEMS + 1];
+    assert_eq!(
+        validate_knapsack_problem(&items, LIMIT),
+        Err(format!("at most {} items can be searched, got {}", MAX_KNAPSACK_ITEMS, MAX_KNAPSACK_ITEMS + 1))
+    );
+
+    // every subset has to add up without overflowing an i32
+    let heavy = [Item { weight: i32::MAX, value: 1 }, Item { weight: 1, value: 1 }];
+    assert!(validate_knapsack_problem(&heavy, i32::MAX).unwrap_err().contains("weights"));
+    let valuable = [Item { weight: 1, value: i32::MAX }, Item { weight: 1, value: -1 }];
+    assert!(validate_knapsack_problem(&valuable, LIMIT).unwrap_err().contains("values"));
+}
+
+#[test]
+fn max_subset_size_does_not_overflow_on_heavy_items() {
+    let items = [Item { weight: i32::MAX - 1, value: 1 }, Item { weight: i32::MAX - 1, value: 1 }];
+    assert_eq!(max_subset_size(&items, i32::MAX), 1);
+}
+
+//=================================================================
+// the dynamic programming solver
+
+// items spread out without being random, with a few equal ones
+fn spread(count: usize) -> Vec<Item> {
+    (0..count).map(|i| Item { weight: (i * 37 % 23 + 1) as i32, value: (i * 53 % 31 + 1) as i32 }).collect()
+}
+
+#[test]
+fn the_dynamic_solver_agrees_with_the_exhaustive_search() {
+    assert_eq!(value(&get_knapsack_items_dynamic(&exact_fill(), LIMIT)), 60);
+    let items = spread(16);
+    for limit in [1, 5, 20, 60, 150, 1000] {
+        let dynamic = get_knapsack_items_dynamic(&items, limit);
+        let exhaustive = get_knapsack_items_par_iter_atomic(&items, limit);
+        assert_eq!(value(&dynamic), value(&exhaustive), "limit {}", limit);
+        assert!(weight(&dynamic) <= limit);
+    }
+}
+
+#[test]
+fn the_dynamic_solver_returns_items_it_was_given_in_order() {
+    let items = spread(16);
+    let chosen = get_knapsack_items_dynamic(&items, 60);
+    let mut rest = items.iter();
+    assert!(chosen.iter().all(|item| rest.any(|other| other == item)));
+}
+
+#[test]
+fn the_dynamic_solver_searches_by_value_when_the_values_are_small() {
+    // the limit is far above the sum of the values, the table is as long as the values add up to
+    let items = [Item { weight: 1_000_000, value: 3 }, Item { weight: 2_000_000, value: 4 }, Item { weight: 2_500_000, value: 6 }];
+    assert_eq!(dynamic_knapsack_steps(&items, 3_500_000), 3 * 14);
+    assert_eq!(get_knapsack_items_dynamic(&items, 3_500_000), [items[0], items[2]]);
+    assert_eq!(get_knapsack_items_dynamic(&items, 999_999), []);
+}
+
+#[test]
+fn the_dynamic_solver_skips_items_worth_nothing_and_adds_up_in_i64() {
+    let items = [Item { weight: 1, value: -5 }, Item { weight: 2, value: 0 }, Item { weight: 3, value: i32::MAX }, Item { weight: 4, value: i32::MAX }];
+    assert_eq!(get_knapsack_items_dynamic(&items, 10), [items[2], items[3]]);
+}
+
+#[test]
+fn dynamic_validation_limits_the_steps_not_the_items() {
+    let items = vec![Item { weight: 1, value: 1 }; 1000];
+    assert_eq!(validate_dynamic_knapsack_problem(&items, 500), Ok(()));
+    assert!(validate_dynamic_knapsack_problem(&items, 0).is_err());
+    assert!(validate_dynamic_knapsack_problem(&[Item { weight: -1, value: 1 }], 10).is_err());
+    // the values add up to less than the limit, so the search counts values
+    let valuable = vec![Item { weight: 1, value: 1_000_000 }; 100];
+    assert_eq!(
+        validate_dynamic_knapsack_problem(&valuable, i32::MAX),
+        Err(format!("the search would take {} steps, at most {} are allowed", 100 * 100_000_001usize, MAX_DYNAMIC_KNAPSACK_STEPS))
+    );
+}
@@ -0,0 +1,99 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+use rust_practice_lab::knapsack::*;
+use rust_practice_lab::knapsack_batch::*;
+
+fn problem(weight_limit: i32) -> KnapsackProblem {
+    KnapsackProblem::new(vec![Item { weight: 10, value: 60 }, Item { weight: 20, value: 100 }, Item { weight: 30, value: 120 }], weight_limit)
+}
+
+fn indices(reports: &[JobReport]) -> Vec<usize> {
+    reports.iter().map(|report| report.index).collect()
+}
+
+#[test]
+fn submission_order_follows_the_batch_and_completion_order_the_clock() {
+    // the first job takes the longest, so it finishes last
+    let slow_first = |items: &mut Vec<Item>, weight_limit: i32| {
+        if weight_limit == 1 {
+            thread::sleep(Duration::from_millis(200));
+        }
+        get_knapsack_items_par_iter_atomic(items, weight_limit)
+    };
+    let problems = || (1..=6).map(problem);
+    let batch = BatchSolver::new(3);
+
+    let by_submission = batch.solve(problems(), slow_first, ResultOrder::Submission);
+    assert_eq!(indices(&by_submission), [0, 1, 2, 3, 4, 5]);
+
+    let by_completion = batch.solve(problems(), slow_first, ResultOrder::Completion);
+    assert_eq!(by_completion.len(), 6);
+    assert_eq!(by_completion.last().unwrap().index, 0);
+
+    let mut seen = Vec::new();
+    batch.for_each_completed(problems(), slow_first, |report| seen.push(report.index));
+    assert_eq!(seen.last(), Some(&0));
+    seen.sort_unstable();
+    assert_eq!(seen, [0, 1, 2, 3, 4, 5]);
+}
+
+#[test]
+fn a_panicking_job_becomes_an_error_and_the_others_finish() {
+    let unlucky = |items: &mut Vec<Item>, weight_limit: i32| {
+        if weight_limit == 13 {
+            panic!("unlucky number");
+        }
+        get_knapsack_items_par_iter_atomic(items, weight_limit)
+    };
+    let problems = vec![problem(50), problem(13), problem(0), problem(30)];
+    let reports = BatchSolver::new(2).solve(problems, unlucky, ResultOrder::Submission);
+
+    assert_eq!(reports[0].total_value(), Some(220));
+    assert_eq!(reports[1].result, Err(JobError::Panicked("unlucky number".to_string())));
+    assert!(matches!(reports[2].result, Err(JobError::InvalidProblem(_))));
+    assert_eq!(reports[3].total_value(), Some(160));
+    assert_eq!(reports[1].result.as_ref().unwrap_err().to_string(), "solver panicked: unlucky number");
+}
+
+#[test]
+fn a_full_queue_holds_the_producer_back() {
+    const JOBS: usize = 20;
+    let pulled = AtomicUsize::new(0);
+    let gate = (Mutex::new(false), Condvar::new());
+    let blocked_solver = |items: &mut Vec<Item>, weight_limit: i32| {
+        let (open, opened) = &gate;
+        drop(opened.wait_while(open.lock().unwrap(), |open| !*open).unwrap());
+        get_knapsack_items_par_iter_atomic(items, weight_limit)
+    };
+    let problems = (0..JOBS).map(|_| {
+        pulled.fetch_add(1, Ordering::SeqCst);
+        problem(50)
+    });
+
+    let (reports, pulled_while_blocked) = thread::scope(|scope| {
+        let watcher = scope.spawn(|| {
+            thread::sleep(Duration::from_millis(100));
+            let pulled_while_blocked = pulled.load(Ordering::SeqCst);
+            let (open, opened) = &gate;
+            *open.lock().unwrap() = true;
+            opened.notify_all();
+            pulled_while_blocked
+        });
+        let reports = BatchSolver::new(1).queue_capacity(2).solve(problems, blocked_solver, ResultOrder::Submission);
+        (reports, watcher.join().unwrap())
+    });
+
+    // one job in the worker, two in the queue and one the producer is trying to send
+    assert!(pulled_while_blocked <= 4, "{} problems were pulled from the batch", pulled_while_blocked);
+    assert_eq!(reports.len(), JOBS);
+    assert!(reports.iter().all(|report| report.total_value() == Some(220)));
+}
+
+#[test]
+fn a_batch_solver_has_at_least_one_worker() {
+    let batch = BatchSolver::new(0);
+    assert_eq!(batch.workers(), 1);
+    assert_eq!(batch.solve(vec![problem(50)], get_knapsack_items, ResultOrder::Completion).len(), 1);
+}
@@ -0,0 +1,143 @@
+use std::net::SocketAddr;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::net::tcp::OwnedReadHalf;
+use tokio::net::{TcpListener, TcpStream};
+use rust_practice_lab::knapsack::*;
+use rust_practice_lab::knapsack_service::*;
+
+// every test gets its own server on a port picked by the OS
+async fn start_server() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(serve(listener));
+    address
+}
+
+fn small_instance() -> Vec<Item> {
+    vec![
+        Item { weight: 10, value: 60 },
+        Item { weight: 20, value: 100 },
+        Item { weight: 30, value: 120 },
+        Item { weight: 25, value: 30 },
+        Item { weight: 5, value: 10 }
+    ]
+}
+
+#[tokio::test]
+async fn exhaustive_job_reports_progress_and_the_best_items() {
+    let client = KnapsackClient::connect(start_server().await).await.unwrap();
+    let mut job = client.submit(small_instance(), 50, Solver::Exhaustive).await.unwrap();
+
+    let mut progress = Vec::new();
+    let outcome = loop {
+        match job.next_event().await.expect("job ended without a final event") {
+            JobEvent::Progress(update) => progress.push(update),
+            event => break event
+        }
+    };
+
+    assert_eq!(progress.iter().map(|update| update.subset_size).collect::<Vec<_>>(), vec![1, 2, 3]);
+    assert!(progress.windows(2).all(|pair| pair[0].best_value <= pair[1].best_value));
+
+    let expected = get_knapsack_items(&mut small_instance(), 50);
+    assert_eq!(outcome, JobEvent::Finished {
+        total_weight: expected.iter().map(|item| item.weight).sum(),
+        total_value: expected.iter().map(|item| item.value).sum(),
+        items: expected
+    });
+    assert_eq!(job.next_event().await, None);
+}
+
+#[tokio::test]
+async fn jobs_on_one_connection_run_side_by_side() {
+    let client = KnapsackClient::connect(start_server().await).await.unwrap();
+    let first = client.submit(small_instance(), 50, Solver::ParallelIter).await.unwrap();
+    let second = client.submit(small_instance(), 35, Solver::ValueWeightRatio).await.unwrap();
+    assert_ne!(first.job_id, second.job_id);
+
+    let (first, second) = tokio::join!(first.outcome(), second.outcome());
+    assert!(matches!(first.unwrap(), JobEvent::Finished { total_value: 220, .. }));
+    assert!(matches!(second.unwrap(), JobEvent::Finished { total_weight: 30, total_value: 160, .. }));
+}
+
+#[tokio::test]
+async fn a_running_job_can_be_cancelled_by_id() {
+    let address = start_server().await;
+    let client = KnapsackClient::connect(address).await.unwrap();
+
+    // 2^30 combinations, this would run for a very long time
+    let items = vec![Item { weight: 1, value: 1 }; 30];
+    let mut job = client.submit(items, 1000, Solver::Exhaustive).await.unwrap();
+    assert!(matches!(job.next_event().await, Some(JobEvent::Progress(_))));
+
+    client.cancel(job.job_id).await.unwrap();
+    assert_eq!(job.outcome().await.unwrap(), JobEvent::Cancelled);
+}
+
+#[tokio::test]
+async fn only_the_submitting_connection_can_cancel_a_job() {
+    let address = start_server().await;
+    let client = KnapsackClient::connect(address).await.unwrap();
+    let items = vec![Item { weight: 1, value: 1 }; 30];
+    let mut job = client.submit(items, 1000, Solver::Exhaustive).await.unwrap();
+    assert!(matches!(job.next_event().await, Some(JobEvent::Progress(_))));
+
+    // another connection that guesses the id is told there is no such job
+    let stream = TcpStream::connect(address).await.unwrap();
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    writer.write_all(protocol::encode_line(&ClientMessage::Cancel { job_id: job.job_id }).as_bytes()).await.unwrap();
+    assert_eq!(next_message(&mut lines).await, ServerMessage::Error { message: format!("no running job with id {}", job.job_id) });
+
+    // and the job keeps running until its owner cancels it
+    assert!(matches!(job.next_event().await, Some(JobEvent::Progress(_))));
+    client.cancel(job.job_id).await.unwrap();
+    assert_eq!(job.outcome().await.unwrap(), JobEvent::Cancelled);
+}
+
+#[tokio::test]
+async fn invalid_jobs_are_rejected() {
+    let client = KnapsackClient::connect(start_server().await).await.unwrap();
+    let error = client.submit(small_instance(), 0, Solver::Exhaustive).await.err().unwrap();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+
+    // too many items for the exhaustive solvers
+    let items = vec![Item { weight: 1, value: 1 }; MAX_KNAPSACK_ITEMS + 1];
+    let error = client.submit(items, 1000, Solver::Exhaustive).await.err().unwrap();
+    assert_eq!(error.to_string(), format!("at most {} items can be searched, got {}", MAX_KNAPSACK_ITEMS, MAX_KNAPSACK_ITEMS + 1));
+
+    // the connection is still usable afterwards
+    let job = client.submit(small_instance(), 50, Solver::Exhaustive).await.unwrap();
+    assert!(matches!(job.outcome().await.unwrap(), JobEvent::Finished { .. }));
+}
+
+async fn next_message(lines: &mut Lines<BufReader<OwnedReadHalf>>) -> ServerMessage {
+    serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap()
+}
+
+#[tokio::test]
+async fn raw_protocol_lines() {
+    let stream = TcpStream::connect(start_server().await).await.unwrap();
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+
+    writer.write_all(b"this is not json\n").await.unwrap();
+    assert!(matches!(next_message(&mut lines).await, ServerMessage::Error { .. }));
+
+    writer.write_all(b"{\"type\":\"cancel\",\"job_id\":12345}\n").await.unwrap();
+    assert!(matches!(next_message(&mut lines).await, ServerMessage::Error { .. }));
+
+    // the solver field is optional and defaults to the exhaustive search
+    writer.write_all(b"{\"type\":\"submit\",\"items\":[{\"weight\":3,\"value\":4}],\"weight_limit\":10}\n").await.unwrap();
+    let ServerMessage::Accepted { job_id } = next_message(&mut lines).await else { panic!("job was not accepted") };
+    assert_eq!(next_message(&mut lines).await, ServerMessage::Progress {
+        job_id,
+        progress: KnapsackProgress { subset_size: 1, max_subset_size: 1, best_value: 4 }
+    });
+    assert_eq!(next_message(&mut lines).await, ServerMessage::Finished {
+        job_id,
+        items: vec![Item { weight: 3, value: 4 }],
+        total_weight: 3,
+        total_value: 4
+    });
+}
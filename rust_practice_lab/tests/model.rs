@@ -0,0 +1,298 @@
+use std::sync::Arc;
+#[cfg(feature = "model")]
+use rust_practice_lab::channels::{bounded, select, unbounded, RecvError};
+#[cfg(feature = "model")]
+use rust_practice_lab::knapsack::SharedBest;
+use rust_practice_lab::knapsack::Item;
+use rust_practice_lab::model::sync::{AtomicBool, AtomicI64, AtomicUsize, Condvar, Mutex, Ordering};
+use rust_practice_lab::model::{self, thread, Builder};
+
+// two threads add one to a counter with a load and a store instead of fetch_add
+// a preemption between the load and the store of one thread loses the other thread's update
+fn racy_counter() {
+    let counter = Arc::new(AtomicUsize::new(0));
+    let other = Arc::clone(&counter);
+    let handle = thread::spawn(move || {
+        let value = other.load(Ordering::SeqCst);
+        other.store(value + 1, Ordering::SeqCst);
+    });
+    let value = counter.load(Ordering::SeqCst);
+    counter.store(value + 1, Ordering::SeqCst);
+    handle.join().unwrap();
+    assert_eq!(counter.load(Ordering::SeqCst), 2, "an increment got lost");
+}
+
+//=================================================================
+// the harness itself
+
+#[test]
+fn a_correct_counter_passes_every_interleaving() {
+    let report = model::check(|| {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let handles: Vec<_> = (0..2)
+            .map(|_| {
+                let counter = Arc::clone(&counter);
+                thread::spawn(move || {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                })
+            })
+            .collect();
+        counter.fetch_add(1, Ordering::SeqCst);
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(counter.load(Ordering::SeqCst), 3);
+    });
+    assert!(report.complete);
+    assert!(report.executions > 1, "only {} execution", report.executions);
+}
+
+#[test]
+fn a_lost_update_is_found_and_replayed() {
+    let failure = Builder::new().explore(racy_counter).unwrap_err();
+    assert!(failure.message.contains("an increment got lost"), "{}", failure);
+
+    // the reported schedule fails again, every time
+    for _ in 0..3 {
+        let replayed = Builder::new().replay(&failure.schedule.to_string()).explore(racy_counter).unwrap_err();
+        assert_eq!(replayed.message, failure.message);
+        assert_eq!(replayed.schedule, failure.schedule);
+    }
+}
+
+#[test]
+fn a_random_failure_is_replayed_from_its_seed() {
+    let failure = Builder::new().random(7).max_executions(10_000).explore(racy_counter).unwrap_err();
+    let seed = failure.seed.expect("random mode reports a seed");
+
+    let replayed = Builder::new().random(seed).max_executions(1).explore(racy_counter).unwrap_err();
+    assert_eq!(replayed.schedule, failure.schedule);
+    assert_eq!(replayed.execution, 0);
+}
+
+#[test]
+fn without_preemptions_the_race_stays_hidden() {
+    let report = Builder::new().preemption_bound(0).check(racy_counter);
+    assert!(report.complete);
+}
+
+#[test]
+fn a_deadlock_is_reported() {
+    let failure = Builder::new()
+        .explore(|| {
+            let locks = Arc::new((Mutex::new(()), Mutex::new(())));
+            let other = Arc::clone(&locks);
+            let handle = thread::spawn(move || {
+                let _second = other.1.lock().unwrap();
+                let _first = other.0.lock().unwrap();
+            });
+            {
+                let _first = locks.0.lock().unwrap();
+                let _second = locks.1.lock().unwrap();
+            }
+            handle.join().unwrap();
+        })
+        .unwrap_err();
+    assert!(failure.message.starts_with("deadlock"), "{}", failure);
+}
+
+#[test]
+fn a_spin_loop_without_yield_is_reported_as_a_livelock() {
+    let failure = Builder::new()
+        .preemption_bound(0)
+        .max_steps(500)
+        .explore(|| {
+            let ready = Arc::new(AtomicBool::new(false));
+            let other = Arc::clone(&ready);
+            let handle = thread::spawn(move || other.store(true, Ordering::SeqCst));
+            while !ready.load(Ordering::SeqCst) {}
+            handle.join().unwrap();
+        })
+        .unwrap_err();
+    assert!(failure.message.starts_with("more than 500 steps"), "{}", failure);
+
+    // with yield_now the loop lets the other thread in
+    Builder::new().preemption_bound(0).max_steps(500).check(|| {
+        let ready = Arc::new(AtomicBool::new(false));
+        let other = Arc::clone(&ready);
+        let handle = thread::spawn(move || other.store(true, Ordering::SeqCst));
+        while !ready.load(Ordering::SeqCst) {
+            thread::yield_now();
+        }
+        handle.join().unwrap();
+    });
+}
+
+#[test]
+fn condvar_handoff_never_loses_the_notify() {
+    let report = model::check(|| {
+        let state = Arc::new((Mutex::new(false), Condvar::new()));
+        let other = Arc::clone(&state);
+        let handle = thread::spawn(move || {
+            *other.0.lock().unwrap() = true;
+            other.1.notify_one();
+        });
+        let mut ready = state.0.lock().unwrap();
+        while !*ready {
+            ready = state.1.wait(ready).unwrap();
+        }
+        drop(ready);
+        handle.join().unwrap();
+    });
+    assert!(report.complete);
+}
+
+#[test]
+fn the_shims_work_like_std_outside_a_model_run() {
+    let counter = Arc::new(Mutex::new(0));
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let counter = Arc::clone(&counter);
+            thread::spawn(move || {
+                for _ in 0..1000 {
+                    *counter.lock().unwrap() += 1;
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    assert_eq!(*counter.lock().unwrap(), 4000);
+}
+
+//=================================================================
+// the knapsack shared best
+// SharedBest and the channels only use the shims with the model feature: cargo test --features model
+
+fn items(values: &[i32]) -> Vec<Item> {
+    values.iter().map(|&value| Item { weight: 1, value }).collect()
+}
+
+#[test]
+#[cfg(feature = "model")]
+fn the_knapsack_shared_best_keeps_the_best_items() {
+    let report = model::check(|| {
+        let best = Arc::new(SharedBest::new());
+        let handles: Vec<_> = [vec![2, 3], vec![7]]
+            .into_iter()
+            .map(|values| {
+                let best = Arc::clone(&best);
+                thread::spawn(move || {
+                    best.offer(values.iter().sum(), || items(&values));
+                })
+            })
+            .collect();
+        best.offer(6, || items(&[1, 5]));
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        let best = Arc::try_unwrap(best).ok().expect("every thread is done");
+        assert_eq!(best.value(), 7);
+        assert_eq!(best.into_items(), items(&[7]));
+    });
+    assert!(report.complete);
+}
+
+// the same update without checking again under the lock
+struct UncheckedBest {
+    highest_value: AtomicI64,
+    best: Mutex<(i32, Vec<Item>)>
+}
+
+impl UncheckedBest {
+    fn offer(&self, value: i32, items: Vec<Item>) {
+        if self.highest_value.fetch_max(value as i64, Ordering::AcqRel) < value as i64 {
+            *self.best.lock().unwrap() = (value, items);
+        }
+    }
+}
+
+#[test]
+fn the_harness_finds_the_missing_check_in_a_shared_best() {
+    let failure = Builder::new()
+        .explore(|| {
+            let best = Arc::new(UncheckedBest { highest_value: AtomicI64::new(0), best: Mutex::new((0, Vec::new())) });
+            let other = Arc::clone(&best);
+            let handle = thread::spawn(move || other.offer(7, items(&[7])));
+            best.offer(5, items(&[5]));
+            handle.join().unwrap();
+            assert_eq!(best.best.lock().unwrap().0, 7, "a worse combination overwrote the best one");
+        })
+        .unwrap_err();
+    assert!(failure.message.contains("a worse combination overwrote the best one"), "{}", failure);
+}
+
+//=================================================================
+// channels
+
+#[test]
+#[cfg(feature = "model")]
+fn a_bounded_channel_delivers_everything_in_order() {
+    let report = model::check(|| {
+        let (sender, receiver) = bounded(1);
+        let handles: Vec<_> = (0..2)
+            .map(|id| {
+                let sender = sender.clone();
+                thread::spawn(move || {
+                    for message in 0..2 {
+                        sender.send((id, message)).unwrap();
+                    }
+                })
+            })
+            .collect();
+        drop(sender);
+        let mut next = [0, 0];
+        while let Ok((id, message)) = receiver.recv() {
+            assert_eq!(message, next[id], "messages of one sender arrived out of order");
+            next[id] += 1;
+        }
+        assert_eq!(next, [2, 2]);
+        for handle in handles {
+            handle.join().unwrap();
+        }
+    });
+    assert!(report.complete);
+}
+
+#[test]
+#[cfg(feature = "model")]
+fn dropping_the_receiver_wakes_a_blocked_sender() {
+    let report = model::check(|| {
+        let (sender, receiver) = bounded(1);
+        let handle = thread::spawn(move || {
+            // the channel holds one message, so the sender ends up blocked until the receiver goes away
+            let mut message = 1;
+            while sender.send(message).is_ok() {
+                message += 1;
+            }
+            assert!(message <= 3, "sent {} messages to a receiver that took one", message - 1);
+        });
+        assert_eq!(receiver.recv(), Ok(1));
+        drop(receiver);
+        handle.join().unwrap();
+    });
+    assert!(report.complete);
+}
+
+#[test]
+#[cfg(feature = "model")]
+fn select_sees_messages_from_both_channels() {
+    let report = model::check(|| {
+        let (first_sender, first) = unbounded();
+        let (second_sender, second) = unbounded();
+        let handle = thread::spawn(move || {
+            first_sender.send(1).unwrap();
+            second_sender.send(2).unwrap();
+        });
+        let mut received = Vec::new();
+        for _ in 0..2 {
+            received.push(select(&[&first, &second]).unwrap().1);
+        }
+        received.sort();
+        assert_eq!(received, [1, 2]);
+        assert_eq!(select(&[&first, &second]), Err(RecvError));
+        handle.join().unwrap();
+    });
+    assert!(report.complete);
+}
@@ -0,0 +1,199 @@
+use std::cell::Cell;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Barrier, TryLockError};
+use std::thread;
+use rust_practice_lab::mutexes::*;
+
+const THREADS: usize = 8;
+const ROUNDS: usize = 2_000;
+
+//=================================================================
+// mutual exclusion under contention
+//
+// every thread increments a plain, non-atomic counter in two steps with a yield in between.
+// a second thread inside the critical section at the same time is caught by the `inside` flag
+// and shows up as lost increments
+
+fn hammer<L: RawLock>() {
+    let counter: Mutex<usize, L> = Mutex::new(0);
+    let inside = AtomicBool::new(false);
+    let start = Barrier::new(THREADS);
+    thread::scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|| {
+                start.wait();
+                for round in 0..ROUNDS {
+                    let mut guard = counter.lock().unwrap();
+                    assert!(!inside.swap(true, Ordering::Relaxed), "two threads hold the {}", L::name());
+                    let seen = *guard;
+                    if round % 64 == 0 {
+                        thread::yield_now();
+                    }
+                    *guard = seen + 1;
+                    inside.store(false, Ordering::Relaxed);
+                }
+            });
+        }
+    });
+    assert_eq!(counter.into_inner().unwrap(), THREADS * ROUNDS, "{} lost increments", L::name());
+}
+
+#[test]
+fn spin_lock_excludes() {
+    hammer::<SpinLock>();
+}
+
+#[test]
+fn ticket_lock_excludes() {
+    hammer::<TicketLock>();
+}
+
+#[test]
+fn mcs_lock_excludes() {
+    hammer::<McsLock>();
+}
+
+#[test]
+fn queue_lock_excludes() {
+    hammer::<QueueLock>();
+}
+
+#[test]
+fn reader_writer_lock_excludes_writers() {
+    hammer::<RawRwLock>();
+}
+
+#[test]
+fn readers_share_but_never_meet_a_writer() {
+    // the pair is only ever written together, a reader seeing different halves saw a half-done write
+    let lock = RwLock::new((0usize, 0usize));
+    let readers_inside = AtomicUsize::new(0);
+    let most_readers = AtomicUsize::new(0);
+    thread::scope(|scope| {
+        for _ in 0..2 {
+            scope.spawn(|| {
+                for _ in 0..ROUNDS {
+                    let mut pair = lock.write();
+                    assert_eq!(readers_inside.load(Ordering::Relaxed), 0, "a writer got in next to a reader");
+                    pair.0 += 1;
+                    thread::yield_now();
+                    pair.1 += 1;
+                }
+            });
+        }
+        for _ in 0..4 {
+            scope.spawn(|| {
+                for _ in 0..ROUNDS {
+                    let pair = lock.read();
+                    let now = readers_inside.fetch_add(1, Ordering::Relaxed) + 1;
+                    most_readers.fetch_max(now, Ordering::Relaxed);
+                    assert_eq!(pair.0, pair.1);
+                    readers_inside.fetch_sub(1, Ordering::Relaxed);
+                }
+            });
+        }
+    });
+    assert_eq!(lock.into_inner(), (2 * ROUNDS, 2 * ROUNDS));
+}
+
+#[test]
+fn try_lock_fails_while_the_lock_is_held() {
+    fn check<L: RawLock>() {
+        let mutex: Mutex<i32, L> = Mutex::new(1);
+        let guard = mutex.lock().unwrap();
+        thread::scope(|scope| {
+            scope.spawn(|| assert!(matches!(mutex.try_lock(), Err(TryLockError::WouldBlock)), "{}", L::name()));
+        });
+        drop(guard);
+        assert_eq!(*mutex.try_lock().unwrap(), 1);
+    }
+    check::<SpinLock>();
+    check::<TicketLock>();
+    check::<McsLock>();
+    check::<QueueLock>();
+    check::<RawRwLock>();
+}
+
+//=================================================================
+// poisoning
+
+fn poison<L: RawLock>(mutex: &Mutex<Vec<i32>, L>) {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let mut data = mutex.lock().unwrap();
+        data.push(2);
+        panic!("panicking while holding the lock");
+    }));
+    assert!(result.is_err());
+}
+
+#[test]
+fn a_panic_while_locked_poisons_the_mutex() {
+    let mutex: Mutex<Vec<i32>, TicketLock> = Mutex::new(vec![1]);
+    assert!(!mutex.is_poisoned());
+    poison(&mutex);
+    assert!(mutex.is_poisoned());
+    // the lock was released during unwinding, the data is still there behind the error
+    match mutex.lock() {
+        Err(error) => assert_eq!(*error.into_inner(), [1, 2]),
+        Ok(_) => panic!("the mutex should be poisoned")
+    }
+    assert!(matches!(mutex.try_lock(), Err(TryLockError::Poisoned(_))));
+}
+
+#[test]
+fn poison_can_be_cleared() {
+    let mut mutex: Mutex<Vec<i32>, McsLock> = Mutex::new(vec![1]);
+    poison(&mutex);
+    assert!(mutex.get_mut().is_err());
+    mutex.clear_poison();
+    assert_eq!(*mutex.lock().unwrap(), [1, 2]);
+    assert_eq!(mutex.into_inner().unwrap(), [1, 2]);
+}
+
+#[test]
+fn poison_reaches_other_threads() {
+    let mutex: Mutex<Vec<i32>, SpinLock> = Mutex::new(vec![1]);
+    thread::scope(|scope| {
+        let result = scope.spawn(|| {
+            let _guard = mutex.lock().unwrap();
+            panic!("a worker dies");
+        });
+        assert!(result.join().is_err());
+    });
+    assert!(mutex.lock().is_err());
+    assert!(mutex.into_inner().is_err());
+}
+
+#[test]
+fn a_guard_taken_while_unwinding_does_not_poison() {
+    struct LockOnDrop<'a>(&'a Mutex<i32, QueueLock>);
+
+    impl Drop for LockOnDrop<'_> {
+        fn drop(&mut self) {
+            *self.0.lock().unwrap() += 1;
+        }
+    }
+
+    let mutex: Mutex<i32, QueueLock> = Mutex::new(0);
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let _lock_on_drop = LockOnDrop(&mutex);
+        panic!("unwinding");
+    }));
+    assert!(result.is_err());
+    assert!(!mutex.is_poisoned());
+    assert_eq!(mutex.into_inner().unwrap(), 1);
+}
+
+//=================================================================
+// auto traits, the compile_fail doc tests on MutexGuard check the other direction
+
+fn assert_sync<T: Sync>() {}
+
+#[test]
+fn guards_are_sync_when_the_data_is() {
+    assert_sync::<MutexGuard<'static, i32, SpinLock>>();
+    assert_sync::<MutexGuard<'static, Vec<String>, TicketLock>>();
+    // the mutex itself only needs Send data, that is what makes it useful for a Cell
+    assert_sync::<Mutex<Cell<i32>, SpinLock>>();
+}
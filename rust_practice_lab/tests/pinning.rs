@@ -0,0 +1,323 @@
+use std::future::Future;
+use std::marker::PhantomPinned;
+use std::pin::{pin, Pin};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+use std::time::Duration;
+use rust_practice_lab::futures::{block_on, sleep, timeout, Elapsed, Executor, Timeout};
+use rust_practice_lab::mutexes::{self, QueueLock};
+use rust_practice_lab::pin_project;
+use rust_practice_lab::pinning::{TokenBuffer, WaitQueue};
+
+// these tests also run under Miri (cargo +nightly miri test --test pinning), which reports any access through
+// a pointer to a value that was moved or freed. Miri is slow, so it gets smaller workloads,
+// and the tests that need the timer thread are skipped there
+const fn scaled(operations: usize) -> usize {
+    if cfg!(miri) { operations / 100 + 1 } else { operations }
+}
+
+fn poll_once<F: Future + ?Sized>(future: Pin<&mut F>) -> Poll<F::Output> {
+    future.poll(&mut Context::from_waker(Waker::noop()))
+}
+
+// only compiles when T is !Unpin: for an Unpin type both impls apply and the call is ambiguous
+trait AmbiguousIfUnpin<A> {
+    fn assert_not_unpin() {}
+}
+impl<T: ?Sized> AmbiguousIfUnpin<()> for T {}
+impl<T: ?Sized + Unpin> AmbiguousIfUnpin<u8> for T {}
+
+fn assert_unpin<T: Unpin>() {}
+
+// remembers its own address on every poll, and is done after `polls` polls
+struct AddressRecorder {
+    addresses: Arc<Mutex<Vec<usize>>>,
+    polls: usize,
+    _pinned: PhantomPinned
+}
+
+impl AddressRecorder {
+    fn new(polls: usize, addresses: &Arc<Mutex<Vec<usize>>>) -> Self {
+        Self { addresses: Arc::clone(addresses), polls, _pinned: PhantomPinned }
+    }
+}
+
+impl Future for AddressRecorder {
+    type Output = usize;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<usize> {
+        let address = &*self as *const Self as usize;
+        let mut addresses = self.addresses.lock().unwrap();
+        addresses.push(address);
+        if addresses.len() >= self.polls {
+            Poll::Ready(addresses.len())
+        } else {
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+fn all_equal(addresses: &[usize]) -> bool {
+    addresses.windows(2).all(|pair| pair[0] == pair[1])
+}
+
+//=================================================================
+// the self-referential token buffer
+
+#[test]
+fn tokens_split_on_whitespace_across_pushes() {
+    let mut buffer = pin!(TokenBuffer::<32>::new());
+    buffer.as_mut().push_str("let answer").unwrap();
+    buffer.as_mut().push_str("_value =  42 ").unwrap();
+    assert_eq!(buffer.as_ref().tokens().collect::<Vec<_>>(), ["let", "answer_value", "=", "42"]);
+    assert_eq!(buffer.as_ref().as_str(), "let answer_value =  42 ");
+
+    buffer.as_mut().clear();
+    assert_eq!(buffer.as_ref().tokens().count(), 0);
+    assert_eq!(buffer.remaining(), 32);
+}
+
+#[test]
+fn a_full_buffer_keeps_its_tokens() {
+    let mut buffer = Box::pin(TokenBuffer::<8>::new());
+    buffer.as_mut().push_str("abc def").unwrap();
+    let error = buffer.as_mut().push_str("ghi").unwrap_err();
+    assert_eq!((error.needed, error.available), (3, 1));
+    assert_eq!(buffer.as_ref().tokens().collect::<Vec<_>>(), ["abc", "def"]);
+}
+
+#[test]
+fn tokens_still_point_into_the_buffer_after_moving_the_box() {
+    let mut buffer = Box::pin(TokenBuffer::<64>::new());
+    buffer.as_mut().push_str("one two three").unwrap();
+    let text = buffer.as_ref().as_str().as_ptr() as usize;
+
+    // moving the box (and a vector of boxes around it) moves the pointer, not the pinned value
+    let mut boxes = vec![buffer];
+    boxes.reserve(100);
+    let buffer = boxes.pop().unwrap();
+
+    assert_eq!(buffer.as_ref().as_str().as_ptr() as usize, text);
+    let range = text..text + buffer.as_ref().as_str().len();
+    for token in buffer.as_ref().tokens() {
+        assert!(range.contains(&(token.as_ptr() as usize)), "{} points outside of the buffer", token);
+    }
+    assert_eq!(buffer.as_ref().tokens().collect::<Vec<_>>(), ["one", "two", "three"]);
+}
+
+#[test]
+fn pinned_types_are_not_unpin() {
+    <TokenBuffer<8> as AmbiguousIfUnpin<_>>::assert_not_unpin();
+    let queue = WaitQueue::new();
+    let wait = queue.wait_if(|| true);
+    fn assert_wait_not_unpin<T>(_: &T) {
+        <T as AmbiguousIfUnpin<_>>::assert_not_unpin();
+    }
+    assert_wait_not_unpin(&wait);
+}
+
+//=================================================================
+// pin projection
+
+pin_project! {
+    #[project(PairProjection)]
+    struct Pair<F> {
+        #[pin]
+        future: F,
+        polls: usize
+    }
+}
+
+impl<F: Future> Future for Pair<F> {
+    type Output = (F::Output, usize);
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        *this.polls += 1;
+        let polls = *this.polls;
+        this.future.poll(cx).map(|output| (output, polls))
+    }
+}
+
+#[test]
+fn a_projected_struct_is_unpin_only_when_its_pinned_fields_are() {
+    assert_unpin::<Pair<std::future::Ready<i32>>>();
+    assert_unpin::<Timeout<std::future::Ready<i32>>>();
+    <Pair<AddressRecorder> as AmbiguousIfUnpin<_>>::assert_not_unpin();
+    <Timeout<AddressRecorder> as AmbiguousIfUnpin<_>>::assert_not_unpin();
+}
+
+#[test]
+fn the_pinned_field_never_moves_between_polls() {
+    let addresses = Arc::new(Mutex::new(Vec::new()));
+    let mut pair = Box::pin(Pair { future: AddressRecorder::new(5, &addresses), polls: 0 });
+    assert!(poll_once(pair.as_mut()).is_pending());
+    assert!(poll_once(pair.as_mut()).is_pending());
+
+    // move the box around between polls
+    let mut moved = vec![pair];
+    moved.reserve(100);
+    let mut pair = moved.pop().unwrap();
+    assert!(poll_once(pair.as_mut()).is_pending());
+    assert!(poll_once(pair.as_mut()).is_pending());
+    assert_eq!(poll_once(pair.as_mut()), Poll::Ready((5, 5)));
+
+    let addresses = addresses.lock().unwrap();
+    assert_eq!(addresses.len(), 5);
+    assert!(all_equal(&addresses), "the future moved: {:?}", addresses);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn timeout_returns_the_output_in_time() {
+    let addresses = Arc::new(Mutex::new(Vec::new()));
+    let result = block_on(timeout(Duration::from_secs(5), AddressRecorder::new(10, &addresses)));
+    assert_eq!(result, Ok(10));
+    assert!(all_equal(&addresses.lock().unwrap()));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn timeout_gives_up_on_a_slow_future() {
+    let result = block_on(timeout(Duration::from_millis(10), async {
+        sleep(Duration::from_secs(5)).await;
+        "too late"
+    }));
+    assert_eq!(result, Err(Elapsed));
+}
+
+//=================================================================
+// the intrusive wait queue
+
+#[test]
+fn a_false_condition_completes_without_waiting() {
+    let queue = WaitQueue::new();
+    let mut wait = pin!(queue.wait_if(|| false));
+    assert!(poll_once(wait.as_mut()).is_ready());
+    assert!(queue.is_empty());
+    assert!(!queue.notify_one());
+}
+
+#[test]
+fn waiters_are_notified_in_fifo_order() {
+    let queue = WaitQueue::new();
+    let mut first = Box::pin(queue.wait_if(|| true));
+    let mut second = Box::pin(queue.wait_if(|| true));
+    assert!(poll_once(first.as_mut()).is_pending());
+    assert!(poll_once(second.as_mut()).is_pending());
+
+    assert!(queue.notify_one());
+    assert!(poll_once(second.as_mut()).is_pending());
+    assert!(poll_once(first.as_mut()).is_ready());
+    assert!(queue.notify_one());
+    assert!(poll_once(second.as_mut()).is_ready());
+    assert!(queue.is_empty());
+}
+
+#[test]
+fn a_dropped_waiter_unlinks_itself() {
+    let queue = WaitQueue::new();
+    let mut waits: Vec<_> = (0..3).map(|_| Box::pin(queue.wait_if(|| true))).collect();
+    for wait in &mut waits {
+        assert!(poll_once(wait.as_mut()).is_pending());
+    }
+    // the middle node goes away, its neighbours have to be linked to each other
+    drop(waits.remove(1));
+    assert_eq!(queue.notify_all(), 2);
+    for wait in &mut waits {
+        assert!(poll_once(wait.as_mut()).is_ready());
+    }
+    drop(waits);
+    assert!(queue.is_empty());
+}
+
+#[test]
+fn a_notify_nobody_saw_is_passed_on() {
+    let queue = WaitQueue::new();
+    let mut first = Box::pin(queue.wait_if(|| true));
+    let mut second = Box::pin(queue.wait_if(|| true));
+    assert!(poll_once(first.as_mut()).is_pending());
+    assert!(poll_once(second.as_mut()).is_pending());
+
+    assert!(queue.notify_one());
+    // the first waiter got the notify but is dropped before it could act on it
+    drop(first);
+    assert!(poll_once(second.as_mut()).is_ready());
+    assert!(queue.is_empty());
+}
+
+#[test]
+fn a_blocked_thread_is_woken_by_notify_one() {
+    let queue = Arc::new(WaitQueue::new());
+    let ready = Arc::new(AtomicBool::new(false));
+    let waiter = {
+        let queue = Arc::clone(&queue);
+        let ready = Arc::clone(&ready);
+        thread::spawn(move || {
+            while !ready.load(Ordering::Acquire) {
+                queue.wait_blocking_if(|| !ready.load(Ordering::Acquire));
+            }
+        })
+    };
+    while queue.is_empty() && !waiter.is_finished() {
+        thread::yield_now();
+    }
+    ready.store(true, Ordering::Release);
+    queue.notify_one();
+    waiter.join().unwrap();
+    assert!(queue.is_empty());
+}
+
+#[test]
+fn async_tasks_wait_on_the_executor() {
+    let executor = Executor::new();
+    let queue = Arc::new(WaitQueue::new());
+    let woken = Arc::new(AtomicUsize::new(0));
+    let handles: Vec<_> = (0..5)
+        .map(|_| {
+            let queue = Arc::clone(&queue);
+            let woken = Arc::clone(&woken);
+            executor.spawn(async move {
+                queue.wait_if(|| true).await;
+                woken.fetch_add(1, Ordering::SeqCst);
+            })
+        })
+        .collect();
+    executor.run_until_stalled();
+    assert_eq!(woken.load(Ordering::SeqCst), 0);
+
+    assert!(queue.notify_one());
+    executor.run_until_stalled();
+    assert_eq!(woken.load(Ordering::SeqCst), 1);
+
+    assert_eq!(queue.notify_all(), 4);
+    executor.block_on(async {
+        for handle in handles {
+            handle.await;
+        }
+    });
+    assert_eq!(woken.load(Ordering::SeqCst), 5);
+}
+
+//=================================================================
+// the parking lock on top of the queue
+
+#[test]
+fn queue_lock_counts_correctly() {
+    const THREADS: usize = 4;
+    let increments = scaled(10000);
+    let counter: mutexes::Mutex<usize, QueueLock> = mutexes::Mutex::new(0);
+    thread::scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|| {
+                for _ in 0..increments {
+                    *counter.lock().unwrap() += 1;
+                }
+            });
+        }
+    });
+    assert_eq!(counter.into_inner().unwrap(), THREADS * increments);
+}
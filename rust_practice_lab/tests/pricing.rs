@@ -0,0 +1,319 @@
+mod common;
+
+use common::{car, traits, vehicle};
+use traits::associated_constants::Category;
+use traits::decimal::{Decimal, RoundingMode};
+use traits::default_type_parameters::{Eur, Length, Mass, Money, Usd};
+use traits::pricing::{Basis, CurrencyCode, Depreciation, LineKind, Price, PricingEngine, PricingError, RatesTable, Rule};
+use traits::traits::poly::{Airplane, AmphibiousBoat, Boat};
+use traits::traits::Vehicles;
+
+fn decimal(text: &str) -> Decimal {
+    text.parse().unwrap()
+}
+
+fn vehicles() -> Vec<Vehicles> {
+    vec![
+        car("Family Car", 32000.0, 150.0),
+        Boat::new(vehicle("Fishing Boat", 45000.0), Mass::from_kilograms(40.0)).into(),
+        AmphibiousBoat::new(Boat::new(vehicle("Duck Tour", 120000.0), Mass::from_kilograms(20.0)), 6).into(),
+        Airplane::new(vehicle("Crop Duster", 180000.0), Length::from_meters(12.0)).into()
+    ]
+}
+
+const RATES: &str = "\
+# 1 USD is worth
+base USD
+EUR 0.92   # euro
+JPY 149.5 0
+CHF 0.8811
+";
+
+fn rates() -> RatesTable {
+    RatesTable::parse(RATES).unwrap()
+}
+
+fn eur() -> CurrencyCode {
+    CurrencyCode::of::<Eur>()
+}
+
+//=================================================================
+// decimals
+
+#[test]
+fn decimals_add_up_exactly() {
+    assert_eq!(decimal("0.1") + decimal("0.2"), decimal("0.3"));
+    let tickets: Decimal = (0..10).map(|_| decimal("0.10")).sum();
+    assert_eq!(tickets, Decimal::ONE);
+    assert_eq!(decimal("19999.99") * Decimal::from(3), decimal("59999.97"));
+    assert_eq!(decimal("1.5") - decimal("2.25"), decimal("-0.75"));
+    assert_eq!(-decimal("0.5"), decimal("-0.5"));
+}
+
+#[test]
+fn decimals_parse_and_print_with_their_scale() {
+    assert_eq!(decimal("4140.00").to_string(), "4140.00");
+    assert_eq!(decimal("-0.05").to_string(), "-0.05");
+    assert_eq!(decimal("1_000.5").to_string(), "1000.5");
+    assert_eq!(Decimal::new(5, 3).to_string(), "0.005");
+    assert_eq!(format!("[{:>8}]", decimal("12.5")), "[    12.5]");
+    for broken in ["", "-", "1.", ".5", "1.2.3", "1e5", "12a", "99999999999999999999999999999999999999999"] {
+        assert_eq!(broken.parse::<Decimal>().unwrap_err().input, broken);
+    }
+}
+
+#[test]
+fn scale_does_not_change_the_value() {
+    assert_eq!(decimal("1.5"), decimal("1.500"));
+    assert!(decimal("1.05") < decimal("1.5"));
+    assert!(decimal("-2") < decimal("-1.99"));
+    assert_eq!(decimal("1.2500").normalize().to_string(), "1.25");
+    // too many digits to line up in an i128, still ordered
+    let huge = Decimal::new(i128::MAX / 2, 0);
+    let tiny = Decimal::new(1, 38);
+    assert!(tiny < huge && -huge < tiny && -huge < -tiny);
+    assert!(Decimal::new(i128::MAX, 10) > Decimal::new(1, 37));
+}
+
+#[test]
+fn floats_become_their_shortest_decimal() {
+    assert_eq!(Decimal::from_f64(0.1).unwrap().to_string(), "0.1");
+    assert_eq!(Decimal::from_f64(4500.0).unwrap().to_string(), "4500");
+    assert_eq!(Decimal::from_f64(-63850.5).unwrap(), decimal("-63850.5"));
+    assert_eq!(Decimal::from_f64(f64::NAN), None);
+    assert_eq!(Decimal::from_f64(f64::INFINITY), None);
+    assert_eq!(Decimal::from_f64(1e300), None);
+}
+
+#[test]
+fn every_rounding_mode() {
+    let round = |text: &str, mode| decimal(text).round(2, mode).to_string();
+    let expected = [
+        (RoundingMode::HalfUp, ["2.35", "-2.35", "2.36", "2.34", "2.35"]),
+        (RoundingMode::HalfDown, ["2.34", "-2.34", "2.35", "2.34", "2.35"]),
+        (RoundingMode::HalfEven, ["2.34", "-2.34", "2.36", "2.34", "2.35"]),
+        (RoundingMode::Up, ["2.35", "-2.35", "2.36", "2.35", "2.35"]),
+        (RoundingMode::Down, ["2.34", "-2.34", "2.35", "2.34", "2.34"]),
+        (RoundingMode::Ceiling, ["2.35", "-2.34", "2.36", "2.35", "2.35"]),
+        (RoundingMode::Floor, ["2.34", "-2.35", "2.35", "2.34", "2.34"])
+    ];
+    assert_eq!(expected.len(), RoundingMode::ALL.len());
+    for (mode, results) in expected {
+        let rounded: Vec<String> = ["2.345", "-2.345", "2.355", "2.341", "2.3451"].iter().map(|number| round(number, mode)).collect();
+        assert_eq!(rounded, results, "{:?}", mode);
+    }
+    // more digits is exact in every mode
+    for mode in RoundingMode::ALL {
+        assert_eq!(decimal("2.5").round(3, mode).to_string(), "2.500");
+    }
+}
+
+#[test]
+fn division_rounds_once() {
+    assert_eq!(Decimal::ONE.div(Decimal::from(3), 4, RoundingMode::HalfEven).to_string(), "0.3333");
+    assert_eq!(Decimal::from(2).div(Decimal::from(3), 2, RoundingMode::Down).to_string(), "0.66");
+    assert_eq!(decimal("-1").div(Decimal::from(8), 2, RoundingMode::HalfEven).to_string(), "-0.12");
+    assert_eq!(decimal("-1").div(Decimal::from(8), 2, RoundingMode::HalfUp).to_string(), "-0.13");
+    assert_eq!(decimal("10").div(decimal("0.25"), 0, RoundingMode::Down), Decimal::from(40));
+    assert_eq!(Decimal::ONE.checked_div(Decimal::ZERO, 2, RoundingMode::HalfUp), None);
+}
+
+#[test]
+fn overflow_is_none_in_the_checked_methods() {
+    let max = Decimal::new(i128::MAX, 0);
+    assert_eq!(max.checked_add(Decimal::ONE), None);
+    assert_eq!(max.checked_mul(Decimal::from(2)), None);
+    assert_eq!(max.checked_round(1, RoundingMode::HalfUp), None);
+    assert_eq!(max.checked_sub(Decimal::ONE), Some(Decimal::new(i128::MAX - 1, 0)));
+}
+
+//=================================================================
+// currencies and rates
+
+#[test]
+fn currency_codes_are_three_capital_letters() {
+    assert_eq!(CurrencyCode::of::<Usd>(), CurrencyCode::USD);
+    assert_eq!("EUR".parse::<CurrencyCode>().unwrap(), eur());
+    for broken in ["eur", "EURO", "E1R", ""] {
+        assert!(matches!(broken.parse::<CurrencyCode>(), Err(PricingError::InvalidCurrency(code)) if code == broken));
+    }
+    let price = Price::from_money(Money::<Eur>::new(99.95)).unwrap();
+    assert_eq!(price.to_string(), "99.95 EUR");
+    assert!(matches!(Price::from_money(Money::<Usd>::new(f64::NAN)), Err(PricingError::InvalidAmount(_))));
+}
+
+#[test]
+fn rates_convert_through_the_base_with_one_rounding() {
+    let rates = rates();
+    assert_eq!(rates.base(), CurrencyCode::USD);
+    let codes: Vec<String> = rates.currencies().map(|code| code.to_string()).collect();
+    assert_eq!(codes, ["CHF", "EUR", "JPY", "USD"]);
+    assert_eq!(rates.minor_units("JPY".parse().unwrap()).unwrap(), 0);
+    assert_eq!(rates.minor_units(eur()).unwrap(), 2);
+
+    let usd = CurrencyCode::USD;
+    let chf = "CHF".parse().unwrap();
+    assert_eq!(rates.convert(decimal("100"), usd, eur(), 2, RoundingMode::HalfEven).unwrap(), decimal("92"));
+    // 100 EUR = 100 / 0.92 * 0.8811 CHF = 95.77173... CHF
+    assert_eq!(rates.convert(decimal("100"), eur(), chf, 2, RoundingMode::HalfEven).unwrap().to_string(), "95.77");
+    assert_eq!(rates.convert(decimal("100"), eur(), chf, 2, RoundingMode::Up).unwrap().to_string(), "95.78");
+    assert_eq!(rates.exchange_rate(eur(), usd, 6, RoundingMode::HalfEven).unwrap().to_string(), "1.086957");
+    let gbp = "GBP".parse().unwrap();
+    assert!(matches!(rates.convert(Decimal::ONE, gbp, usd, 2, RoundingMode::HalfUp), Err(PricingError::UnknownCurrency(code)) if code == gbp));
+    let rates = rates.rate(gbp, decimal("0.79"), 2);
+    assert_eq!(rates.convert(decimal("79"), gbp, usd, 2, RoundingMode::HalfUp).unwrap(), decimal("100"));
+}
+
+#[test]
+fn broken_rates_tables_name_the_line() {
+    let error = |text: &str| match RatesTable::parse(text) {
+        Err(PricingError::InvalidRates { line, reason }) => (line, reason),
+        other => panic!("{:?}", other)
+    };
+    assert_eq!(error(""), (0, "the table is empty".to_string()));
+    assert_eq!(error("# only a comment\nEUR 0.92"), (2, "the table has to start with `base` and a currency".to_string()));
+    assert_eq!(error("base USD\nbase EUR"), (2, "the base currency is given twice".to_string()));
+    assert_eq!(error("base USD\n\nEUR 0,92"), (3, "\"0,92\" is not a decimal number".to_string()));
+    assert_eq!(error("base USD\nEUR -0.92"), (2, "the rate of EUR has to be positive".to_string()));
+    assert_eq!(error("base USD\nUSD 2"), (2, "the base currency USD is worth 1 of itself".to_string()));
+    assert_eq!(error("base USD\neuro 0.92"), (2, "\"euro\" is not a currency code, those are three capital letters like EUR".to_string()));
+    assert_eq!(error("base USD\nEUR 0.92 two"), (2, "\"two\" is not a number of digits".to_string()));
+    assert_eq!(error("base USD\nEUR"), (2, "expected a currency, its rate and optionally its digits".to_string()));
+    assert_eq!(error("base USD\nXAU 2 39"), (2, "XAU can have at most 18 digits, got 39".to_string()));
+    assert_eq!(RatesTable::parse("base USD\nEUR -1").unwrap_err().to_string(), "invalid rates table, line 2: the rate of EUR has to be positive");
+}
+
+//=================================================================
+// depreciation
+
+#[test]
+fn depreciation_schedules() {
+    let price = Decimal::from(10000);
+    let value = |schedule: &Depreciation, years| schedule.value_after(price, years, 2, RoundingMode::HalfEven).to_string();
+
+    let declining = Depreciation::DecliningBalance { yearly: decimal("0.15") };
+    assert_eq!(value(&declining, 0), "10000.00");
+    assert_eq!(value(&declining, 1), "8500.00");
+    // 7225.00 * 0.85 = 6141.25, then 5220.0625 is booked as 5220.06
+    assert_eq!(value(&declining, 4), "5220.06");
+
+    let straight = Depreciation::StraightLine { yearly: decimal("0.06"), residual: decimal("0.25") };
+    assert_eq!(value(&straight, 5), "7000.00");
+    assert_eq!(value(&straight, 30), "2500.00");
+
+    let table = Depreciation::Table(vec![decimal("0.9"), decimal("0.8")]);
+    assert_eq!(value(&table, 1), "9000.00");
+    assert_eq!(value(&table, 7), "8000.00");
+    assert_eq!(value(&Depreciation::None, 7), "10000.00");
+    assert_eq!(Depreciation::default_for(Category::Automobile), declining);
+}
+
+//=================================================================
+// quotes
+
+fn engine() -> PricingEngine {
+    PricingEngine::new(rates(), eur())
+        .rule(Rule::flat("registration", Price::new(Decimal::from(150), eur())).only(Category::Automobile).only(Category::Amphibian))
+        .rule(Rule::above("luxury tax", Price::new(Decimal::from(60000), CurrencyCode::USD), decimal("0.1")))
+        .rule(Rule::percentage("VAT", decimal("0.21"), Basis::Subtotal))
+}
+
+#[test]
+fn a_quote_is_itemised() {
+    let quote = engine().quote(vehicles()[0].as_vehicle(), 3).unwrap();
+    let lines: Vec<(LineKind, &str, String)> = quote.lines.iter().map(|line| (line.kind, line.label.as_str(), line.amount.to_string())).collect();
+    assert_eq!(
+        lines,
+        [
+            (LineKind::ListPrice, "list price 32000 USD", "29440.00".to_string()),
+            // 29440 * 0.85^3 booked every year: 25024.00, 21270.40, 18079.84
+            (LineKind::Depreciation, "depreciation after 3 years, 15% declining", "-11360.16".to_string()),
+            (LineKind::Charge, "registration", "150.00".to_string()),
+            (LineKind::Charge, "luxury tax 10% above 60000 USD", "0.00".to_string()),
+            // 21% of 18229.84 = 3828.2664
+            (LineKind::Charge, "VAT 21% of the subtotal", "3828.27".to_string())
+        ]
+    );
+    assert_eq!(quote.total, decimal("22058.11"));
+    assert_eq!(quote.currency, eur());
+    assert!(quote.to_string().ends_with("    total                                               22058.11 EUR"));
+}
+
+#[test]
+fn rules_only_apply_to_their_categories() {
+    let engine = engine();
+    let vehicles = vehicles();
+    let labels = |index: usize| -> Vec<String> { engine.quote(vehicles[index].as_vehicle(), 0).unwrap().lines.into_iter().map(|line| line.label).collect() };
+    assert!(labels(0).contains(&"registration".to_string()));
+    assert!(!labels(1).contains(&"registration".to_string()));
+    assert!(labels(2).contains(&"registration".to_string()));
+    // a new vehicle has no depreciation line
+    assert!(labels(3).iter().all(|label| !label.starts_with("depreciation")));
+}
+
+#[test]
+fn luxury_tax_is_on_the_part_above_the_threshold() {
+    let engine = PricingEngine::new(rates(), CurrencyCode::USD).rule(Rule::above("luxury tax", Price::new(Decimal::from(55200), eur()), decimal("0.1")));
+    let quote = engine.quote(vehicles()[3].as_vehicle(), 0).unwrap();
+    // 55200 EUR is 60000 USD, 10% of 120000
+    assert_eq!(quote.lines[1].amount, Decimal::from(12000));
+    assert_eq!(quote.total, Decimal::from(192000));
+}
+
+#[test]
+fn quotes_add_up_in_every_rounding_mode() {
+    let vehicles = vehicles();
+    let ages = [3, 7, 2, 9];
+    let mut totals = Vec::new();
+    for mode in RoundingMode::ALL {
+        let engine = PricingEngine::new(rates(), "CHF".parse().unwrap()).rounding(mode).rule(Rule::percentage("VAT", decimal("0.077"), Basis::Value));
+        let fleet = engine.quote_fleet(vehicles.iter().zip(ages)).unwrap();
+        for quote in &fleet.quotes {
+            assert_eq!(quote.lines.iter().map(|line| line.amount).sum::<Decimal>(), quote.total, "{:?} {}", mode, quote.name);
+            assert!(quote.lines.iter().all(|line| line.amount.round(2, RoundingMode::Down) == line.amount), "{:?} has more than cents", mode);
+        }
+        assert_eq!(fleet.quotes.iter().map(|quote| quote.total).sum::<Decimal>(), fleet.total);
+        totals.push(fleet.total);
+    }
+    // the same engine gives the same cents every time
+    let again = PricingEngine::new(rates(), "CHF".parse().unwrap()).rounding(RoundingMode::Floor).rule(Rule::percentage("VAT", decimal("0.077"), Basis::Value));
+    assert_eq!(again.quote_fleet(vehicles.iter().zip(ages)).unwrap().total, totals[6]);
+    // towards zero never gives more than away from it
+    assert!(totals[4] <= totals[3] && totals[6] <= totals[5]);
+}
+
+#[test]
+fn quotes_use_the_digits_of_their_currency() {
+    let engine = PricingEngine::new(rates(), "JPY".parse().unwrap());
+    let quote = engine.quote(vehicles()[0].as_vehicle(), 1).unwrap();
+    assert_eq!(quote.lines[0].amount.to_string(), "4784000");
+    assert_eq!(quote.total.to_string(), "4066400");
+
+    let missing = PricingEngine::new(rates(), "GBP".parse().unwrap()).quote(vehicles()[0].as_vehicle(), 0);
+    assert!(matches!(missing, Err(PricingError::UnknownCurrency(_))));
+}
+
+#[test]
+fn a_replaced_schedule_is_used() {
+    let engine = PricingEngine::new(rates(), CurrencyCode::USD).depreciation(Category::Aircraft, Depreciation::None);
+    assert_eq!(engine.schedule(Category::Aircraft), &Depreciation::None);
+    let quote = engine.quote(vehicles()[3].as_vehicle(), 10).unwrap();
+    assert_eq!(quote.lines.len(), 1);
+    assert_eq!(quote.total, Decimal::from(180000));
+}
+
+#[test]
+fn amounts_too_large_for_a_decimal_are_an_error() {
+    let gold = |text: &str| PricingEngine::new(RatesTable::parse(text).unwrap(), "XAU".parse().unwrap());
+    // 32000 USD at 18 digits fits, a price 10^16 times larger doesn't
+    let engine = gold("base USD\nXAU 2 18");
+    assert_eq!(engine.quote(vehicles()[0].as_vehicle(), 0).unwrap().total.to_string(), "64000.000000000000000000");
+    let gold_plated = car("Gold Plated", 1e20, 500.0);
+    assert!(matches!(engine.quote(gold_plated.as_vehicle(), 0), Err(PricingError::Overflow)));
+
+    let engine = gold("base USD\nXAU 100000000000000000000000000000000");
+    assert!(matches!(engine.quote(vehicles()[0].as_vehicle(), 3), Err(PricingError::Overflow)));
+    let rates = RatesTable::parse("base USD\nXAU 100000000000000000000000000000000").unwrap();
+    let error = rates.convert(Decimal::from(32000), CurrencyCode::USD, "XAU".parse().unwrap(), 2, RoundingMode::HalfEven).unwrap_err();
+    assert_eq!(error.to_string(), "the amount has more digits than a Decimal can hold");
+}
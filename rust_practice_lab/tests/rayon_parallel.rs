@@ -0,0 +1,171 @@
+use std::cmp::Ordering;
+use rust_practice_lab::rayon_parallel::*;
+
+// large enough to be split into many chunks, the cutoff for going parallel is 4096
+const LEN: usize = 100_000;
+
+// the same pseudo-random numbers every run
+fn numbers(len: usize, seed: u64) -> Vec<u64> {
+    let mut state = seed;
+    (0..len)
+        .map(|_| {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            state >> 33
+        })
+        .collect()
+}
+
+// every check runs on pools of a few sizes, a single thread has to give the same answer as many
+fn on_pools(check: impl Fn() + Send + Sync) {
+    for threads in [1, 3, 8] {
+        time_on_threads(threads, &check);
+    }
+}
+
+//=================================================================
+// scan
+
+#[test]
+fn par_scan_matches_scan() {
+    on_pools(|| {
+        for len in [0, 1, 4095, 4096, 4097, LEN] {
+            let values = numbers(len, 1);
+            assert_eq!(par_prefix_sum(&values), prefix_sum(&values), "len {}", len);
+            assert_eq!(par_scan(&values, 0, u64::max), scan(&values, 0, u64::max), "len {}", len);
+        }
+    });
+}
+
+#[test]
+fn par_scan_keeps_the_order_of_a_non_commutative_op() {
+    // composing x -> a * x + b maps is associative but not commutative
+    let compose = |(a1, b1): (u64, u64), (a2, b2): (u64, u64)| (a1.wrapping_mul(a2), b1.wrapping_mul(a2).wrapping_add(b2));
+    let maps: Vec<(u64, u64)> = numbers(LEN, 2).into_iter().zip(numbers(LEN, 3)).collect();
+    on_pools(|| assert_eq!(par_scan(&maps, (1, 0), compose), scan(&maps, (1, 0), compose)));
+}
+
+#[test]
+fn prefix_sums_wrap_around() {
+    assert_eq!(prefix_sum(&[u64::MAX, 2, 3]), [u64::MAX, 1, 4]);
+    assert_eq!(par_prefix_sum(&[u64::MAX, 2, 3]), [u64::MAX, 1, 4]);
+}
+
+//=================================================================
+// sorting
+
+// ordered by key only, position tells equal keys apart
+#[derive(Debug, Copy, Clone)]
+struct Keyed {
+    key: u64,
+    position: usize
+}
+
+impl PartialEq for Keyed {
+    fn eq(&self, other: &Keyed) -> bool {
+        self.key == other.key
+    }
+}
+
+impl Eq for Keyed {}
+
+impl PartialOrd for Keyed {
+    fn partial_cmp(&self, other: &Keyed) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Keyed {
+    fn cmp(&self, other: &Keyed) -> Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+// few distinct keys, so there are long runs of equal ones
+fn keyed(len: usize) -> Vec<Keyed> {
+    numbers(len, 4).into_iter().enumerate().map(|(position, number)| Keyed { key: number % 16, position }).collect()
+}
+
+fn positions(values: &[Keyed]) -> Vec<(u64, usize)> {
+    values.iter().map(|value| (value.key, value.position)).collect()
+}
+
+#[test]
+fn merge_sorts_are_stable() {
+    let mut expected = keyed(LEN);
+    // the standard library's sort is stable too
+    expected.sort();
+    let mut sequential = keyed(LEN);
+    merge_sort(&mut sequential);
+    assert_eq!(positions(&sequential), positions(&expected));
+    on_pools(|| {
+        let mut parallel = keyed(LEN);
+        par_merge_sort(&mut parallel);
+        assert_eq!(positions(&parallel), positions(&expected));
+    });
+}
+
+#[test]
+fn parallel_sorts_match_the_sequential_one() {
+    for len in [0, 1, 2, 17, 4096, 4097, LEN] {
+        let mut expected = numbers(len, 5);
+        merge_sort(&mut expected);
+        assert!(expected.windows(2).all(|pair| pair[0] <= pair[1]));
+        on_pools(|| {
+            let mut merged = numbers(len, 5);
+            par_merge_sort(&mut merged);
+            assert_eq!(merged, expected, "merge sort, len {}", len);
+            let mut sampled = numbers(len, 5);
+            par_sample_sort(&mut sampled);
+            assert_eq!(sampled, expected, "sample sort, len {}", len);
+        });
+    }
+}
+
+#[test]
+fn sample_sort_copes_with_many_equal_values() {
+    let mut values: Vec<u64> = numbers(LEN, 6).into_iter().map(|number| number % 3).collect();
+    let mut expected = values.clone();
+    expected.sort_unstable();
+    on_pools(|| {
+        let mut sampled = values.clone();
+        par_sample_sort(&mut sampled);
+        assert_eq!(sampled, expected);
+    });
+    values.fill(7);
+    par_sample_sort(&mut values);
+    assert!(values.iter().all(|&value| value == 7));
+}
+
+//=================================================================
+// aggregates
+
+#[test]
+fn par_histogram_matches_histogram() {
+    let values = numbers(LEN, 7);
+    let bucket_of = |value: &u64| (*value % 10) as usize;
+    let expected = histogram(&values, 10, bucket_of);
+    assert_eq!(expected.iter().sum::<usize>(), LEN);
+    on_pools(|| assert_eq!(par_histogram(&values, 10, bucket_of), expected));
+    assert_eq!(par_histogram(&[] as &[u64], 4, bucket_of), [0, 0, 0, 0]);
+}
+
+#[test]
+fn par_reduce_by_key_matches_reduce_by_key() {
+    let pairs: Vec<(u64, u64)> = numbers(LEN, 8).into_iter().map(|number| (number % 100, number)).collect();
+    let expected = reduce_by_key(&pairs, u64::wrapping_add);
+    assert_eq!(expected.len(), 100);
+    on_pools(|| assert_eq!(par_reduce_by_key(&pairs, u64::wrapping_add), expected));
+}
+
+#[test]
+fn par_reduce_by_key_keeps_the_order_of_a_non_commutative_op() {
+    // appending keeps every value of a key in the order of the input
+    let pairs: Vec<(u64, Vec<usize>)> = numbers(20_000, 9).into_iter().enumerate().map(|(position, number)| (number % 7, vec![position])).collect();
+    let append = |mut earlier: Vec<usize>, later: Vec<usize>| {
+        earlier.extend(later);
+        earlier
+    };
+    let expected = reduce_by_key(&pairs, append);
+    assert!(expected.values().all(|positions| positions.windows(2).all(|pair| pair[0] < pair[1])));
+    on_pools(|| assert_eq!(par_reduce_by_key(&pairs, append), expected));
+}
@@ -0,0 +1,219 @@
+mod common;
+
+use std::time::Duration;
+use common::traits;
+use traits::associated_constants::{Terrain, VehicleSpec};
+use traits::default_type_parameters::Length;
+use traits::route_simulation::{simulate, Event, EventKind, InvalidLength, PlaceId, World};
+use traits::traits::poly::{Airplane, AmphibiousBoat, Boat, Car};
+
+// City -- road 30 km -- Harbour -- water 20 nm -- Island
+//   |                                               |
+//   +-- road 15 km -- Airport -- air 60 km ---------+
+struct Map {
+    world: World,
+    city: PlaceId,
+    harbour: PlaceId,
+    airport: PlaceId,
+    island: PlaceId
+}
+
+fn map() -> Map {
+    let mut world = World::new();
+    let city = world.add_place("City");
+    let harbour = world.add_place("Harbour");
+    let airport = world.add_place("Airport");
+    let island = world.add_place("Island");
+    world.connect(city, harbour, Terrain::Road, Length::from_kilometers(30.0)).unwrap();
+    world.connect(harbour, island, Terrain::Water, Length::from_nautical_miles(20.0)).unwrap();
+    world.connect(city, airport, Terrain::Road, Length::from_kilometers(15.0)).unwrap();
+    world.connect(airport, island, Terrain::Air, Length::from_kilometers(60.0)).unwrap();
+    Map { world, city, harbour, airport, island }
+}
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!((actual - expected).abs() < 1e-6, "expected {}, got {}", expected, actual);
+}
+
+// the log without the positions, those are checked separately
+fn milestones(events: &[Event]) -> Vec<(u64, EventKind)> {
+    events
+        .iter()
+        .filter(|event| !matches!(event.kind, EventKind::Travelling { .. }))
+        .map(|event| (event.tick, event.kind))
+        .collect()
+}
+
+//=================================================================
+// routes
+
+#[test]
+fn a_car_cannot_reach_the_island() {
+    let map = map();
+    let error = map.world.route(&VehicleSpec::of::<Car>(), map.city, map.island).unwrap_err();
+    assert_eq!(error.to_string(), "a Car can't get from City to Island");
+}
+
+#[test]
+fn an_amphibious_boat_crosses_land_and_sea() {
+    let map = map();
+    let route = map.world.route(&VehicleSpec::of::<AmphibiousBoat>(), map.city, map.island).unwrap();
+    assert_eq!(route.places(), [map.city, map.harbour, map.island]);
+    let terrain: Vec<Terrain> = route.legs.iter().map(|leg| leg.terrain).collect();
+    assert_eq!(terrain, [Terrain::Road, Terrain::Water]);
+    assert_close(route.length().kilometers(), 30.0 + 37.04);
+}
+
+#[test]
+fn a_boat_only_uses_the_water() {
+    let map = map();
+    let spec = VehicleSpec::of::<Boat>();
+    assert!(map.world.route(&spec, map.city, map.island).is_err());
+    let route = map.world.route(&spec, map.harbour, map.island).unwrap();
+    assert_eq!(route.places(), [map.harbour, map.island]);
+    // 20 nautical miles at 30 knots
+    assert_close(route.duration().as_secs_f64(), 40.0 * 60.0);
+}
+
+#[test]
+fn the_fastest_route_wins_over_the_shortest() {
+    let mut world = World::new();
+    let north = world.add_place("North");
+    let south = world.add_place("South");
+    let east = world.add_place("East");
+    // 20 km of taxiing at 50 km/h takes 24 minutes, 150 km of flying at 900 km/h only 10
+    world.connect(north, south, Terrain::Road, Length::from_kilometers(20.0)).unwrap();
+    world.connect(north, east, Terrain::Air, Length::from_kilometers(75.0)).unwrap();
+    world.connect(east, south, Terrain::Air, Length::from_kilometers(75.0)).unwrap();
+
+    let route = world.route(&VehicleSpec::of::<Airplane>(), north, south).unwrap();
+    assert_eq!(route.places(), [north, east, south]);
+    assert_close(route.duration().as_secs_f64(), 10.0 * 60.0);
+    assert_close(route.length().kilometers(), 150.0);
+}
+
+#[test]
+fn travel_time_follows_the_specification_per_terrain() {
+    let map = map();
+    let route = map.world.route(&VehicleSpec::of::<AmphibiousBoat>(), map.city, map.island).unwrap();
+    // 30 km of road at 110 km/h, then 20 nautical miles at 6 knots
+    assert_close(route.legs[0].duration.as_secs_f64(), 30.0 / 110.0 * 3600.0);
+    assert_close(route.legs[1].duration.as_secs_f64(), 20.0 / 6.0 * 3600.0);
+    assert_close(route.duration().as_secs_f64(), (30.0 / 110.0 + 20.0 / 6.0) * 3600.0);
+}
+
+#[test]
+fn staying_put_is_an_empty_route_without_events() {
+    let map = map();
+    let route = map.world.route(&VehicleSpec::of::<Car>(), map.city, map.city).unwrap();
+    assert!(route.legs.is_empty());
+    assert_eq!(route.duration(), Duration::ZERO);
+    assert!(simulate(&route, Duration::from_secs(60)).is_empty());
+}
+
+#[test]
+fn connections_need_a_length_that_can_be_travelled() {
+    let mut world = World::new();
+    let home = world.add_place("Home");
+    let work = world.add_place("Work");
+    for length in [-1.0, f64::NAN, f64::INFINITY] {
+        let error = world.connect(home, work, Terrain::Road, Length::from_meters(length)).unwrap_err();
+        assert!(error.0.meters().to_bits() == length.to_bits(), "{:?}", error);
+    }
+    assert_eq!(
+        InvalidLength(Length::from_meters(-1.0)).to_string(),
+        "-1 m is not the length of a connection, it has to be finite and zero or more"
+    );
+    assert!(world.route(&VehicleSpec::of::<Car>(), home, work).is_err());
+
+    // a connection too long to travel in a Duration is left out, the way around is taken
+    let detour = world.add_place("Detour");
+    world.connect(home, work, Terrain::Road, Length::from_meters(f64::MAX)).unwrap();
+    world.connect(home, detour, Terrain::Road, Length::from_kilometers(1.0)).unwrap();
+    world.connect(detour, work, Terrain::Road, Length::from_kilometers(1.0)).unwrap();
+    let route = world.route(&VehicleSpec::of::<Car>(), home, work).unwrap();
+    assert_eq!(route.places(), [home, detour, work]);
+}
+
+//=================================================================
+// the event log
+
+#[test]
+fn a_single_leg_is_logged_tick_by_tick() {
+    let mut world = World::new();
+    let home = world.add_place("Home");
+    let work = world.add_place("Work");
+    world.connect(home, work, Terrain::Road, Length::from_kilometers(100.0)).unwrap();
+    let route = world.route(&VehicleSpec::of::<Car>(), home, work).unwrap();
+
+    // 100 km at 200 km/h is 30 minutes, in ticks of 7 minutes the car arrives during tick 5
+    let events = simulate(&route, Duration::from_secs(7 * 60));
+    assert_eq!(
+        milestones(&events),
+        [
+            (0, EventKind::Departed { place: home, leg: 0, terrain: Terrain::Road }),
+            (5, EventKind::Arrived { place: work }),
+            (5, EventKind::Finished { place: work })
+        ]
+    );
+    let positions: Vec<(u64, f64)> = events
+        .iter()
+        .filter_map(|event| match event.kind {
+            EventKind::Travelling { leg: 0, covered } => Some((event.tick, covered.kilometers())),
+            _ => None
+        })
+        .collect();
+    assert_eq!(positions.len(), 4);
+    for (i, (tick, kilometers)) in positions.into_iter().enumerate() {
+        assert_eq!(tick, i as u64 + 1);
+        assert_close(kilometers, 100.0 * tick as f64 * 7.0 / 30.0);
+    }
+}
+
+#[test]
+fn legs_that_end_within_one_tick_are_logged_in_that_tick() {
+    let map = map();
+    let route = map.world.route(&VehicleSpec::of::<Airplane>(), map.city, map.island).unwrap();
+    // 18 minutes of taxiing, then 4 minutes of flying: both end in the second tick of 15 minutes
+    let events = simulate(&route, Duration::from_secs(15 * 60));
+    assert_eq!(
+        milestones(&events),
+        [
+            (0, EventKind::Departed { place: map.city, leg: 0, terrain: Terrain::Road }),
+            (2, EventKind::Arrived { place: map.airport }),
+            (2, EventKind::Departed { place: map.airport, leg: 1, terrain: Terrain::Air }),
+            (2, EventKind::Arrived { place: map.island }),
+            (2, EventKind::Finished { place: map.island })
+        ]
+    );
+    let EventKind::Travelling { leg: 0, covered } = events[1].kind else {
+        panic!("expected the airplane on the road after the first tick, got {:?}", events[1]);
+    };
+    assert_close(covered.kilometers(), 12.5);
+}
+
+#[test]
+fn every_tick_moves_forward_until_the_end() {
+    let map = map();
+    let route = map.world.route(&VehicleSpec::of::<AmphibiousBoat>(), map.city, map.island).unwrap();
+    let tick = Duration::from_secs(5 * 60);
+    let events = simulate(&route, tick);
+
+    let last = events.last().unwrap();
+    assert_eq!(last.kind, EventKind::Finished { place: map.island });
+    assert_eq!(last.tick, (route.duration().as_secs_f64() / tick.as_secs_f64()).ceil() as u64);
+    // ticks never go back, and every tick between the first and the last one logged something
+    for pair in events.windows(2) {
+        assert!(pair[1].tick == pair[0].tick || pair[1].tick == pair[0].tick + 1, "{:?}", pair);
+    }
+    // positions grow within a leg
+    let mut previous = (usize::MAX, 0.0);
+    for event in &events {
+        if let EventKind::Travelling { leg, covered } = event.kind {
+            if leg == previous.0 {
+                assert!(covered.meters() > previous.1);
+            }
+            previous = (leg, covered.meters());
+        }
+    }
+}
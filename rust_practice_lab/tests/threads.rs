@@ -0,0 +1,220 @@
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+use rust_practice_lab::threads::*;
+
+// a deadlock should fail the test, not hang it
+fn within<T: Send + 'static>(seconds: u64, f: impl FnOnce() -> T + Send + 'static) -> T {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let _ = sender.send(f());
+    });
+    receiver.recv_timeout(Duration::from_secs(seconds)).expect("the pool got stuck")
+}
+
+fn panic_message(panic: Box<dyn std::any::Any + Send>) -> String {
+    match panic.downcast::<&str>() {
+        Ok(message) => message.to_string(),
+        Err(panic) => *panic.downcast::<String>().expect("a panic with a message")
+    }
+}
+
+//=================================================================
+// spawn and JoinHandle
+
+#[test]
+fn spawned_jobs_return_their_result() {
+    let pool = ThreadPool::new(3);
+    let handles: Vec<JoinHandle<u64>> = (0..20u64).map(|n| pool.spawn(move || n * n)).collect();
+    let squares: Vec<u64> = handles.into_iter().map(JoinHandle::join).collect();
+    assert_eq!(squares, (0..20u64).map(|n| n * n).collect::<Vec<u64>>());
+}
+
+#[test]
+fn join_rethrows_the_panic_of_the_job() {
+    let pool = ThreadPool::new(2);
+    let handle = pool.spawn(|| -> i32 { panic!("the job failed") });
+    let panic = panic::catch_unwind(AssertUnwindSafe(|| handle.join())).unwrap_err();
+    assert_eq!(panic_message(panic), "the job failed");
+    // the worker caught it and keeps going
+    assert_eq!(pool.spawn(|| 7).join(), 7);
+}
+
+#[test]
+fn is_finished_turns_true_once_the_job_ran() {
+    let pool = ThreadPool::new(1);
+    let (sender, receiver) = mpsc::channel::<()>();
+    let handle = pool.spawn(move || receiver.recv().unwrap());
+    assert!(!handle.is_finished());
+    sender.send(()).unwrap();
+    while !handle.is_finished() {
+        thread::yield_now();
+    }
+    handle.join();
+}
+
+//=================================================================
+// scope
+
+#[test]
+fn scope_waits_for_every_borrowed_job() {
+    let pool = ThreadPool::new(4);
+    let mut slots = vec![0usize; 32];
+    pool.scope(|scope| {
+        for (index, slot) in slots.iter_mut().enumerate() {
+            scope.spawn(move |_| {
+                thread::sleep(Duration::from_millis(1));
+                *slot = index * 2;
+            });
+        }
+    });
+    assert_eq!(slots, (0..32).map(|index| index * 2).collect::<Vec<usize>>());
+}
+
+#[test]
+fn jobs_can_spawn_more_jobs_on_their_scope() {
+    let pool = ThreadPool::new(2);
+    let ran = AtomicUsize::new(0);
+    pool.scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|scope| {
+                ran.fetch_add(1, Ordering::SeqCst);
+                for _ in 0..4 {
+                    scope.spawn(|_| {
+                        thread::sleep(Duration::from_millis(1));
+                        ran.fetch_add(1, Ordering::SeqCst);
+                    });
+                }
+            });
+        }
+    });
+    assert_eq!(ran.load(Ordering::SeqCst), 4 + 16);
+}
+
+#[test]
+fn scope_rethrows_the_first_panic_after_every_job_finished() {
+    // one worker runs the jobs in the order they were spawned
+    let pool = ThreadPool::new(1);
+    let finished = AtomicUsize::new(0);
+    let panic = panic::catch_unwind(AssertUnwindSafe(|| {
+        pool.scope(|scope| {
+            scope.spawn(|_| panic!("first"));
+            scope.spawn(|_| {
+                thread::sleep(Duration::from_millis(20));
+                finished.fetch_add(1, Ordering::SeqCst);
+            });
+            scope.spawn(|_| panic!("second"));
+            scope.spawn(|_| {
+                finished.fetch_add(1, Ordering::SeqCst);
+            });
+        })
+    }))
+    .unwrap_err();
+    assert_eq!(panic_message(panic), "first");
+    assert_eq!(finished.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn scope_waits_for_its_jobs_when_the_body_panics() {
+    let pool = ThreadPool::new(2);
+    let finished = AtomicUsize::new(0);
+    let panic = panic::catch_unwind(AssertUnwindSafe(|| {
+        pool.scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|_| {
+                    thread::sleep(Duration::from_millis(10));
+                    finished.fetch_add(1, Ordering::SeqCst);
+                });
+            }
+            scope.spawn(|_| panic!("a job failed too"));
+            panic!("the body failed");
+        })
+    }))
+    .unwrap_err();
+    // the panic of the body wins over the ones of the jobs
+    assert_eq!(panic_message(panic), "the body failed");
+    assert_eq!(finished.load(Ordering::SeqCst), 8);
+}
+
+//=================================================================
+// join
+
+fn fibonacci(pool: &ThreadPool, n: u64) -> u64 {
+    if n < 2 {
+        return n;
+    }
+    let (a, b) = pool.join(|| fibonacci(pool, n - 1), || fibonacci(pool, n - 2));
+    a + b
+}
+
+#[test]
+fn join_returns_both_results() {
+    let pool = ThreadPool::new(2);
+    let numbers = [3, 1, 4, 1, 5];
+    let (sum, max) = pool.join(|| numbers.iter().sum::<i32>(), || numbers.iter().max().copied());
+    assert_eq!((sum, max), (14, Some(5)));
+}
+
+#[test]
+fn nested_joins_inside_workers_do_not_deadlock() {
+    // far more nested joins than workers, every waiting worker has to keep running jobs
+    let result = within(60, || {
+        let pool = Arc::new(ThreadPool::new(2));
+        let worker_pool = Arc::clone(&pool);
+        pool.spawn(move || fibonacci(&worker_pool, 18)).join()
+    });
+    assert_eq!(result, 2584);
+}
+
+#[test]
+fn nested_joins_on_a_single_worker_do_not_deadlock() {
+    let result = within(60, || {
+        let pool = Arc::new(ThreadPool::new(1));
+        let worker_pool = Arc::clone(&pool);
+        pool.spawn(move || fibonacci(&worker_pool, 12)).join()
+    });
+    assert_eq!(result, 144);
+}
+
+//=================================================================
+// shutting down
+
+// the first job holds the only worker up, so the others are still queued when the pool goes away
+fn queue_jobs(pool: &ThreadPool, ran: &Arc<AtomicUsize>) {
+    pool.spawn(|| thread::sleep(Duration::from_millis(50)));
+    for _ in 0..100 {
+        let ran = Arc::clone(ran);
+        pool.spawn(move || {
+            ran.fetch_add(1, Ordering::SeqCst);
+        });
+    }
+}
+
+#[test]
+fn shutdown_runs_every_queued_job() {
+    let pool = ThreadPool::new(1);
+    let ran = Arc::new(AtomicUsize::new(0));
+    queue_jobs(&pool, &ran);
+    assert!(ran.load(Ordering::SeqCst) < 100);
+    pool.shutdown();
+    assert_eq!(ran.load(Ordering::SeqCst), 100);
+}
+
+#[test]
+fn dropping_the_pool_runs_every_queued_job() {
+    let ran = Arc::new(AtomicUsize::new(0));
+    {
+        let pool = ThreadPool::new(1);
+        queue_jobs(&pool, &ran);
+    }
+    assert_eq!(ran.load(Ordering::SeqCst), 100);
+}
+
+#[test]
+fn a_pool_has_at_least_one_worker() {
+    let pool = ThreadPool::new(0);
+    assert_eq!(pool.threads(), 1);
+    assert_eq!(pool.spawn(|| "ran").join(), "ran");
+}
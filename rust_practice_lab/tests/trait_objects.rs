@@ -0,0 +1,137 @@
+mod common;
+
+use common::{car, on_sale, traits, vehicle};
+use traits::default_type_parameters::{Length, Mass, Power};
+use traits::trait_objects::{downcast, Capability, Fleet};
+use traits::traits::poly::{Airplane, AmphibiousBoat, Boat, Car, Vehicle};
+use traits::traits::Vehicles;
+
+fn boxed(vehicle: Vehicles) -> Box<dyn Vehicle> {
+    vehicle.into()
+}
+
+fn names<'a>(vehicles: impl Iterator<Item = &'a dyn Vehicle>) -> Vec<&'a str> {
+    vehicles.map(|vehicle| vehicle.name()).collect()
+}
+
+//=================================================================
+// capabilities
+
+#[test]
+fn every_vehicle_type_answers_the_capability_accessors() {
+    let car = boxed(car("Family Car", 32000.0, 150.0));
+    assert!(car.as_land().is_some() && car.as_sea().is_none() && car.as_air().is_none());
+
+    let boat = boxed(Boat::new(vehicle("Fishing Boat", 45000.0), Mass::from_kilograms(40.0)).into());
+    assert!(boat.as_land().is_none() && boat.as_sea().is_some() && boat.as_air().is_none());
+
+    let amphibious_boat = boxed(AmphibiousBoat::new(Boat::new(vehicle("Duck Tour", 120000.0), Mass::from_kilograms(20.0)), 6).into());
+    assert!(amphibious_boat.as_land().is_some() && amphibious_boat.as_sea().is_some() && amphibious_boat.as_air().is_none());
+
+    let airplane = boxed(Airplane::new(vehicle("Crop Duster", 180000.0), Length::from_meters(12.0)).into());
+    assert!(airplane.as_land().is_some() && airplane.as_sea().is_none() && airplane.as_air().is_some());
+}
+
+#[test]
+fn the_accessors_hand_out_the_same_vehicle() {
+    let amphibious_boat = boxed(AmphibiousBoat::new(Boat::new(vehicle("Duck Tour", 120000.0), Mass::from_kilograms(20.0)), 6).into());
+    assert_eq!(amphibious_boat.as_land().unwrap().name(), "Duck Tour");
+    assert_eq!(amphibious_boat.as_sea().unwrap().drop_sail(), amphibious_boat.as_sea().unwrap().drop_sail());
+    assert_eq!(amphibious_boat.as_sea().unwrap().price(), amphibious_boat.price());
+}
+
+#[test]
+fn capabilities_are_listed_in_a_fixed_order() {
+    let capabilities: Vec<Vec<Capability>> = on_sale().into_iter().map(|vehicle| boxed(vehicle).capabilities()).collect();
+    assert_eq!(
+        capabilities,
+        [
+            vec![Capability::Land],
+            vec![Capability::Land],
+            vec![Capability::Land],
+            vec![Capability::Sea],
+            vec![Capability::Land, Capability::Sea],
+            vec![Capability::Land, Capability::Air]
+        ]
+    );
+}
+
+//=================================================================
+// downcasting
+
+#[test]
+fn a_trait_object_knows_its_concrete_type() {
+    let car = boxed(car("Family Car", 32000.0, 150.0));
+    assert!(car.is::<Car>());
+    assert!(!car.is::<Boat>());
+    assert_eq!(car.downcast_ref::<Car>().unwrap().horse_power, Power::from_horse_power(150.0));
+    assert!(car.downcast_ref::<Airplane>().is_none());
+}
+
+#[test]
+fn an_amphibious_boat_is_not_a_boat() {
+    // the boat inside is a field, the trait object is the amphibious boat
+    let amphibious_boat = boxed(AmphibiousBoat::new(Boat::new(vehicle("Duck Tour", 120000.0), Mass::from_kilograms(20.0)), 6).into());
+    assert!(amphibious_boat.is::<AmphibiousBoat>());
+    assert!(amphibious_boat.downcast_ref::<Boat>().is_none());
+}
+
+#[test]
+fn a_failed_downcast_gives_the_vehicle_back() {
+    let airplane = boxed(Airplane::new(vehicle("Crop Duster", 180000.0), Length::from_meters(12.0)).into());
+    let airplane = downcast::<Car>(airplane).expect_err("an airplane is not a car");
+    assert_eq!(airplane.name(), "Crop Duster");
+    let airplane = downcast::<Airplane>(airplane).ok().expect("it is still an airplane");
+    assert_eq!(airplane.wing_length, Length::from_meters(12.0));
+}
+
+#[test]
+fn a_boxed_enum_downcasts_to_the_enum() {
+    // Box::new keeps the Vehicles around the vehicle, the From impl unwraps it
+    let wrapped: Box<dyn Vehicle> = Box::new(car("Family Car", 32000.0, 150.0));
+    assert!(wrapped.is::<Vehicles>());
+    assert!(!wrapped.is::<Car>());
+    // the enum still answers the capability accessors of the vehicle inside
+    assert_eq!(wrapped.capabilities(), [Capability::Land]);
+}
+
+//=================================================================
+// the fleet
+
+#[test]
+fn the_fleet_filters_by_capability() {
+    let fleet: Fleet = on_sale().into_iter().collect();
+    assert_eq!(fleet.len(), 6);
+    assert_eq!(names(fleet.with(Capability::Land)), ["Family Car", "Sports Car", "Old Van", "Duck Tour", "Crop Duster"]);
+    assert_eq!(names(fleet.with(Capability::Sea)), ["Fishing Boat", "Duck Tour"]);
+    assert_eq!(names(fleet.with(Capability::Air)), ["Crop Duster"]);
+    assert_eq!(fleet.land().count(), 5);
+    assert_eq!(fleet.sea().map(|vehicle| vehicle.name()).collect::<Vec<_>>(), ["Fishing Boat", "Duck Tour"]);
+    assert_eq!(fleet.air().map(|vehicle| vehicle.name()).collect::<Vec<_>>(), ["Crop Duster"]);
+}
+
+#[test]
+fn the_fleet_filters_by_concrete_type() {
+    let fleet: Fleet = on_sale().into_iter().collect();
+    assert_eq!(fleet.of_type::<Car>().map(|car| car.vehicle.name.as_str()).collect::<Vec<_>>(), ["Family Car", "Sports Car", "Old Van"]);
+    assert_eq!(fleet.of_type::<Boat>().count(), 1);
+    assert_eq!(fleet.of_type::<Vehicles>().count(), 0);
+}
+
+#[test]
+fn taking_one_type_keeps_the_others_in_order() {
+    let mut fleet: Fleet = on_sale().into_iter().collect();
+    let cars = fleet.take::<Car>();
+    assert_eq!(cars.iter().map(|car| car.vehicle.name.as_str()).collect::<Vec<_>>(), ["Family Car", "Sports Car", "Old Van"]);
+    assert_eq!(names(fleet.iter()), ["Fishing Boat", "Duck Tour", "Crop Duster"]);
+    assert!(fleet.take::<Car>().is_empty());
+}
+
+#[test]
+fn a_fleet_can_be_built_one_vehicle_at_a_time() {
+    let mut fleet = Fleet::new();
+    assert!(fleet.is_empty());
+    fleet.add(Boat::new(vehicle("Fishing Boat", 45000.0), Mass::from_kilograms(40.0)));
+    fleet.extend(on_sale().into_iter().take(2).map(Box::<dyn Vehicle>::from));
+    assert_eq!(names(fleet.iter()), ["Fishing Boat", "Family Car", "Sports Car"]);
+}
@@ -0,0 +1,120 @@
+mod common;
+
+use std::process::Command;
+use serde_json::{json, Value};
+use common::{traits, vehicle};
+use traits::associated_constants::test_drive_land_vehicle_v2;
+use traits::default_type_parameters::{Length, Mass, Money, Power, Speed};
+use traits::traits::poly::{self, AirVehicle, Airplane, AmphibiousBoat, Boat, Car, LandVehicle, Vehicle};
+use traits::traits::vehicle_events::{render_json, render_text, Description, TestDrive, VehicleEvent};
+use traits::traits::VehicleKind;
+
+fn car() -> Car {
+    Car::new(vehicle("Carry the Car", 4500.0), Power::from_horse_power(110.0))
+}
+
+fn amphibious_boat() -> AmphibiousBoat {
+    AmphibiousBoat::new(Boat::new(vehicle("Amber", 132000.0), Mass::from_kilograms(3.0)), 4)
+}
+
+fn airplane() -> Airplane {
+    Airplane::new(vehicle("Aimy", 4500000.0), Length::from_meters(30.0))
+}
+
+//=================================================================
+// the events
+
+#[test]
+fn behaviour_returns_what_happened() {
+    let car = car();
+    assert_eq!(car.drive(), VehicleEvent::Drove { kind: VehicleKind::Car, name: "Carry the Car".to_string() });
+    assert_eq!(
+        car.describe(),
+        VehicleEvent::Described(Description::Car { name: "Carry the Car".to_string(), price: Money::new(4500.0), power: Power::from_horse_power(110.0) })
+    );
+    assert_eq!(amphibious_boat().drift(), VehicleEvent::Drifted { kind: VehicleKind::AmphibiousBoat, name: "Amber".to_string(), wheels: Some(4) });
+    assert_eq!(airplane().fly(), VehicleEvent::Flew { kind: VehicleKind::Airplane, name: "Aimy".to_string() });
+}
+
+#[test]
+fn test_drives_return_their_events_in_order() {
+    let events = poly::test_drive_hybrid_vehicle_v1(&amphibious_boat());
+    assert_eq!(
+        events,
+        [
+            VehicleEvent::TestDrive(TestDrive::HybridVehicleV1),
+            VehicleEvent::Described(Description::AmphibiousBoat { name: "Amber".to_string(), price: Money::new(132000.0), wheels: 4 }),
+            VehicleEvent::Drove { kind: VehicleKind::AmphibiousBoat, name: "Amber".to_string() },
+            VehicleEvent::Drifted { kind: VehicleKind::AmphibiousBoat, name: "Amber".to_string(), wheels: Some(4) },
+            VehicleEvent::DroppedSail { kind: VehicleKind::AmphibiousBoat, name: "Amber".to_string() }
+        ]
+    );
+}
+
+#[test]
+fn only_amphibious_vehicles_continue_on_the_water() {
+    let continued = |events: &[VehicleEvent]| events.iter().any(|event| matches!(event, VehicleEvent::ContinuedOnWater { .. }));
+    assert!(continued(&test_drive_land_vehicle_v2(&amphibious_boat())));
+    assert!(!continued(&test_drive_land_vehicle_v2(&car())));
+    assert!(!continued(&test_drive_land_vehicle_v2(&airplane())));
+}
+
+//=================================================================
+// rendering
+
+#[test]
+fn text_is_one_line_per_event() {
+    let text = render_text(&poly::test_drive_air_vehicle_v1(&airplane()));
+    assert_eq!(
+        text,
+        "Test drive air vehicle\n\
+         I'm an airplane! My name is Aimy, i cost 4500000 and my wings are 30 meters tall!\n\
+         Aimy (type Airplane) is now driving\n\
+         I'm not so good at drifting, sorry!\n\
+         I'm very good at flying, see ya later!\n"
+    );
+    assert_eq!(render_text(&[]), "");
+}
+
+#[test]
+fn json_tags_every_event() {
+    let json: Value = serde_json::from_str(&render_json(&poly::test_drive_land_vehicle_v1(&car()))).unwrap();
+    assert_eq!(
+        json,
+        json!([
+            { "event": "test_drive", "test": "land_vehicle_v1" },
+            {
+                "event": "described",
+                "kind": "Car",
+                "name": "Carry the Car",
+                "price": { "amount": 4500.0, "currency": "USD" },
+                "power": Power::from_horse_power(110.0).watts()
+            },
+            { "event": "drove", "kind": "Car", "name": "Carry the Car" },
+            { "event": "drifted", "kind": "Car", "name": "Carry the Car" }
+        ])
+    );
+}
+
+#[test]
+fn json_writes_quantities_in_si_units() {
+    let json: Value = serde_json::from_str(&render_json(&test_drive_land_vehicle_v2(&amphibious_boat()))).unwrap();
+    assert_eq!(json[0], json!({ "event": "test_drive", "test": "land_vehicle_v2", "category": "Amphibian", "wheels": 4, "max_speed": Speed::from_kilometers_per_hour(110.0).meters_per_second() }));
+    assert_eq!(json[2], json!({ "event": "continued_on_water", "model": "AmphibiousBoat", "speed": Speed::from_knots(6.0).meters_per_second() }));
+    assert_eq!(json[3], json!({ "event": "drifted", "kind": "AmphibiousBoat", "name": "Amber", "wheels": 4 }));
+}
+
+//=================================================================
+// the polymorphism binary prints the events as text, its output may not change
+
+#[test]
+fn polymorphism_output_matches_the_snapshot() {
+    let output = Command::new(env!("CARGO_BIN_EXE_polymorphism")).output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let snapshot = include_str!("snapshots/polymorphism.txt");
+    for (line, (actual, expected)) in stdout.lines().zip(snapshot.lines()).enumerate() {
+        assert_eq!(actual, expected, "line {} differs from tests/snapshots/polymorphism.txt", line + 1);
+    }
+    assert_eq!(stdout, snapshot);
+}
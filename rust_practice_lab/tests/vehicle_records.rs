@@ -0,0 +1,190 @@
+mod common;
+
+use serde_json::{json, Value};
+use common::{traits, vehicle};
+use traits::default_type_parameters::{Length, Mass, Power};
+use traits::traits::poly::{Airplane, AmphibiousBoat, Boat, Car, Vehicle};
+use traits::traits::Vehicles;
+use traits::vehicle_records::{from_bytes, from_json, to_bytes, to_json, RecordError, VERSION};
+
+// one of every variant, with names that need escaping or more than one byte per character
+fn every_variant() -> Vec<Vehicles> {
+    vec![
+        Car::new(vehicle("Carry the \"Car\"", 4500.0), Power::from_horse_power(110.0)).into(),
+        Boat::new(vehicle("Bobby\nthe Boat", 63850.5), Mass::from_kilograms(230.0)).into(),
+        AmphibiousBoat::new(Boat::new(vehicle("Ämber 🦆", 132000.0), Mass::from_kilograms(3.0)), 4).into(),
+        Airplane::new(vehicle("", 4500000.0), Length::from_meters(30.0)).into()
+    ]
+}
+
+fn json_value(vehicles: &[Vehicles]) -> Value {
+    serde_json::from_str(&to_json(vehicles)).unwrap()
+}
+
+//=================================================================
+// round trips
+
+#[test]
+fn every_variant_survives_json() {
+    for vehicle in every_variant() {
+        let vehicles = vec![vehicle];
+        assert_eq!(from_json(&to_json(&vehicles)).unwrap(), vehicles);
+    }
+    assert_eq!(from_json(&to_json(&every_variant())).unwrap(), every_variant());
+}
+
+#[test]
+fn every_variant_survives_binary() {
+    for vehicle in every_variant() {
+        let vehicles = vec![vehicle];
+        assert_eq!(from_bytes(&to_bytes(&vehicles)).unwrap(), vehicles);
+    }
+    assert_eq!(from_bytes(&to_bytes(&every_variant())).unwrap(), every_variant());
+}
+
+#[test]
+fn an_empty_garage_survives_both() {
+    assert!(from_json(&to_json(&[])).unwrap().is_empty());
+    assert!(from_bytes(&to_bytes(&[])).unwrap().is_empty());
+}
+
+#[test]
+fn names_can_be_built_at_runtime() {
+    let vehicles: Vec<Vehicles> = (1..=3).map(|number| Car::new(vehicle(&format!("Rental {}", number), 21000.0), Power::from_kilowatts(70.0)).into()).collect();
+    let read = from_bytes(&to_bytes(&vehicles)).unwrap();
+    let names: Vec<&str> = read.iter().map(|vehicle| vehicle.name()).collect();
+    assert_eq!(names, ["Rental 1", "Rental 2", "Rental 3"]);
+}
+
+#[test]
+fn binary_is_smaller_than_json() {
+    let vehicles = every_variant();
+    assert!(to_bytes(&vehicles).len() * 3 < to_json(&vehicles).len());
+}
+
+//=================================================================
+// the JSON layout
+
+#[test]
+fn variants_are_tagged_with_their_type() {
+    let json = json_value(&every_variant());
+    assert_eq!(json["version"], VERSION);
+    let types: Vec<&str> = json["vehicles"].as_array().unwrap().iter().map(|vehicle| vehicle["type"].as_str().unwrap()).collect();
+    assert_eq!(types, ["Car", "Boat", "AmphibiousBoat", "Airplane"]);
+    assert_eq!(json["vehicles"][0]["power"], Power::from_horse_power(110.0).watts());
+    assert_eq!(json["vehicles"][3]["wing_length"], 30.0);
+}
+
+#[test]
+fn an_amphibious_boat_holds_a_whole_boat() {
+    let json = json_value(&every_variant()[2..3]);
+    assert_eq!(
+        json["vehicles"][0],
+        json!({
+            "type": "AmphibiousBoat",
+            "boat": {
+                "vehicle": { "name": "Ämber 🦆", "price": { "amount": 132000.0, "currency": "USD" } },
+                "anchor_weight": 3.0
+            },
+            "nr_wheels": 4
+        })
+    );
+}
+
+//=================================================================
+// errors
+
+fn json_error(json: Value) -> String {
+    let error = from_json(&json.to_string()).unwrap_err();
+    assert!(matches!(error, RecordError::Json(_)), "{:?}", error);
+    error.to_string()
+}
+
+fn boat_json() -> Value {
+    json!({ "type": "Boat", "vehicle": { "name": "Tub", "price": { "amount": 50.0, "currency": "USD" } }, "anchor_weight": 1.0 })
+}
+
+#[test]
+fn unknown_fields_are_named() {
+    let mut boat = boat_json();
+    boat["colour"] = json!("red");
+    let error = json_error(json!({ "version": 1, "vehicles": [boat] }));
+    assert!(error.contains("unknown field `colour`, expected `vehicle` or `anchor_weight`"), "{}", error);
+
+    let mut boat = boat_json();
+    boat["vehicle"]["owner"] = json!("me");
+    let error = json_error(json!({ "version": 1, "vehicles": [boat] }));
+    assert!(error.contains("unknown field `owner`"), "{}", error);
+
+    let error = json_error(json!({ "version": 1, "vehicles": [], "comment": "" }));
+    assert!(error.contains("unknown field `comment`"), "{}", error);
+}
+
+#[test]
+fn unknown_types_and_missing_fields_are_named() {
+    let mut bike = boat_json();
+    bike["type"] = json!("Bike");
+    let error = json_error(json!({ "version": 1, "vehicles": [bike] }));
+    assert!(error.contains("unknown variant `Bike`"), "{}", error);
+
+    let mut boat = boat_json();
+    boat.as_object_mut().unwrap().remove("anchor_weight");
+    let error = json_error(json!({ "version": 1, "vehicles": [boat] }));
+    assert!(error.contains("missing field `anchor_weight`"), "{}", error);
+}
+
+#[test]
+fn prices_have_to_be_in_dollars() {
+    let mut boat = boat_json();
+    boat["vehicle"]["price"]["currency"] = json!("EUR");
+    let error = json_error(json!({ "version": 1, "vehicles": [boat] }));
+    assert!(error.contains("expected an amount in USD, got EUR"), "{}", error);
+}
+
+#[test]
+fn other_versions_are_rejected_before_the_vehicles_are_read() {
+    // a future version may have fields this one doesn't know
+    let mut boat = boat_json();
+    boat["colour"] = json!("red");
+    let error = from_json(&json!({ "version": 2, "vehicles": [boat] }).to_string()).unwrap_err();
+    assert!(matches!(error, RecordError::UnsupportedVersion { found: 2 }), "{:?}", error);
+    assert_eq!(error.to_string(), "unsupported record version 2, this build reads version 1");
+
+    let error = json_error(json!({ "vehicles": [] }));
+    assert!(error.contains("missing field `version`"), "{}", error);
+
+    let mut bytes = to_bytes(&every_variant());
+    bytes[4..8].copy_from_slice(&7u32.to_le_bytes());
+    assert!(matches!(from_bytes(&bytes), Err(RecordError::UnsupportedVersion { found: 7 })));
+}
+
+#[test]
+fn broken_binary_data_says_where() {
+    let bytes = to_bytes(&every_variant());
+    assert!(matches!(from_bytes(b"{\"version\": 1}"), Err(RecordError::NotARecord)));
+    assert!(matches!(from_bytes(&bytes[..6]), Err(RecordError::Truncated { offset: 4, field: "version" })));
+
+    // the last field of the airplane is its wing length
+    let error = from_bytes(&bytes[..bytes.len() - 1]).unwrap_err();
+    assert!(matches!(error, RecordError::Truncated { field: "wing length", .. }), "{:?}", error);
+    assert_eq!(error.to_string(), format!("the record ends in the middle of the wing length at byte {}", bytes.len() - 8));
+
+    let mut extra = bytes.clone();
+    extra.push(0);
+    assert!(matches!(from_bytes(&extra), Err(RecordError::TrailingBytes { offset }) if offset == bytes.len()));
+
+    // the first vehicle starts after the magic, the version and the count
+    let mut unknown = bytes.clone();
+    unknown[12] = 9;
+    assert_eq!(from_bytes(&unknown).unwrap_err().to_string(), "unknown vehicle type 9 at byte 12");
+
+    // the name of the car starts after its tag and its length
+    let mut invalid = bytes.clone();
+    invalid[17] = 0xff;
+    assert!(matches!(from_bytes(&invalid), Err(RecordError::InvalidName { offset: 17 })));
+
+    // a name that claims to be longer than the data
+    let mut too_long = bytes;
+    too_long[13..17].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert!(matches!(from_bytes(&too_long), Err(RecordError::Truncated { offset: 17, field: "name" })));
+}
@@ -0,0 +1,100 @@
+mod common;
+
+use std::any::Any;
+use common::{car, on_sale, traits, vehicle};
+use traits::default_type_parameters::{Length, Mass, Money, Power};
+use traits::traits::poly::{AbstractVehicle, Airplane, AmphibiousBoat, Boat, Car, Vehicle};
+use traits::traits::vehicle_events::VehicleEvent;
+use traits::traits::{VehicleKind, Vehicles};
+
+// a vehicle type that was never added to the vehicles! list
+struct Scooter {
+    vehicle: AbstractVehicle
+}
+
+impl Vehicle for Scooter {
+    fn describe(&self) -> VehicleEvent {
+        self.drive()
+    }
+
+    fn drive(&self) -> VehicleEvent {
+        VehicleEvent::Drove { kind: VehicleKind::Car, name: self.vehicle.name.clone() }
+    }
+
+    fn abstract_vehicle(&self) -> &AbstractVehicle {
+        &self.vehicle
+    }
+}
+
+//=================================================================
+// the generated enum
+
+#[test]
+fn every_vehicle_type_converts_into_its_variant() {
+    let car = Car::new(vehicle("Family Car", 32000.0), Power::from_horse_power(150.0));
+    assert_eq!(Vehicles::from(car.clone()), Vehicles::Car(car));
+    let boat = Boat::new(vehicle("Fishing Boat", 45000.0), Mass::from_kilograms(40.0));
+    assert_eq!(Vehicles::from(boat.clone()), Vehicles::Boat(boat.clone()));
+    let amphibious_boat = AmphibiousBoat::new(boat, 6);
+    assert_eq!(Vehicles::from(amphibious_boat.clone()), Vehicles::AmphibiousBoat(amphibious_boat));
+    let airplane = Airplane::new(vehicle("Crop Duster", 180000.0), Length::from_meters(12.0));
+    assert_eq!(Vehicles::from(airplane.clone()), Vehicles::Airplane(airplane));
+}
+
+#[test]
+fn the_kinds_follow_the_variants() {
+    assert_eq!(VehicleKind::ALL, [VehicleKind::Car, VehicleKind::Boat, VehicleKind::AmphibiousBoat, VehicleKind::Airplane]);
+    let kinds: Vec<VehicleKind> = on_sale().iter().map(Vehicles::kind).collect();
+    assert_eq!(
+        kinds,
+        [VehicleKind::Car, VehicleKind::Car, VehicleKind::Car, VehicleKind::Boat, VehicleKind::AmphibiousBoat, VehicleKind::Airplane]
+    );
+}
+
+//=================================================================
+// dispatch to the vehicle inside
+
+#[test]
+fn as_vehicle_hands_out_the_vehicle_inside() {
+    for vehicle in on_sale() {
+        let inner = vehicle.as_vehicle();
+        assert!(!(inner as &dyn Any).is::<Vehicles>());
+        assert_eq!(inner.name(), vehicle.name());
+        assert_eq!(VehicleKind::of(inner), Some(vehicle.kind()));
+    }
+    let car = car("Family Car", 32000.0, 150.0);
+    assert!((car.as_vehicle() as &dyn Any).is::<Car>());
+}
+
+#[test]
+fn into_vehicle_keeps_the_vehicle_and_drops_the_enum() {
+    let boxed = car("Family Car", 32000.0, 150.0).into_vehicle();
+    let car = (boxed as Box<dyn Any>).downcast::<Car>().expect("the box holds the car itself");
+    assert_eq!(car.vehicle.price, Money::new(32000.0));
+}
+
+#[test]
+fn the_enum_behaves_like_the_vehicle_inside() {
+    for vehicle in on_sale() {
+        let inner = vehicle.as_vehicle();
+        assert_eq!(vehicle.describe(), inner.describe());
+        assert_eq!(vehicle.drive(), inner.drive());
+        assert_eq!(vehicle.abstract_vehicle(), inner.abstract_vehicle());
+        assert_eq!(vehicle.price(), inner.price());
+        assert_eq!(vehicle.as_land().map(|land| land.drift()), inner.as_land().map(|land| land.drift()));
+        assert_eq!(vehicle.as_sea().map(|sea| sea.drop_sail()), inner.as_sea().map(|sea| sea.drop_sail()));
+        assert_eq!(vehicle.as_air().map(|air| air.fly()), inner.as_air().map(|air| air.fly()));
+    }
+}
+
+#[test]
+fn the_kind_of_a_trait_object_is_found_through_the_enum_too() {
+    let car = car("Family Car", 32000.0, 150.0);
+    // the whole enum as a trait object, and the vehicle inside it
+    assert_eq!(VehicleKind::of(&car), Some(VehicleKind::Car));
+    assert_eq!(VehicleKind::of(car.as_vehicle()), Some(VehicleKind::Car));
+    // a type that isn't in the list has no kind
+    let scooter = Scooter { vehicle: vehicle("Scooter", 900.0) };
+    assert_eq!(VehicleKind::of(&scooter), None);
+    assert_eq!(scooter.name(), "Scooter");
+}